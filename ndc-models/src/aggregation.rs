@@ -5,13 +5,14 @@ use serde_with::skip_serializing_none;
 use std::collections::BTreeMap;
 
 use crate::{
-    AggregateFunctionName, Argument, ArgumentName, ComparisonOperatorName, ExtractionFunctionName,
-    FieldName, OrderDirection, PathElement, UnaryComparisonOperator, VariableName,
+    AggregateFunctionName, Argument, ArgumentName, ComparisonOperatorName, Expression,
+    ExtractionFunctionName, FieldName, OrderDirection, PathElement, UnaryComparisonOperator,
+    VariableName,
 };
 
 // ANCHOR: Aggregate
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[skip_serializing_none]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[schemars(title = "Aggregate")]
 pub enum Aggregate {
@@ -25,6 +26,10 @@ pub enum Aggregate {
         field_path: Option<Vec<FieldName>>,
         /// Whether or not only distinct items should be counted
         distinct: bool,
+        /// Optionally restrict the rows which are counted, as in SQL's
+        /// FILTER (WHERE ...) clause.
+        /// Only used if the 'query.aggregates.filter' capability is supported.
+        filter: Option<Box<Expression>>,
     },
     SingleColumn {
         /// The column to apply the aggregation function to
@@ -36,14 +41,26 @@ pub enum Aggregate {
         field_path: Option<Vec<FieldName>>,
         /// Single column aggregate function name.
         function: AggregateFunctionName,
+        /// Whether or not only distinct values should be aggregated.
+        /// Only used if the 'query.aggregates.distinct' capability is supported.
+        distinct: Option<bool>,
+        /// Optionally restrict the rows which are aggregated, as in SQL's
+        /// FILTER (WHERE ...) clause.
+        /// Only used if the 'query.aggregates.filter' capability is supported.
+        filter: Option<Box<Expression>>,
+    },
+    StarCount {
+        /// Optionally restrict the rows which are counted, as in SQL's
+        /// FILTER (WHERE ...) clause.
+        /// Only used if the 'query.aggregates.filter' capability is supported.
+        filter: Option<Box<Expression>>,
     },
-    StarCount {},
 }
 // ANCHOR_END: Aggregate
 
 // ANCHOR: Grouping
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Grouping")]
 pub struct Grouping {
     /// Dimensions along which to partition the data
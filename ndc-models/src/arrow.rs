@@ -0,0 +1,1035 @@
+//! Encoding of relational query responses as Apache Arrow IPC streams.
+//!
+//! Each column of a response becomes an Arrow field, with a data type chosen
+//! from its [`RelationalType`]:
+//!
+//! - built-in types use the Arrow type of the corresponding relational literal,
+//!   for example `date` is sent as `Date32` and `timestamp` as microseconds,
+//! - scalar types use the Arrow type of their representation where one exists,
+//!   and otherwise the string used by the JSON wire format,
+//! - objects, arrays, arbitrary JSON and GeoJSON are sent as JSON text.
+//!
+//! The relational type of each column is stored in the field metadata, so a
+//! decoded response has the same columns and rows as the encoded one.
+//!
+//! This module is only available with the `arrow` feature.
+
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    ArrowPrimitiveType, Date32Type, Decimal128Type, Decimal256Type, DurationMicrosecondType,
+    Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, IntervalMonthDayNanoType,
+    Time64MicrosecondType, TimestampMicrosecondType, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+};
+use arrow_array::{
+    Array, ArrayRef, BinaryArray, BooleanArray, NullArray, PrimitiveArray, RecordBatch,
+    RecordBatchOptions, StringArray,
+};
+use arrow_buffer::{i256, IntervalMonthDayNano};
+use arrow_ipc::reader::StreamReader;
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::{ArrowError, DataType, Field, IntervalUnit, Schema, TimeUnit};
+use serde_json::Value;
+
+use crate::calendar;
+use crate::value::{ScalarValue, ValueError};
+use crate::{
+    CastType, RelationalColumn, RelationalQueryResponse, RelationalType, SchemaResponse,
+    TypeRepresentation,
+};
+
+/// The content type of a relational query response encoded as an Arrow IPC stream
+pub const ARROW_STREAM_CONTENT_TYPE: &str = "application/vnd.apache.arrow.stream";
+/// The content type of a relational query response encoded as JSON
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+/// The field metadata key which holds the JSON-encoded [`RelationalType`] of a column
+pub const RELATIONAL_TYPE_METADATA_KEY: &str = "ndc.relational_type";
+
+const NANOSECONDS_PER_MICROSECOND: i64 = 1_000;
+const MICROSECONDS_PER_DAY: i64 = calendar::NANOSECONDS_PER_DAY / NANOSECONDS_PER_MICROSECOND;
+
+/// An error found while encoding or decoding an Arrow IPC stream
+#[derive(Debug, thiserror::Error)]
+pub enum ArrowEncodingError {
+    #[error("Arrow error: {0}")]
+    Arrow(#[from] ArrowError),
+    #[error("the response does not include column metadata")]
+    MissingColumns,
+    #[error("row {row} has {found} values, but there are {expected} columns")]
+    RowLength {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("invalid value in row {row} of column {column}: {error}")]
+    InvalidValue {
+        column: String,
+        row: usize,
+        error: ValueError,
+    },
+    #[error("column {0} has no relational type metadata")]
+    MissingTypeMetadata(String),
+    #[error("column {column} has invalid relational type metadata: {error}")]
+    InvalidTypeMetadata {
+        column: String,
+        error: serde_json::Error,
+    },
+    #[error("column {column} should have Arrow type {expected}, but has {found}")]
+    UnexpectedDataType {
+        column: String,
+        expected: DataType,
+        found: DataType,
+    },
+}
+
+/// The encoding used for the body of a relational query response
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RelationalQueryResponseFormat {
+    Json,
+    Arrow,
+}
+
+impl RelationalQueryResponseFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            RelationalQueryResponseFormat::Json => JSON_CONTENT_TYPE,
+            RelationalQueryResponseFormat::Arrow => ARROW_STREAM_CONTENT_TYPE,
+        }
+    }
+
+    /// Choose a response format from the value of an `Accept` header.
+    ///
+    /// JSON is used when there is no header, or when both formats are equally
+    /// acceptable. Returns `None` if neither format is acceptable, in which case
+    /// the connector should respond with `406 Not Acceptable`.
+    pub fn negotiate(accept: Option<&str>) -> Option<Self> {
+        let Some(accept) = accept else {
+            return Some(RelationalQueryResponseFormat::Json);
+        };
+        let json = quality(accept, JSON_CONTENT_TYPE);
+        let arrow = quality(accept, ARROW_STREAM_CONTENT_TYPE);
+        if json <= 0.0 && arrow <= 0.0 {
+            None
+        } else if arrow > json {
+            Some(RelationalQueryResponseFormat::Arrow)
+        } else {
+            Some(RelationalQueryResponseFormat::Json)
+        }
+    }
+}
+
+/// The quality value of the most specific media range which matches a content type
+fn quality(accept: &str, content_type: &str) -> f32 {
+    let (main_type, _) = content_type.split_once('/').unwrap_or((content_type, ""));
+    accept
+        .split(',')
+        .filter_map(|range| {
+            let mut parameters = range.split(';');
+            let media_range = parameters.next()?.trim();
+            let (range_type, range_subtype) = media_range.split_once('/')?;
+            let specificity = if media_range.eq_ignore_ascii_case(content_type) {
+                2
+            } else if range_subtype == "*" && range_type.eq_ignore_ascii_case(main_type) {
+                1
+            } else if media_range == "*/*" {
+                0
+            } else {
+                return None;
+            };
+            let quality = parameters
+                .find_map(|parameter| {
+                    let (name, value) = parameter.split_once('=')?;
+                    name.trim()
+                        .eq_ignore_ascii_case("q")
+                        .then(|| value.trim().parse::<f32>().ok())
+                })
+                .unwrap_or(Some(1.0))?;
+            Some((specificity, quality))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+        .map_or(0.0, |(_, quality)| quality)
+}
+
+/// The Arrow schema of a response with the given columns
+pub fn arrow_schema(
+    schema: &SchemaResponse,
+    columns: &[RelationalColumn],
+) -> Result<Schema, ArrowEncodingError> {
+    columns
+        .iter()
+        .map(|column| {
+            let metadata = serde_json::to_string(&column.r#type).map_err(|error| {
+                ArrowEncodingError::InvalidTypeMetadata {
+                    column: column.name.clone(),
+                    error,
+                }
+            })?;
+            Ok(Field::new(
+                &column.name,
+                data_type(encoding(schema, &column.r#type)),
+                column.nullable,
+            )
+            .with_metadata([(RELATIONAL_TYPE_METADATA_KEY.to_string(), metadata)].into()))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Schema::new)
+}
+
+/// Encode a response as an Arrow IPC stream containing a single record batch.
+///
+/// The response must include column metadata.
+pub fn encode_response(
+    schema: &SchemaResponse,
+    response: &RelationalQueryResponse,
+) -> Result<Vec<u8>, ArrowEncodingError> {
+    let columns = response
+        .columns
+        .as_deref()
+        .ok_or(ArrowEncodingError::MissingColumns)?;
+    if let Some((row, values)) = response
+        .rows
+        .iter()
+        .enumerate()
+        .find(|(_, values)| values.len() != columns.len())
+    {
+        return Err(ArrowEncodingError::RowLength {
+            row,
+            expected: columns.len(),
+            found: values.len(),
+        });
+    }
+
+    let arrow_schema = Arc::new(arrow_schema(schema, columns)?);
+    let arrays = columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| {
+            let values = response
+                .rows
+                .iter()
+                .map(|row| &row[index])
+                .collect::<Vec<_>>();
+            encode_column(encoding(schema, &column.r#type), &values).map_err(|(row, error)| {
+                ArrowEncodingError::InvalidValue {
+                    column: column.name.clone(),
+                    row,
+                    error,
+                }
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let batch = RecordBatch::try_new_with_options(
+        arrow_schema.clone(),
+        arrays,
+        &RecordBatchOptions::new().with_row_count(Some(response.rows.len())),
+    )?;
+
+    let mut writer = StreamWriter::try_new(Vec::new(), &arrow_schema)?;
+    writer.write(&batch)?;
+    writer.finish()?;
+    Ok(writer.into_inner()?)
+}
+
+/// Decode a response from an Arrow IPC stream, which may contain any number of record batches
+pub fn decode_response(
+    schema: &SchemaResponse,
+    bytes: &[u8],
+) -> Result<RelationalQueryResponse, ArrowEncodingError> {
+    let reader = StreamReader::try_new(bytes, None)?;
+    let columns = reader
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| column(schema, field))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut rows: Vec<Vec<Value>> = vec![];
+    for batch in reader {
+        let batch = batch?;
+        let offset = rows.len();
+        rows.extend((0..batch.num_rows()).map(|_| Vec::with_capacity(columns.len())));
+        for (column, array) in columns.iter().zip(batch.columns()) {
+            let values = decode_column(encoding(schema, &column.r#type), array).map_err(
+                |(row, error)| ArrowEncodingError::InvalidValue {
+                    column: column.name.clone(),
+                    row: offset + row,
+                    error,
+                },
+            )?;
+            for (row, value) in rows[offset..].iter_mut().zip(values) {
+                row.push(value);
+            }
+        }
+    }
+
+    Ok(RelationalQueryResponse {
+        columns: Some(columns),
+        rows,
+    })
+}
+
+fn column(schema: &SchemaResponse, field: &Field) -> Result<RelationalColumn, ArrowEncodingError> {
+    let metadata = field
+        .metadata()
+        .get(RELATIONAL_TYPE_METADATA_KEY)
+        .ok_or_else(|| ArrowEncodingError::MissingTypeMetadata(field.name().clone()))?;
+    let r#type: RelationalType = serde_json::from_str(metadata).map_err(|error| {
+        ArrowEncodingError::InvalidTypeMetadata {
+            column: field.name().clone(),
+            error,
+        }
+    })?;
+    let expected = data_type(encoding(schema, &r#type));
+    if field.data_type() != &expected {
+        return Err(ArrowEncodingError::UnexpectedDataType {
+            column: field.name().clone(),
+            expected,
+            found: field.data_type().clone(),
+        });
+    }
+    Ok(RelationalColumn {
+        name: field.name().clone(),
+        r#type,
+        nullable: field.is_nullable(),
+    })
+}
+
+/// The JSON form of a built-in interval value
+#[derive(serde::Deserialize)]
+struct Interval {
+    months: i32,
+    days: i32,
+    nanoseconds: i64,
+}
+
+/// How the values of a column are converted between JSON and Arrow
+#[derive(Clone, Copy)]
+enum Encoding<'a> {
+    Builtin(CastType),
+    Scalar(&'a TypeRepresentation),
+    JsonText,
+    Null,
+}
+
+fn encoding<'a>(schema: &'a SchemaResponse, r#type: &RelationalType) -> Encoding<'a> {
+    match r#type {
+        RelationalType::Builtin { cast_type } => Encoding::Builtin(*cast_type),
+        RelationalType::Scalar { name } => match schema
+            .scalar_types
+            .get(name)
+            .map(|scalar_type| &scalar_type.representation)
+        {
+            Some(
+                TypeRepresentation::Geography
+                | TypeRepresentation::Geometry
+                | TypeRepresentation::JSON
+                | TypeRepresentation::Vector { .. },
+            )
+            | None => Encoding::JsonText,
+            Some(representation) => Encoding::Scalar(representation),
+        },
+        RelationalType::Object { .. } | RelationalType::Array { .. } | RelationalType::Json => {
+            Encoding::JsonText
+        }
+        RelationalType::Null => Encoding::Null,
+    }
+}
+
+fn data_type(encoding: Encoding) -> DataType {
+    match encoding {
+        Encoding::Builtin(cast_type) => match cast_type {
+            CastType::Boolean => DataType::Boolean,
+            CastType::Utf8 => DataType::Utf8,
+            CastType::Int8 => DataType::Int8,
+            CastType::Int16 => DataType::Int16,
+            CastType::Int32 => DataType::Int32,
+            CastType::Int64 => DataType::Int64,
+            CastType::UInt8 => DataType::UInt8,
+            CastType::UInt16 => DataType::UInt16,
+            CastType::UInt32 => DataType::UInt32,
+            CastType::UInt64 => DataType::UInt64,
+            CastType::Float32 => DataType::Float32,
+            CastType::Float64 => DataType::Float64,
+            CastType::Decimal128 { scale, prec } => DataType::Decimal128(prec, scale),
+            CastType::Decimal256 { scale, prec } => DataType::Decimal256(prec, scale),
+            CastType::Date => DataType::Date32,
+            CastType::Time => DataType::Time64(TimeUnit::Microsecond),
+            CastType::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, None),
+            CastType::Duration => DataType::Duration(TimeUnit::Microsecond),
+            CastType::Interval => DataType::Interval(IntervalUnit::MonthDayNano),
+        },
+        Encoding::Scalar(representation) => match representation {
+            TypeRepresentation::Boolean => DataType::Boolean,
+            TypeRepresentation::Int8 => DataType::Int8,
+            TypeRepresentation::Int16 => DataType::Int16,
+            TypeRepresentation::Int32 => DataType::Int32,
+            TypeRepresentation::Int64 => DataType::Int64,
+            TypeRepresentation::Float32 => DataType::Float32,
+            TypeRepresentation::Float64 => DataType::Float64,
+            TypeRepresentation::Bytes => DataType::Binary,
+            _ => DataType::Utf8,
+        },
+        Encoding::JsonText => DataType::Utf8,
+        Encoding::Null => DataType::Null,
+    }
+}
+
+type ColumnResult<T> = Result<T, (usize, ValueError)>;
+
+/// Convert each non-null value, recording the row of the first failure
+fn convert<T>(
+    values: &[&Value],
+    f: impl Fn(&Value) -> Result<T, ValueError>,
+) -> ColumnResult<Vec<Option<T>>> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(row, value)| match value {
+            Value::Null => Ok(None),
+            value => f(value).map(Some).map_err(|error| (row, error)),
+        })
+        .collect()
+}
+
+fn primitive<T: ArrowPrimitiveType>(
+    values: &[&Value],
+    f: impl Fn(&Value) -> Result<T::Native, ValueError>,
+) -> ColumnResult<ArrayRef> {
+    Ok(Arc::new(
+        convert(values, f)?
+            .into_iter()
+            .collect::<PrimitiveArray<T>>(),
+    ))
+}
+
+fn encode_column(encoding: Encoding, values: &[&Value]) -> ColumnResult<ArrayRef> {
+    match encoding {
+        Encoding::Builtin(cast_type) => encode_builtin(cast_type, values),
+        Encoding::Scalar(representation) => {
+            let scalars = convert(values, |value| ScalarValue::decode(representation, value))?;
+            Ok(match data_type(encoding) {
+                DataType::Boolean => Arc::new(BooleanArray::from(
+                    scalars
+                        .into_iter()
+                        .map(|scalar| match scalar? {
+                            ScalarValue::Boolean(b) => Some(b),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>(),
+                )),
+                DataType::Int8 => scalar_array::<Int8Type>(scalars, |scalar| match scalar {
+                    ScalarValue::Int8(i) => Some(i),
+                    _ => None,
+                }),
+                DataType::Int16 => scalar_array::<Int16Type>(scalars, |scalar| match scalar {
+                    ScalarValue::Int16(i) => Some(i),
+                    _ => None,
+                }),
+                DataType::Int32 => scalar_array::<Int32Type>(scalars, |scalar| match scalar {
+                    ScalarValue::Int32(i) => Some(i),
+                    _ => None,
+                }),
+                DataType::Int64 => scalar_array::<Int64Type>(scalars, |scalar| match scalar {
+                    ScalarValue::Int64(i) => Some(i),
+                    _ => None,
+                }),
+                DataType::Float32 => scalar_array::<Float32Type>(scalars, |scalar| match scalar {
+                    ScalarValue::Float32(f) => Some(f),
+                    _ => None,
+                }),
+                DataType::Float64 => scalar_array::<Float64Type>(scalars, |scalar| match scalar {
+                    ScalarValue::Float64(f) => Some(f),
+                    _ => None,
+                }),
+                DataType::Binary => Arc::new(BinaryArray::from(
+                    scalars
+                        .iter()
+                        .map(|scalar| match scalar {
+                            Some(ScalarValue::Bytes(bytes)) => Some(bytes.as_slice()),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>(),
+                )),
+                // Every other representation is a JSON string
+                _ => Arc::new(StringArray::from(
+                    values
+                        .iter()
+                        .map(|value| value.as_str())
+                        .collect::<Vec<_>>(),
+                )),
+            })
+        }
+        Encoding::JsonText => Ok(Arc::new(StringArray::from(convert(values, |value| {
+            Ok(value.to_string())
+        })?))),
+        Encoding::Null => {
+            if let Some(row) = values.iter().position(|value| !value.is_null()) {
+                return Err((row, unexpected("null", values[row])));
+            }
+            Ok(Arc::new(NullArray::new(values.len())))
+        }
+    }
+}
+
+fn scalar_array<T: ArrowPrimitiveType>(
+    scalars: Vec<Option<ScalarValue>>,
+    f: impl Fn(ScalarValue) -> Option<T::Native>,
+) -> ArrayRef {
+    Arc::new(
+        scalars
+            .into_iter()
+            .map(|scalar| scalar.and_then(&f))
+            .collect::<PrimitiveArray<T>>(),
+    )
+}
+
+fn encode_builtin(cast_type: CastType, values: &[&Value]) -> ColumnResult<ArrayRef> {
+    match cast_type {
+        CastType::Boolean => Ok(Arc::new(BooleanArray::from(convert(values, |value| {
+            value.as_bool().ok_or_else(|| unexpected("boolean", value))
+        })?))),
+        CastType::Utf8 => Ok(Arc::new(StringArray::from(convert(values, |value| {
+            value
+                .as_str()
+                .map(str::to_owned)
+                .ok_or_else(|| unexpected("string", value))
+        })?))),
+        CastType::Int8 => primitive::<Int8Type>(values, |value| integer("int8", value)),
+        CastType::Int16 => primitive::<Int16Type>(values, |value| integer("int16", value)),
+        CastType::Int32 => primitive::<Int32Type>(values, |value| integer("int32", value)),
+        CastType::Int64 => primitive::<Int64Type>(values, |value| integer("int64", value)),
+        CastType::UInt8 => primitive::<UInt8Type>(values, |value| integer("uint8", value)),
+        CastType::UInt16 => primitive::<UInt16Type>(values, |value| integer("uint16", value)),
+        CastType::UInt32 => primitive::<UInt32Type>(values, |value| integer("uint32", value)),
+        CastType::UInt64 => primitive::<UInt64Type>(values, |value| integer("uint64", value)),
+        #[allow(clippy::cast_possible_truncation)]
+        CastType::Float32 => {
+            primitive::<Float32Type>(values, |value| number(value).map(|f| f as f32))
+        }
+        CastType::Float64 => primitive::<Float64Type>(values, number),
+        CastType::Decimal128 { scale, prec } => {
+            let array = PrimitiveArray::<Decimal128Type>::from(convert(values, |value| {
+                let digits = decimal_digits("decimal128", value, scale, prec)?;
+                digits
+                    .parse()
+                    .map_err(|_| out_of_range("decimal128", value))
+            })?);
+            Ok(Arc::new(
+                array.with_data_type(DataType::Decimal128(prec, scale)),
+            ))
+        }
+        CastType::Decimal256 { scale, prec } => {
+            let array = PrimitiveArray::<Decimal256Type>::from(convert(values, |value| {
+                let digits = decimal_digits("decimal256", value, scale, prec)?;
+                i256::from_string(&digits).ok_or_else(|| out_of_range("decimal256", value))
+            })?);
+            Ok(Arc::new(
+                array.with_data_type(DataType::Decimal256(prec, scale)),
+            ))
+        }
+        CastType::Date => primitive::<Date32Type>(values, |value| {
+            let s = string(value)?;
+            calendar::parse_date(s)
+                .and_then(|days| i32::try_from(days).ok())
+                .ok_or_else(|| invalid("date", s))
+        }),
+        CastType::Time => primitive::<Time64MicrosecondType>(values, |value| {
+            let s = string(value)?;
+            time(s).ok_or_else(|| invalid("time", s))
+        }),
+        CastType::Timestamp => primitive::<TimestampMicrosecondType>(values, |value| {
+            let s = string(value)?;
+            timestamp(s).ok_or_else(|| invalid("timestamp", s))
+        }),
+        CastType::Duration => {
+            primitive::<DurationMicrosecondType>(values, |value| integer("duration", value))
+        }
+        CastType::Interval => primitive::<IntervalMonthDayNanoType>(values, |value| {
+            let Interval {
+                months,
+                days,
+                nanoseconds,
+            } = serde_json::from_value(value.clone())
+                .map_err(|_| unexpected("interval object", value))?;
+            Ok(IntervalMonthDayNano::new(months, days, nanoseconds))
+        }),
+    }
+}
+
+fn decode_column(encoding: Encoding, array: &dyn Array) -> ColumnResult<Vec<Value>> {
+    match encoding {
+        Encoding::Builtin(cast_type) => decode_builtin(cast_type, array),
+        Encoding::Scalar(representation) => match array.data_type() {
+            DataType::Boolean => Ok(array
+                .as_boolean()
+                .iter()
+                .map(|b| b.map_or(Value::Null, Value::from))
+                .collect()),
+            DataType::Int8 => {
+                decode_primitive::<Int8Type>(array, |i| ScalarValue::Int8(i).encode())
+            }
+            DataType::Int16 => {
+                decode_primitive::<Int16Type>(array, |i| ScalarValue::Int16(i).encode())
+            }
+            DataType::Int32 => {
+                decode_primitive::<Int32Type>(array, |i| ScalarValue::Int32(i).encode())
+            }
+            DataType::Int64 => {
+                decode_primitive::<Int64Type>(array, |i| ScalarValue::Int64(i).encode())
+            }
+            DataType::Float32 => {
+                decode_primitive::<Float32Type>(array, |f| ScalarValue::Float32(f).encode())
+            }
+            DataType::Float64 => {
+                decode_primitive::<Float64Type>(array, |f| ScalarValue::Float64(f).encode())
+            }
+            DataType::Binary => decode_rows(array.as_binary::<i32>().iter(), |bytes| {
+                ScalarValue::Bytes(bytes.to_vec()).encode()
+            }),
+            _ => decode_rows(array.as_string::<i32>().iter(), |s| {
+                ScalarValue::decode(representation, &Value::from(s))?.encode()
+            }),
+        },
+        Encoding::JsonText => decode_rows(array.as_string::<i32>().iter(), |s| {
+            serde_json::from_str(s).map_err(|_| invalid("json", s))
+        }),
+        Encoding::Null => Ok(vec![Value::Null; array.len()]),
+    }
+}
+
+fn decode_rows<T>(
+    values: impl Iterator<Item = Option<T>>,
+    f: impl Fn(T) -> Result<Value, ValueError>,
+) -> ColumnResult<Vec<Value>> {
+    values
+        .enumerate()
+        .map(|(row, value)| value.map_or(Ok(Value::Null), |value| f(value).map_err(|e| (row, e))))
+        .collect()
+}
+
+fn decode_primitive<T: ArrowPrimitiveType>(
+    array: &dyn Array,
+    f: impl Fn(T::Native) -> Result<Value, ValueError>,
+) -> ColumnResult<Vec<Value>> {
+    decode_rows(array.as_primitive::<T>().iter(), f)
+}
+
+fn decode_builtin(cast_type: CastType, array: &dyn Array) -> ColumnResult<Vec<Value>> {
+    match cast_type {
+        CastType::Boolean => Ok(array
+            .as_boolean()
+            .iter()
+            .map(|b| b.map_or(Value::Null, Value::from))
+            .collect()),
+        CastType::Utf8 => Ok(array
+            .as_string::<i32>()
+            .iter()
+            .map(|s| s.map_or(Value::Null, Value::from))
+            .collect()),
+        CastType::Int8 => decode_primitive::<Int8Type>(array, |i| Ok(i.into())),
+        CastType::Int16 => decode_primitive::<Int16Type>(array, |i| Ok(i.into())),
+        CastType::Int32 => decode_primitive::<Int32Type>(array, |i| Ok(i.into())),
+        CastType::Int64 => decode_primitive::<Int64Type>(array, |i| Ok(i.into())),
+        CastType::UInt8 => decode_primitive::<UInt8Type>(array, |i| Ok(i.into())),
+        CastType::UInt16 => decode_primitive::<UInt16Type>(array, |i| Ok(i.into())),
+        CastType::UInt32 => decode_primitive::<UInt32Type>(array, |i| Ok(i.into())),
+        CastType::UInt64 => decode_primitive::<UInt64Type>(array, |i| Ok(i.into())),
+        CastType::Float32 => {
+            decode_primitive::<Float32Type>(array, |f| ScalarValue::Float32(f).encode())
+        }
+        CastType::Float64 => {
+            decode_primitive::<Float64Type>(array, |f| ScalarValue::Float64(f).encode())
+        }
+        CastType::Decimal128 { scale, .. } => decode_primitive::<Decimal128Type>(array, |i| {
+            Ok(decimal_string(&i.to_string(), scale).into())
+        }),
+        CastType::Decimal256 { scale, .. } => decode_primitive::<Decimal256Type>(array, |i| {
+            Ok(decimal_string(&i.to_string(), scale).into())
+        }),
+        CastType::Date => decode_primitive::<Date32Type>(array, |days| {
+            Ok(date_string(i64::from(days))
+                .ok_or_else(|| out_of_range("date", &days.into()))?
+                .into())
+        }),
+        CastType::Time => decode_primitive::<Time64MicrosecondType>(array, |micros| {
+            if (0..MICROSECONDS_PER_DAY).contains(&micros) {
+                Ok(time_string(micros).into())
+            } else {
+                Err(out_of_range("time", &micros.into()))
+            }
+        }),
+        CastType::Timestamp => decode_primitive::<TimestampMicrosecondType>(array, |micros| {
+            let date = date_string(micros.div_euclid(MICROSECONDS_PER_DAY))
+                .ok_or_else(|| out_of_range("timestamp", &micros.into()))?;
+            let time = time_string(micros.rem_euclid(MICROSECONDS_PER_DAY));
+            Ok(format!("{date}T{time}").into())
+        }),
+        CastType::Duration => decode_primitive::<DurationMicrosecondType>(array, |i| Ok(i.into())),
+        CastType::Interval => decode_primitive::<IntervalMonthDayNanoType>(array, |interval| {
+            Ok(serde_json::json!({
+                "months": interval.months,
+                "days": interval.days,
+                "nanoseconds": interval.nanoseconds,
+            }))
+        }),
+    }
+}
+
+fn json_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn unexpected(expected: &'static str, value: &Value) -> ValueError {
+    ValueError::UnexpectedJson {
+        expected,
+        found: json_kind(value),
+    }
+}
+
+fn invalid(representation: &'static str, value: &str) -> ValueError {
+    ValueError::InvalidString {
+        representation,
+        value: value.to_string(),
+    }
+}
+
+fn out_of_range(representation: &'static str, value: &Value) -> ValueError {
+    ValueError::OutOfRange {
+        representation,
+        value: value.to_string(),
+    }
+}
+
+fn string(value: &Value) -> Result<&str, ValueError> {
+    value.as_str().ok_or_else(|| unexpected("string", value))
+}
+
+fn number(value: &Value) -> Result<f64, ValueError> {
+    value.as_f64().ok_or_else(|| unexpected("number", value))
+}
+
+/// Parse an integer, given as a JSON number or a string
+fn integer<T: TryFrom<i128>>(representation: &'static str, value: &Value) -> Result<T, ValueError> {
+    let integer = match value {
+        Value::Number(n) => n
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| n.as_u64().map(i128::from))
+            .ok_or_else(|| unexpected("integer", value))?,
+        Value::String(s) => s.parse().map_err(|_| invalid(representation, s))?,
+        _ => return Err(unexpected("integer", value)),
+    };
+    T::try_from(integer).map_err(|_| out_of_range(representation, value))
+}
+
+/// The unscaled digits of a decimal, given as a JSON number or a string,
+/// with an optional leading minus sign.
+///
+/// A negative scale means that the unscaled value is multiplied by a power of
+/// ten, so only multiples of that power can be represented.
+fn decimal_digits(
+    representation: &'static str,
+    value: &Value,
+    scale: i8,
+    prec: u8,
+) -> Result<String, ValueError> {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        _ => return Err(unexpected("decimal", value)),
+    };
+    let (negative, unsigned) = match text.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, text.as_str()),
+    };
+    let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if whole.is_empty()
+        || !whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid(representation, &text));
+    }
+
+    let fraction = fraction.trim_end_matches('0');
+    let shift = usize::from(scale.unsigned_abs());
+    let digits = if scale >= 0 && shift >= fraction.len() {
+        format!("{whole}{fraction}{}", "0".repeat(shift - fraction.len()))
+    } else if scale < 0 && fraction.is_empty() {
+        let whole = whole.trim_start_matches('0');
+        match whole.len().checked_sub(shift) {
+            _ if whole.is_empty() => "0".to_string(),
+            Some(len) if whole[len..].bytes().all(|b| b == b'0') => whole[..len].to_string(),
+            _ => return Err(out_of_range(representation, value)),
+        }
+    } else {
+        return Err(out_of_range(representation, value));
+    };
+    let digits = match digits.trim_start_matches('0') {
+        "" => "0",
+        digits => digits,
+    };
+    if digits.len() > usize::from(prec) {
+        return Err(out_of_range(representation, value));
+    }
+    Ok(if negative {
+        format!("-{digits}")
+    } else {
+        digits.to_string()
+    })
+}
+
+/// Format unscaled digits as a decimal string
+fn decimal_string(digits: &str, scale: i8) -> String {
+    let (sign, digits) = match digits.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", digits),
+    };
+    let shift = usize::from(scale.unsigned_abs());
+    if scale < 0 && digits != "0" {
+        return format!("{sign}{digits}{}", "0".repeat(shift));
+    }
+    if scale <= 0 {
+        return format!("{sign}{digits}");
+    }
+    let digits = format!("{digits:0>width$}", width = shift + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - shift);
+    format!("{sign}{whole}.{fraction}")
+}
+
+/// Parse a time of day as a number of microseconds since midnight
+fn time(value: &str) -> Option<i64> {
+    microseconds(calendar::parse_time(value)?.into())
+}
+
+/// Parse a timestamp as a number of microseconds since 1970-01-01T00:00:00Z
+fn timestamp(value: &str) -> Option<i64> {
+    microseconds(calendar::parse_timestamp(value)?.epoch_nanoseconds())
+}
+
+/// Convert a number of nanoseconds to microseconds, if no precision is lost
+fn microseconds(nanoseconds: i128) -> Option<i64> {
+    let per_microsecond = i128::from(NANOSECONDS_PER_MICROSECOND);
+    if nanoseconds % per_microsecond == 0 {
+        i64::try_from(nanoseconds / per_microsecond).ok()
+    } else {
+        None
+    }
+}
+
+/// Format a number of days since 1970-01-01 as a `YYYY-MM-DD` date
+fn date_string(days: i64) -> Option<String> {
+    calendar::format_date(days)
+}
+
+/// Format a number of microseconds since midnight as an `HH:MM:SS[.ffffff]` time
+fn time_string(micros: i64) -> String {
+    calendar::format_time(micros * NANOSECONDS_PER_MICROSECOND)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> SchemaResponse {
+        serde_json::from_value(serde_json::json!({
+            "scalar_types": {
+                "Int": {
+                    "representation": { "type": "int64" },
+                    "aggregate_functions": {},
+                    "comparison_operators": {}
+                },
+                "String": {
+                    "representation": { "type": "string" },
+                    "aggregate_functions": {},
+                    "comparison_operators": {}
+                },
+                "Date": {
+                    "representation": { "type": "date" },
+                    "aggregate_functions": {},
+                    "comparison_operators": {}
+                },
+                "Bytes": {
+                    "representation": { "type": "bytes" },
+                    "aggregate_functions": {},
+                    "comparison_operators": {}
+                }
+            },
+            "object_types": {},
+            "collections": [],
+            "functions": [],
+            "procedures": []
+        }))
+        .unwrap()
+    }
+
+    fn response() -> RelationalQueryResponse {
+        serde_json::from_value(serde_json::json!({
+            "columns": [
+                { "name": "id", "type": { "type": "scalar", "name": "Int" }, "nullable": false },
+                { "name": "title", "type": { "type": "scalar", "name": "String" }, "nullable": true },
+                { "name": "published", "type": { "type": "scalar", "name": "Date" }, "nullable": true },
+                { "name": "cover", "type": { "type": "scalar", "name": "Bytes" }, "nullable": true },
+                { "name": "price", "type": { "type": "builtin", "cast_type": { "type": "Decimal128", "scale": 2, "prec": 10 } }, "nullable": true },
+                { "name": "updated_at", "type": { "type": "builtin", "cast_type": { "type": "Timestamp" } }, "nullable": true },
+                { "name": "day", "type": { "type": "builtin", "cast_type": { "type": "Date" } }, "nullable": true },
+                { "name": "elapsed", "type": { "type": "builtin", "cast_type": { "type": "Interval" } }, "nullable": true },
+                { "name": "tags", "type": { "type": "json" }, "nullable": true },
+                { "name": "nothing", "type": { "type": "null" }, "nullable": true }
+            ],
+            "rows": [
+                ["1", "Moby Dick", "1851-10-18", "aGk=", "12.50", "2024-02-29T13:45:00.25", "1969-12-31", { "months": 1, "days": 2, "nanoseconds": 3 }, ["classic"], null],
+                ["2", null, null, null, "-0.07", "1970-01-01T00:00:00", "2000-03-01", null, { "a": [1, null] }, null]
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let schema = schema();
+        let response = response();
+        let bytes = encode_response(&schema, &response).unwrap();
+        let decoded = decode_response(&schema, &bytes).unwrap();
+        assert_eq!(decoded, response);
+
+        let arrow_schema = arrow_schema(&schema, response.columns.as_deref().unwrap()).unwrap();
+        let data_types = arrow_schema
+            .fields()
+            .iter()
+            .map(|field| field.data_type().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            data_types,
+            vec![
+                DataType::Int64,
+                DataType::Utf8,
+                DataType::Utf8,
+                DataType::Binary,
+                DataType::Decimal128(10, 2),
+                DataType::Timestamp(TimeUnit::Microsecond, None),
+                DataType::Date32,
+                DataType::Interval(IntervalUnit::MonthDayNano),
+                DataType::Utf8,
+                DataType::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_responses() {
+        let schema = schema();
+        let mut response = response();
+        response.rows[1][0] = serde_json::json!("two");
+        assert!(matches!(
+            encode_response(&schema, &response),
+            Err(ArrowEncodingError::InvalidValue { column, row: 1, .. }) if column == "id"
+        ));
+
+        response.rows[1][0] = Value::Null;
+        assert!(matches!(
+            encode_response(&schema, &response),
+            Err(ArrowEncodingError::Arrow(_))
+        ));
+
+        response.rows[1].pop();
+        assert!(matches!(
+            encode_response(&schema, &response),
+            Err(ArrowEncodingError::RowLength {
+                row: 1,
+                expected: 10,
+                found: 9
+            })
+        ));
+
+        response.columns = None;
+        assert!(matches!(
+            encode_response(&schema, &response),
+            Err(ArrowEncodingError::MissingColumns)
+        ));
+    }
+
+    #[test]
+    fn test_negative_decimal_scale() {
+        let cast_type = CastType::Decimal128 { scale: -2, prec: 5 };
+        let values = [
+            serde_json::json!("12300"),
+            serde_json::json!("-100"),
+            serde_json::json!("0"),
+        ];
+        let array = encode_builtin(cast_type, &values.iter().collect::<Vec<_>>()).unwrap();
+        assert_eq!(
+            array.as_primitive::<Decimal128Type>().values().to_vec(),
+            vec![123, -1, 0]
+        );
+        assert_eq!(decode_builtin(cast_type, array.as_ref()).unwrap(), values);
+
+        for value in ["1", "12350", "100.5", "12300000"] {
+            assert!(
+                decimal_digits("decimal128", &serde_json::json!(value), -2, 5).is_err(),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_timestamps_agree_with_values() {
+        let values = [
+            serde_json::json!("2024-02-29T13:30:00+01:30"),
+            serde_json::json!("2024-02-29T11:59:60Z"),
+            serde_json::json!("2024-02-29 12:00:00"),
+        ];
+        for value in &values[..2] {
+            assert!(ScalarValue::decode(&crate::TypeRepresentation::TimestampTZ, value).is_ok());
+        }
+        let array =
+            encode_builtin(CastType::Timestamp, &values.iter().collect::<Vec<_>>()).unwrap();
+        assert_eq!(
+            decode_builtin(CastType::Timestamp, array.as_ref()).unwrap(),
+            vec![serde_json::json!("2024-02-29T12:00:00"); 3]
+        );
+
+        let value = serde_json::json!("2024-02-29T12:00:00.0000001");
+        assert!(encode_builtin(CastType::Timestamp, &[&value]).is_err());
+    }
+
+    #[test]
+    fn test_negotiate() {
+        use RelationalQueryResponseFormat::{Arrow, Json};
+
+        assert_eq!(RelationalQueryResponseFormat::negotiate(None), Some(Json));
+        assert_eq!(
+            RelationalQueryResponseFormat::negotiate(Some("*/*")),
+            Some(Json)
+        );
+        assert_eq!(
+            RelationalQueryResponseFormat::negotiate(Some(ARROW_STREAM_CONTENT_TYPE)),
+            Some(Arrow)
+        );
+        assert_eq!(
+            RelationalQueryResponseFormat::negotiate(Some(
+                "application/json;q=0.5, application/vnd.apache.arrow.stream"
+            )),
+            Some(Arrow)
+        );
+        assert_eq!(
+            RelationalQueryResponseFormat::negotiate(Some(
+                "application/*;q=0.9, application/json;q=0.1"
+            )),
+            Some(Arrow)
+        );
+        assert_eq!(
+            RelationalQueryResponseFormat::negotiate(Some("text/html, */*;q=0")),
+            None
+        );
+    }
+}
@@ -0,0 +1,864 @@
+//! A fluent API for constructing queries and predicates.
+//!
+//! ```
+//! use ndc_models::builders::{col, lit};
+//! use ndc_models::QueryRequest;
+//!
+//! let request = QueryRequest::collection("articles")
+//!     .field("title")
+//!     .filter(col("author_id").equals(lit(1)))
+//!     .order_by(col("id").desc())
+//!     .limit(10);
+//! ```
+//!
+//! ## Operator names
+//!
+//! The specification does not fix the names of comparison operators: each
+//! connector names its operators in its schema, and gives their meaning using
+//! [`ComparisonOperatorDefinition`](crate::ComparisonOperatorDefinition). The
+//! comparison methods on [`ColumnRef`], such as [`ColumnRef::equals`] and
+//! [`ColumnRef::lt`], are a convenience which use the names from the reference
+//! connector (`eq`, `in`, `lt`, `lte`, `gt`, `gte` and `like`), and are only
+//! correct for connectors which follow the same convention. For any other
+//! connector, look up the name of the operator with the required definition in
+//! the scalar type of the column, and use [`ColumnRef::op`]:
+//!
+//! ```
+//! use ndc_models::builders::{col, lit};
+//! use ndc_models::{ComparisonOperatorDefinition, Expression, ScalarType};
+//!
+//! fn author_is(scalar_type: &ScalarType, author_id: i32) -> Option<Expression> {
+//!     let (name, _) = scalar_type
+//!         .comparison_operators
+//!         .iter()
+//!         .find(|(_, definition)| **definition == ComparisonOperatorDefinition::Equal)?;
+//!     Some(col("author_id").op(name.clone(), lit(author_id)))
+//! }
+//! ```
+//!
+//! The equality method is named `equals` rather than `eq`, since `ColumnRef`
+//! implements [`PartialEq`], and `a.eq(b)` would read as a comparison of two
+//! column references.
+
+use std::collections::BTreeMap;
+
+use indexmap::IndexMap;
+
+use crate::{
+    Aggregate, AggregateFunctionName, Argument, ArgumentName, CollectionName,
+    ComparisonOperatorName, ComparisonTarget, ComparisonValue, Dimension, Distinct,
+    ExistsInCollection, Expression, Field, FieldName, GroupComparisonTarget, GroupComparisonValue,
+    GroupExpression, GroupOrderBy, GroupOrderByElement, GroupOrderByTarget, Grouping, NestedField,
+    OrderBy, OrderByElement, OrderByTarget, OrderDirection, Query, QueryRequest, Relationship,
+    RelationshipName, UnaryComparisonOperator, VariableName,
+};
+
+/// Refer to a column of the current collection, in a predicate, ordering or aggregate
+pub fn col(name: impl Into<FieldName>) -> ColumnRef {
+    ColumnRef {
+        name: name.into(),
+        arguments: BTreeMap::new(),
+        field_path: None,
+    }
+}
+
+/// A literal scalar value, for use on the right hand side of a comparison
+pub fn lit(value: impl Into<serde_json::Value>) -> ComparisonValue {
+    ComparisonValue::Scalar {
+        value: value.into(),
+    }
+}
+
+/// A variable, for use on the right hand side of a comparison
+pub fn var(name: impl Into<VariableName>) -> ComparisonValue {
+    ComparisonValue::Variable { name: name.into() }
+}
+
+/// The conjunction of a list of expressions
+pub fn and(expressions: impl IntoIterator<Item = Expression>) -> Expression {
+    Expression::And {
+        expressions: expressions.into_iter().collect(),
+    }
+}
+
+/// The disjunction of a list of expressions
+pub fn or(expressions: impl IntoIterator<Item = Expression>) -> Expression {
+    Expression::Or {
+        expressions: expressions.into_iter().collect(),
+    }
+}
+
+/// The negation of an expression
+pub fn not(expression: Expression) -> Expression {
+    Expression::Not {
+        expression: Box::new(expression),
+    }
+}
+
+/// Test whether any row of a related collection satisfies a predicate
+pub fn exists_related(
+    relationship: impl Into<RelationshipName>,
+    predicate: Option<Expression>,
+) -> Expression {
+    Expression::Exists {
+        in_collection: ExistsInCollection::Related {
+            field_path: None,
+            relationship: relationship.into(),
+            arguments: BTreeMap::new(),
+        },
+        predicate: predicate.map(Box::new),
+    }
+}
+
+/// Count all rows
+pub fn star_count() -> Aggregate {
+    Aggregate::StarCount { filter: None }
+}
+
+/// Group by a column of the current collection
+pub fn dim(column_name: impl Into<FieldName>) -> Dimension {
+    Dimension::Column {
+        path: vec![],
+        column_name: column_name.into(),
+        arguments: BTreeMap::new(),
+        field_path: None,
+        extraction: None,
+    }
+}
+
+/// A reference to a column, constructed using [`col`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnRef {
+    name: FieldName,
+    arguments: BTreeMap<ArgumentName, Argument>,
+    field_path: Option<Vec<FieldName>>,
+}
+
+impl ColumnRef {
+    /// Provide a literal value for an argument of the column
+    #[must_use]
+    pub fn argument(
+        mut self,
+        name: impl Into<ArgumentName>,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        self.arguments.insert(
+            name.into(),
+            Argument::Literal {
+                value: value.into(),
+            },
+        );
+        self
+    }
+
+    /// Refer to a field nested inside the column's object value
+    #[must_use]
+    pub fn nested(mut self, field: impl Into<FieldName>) -> Self {
+        self.field_path
+            .get_or_insert_with(Vec::new)
+            .push(field.into());
+        self
+    }
+
+    fn target(self) -> ComparisonTarget {
+        ComparisonTarget::Column {
+            name: self.name,
+            arguments: self.arguments,
+            field_path: self.field_path,
+        }
+    }
+
+    /// Compare the column using a binary operator, by name
+    pub fn op(
+        self,
+        operator: impl Into<ComparisonOperatorName>,
+        value: impl Into<ComparisonValue>,
+    ) -> Expression {
+        Expression::BinaryComparisonOperator {
+            column: self.target(),
+            operator: operator.into(),
+            value: value.into(),
+        }
+    }
+
+    /// Compare the column using the operator named `eq`, by convention
+    pub fn equals(self, value: impl Into<ComparisonValue>) -> Expression {
+        self.op("eq", value)
+    }
+
+    /// Compare the column using the operator named `in`, by convention
+    pub fn is_in(self, values: impl IntoIterator<Item = serde_json::Value>) -> Expression {
+        self.op(
+            "in",
+            lit(serde_json::Value::Array(values.into_iter().collect())),
+        )
+    }
+
+    /// Compare the column using the operator named `lt`, by convention
+    pub fn lt(self, value: impl Into<ComparisonValue>) -> Expression {
+        self.op("lt", value)
+    }
+
+    /// Compare the column using the operator named `lte`, by convention
+    pub fn lte(self, value: impl Into<ComparisonValue>) -> Expression {
+        self.op("lte", value)
+    }
+
+    /// Compare the column using the operator named `gt`, by convention
+    pub fn gt(self, value: impl Into<ComparisonValue>) -> Expression {
+        self.op("gt", value)
+    }
+
+    /// Compare the column using the operator named `gte`, by convention
+    pub fn gte(self, value: impl Into<ComparisonValue>) -> Expression {
+        self.op("gte", value)
+    }
+
+    /// Compare the column using the operator named `like`, by convention
+    pub fn like(self, value: impl Into<ComparisonValue>) -> Expression {
+        self.op("like", value)
+    }
+
+    /// Test whether the column is null
+    pub fn is_null(self) -> Expression {
+        Expression::UnaryComparisonOperator {
+            column: self.target(),
+            operator: UnaryComparisonOperator::IsNull,
+        }
+    }
+
+    fn order(self, order_direction: OrderDirection) -> OrderByElement {
+        OrderByElement {
+            order_direction,
+            target: OrderByTarget::Column {
+                path: vec![],
+                name: self.name,
+                arguments: self.arguments,
+                field_path: self.field_path,
+            },
+        }
+    }
+
+    /// Order by the column, in ascending order
+    pub fn asc(self) -> OrderByElement {
+        self.order(OrderDirection::Asc)
+    }
+
+    /// Order by the column, in descending order
+    pub fn desc(self) -> OrderByElement {
+        self.order(OrderDirection::Desc)
+    }
+
+    /// Count the values in the column
+    pub fn count(self, distinct: bool) -> Aggregate {
+        Aggregate::ColumnCount {
+            column: self.name,
+            arguments: self.arguments,
+            field_path: self.field_path,
+            distinct,
+            filter: None,
+        }
+    }
+
+    /// Aggregate the values in the column using an aggregate function, by name
+    pub fn aggregate(self, function: impl Into<AggregateFunctionName>) -> Aggregate {
+        Aggregate::SingleColumn {
+            column: self.name,
+            arguments: self.arguments,
+            field_path: self.field_path,
+            function: function.into(),
+            distinct: None,
+            filter: None,
+        }
+    }
+}
+
+impl From<ColumnRef> for ComparisonValue {
+    fn from(column: ColumnRef) -> Self {
+        ComparisonValue::Column {
+            path: vec![],
+            name: column.name,
+            arguments: column.arguments,
+            field_path: column.field_path,
+            scope: None,
+        }
+    }
+}
+
+impl Aggregate {
+    /// Aggregate only the distinct values of the column. Has no effect on a
+    /// star count.
+    #[must_use]
+    pub fn distinct(mut self) -> Self {
+        match &mut self {
+            Aggregate::ColumnCount { distinct, .. } => *distinct = true,
+            Aggregate::SingleColumn { distinct, .. } => *distinct = Some(true),
+            Aggregate::StarCount { .. } => {}
+        }
+        self
+    }
+
+    /// Add a predicate restricting the aggregated rows. If a predicate is
+    /// already present, rows must satisfy both.
+    #[must_use]
+    pub fn filter(mut self, predicate: Expression) -> Self {
+        let (Aggregate::ColumnCount { filter, .. }
+        | Aggregate::SingleColumn { filter, .. }
+        | Aggregate::StarCount { filter }) = &mut self;
+        *filter = Some(Box::new(match filter.take().map(|filter| *filter) {
+            None => predicate,
+            Some(Expression::And { mut expressions }) => {
+                expressions.push(predicate);
+                Expression::And { expressions }
+            }
+            Some(existing) => Expression::And {
+                expressions: vec![existing, predicate],
+            },
+        }));
+        self
+    }
+
+    /// Compare the aggregated value using a binary operator, by name, in a group predicate
+    pub fn op(
+        self,
+        operator: impl Into<ComparisonOperatorName>,
+        value: impl Into<serde_json::Value>,
+    ) -> GroupExpression {
+        GroupExpression::BinaryComparisonOperator {
+            target: GroupComparisonTarget::Aggregate { aggregate: self },
+            operator: operator.into(),
+            value: GroupComparisonValue::Scalar {
+                value: value.into(),
+            },
+        }
+    }
+
+    fn group_order(self, order_direction: OrderDirection) -> GroupOrderByElement {
+        GroupOrderByElement {
+            order_direction,
+            target: GroupOrderByTarget::Aggregate { aggregate: self },
+        }
+    }
+
+    /// Order groups by the aggregated value, in ascending order
+    pub fn asc(self) -> GroupOrderByElement {
+        self.group_order(OrderDirection::Asc)
+    }
+
+    /// Order groups by the aggregated value, in descending order
+    pub fn desc(self) -> GroupOrderByElement {
+        self.group_order(OrderDirection::Desc)
+    }
+}
+
+impl Field {
+    /// Select a column
+    pub fn column(column: impl Into<FieldName>) -> Self {
+        Field::Column {
+            column: column.into(),
+            fields: None,
+            arguments: BTreeMap::new(),
+        }
+    }
+
+    /// Select rows from a related collection
+    pub fn relationship(relationship: impl Into<RelationshipName>, query: Query) -> Self {
+        Field::Relationship {
+            query: Box::new(query),
+            relationship: relationship.into(),
+            arguments: BTreeMap::new(),
+        }
+    }
+
+    /// Select fields from the value of a column, using a nested field selection.
+    /// Has no effect on relationship fields.
+    #[must_use]
+    pub fn with_fields(mut self, nested_field: NestedField) -> Self {
+        if let Field::Column { fields, .. } = &mut self {
+            *fields = Some(Box::new(nested_field));
+        }
+        self
+    }
+}
+
+impl Query {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Select a column, using the column name as the field alias
+    #[must_use]
+    pub fn field(self, column: impl Into<FieldName>) -> Self {
+        let column = column.into();
+        self.field_as(column.clone(), Field::column(column))
+    }
+
+    /// Select a field under the given alias
+    #[must_use]
+    pub fn field_as(mut self, alias: impl Into<FieldName>, field: Field) -> Self {
+        self.fields
+            .get_or_insert_with(IndexMap::new)
+            .insert(alias.into(), field);
+        self
+    }
+
+    /// Compute an aggregate under the given alias
+    #[must_use]
+    pub fn aggregate(mut self, alias: impl Into<FieldName>, aggregate: Aggregate) -> Self {
+        self.aggregates
+            .get_or_insert_with(IndexMap::new)
+            .insert(alias.into(), aggregate);
+        self
+    }
+
+    /// Add a predicate. If a predicate is already present, rows must satisfy both.
+    #[must_use]
+    pub fn filter(mut self, predicate: Expression) -> Self {
+        self.predicate = Some(match self.predicate.take() {
+            None => predicate,
+            Some(Expression::And { mut expressions }) => {
+                expressions.push(predicate);
+                Expression::And { expressions }
+            }
+            Some(existing) => Expression::And {
+                expressions: vec![existing, predicate],
+            },
+        });
+        self
+    }
+
+    /// Add an ordering element, after any existing elements
+    #[must_use]
+    pub fn order_by(mut self, element: OrderByElement) -> Self {
+        self.order_by
+            .get_or_insert_with(|| OrderBy { elements: vec![] })
+            .elements
+            .push(element);
+        self
+    }
+
+    #[must_use]
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    #[must_use]
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Return only distinct rows, comparing every selected field
+    #[must_use]
+    pub fn distinct(mut self) -> Self {
+        self.distinct = Some(Distinct::Fields);
+        self
+    }
+
+    /// Return only the first row of each set of rows with equal values for the given columns
+    #[must_use]
+    pub fn distinct_on(mut self, columns: impl IntoIterator<Item = impl Into<FieldName>>) -> Self {
+        self.distinct = Some(Distinct::On {
+            columns: columns.into_iter().map(Into::into).collect(),
+        });
+        self
+    }
+
+    /// Return only the rows after the row identified by a cursor
+    #[must_use]
+    pub fn after(mut self, cursor: impl Into<String>) -> Self {
+        self.after = Some(cursor.into());
+        self
+    }
+
+    /// Group rows, replacing any existing grouping
+    #[must_use]
+    pub fn group_by(mut self, grouping: Grouping) -> Self {
+        self.groups = Some(grouping);
+        self
+    }
+}
+
+impl Grouping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a dimension, after any existing dimensions
+    #[must_use]
+    pub fn dimension(mut self, dimension: Dimension) -> Self {
+        self.dimensions.push(dimension);
+        self
+    }
+
+    /// Compute an aggregate for each group, under the given alias
+    #[must_use]
+    pub fn aggregate(mut self, alias: impl Into<FieldName>, aggregate: Aggregate) -> Self {
+        self.aggregates.insert(alias.into(), aggregate);
+        self
+    }
+
+    /// Add a group predicate. If a predicate is already present, groups must satisfy both.
+    #[must_use]
+    pub fn filter(mut self, predicate: GroupExpression) -> Self {
+        self.predicate = Some(match self.predicate.take() {
+            None => predicate,
+            Some(GroupExpression::And { mut expressions }) => {
+                expressions.push(predicate);
+                GroupExpression::And { expressions }
+            }
+            Some(existing) => GroupExpression::And {
+                expressions: vec![existing, predicate],
+            },
+        });
+        self
+    }
+
+    /// Add a group ordering element, after any existing elements
+    #[must_use]
+    pub fn order_by(mut self, element: GroupOrderByElement) -> Self {
+        self.order_by
+            .get_or_insert_with(|| GroupOrderBy { elements: vec![] })
+            .elements
+            .push(element);
+        self
+    }
+
+    #[must_use]
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    #[must_use]
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+}
+
+impl QueryRequest {
+    /// Start building a request which queries the given collection
+    pub fn collection(collection: impl Into<CollectionName>) -> Self {
+        QueryRequest {
+            collection: collection.into(),
+            query: Query::new(),
+            arguments: BTreeMap::new(),
+            collection_relationships: BTreeMap::new(),
+            variables: None,
+            request_arguments: None,
+        }
+    }
+
+    /// Replace the query
+    #[must_use]
+    pub fn query(mut self, query: Query) -> Self {
+        self.query = query;
+        self
+    }
+
+    /// Provide a literal value for a collection argument
+    #[must_use]
+    pub fn argument(
+        mut self,
+        name: impl Into<ArgumentName>,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        self.arguments.insert(
+            name.into(),
+            Argument::Literal {
+                value: value.into(),
+            },
+        );
+        self
+    }
+
+    /// Define a relationship which can be used in the query
+    #[must_use]
+    pub fn relationship(
+        mut self,
+        name: impl Into<RelationshipName>,
+        relationship: Relationship,
+    ) -> Self {
+        self.collection_relationships
+            .insert(name.into(), relationship);
+        self
+    }
+
+    /// Add a set of variables. The query is evaluated once for each set.
+    #[must_use]
+    pub fn variables(mut self, variables: BTreeMap<VariableName, serde_json::Value>) -> Self {
+        self.variables.get_or_insert_with(Vec::new).push(variables);
+        self
+    }
+
+    /// See [`Query::field`]
+    #[must_use]
+    pub fn field(self, column: impl Into<FieldName>) -> Self {
+        self.map_query(|query| query.field(column))
+    }
+
+    /// See [`Query::field_as`]
+    #[must_use]
+    pub fn field_as(self, alias: impl Into<FieldName>, field: Field) -> Self {
+        self.map_query(|query| query.field_as(alias, field))
+    }
+
+    /// See [`Query::aggregate`]
+    #[must_use]
+    pub fn aggregate(self, alias: impl Into<FieldName>, aggregate: Aggregate) -> Self {
+        self.map_query(|query| query.aggregate(alias, aggregate))
+    }
+
+    /// See [`Query::filter`]
+    #[must_use]
+    pub fn filter(self, predicate: Expression) -> Self {
+        self.map_query(|query| query.filter(predicate))
+    }
+
+    /// See [`Query::order_by`]
+    #[must_use]
+    pub fn order_by(self, element: OrderByElement) -> Self {
+        self.map_query(|query| query.order_by(element))
+    }
+
+    /// See [`Query::limit`]
+    #[must_use]
+    pub fn limit(self, limit: u32) -> Self {
+        self.map_query(|query| query.limit(limit))
+    }
+
+    /// See [`Query::offset`]
+    #[must_use]
+    pub fn offset(self, offset: u32) -> Self {
+        self.map_query(|query| query.offset(offset))
+    }
+
+    /// See [`Query::group_by`]
+    #[must_use]
+    pub fn group_by(self, grouping: Grouping) -> Self {
+        self.map_query(|query| query.group_by(grouping))
+    }
+
+    fn map_query(mut self, f: impl FnOnce(Query) -> Query) -> Self {
+        self.query = f(self.query);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_query_request() {
+        let request = QueryRequest::collection("articles")
+            .field("title")
+            .filter(col("author_id").equals(lit(1)))
+            .order_by(col("id").desc())
+            .limit(10);
+
+        let expected: QueryRequest = serde_json::from_value(serde_json::json!({
+            "collection": "articles",
+            "arguments": {},
+            "collection_relationships": {},
+            "query": {
+                "fields": {
+                    "title": { "type": "column", "column": "title" },
+                },
+                "predicate": {
+                    "type": "binary_comparison_operator",
+                    "column": { "type": "column", "name": "author_id" },
+                    "operator": "eq",
+                    "value": { "type": "scalar", "value": 1 },
+                },
+                "order_by": {
+                    "elements": [{
+                        "order_direction": "desc",
+                        "target": { "type": "column", "name": "id", "path": [] },
+                    }],
+                },
+                "limit": 10,
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(request, expected);
+    }
+
+    #[test]
+    fn test_unset_aggregate_options_are_omitted() {
+        assert_eq!(
+            serde_json::to_value(star_count()).unwrap(),
+            serde_json::json!({ "type": "star_count" })
+        );
+        assert_eq!(
+            serde_json::to_value(col("total").aggregate("sum")).unwrap(),
+            serde_json::json!({ "type": "single_column", "column": "total", "function": "sum" })
+        );
+    }
+
+    #[test]
+    fn test_filters_are_combined_with_and() {
+        let query = Query::new()
+            .filter(col("a").equals(lit(1)))
+            .filter(col("b").lt(var("b")))
+            .filter(or([col("c").is_null(), not(col("d").like(lit("x%")))]));
+
+        let expected: Query = serde_json::from_value(serde_json::json!({
+            "predicate": {
+                "type": "and",
+                "expressions": [
+                    {
+                        "type": "binary_comparison_operator",
+                        "column": { "type": "column", "name": "a" },
+                        "operator": "eq",
+                        "value": { "type": "scalar", "value": 1 },
+                    },
+                    {
+                        "type": "binary_comparison_operator",
+                        "column": { "type": "column", "name": "b" },
+                        "operator": "lt",
+                        "value": { "type": "variable", "name": "b" },
+                    },
+                    {
+                        "type": "or",
+                        "expressions": [
+                            {
+                                "type": "unary_comparison_operator",
+                                "column": { "type": "column", "name": "c" },
+                                "operator": "is_null",
+                            },
+                            {
+                                "type": "not",
+                                "expression": {
+                                    "type": "binary_comparison_operator",
+                                    "column": { "type": "column", "name": "d" },
+                                    "operator": "like",
+                                    "value": { "type": "scalar", "value": "x%" },
+                                },
+                            },
+                        ],
+                    },
+                ],
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(query, expected);
+    }
+
+    #[test]
+    fn test_column_arguments_and_nested_fields() {
+        let expression = col("address")
+            .argument("limit", 1)
+            .nested("city")
+            .is_in([serde_json::json!("London"), serde_json::json!("Paris")]);
+
+        let expected: Expression = serde_json::from_value(serde_json::json!({
+            "type": "binary_comparison_operator",
+            "column": {
+                "type": "column",
+                "name": "address",
+                "arguments": { "limit": { "type": "literal", "value": 1 } },
+                "field_path": ["city"],
+            },
+            "operator": "in",
+            "value": { "type": "scalar", "value": ["London", "Paris"] },
+        }))
+        .unwrap();
+
+        assert_eq!(expression, expected);
+    }
+
+    #[test]
+    fn test_relationship_fields_and_exists() {
+        let request = QueryRequest::collection("authors").query(
+            Query::new()
+                .field("name")
+                .field_as(
+                    "articles",
+                    Field::relationship("author_articles", Query::new().field("title").limit(5)),
+                )
+                .filter(exists_related(
+                    "author_articles",
+                    Some(col("title").gte(lit("M"))),
+                ))
+                .distinct_on(["name"]),
+        );
+
+        let expected: QueryRequest = serde_json::from_value(serde_json::json!({
+            "collection": "authors",
+            "arguments": {},
+            "collection_relationships": {},
+            "query": {
+                "fields": {
+                    "name": { "type": "column", "column": "name" },
+                    "articles": {
+                        "type": "relationship",
+                        "relationship": "author_articles",
+                        "arguments": {},
+                        "query": {
+                            "fields": {
+                                "title": { "type": "column", "column": "title" },
+                            },
+                            "limit": 5,
+                        },
+                    },
+                },
+                "predicate": {
+                    "type": "exists",
+                    "in_collection": {
+                        "type": "related",
+                        "relationship": "author_articles",
+                        "arguments": {},
+                    },
+                    "predicate": {
+                        "type": "binary_comparison_operator",
+                        "column": { "type": "column", "name": "title" },
+                        "operator": "gte",
+                        "value": { "type": "scalar", "value": "M" },
+                    },
+                },
+                "distinct": { "type": "on", "columns": ["name"] },
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(request, expected);
+    }
+
+    #[test]
+    fn test_build_grouping() {
+        let grouping = Grouping::new()
+            .dimension(dim("author_id"))
+            .aggregate("total", col("id").count(true))
+            .filter(star_count().op("gt", 1))
+            .order_by(star_count().desc())
+            .limit(3);
+
+        let expected: Grouping = serde_json::from_value(serde_json::json!({
+            "dimensions": [{ "type": "column", "column_name": "author_id", "path": [] }],
+            "aggregates": {
+                "total": { "type": "column_count", "column": "id", "distinct": true },
+            },
+            "predicate": {
+                "type": "binary_comparison_operator",
+                "target": { "type": "aggregate", "aggregate": { "type": "star_count" } },
+                "operator": "gt",
+                "value": { "type": "scalar", "value": 1 },
+            },
+            "order_by": {
+                "elements": [{
+                    "order_direction": "desc",
+                    "target": { "type": "aggregate", "aggregate": { "type": "star_count" } },
+                }],
+            },
+            "limit": 3,
+        }))
+        .unwrap();
+
+        assert_eq!(grouping, expected);
+    }
+}
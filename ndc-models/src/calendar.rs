@@ -0,0 +1,287 @@
+//! Parsing and formatting of ISO 8601 dates, times and timestamps.
+//!
+//! Value validation, lowering, Arrow encoding and SQL rendering all use these
+//! functions, so that they accept and produce the same strings:
+//!
+//! - a date is `YYYY-MM-DD`, with a year between 0000 and 9999,
+//! - a time is `hh:mm:ss`, with up to nine digits of fractional seconds. A
+//!   leap second (`ss` is `60`) is rolled over into the following minute, as
+//!   PostgreSQL does, so `23:59:60` is only valid as part of a timestamp,
+//! - a timestamp is a date and a time separated by `T` or a space, and
+//!   optionally followed by a UTC offset: `Z`, `±hh:mm`, `±hhmm` or `±hh`.
+
+pub(crate) const NANOSECONDS_PER_SECOND: i64 = 1_000_000_000;
+#[cfg_attr(not(feature = "arrow"), allow(dead_code))]
+pub(crate) const NANOSECONDS_PER_DAY: i64 = 86_400 * NANOSECONDS_PER_SECOND;
+
+/// A parsed timestamp
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Timestamp {
+    /// The number of days since 1970-01-01
+    pub days: i64,
+    /// The number of nanoseconds since midnight, which is at least a whole
+    /// day for a leap second at the end of the day
+    pub nanoseconds: i64,
+    /// The UTC offset in minutes, if one was given
+    pub offset_minutes: Option<i64>,
+}
+
+impl Timestamp {
+    /// The number of nanoseconds since 1970-01-01T00:00:00Z. A timestamp
+    /// without a UTC offset is interpreted as UTC.
+    #[cfg_attr(not(feature = "arrow"), allow(dead_code))]
+    pub fn epoch_nanoseconds(&self) -> i128 {
+        let offset = i128::from(self.offset_minutes.unwrap_or(0)) * 60;
+        (i128::from(self.days) * 86_400 - offset) * i128::from(NANOSECONDS_PER_SECOND)
+            + i128::from(self.nanoseconds)
+    }
+}
+
+/// Parse a `YYYY-MM-DD` date as a number of days since 1970-01-01
+pub(crate) fn parse_date(value: &str) -> Option<i64> {
+    let mut parts = value.split('-');
+    let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let (year, month) = (number(year, 4, 0, 9999)?, number(month, 2, 1, 12)?);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    let day = number(day, 2, 1, days_in_month)?;
+
+    // See http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    Some(era * 146_097 + day_of_era - 719_468)
+}
+
+/// Parse an `hh:mm:ss[.fffffffff]` time of day as a number of nanoseconds
+/// since midnight
+#[cfg_attr(not(feature = "arrow"), allow(dead_code))]
+pub(crate) fn parse_time(value: &str) -> Option<i64> {
+    time(value).filter(|nanoseconds| *nanoseconds < NANOSECONDS_PER_DAY)
+}
+
+/// Parse a date and time, with an optional UTC offset
+pub(crate) fn parse_timestamp(value: &str) -> Option<Timestamp> {
+    let (date, time_and_offset) = value.split_once(['T', ' '])?;
+    let (time_of_day, offset_minutes) = match time_and_offset.find(['Z', 'z', '+', '-']) {
+        Some(index) => {
+            let (time_of_day, offset) = time_and_offset.split_at(index);
+            (time_of_day, Some(self::offset(offset)?))
+        }
+        None => (time_and_offset, None),
+    };
+    Some(Timestamp {
+        days: parse_date(date)?,
+        nanoseconds: time(time_of_day)?,
+        offset_minutes,
+    })
+}
+
+/// Format a number of days since 1970-01-01 as a `YYYY-MM-DD` date, if its
+/// year is between 0000 and 9999
+#[cfg_attr(not(any(feature = "arrow", feature = "sql")), allow(dead_code))]
+pub(crate) fn format_date(days: i64) -> Option<String> {
+    // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = days.checked_add(719_468)?;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (0..=9999)
+        .contains(&year)
+        .then(|| format!("{year:04}-{month:02}-{day:02}"))
+}
+
+/// Format a number of nanoseconds since midnight as an `hh:mm:ss[.fffffffff]`
+/// time, omitting trailing zeros from the fractional seconds
+#[cfg_attr(not(any(feature = "arrow", feature = "sql")), allow(dead_code))]
+pub(crate) fn format_time(nanoseconds: i64) -> String {
+    let seconds = nanoseconds / NANOSECONDS_PER_SECOND;
+    let fraction = nanoseconds % NANOSECONDS_PER_SECOND;
+    let time = format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    if fraction == 0 {
+        time
+    } else {
+        format!("{time}.{}", format!("{fraction:09}").trim_end_matches('0'))
+    }
+}
+
+/// Parse an `hh:mm:ss[.fffffffff]` time, allowing a leap second to roll over
+/// past midnight
+fn time(value: &str) -> Option<i64> {
+    let (hms, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let mut parts = hms.split(':');
+    let (hours, minutes, seconds) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some()
+        || value.ends_with('.')
+        || fraction.len() > 9
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let (hours, minutes, seconds) = (
+        number(hours, 2, 0, 23)?,
+        number(minutes, 2, 0, 59)?,
+        number(seconds, 2, 0, 60)?,
+    );
+    let fraction: i64 = format!("{fraction:0<9}").parse().ok()?;
+    Some(((hours * 60 + minutes) * 60 + seconds) * NANOSECONDS_PER_SECOND + fraction)
+}
+
+/// Parse a `Z`, `±hh:mm`, `±hhmm` or `±hh` UTC offset as a number of minutes
+fn offset(value: &str) -> Option<i64> {
+    let sign = match value.chars().next()? {
+        'Z' | 'z' if value.len() == 1 => return Some(0),
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let value = &value[1..];
+    let (hours, minutes) = match value.split_once(':') {
+        Some((hours, minutes)) => (hours, Some(minutes)),
+        None if value.len() == 4 && value.is_ascii() => (&value[..2], Some(&value[2..])),
+        None => (value, None),
+    };
+    let hours = number(hours, 2, 0, 23)?;
+    let minutes = match minutes {
+        Some(minutes) => number(minutes, 2, 0, 59)?,
+        None => 0,
+    };
+    Some(sign * (hours * 60 + minutes))
+}
+
+/// Parse a fixed-width string of decimal digits within a range
+fn number(value: &str, len: usize, min: i64, max: i64) -> Option<i64> {
+    if value.len() != len || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    value
+        .parse()
+        .ok()
+        .filter(|number| (min..=max).contains(number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dates() {
+        for (value, days) in [
+            ("1970-01-01", 0),
+            ("1969-12-31", -1),
+            ("2000-03-01", 11_017),
+            ("2024-02-29", 19_782),
+            ("0000-01-01", -719_528),
+            ("9999-12-31", 2_932_896),
+        ] {
+            assert_eq!(parse_date(value), Some(days), "{value}");
+            assert_eq!(format_date(days).as_deref(), Some(value), "{value}");
+        }
+        for value in [
+            "2023-02-29",
+            "2024-2-01",
+            "2024-13-01",
+            "2024-01-1é",
+            "+2024-01-01",
+        ] {
+            assert_eq!(parse_date(value), None, "{value}");
+        }
+        assert_eq!(format_date(-719_529), None);
+        assert_eq!(format_date(2_932_897), None);
+        assert_eq!(format_date(i64::MAX), None);
+    }
+
+    #[test]
+    fn test_times() {
+        assert_eq!(parse_time("00:00:00"), Some(0));
+        assert_eq!(
+            parse_time("13:45:00.25"),
+            Some((13 * 3600 + 45 * 60) * NANOSECONDS_PER_SECOND + 250_000_000)
+        );
+        assert_eq!(
+            parse_time("12:30:60"),
+            Some((12 * 3600 + 31 * 60) * NANOSECONDS_PER_SECOND)
+        );
+        for value in [
+            "23:59:60",
+            "24:00:00",
+            "12:00",
+            "12:00:00.",
+            "12:00:00.0000000001",
+        ] {
+            assert_eq!(parse_time(value), None, "{value}");
+        }
+        assert_eq!(
+            format_time(parse_time("13:45:00.25").unwrap()),
+            "13:45:00.25"
+        );
+        assert_eq!(format_time(1), "00:00:00.000000001");
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let utc = parse_timestamp("2024-02-29T12:00:00Z").unwrap();
+        assert_eq!(utc.offset_minutes, Some(0));
+        for value in [
+            "2024-02-29 13:30:00+01:30",
+            "2024-02-29T13:30:00+0130",
+            "2024-02-29T10:30:00-01:30",
+            "2024-02-29T11:59:60-00",
+        ] {
+            let timestamp = parse_timestamp(value).unwrap();
+            assert_eq!(
+                timestamp.epoch_nanoseconds(),
+                utc.epoch_nanoseconds(),
+                "{value}"
+            );
+        }
+        assert_eq!(
+            parse_timestamp("2024-02-29T23:59:60.5")
+                .unwrap()
+                .epoch_nanoseconds(),
+            parse_timestamp("2024-03-01T00:00:00.5")
+                .unwrap()
+                .epoch_nanoseconds()
+        );
+        assert_eq!(
+            parse_timestamp("2024-02-29T12:00:00")
+                .unwrap()
+                .offset_minutes,
+            None
+        );
+        for value in [
+            "2024-02-29",
+            "2024-02-29T12:00:00+1é2",
+            "2024-02-29T12:00:00+24:00",
+            "2024-02-29T12:00:00ZZ",
+            "2024-02-29T12:00:00+",
+        ] {
+            assert_eq!(parse_timestamp(value), None, "{value}");
+        }
+    }
+}
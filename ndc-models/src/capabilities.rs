@@ -18,14 +18,14 @@ pub struct CapabilitiesResponse {
 // ANCHOR: LeafCapability
 /// A unit value to indicate a particular leaf capability is supported.
 /// This is an empty struct to allow for future sub-capabilities.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct LeafCapability {}
 // ANCHOR_END: LeafCapability
 
 // ANCHOR: Capabilities
 /// Describes the features of the specification which a data connector implements.
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Capabilities")]
 pub struct Capabilities {
     pub query: QueryCapabilities,
@@ -42,7 +42,7 @@ pub struct Capabilities {
 
 // ANCHOR: QueryCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Query Capabilities")]
 pub struct QueryCapabilities {
     /// Does the connector support aggregate queries
@@ -57,6 +57,17 @@ pub struct QueryCapabilities {
     /// Does the connector support EXISTS predicates
     #[serde(default)]
     pub exists: ExistsCapabilities,
+    /// Does the connector support returning distinct rows
+    pub distinct: Option<DistinctCapabilities>,
+    /// Does the connector support pagination other than limit and offset
+    #[serde(default)]
+    pub pagination: PaginationCapabilities,
+    /// Does the connector support full-text search operators
+    pub full_text_search: Option<FullTextSearchCapabilities>,
+    /// Does the connector support geospatial operators
+    pub geospatial: Option<GeospatialCapabilities>,
+    /// Does the connector support ordering by vector similarity
+    pub vector_search: Option<VectorSearchCapabilities>,
 }
 // ANCHOR_END: QueryCapabilities
 
@@ -78,6 +89,64 @@ pub struct ExistsCapabilities {
 }
 // ANCHOR_END: ExistsCapabilities
 
+// ANCHOR: DistinctCapabilities
+#[skip_serializing_none]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(title = "Distinct Capabilities")]
+pub struct DistinctCapabilities {
+    /// Does the connector support returning distinct rows for a subset of
+    /// columns, as in DISTINCT ON
+    pub on: Option<LeafCapability>,
+}
+// ANCHOR_END: DistinctCapabilities
+
+// ANCHOR: PaginationCapabilities
+#[skip_serializing_none]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(title = "Pagination Capabilities")]
+pub struct PaginationCapabilities {
+    /// Does the connector support paginating rows using a cursor, as
+    /// returned in `next_cursor` and passed in `after`
+    pub cursor: Option<LeafCapability>,
+}
+// ANCHOR_END: PaginationCapabilities
+
+// ANCHOR: FullTextSearchCapabilities
+#[skip_serializing_none]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(title = "Full Text Search Capabilities")]
+pub struct FullTextSearchCapabilities {
+    /// Does the connector support ordering by the relevance score of a
+    /// full-text search
+    pub order_by_relevance: Option<LeafCapability>,
+}
+// ANCHOR_END: FullTextSearchCapabilities
+
+// ANCHOR: GeospatialCapabilities
+#[skip_serializing_none]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(title = "Geospatial Capabilities")]
+pub struct GeospatialCapabilities {
+    /// Does the connector support ordering by the distance between a
+    /// geospatial column and a value
+    pub order_by_distance: Option<LeafCapability>,
+}
+// ANCHOR_END: GeospatialCapabilities
+
+// ANCHOR: VectorSearchCapabilities
+#[skip_serializing_none]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(title = "Vector Search Capabilities")]
+pub struct VectorSearchCapabilities {
+    /// Does the connector support ordering by cosine similarity
+    pub cosine: Option<LeafCapability>,
+    /// Does the connector support ordering by Euclidean (L2) distance
+    pub l2: Option<LeafCapability>,
+    /// Does the connector support ordering by inner product
+    pub inner_product: Option<LeafCapability>,
+}
+// ANCHOR_END: VectorSearchCapabilities
+
 // ANCHOR: NestedFieldCapabilities
 #[skip_serializing_none]
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -129,6 +198,12 @@ pub struct AggregateCapabilities {
     pub filter_by: Option<LeafCapability>,
     /// Does the connector support aggregations over groups
     pub group_by: Option<GroupByCapabilities>,
+    /// Does the connector support restricting the rows of individual
+    /// aggregates with a predicate
+    pub filter: Option<LeafCapability>,
+    /// Does the connector support aggregating only the distinct values of a
+    /// column with an aggregate function
+    pub distinct: Option<LeafCapability>,
 }
 // ANCHOR_END: AggregateCapabilities
 
@@ -148,7 +223,7 @@ pub struct GroupByCapabilities {
 
 // ANCHOR: MutationCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Mutation Capabilities")]
 pub struct MutationCapabilities {
     /// Does the connector support executing multiple mutations in a transaction.
@@ -160,7 +235,7 @@ pub struct MutationCapabilities {
 
 // ANCHOR: RelationshipCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relationship Capabilities")]
 pub struct RelationshipCapabilities {
     /// Does the connector support comparisons that involve related collections (ie. joins)?
@@ -174,7 +249,7 @@ pub struct RelationshipCapabilities {
 
 // ANCHOR: NestedRelationshipCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Nested Relationship Capabilities")]
 pub struct NestedRelationshipCapabilities {
     /// Does the connector support navigating a relationship from inside a nested object inside a nested array
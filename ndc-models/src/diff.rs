@@ -0,0 +1,778 @@
+//! Semantic comparison of connector schemas.
+//!
+//! [`diff_schemas`] compares two [`SchemaResponse`]s and reports every
+//! collection, function, procedure, type, field, argument, operator, function
+//! and constraint which was added, removed or changed, classifying each change
+//! as breaking or non-breaking for existing clients. Descriptions are ignored.
+//!
+//! Types are compared by the values they admit. A change to the type of a
+//! value which is read by clients (a field, or a function or procedure result)
+//! is breaking if it admits new values, such as making a field nullable. A
+//! change to the type of a value written by clients (an argument, or a field of
+//! an object type used in an argument) is breaking if it rejects values which
+//! were previously accepted.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::path::at;
+use crate::validate::{describe_type, unwrap_nullable};
+use crate::{
+    ArgumentInfo, ArgumentName, CollectionInfo, ObjectField, ObjectType, ObjectTypeName,
+    ScalarTypeName, SchemaResponse, Type,
+};
+
+/// A difference between two schemas
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaChange {
+    /// The path to the changed item, such as
+    /// `["object_types", "article", "fields", "title"]`
+    pub path: Vec<String>,
+    pub kind: SchemaChangeKind,
+    /// Whether requests or responses which were valid for the old schema may
+    /// be invalid for the new schema
+    pub breaking: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaChangeKind {
+    Added,
+    Removed,
+    TypeChanged {
+        old: Type,
+        new: Type,
+    },
+    /// A definition changed in some way other than its type
+    Changed,
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = if self.breaking {
+            "breaking"
+        } else {
+            "non-breaking"
+        };
+        let path = self.path.join(".");
+        match &self.kind {
+            SchemaChangeKind::Added => write!(f, "{severity}: {path} was added"),
+            SchemaChangeKind::Removed => write!(f, "{severity}: {path} was removed"),
+            SchemaChangeKind::TypeChanged { old, new } => write!(
+                f,
+                "{severity}: the type of {path} changed from {} to {}",
+                describe_type(old),
+                describe_type(new)
+            ),
+            SchemaChangeKind::Changed => write!(f, "{severity}: {path} was changed"),
+        }
+    }
+}
+
+/// Compare two schemas, returning every change from `old` to `new`.
+///
+/// ```
+/// # use ndc_models::{diff::diff_schemas, SchemaResponse};
+/// let schema = SchemaResponse::default();
+/// assert!(diff_schemas(&schema, &schema).is_empty());
+/// ```
+pub fn diff_schemas(old: &SchemaResponse, new: &SchemaResponse) -> Vec<SchemaChange> {
+    let mut input_types = input_object_types(old);
+    input_types.extend(input_object_types(new));
+
+    let mut output_types = output_object_types(old);
+    output_types.extend(output_object_types(new));
+
+    let mut differ = Differ {
+        input_types,
+        output_types,
+        changes: vec![],
+    };
+    differ.schema(old, new);
+    differ.changes
+}
+
+/// Whether any changes are breaking
+pub fn is_breaking(changes: &[SchemaChange]) -> bool {
+    changes.iter().any(|change| change.breaking)
+}
+
+/// Whether every value admitted by `narrower` is admitted by `wider`
+fn admits(wider: &Type, narrower: &Type) -> bool {
+    match (wider, narrower) {
+        (Type::Named { name: a }, Type::Named { name: b }) => a == b,
+        (Type::Nullable { underlying_type }, narrower) => {
+            admits(underlying_type, unwrap_nullable(narrower))
+        }
+        (Type::Array { element_type: a }, Type::Array { element_type: b }) => admits(a, b),
+        (
+            Type::Predicate {
+                object_type_name: a,
+            },
+            Type::Predicate {
+                object_type_name: b,
+            },
+        ) => a == b,
+        _ => false,
+    }
+}
+
+fn count_scalar_type(schema: &SchemaResponse) -> Option<&ScalarTypeName> {
+    Some(
+        &schema
+            .capabilities
+            .as_ref()?
+            .query
+            .as_ref()?
+            .aggregates
+            .as_ref()?
+            .count_scalar_type,
+    )
+}
+
+/// The object types whose values may be written by clients: those reachable
+/// from the type of any argument
+fn input_object_types(schema: &SchemaResponse) -> BTreeSet<&ObjectTypeName> {
+    let request_arguments = schema.request_arguments.iter().flat_map(|arguments| {
+        arguments
+            .query_arguments
+            .values()
+            .chain(arguments.mutation_arguments.values())
+            .chain(arguments.relational_query_arguments.values())
+    });
+    let field_arguments = schema
+        .object_types
+        .values()
+        .flat_map(|object_type| object_type.fields.values())
+        .flat_map(|field| field.arguments.values());
+    let types = schema
+        .collections
+        .iter()
+        .flat_map(|c| c.arguments.values())
+        .chain(schema.functions.iter().flat_map(|f| f.arguments.values()))
+        .chain(schema.procedures.iter().flat_map(|p| p.arguments.values()))
+        .chain(request_arguments)
+        .chain(field_arguments)
+        .map(|argument| &argument.argument_type)
+        .collect();
+    reachable_object_types(schema, vec![], types)
+}
+
+/// The object types whose values may be read by clients: those reachable from
+/// the type of any collection, or the result type of any function or procedure
+fn output_object_types(schema: &SchemaResponse) -> BTreeSet<&ObjectTypeName> {
+    let names = schema
+        .collections
+        .iter()
+        .map(|c| &c.collection_type)
+        .collect();
+    let types = schema
+        .functions
+        .iter()
+        .map(|f| &f.result_type)
+        .chain(schema.procedures.iter().map(|p| &p.result_type))
+        .collect();
+    reachable_object_types(schema, names, types)
+}
+
+fn reachable_object_types<'s>(
+    schema: &'s SchemaResponse,
+    mut names: Vec<&'s ObjectTypeName>,
+    mut types: Vec<&'s Type>,
+) -> BTreeSet<&'s ObjectTypeName> {
+    let mut object_types = BTreeSet::new();
+    loop {
+        if let Some(name) = names.pop() {
+            if let Some((name, object_type)) = schema.object_types.get_key_value(name) {
+                if object_types.insert(name) {
+                    types.extend(object_type.fields.values().map(|field| &field.r#type));
+                }
+            }
+        } else if let Some(ty) = types.pop() {
+            match ty {
+                Type::Named { name } => {
+                    if let Some((name, _)) = schema.object_types.get_key_value(name.as_str()) {
+                        names.push(name);
+                    }
+                }
+                Type::Nullable { underlying_type } => types.push(underlying_type),
+                Type::Array { element_type } => types.push(element_type),
+                Type::Predicate { .. } => {}
+            }
+        } else {
+            return object_types;
+        }
+    }
+}
+
+struct Differ<'a> {
+    /// Object types whose values may be written by clients
+    input_types: BTreeSet<&'a ObjectTypeName>,
+    /// Object types whose values may be read by clients
+    output_types: BTreeSet<&'a ObjectTypeName>,
+    changes: Vec<SchemaChange>,
+}
+
+impl Differ<'_> {
+    fn change(&mut self, path: Vec<String>, kind: SchemaChangeKind, breaking: bool) {
+        self.changes.push(SchemaChange {
+            path,
+            kind,
+            breaking,
+        });
+    }
+
+    /// Report added and removed keys, and compare the values of common keys.
+    /// Removals are always breaking.
+    fn map<'v, K: Ord + fmt::Display, V>(
+        &mut self,
+        path: &[String],
+        old: &BTreeMap<K, &'v V>,
+        new: &BTreeMap<K, &'v V>,
+        added_is_breaking: impl Fn(&V) -> bool,
+        mut changed: impl FnMut(&mut Self, &[String], &'v V, &'v V),
+    ) {
+        for (key, old_value) in old {
+            let path = at(path, key);
+            match new.get(key) {
+                Some(new_value) => changed(self, &path, old_value, new_value),
+                None => self.change(path, SchemaChangeKind::Removed, true),
+            }
+        }
+        for (key, new_value) in new {
+            if !old.contains_key(key) {
+                let breaking = added_is_breaking(new_value);
+                self.change(at(path, key), SchemaChangeKind::Added, breaking);
+            }
+        }
+    }
+
+    /// Report a changed definition, which is always breaking
+    fn definition<V: PartialEq>(&mut self, path: &[String], old: &V, new: &V) {
+        if old != new {
+            self.change(path.to_vec(), SchemaChangeKind::Changed, true);
+        }
+    }
+
+    /// Compare the type of a value which is read by clients
+    fn output_type(&mut self, path: &[String], old: &Type, new: &Type) {
+        if old != new {
+            self.change(
+                path.to_vec(),
+                SchemaChangeKind::TypeChanged {
+                    old: old.clone(),
+                    new: new.clone(),
+                },
+                !admits(old, new),
+            );
+        }
+    }
+
+    /// Compare the type of a value which is written by clients
+    fn input_type(&mut self, path: &[String], old: &Type, new: &Type) {
+        if old != new {
+            self.change(
+                path.to_vec(),
+                SchemaChangeKind::TypeChanged {
+                    old: old.clone(),
+                    new: new.clone(),
+                },
+                !admits(new, old),
+            );
+        }
+    }
+
+    fn schema(&mut self, old: &SchemaResponse, new: &SchemaResponse) {
+        let path = vec!["$".to_string()];
+
+        self.map(
+            &at(&path, "scalar_types"),
+            &old.scalar_types.iter().collect(),
+            &new.scalar_types.iter().collect(),
+            |_| false,
+            |this, path, old, new| {
+                this.definition(
+                    &at(path, "representation"),
+                    &old.representation,
+                    &new.representation,
+                );
+                this.map(
+                    &at(path, "comparison_operators"),
+                    &old.comparison_operators.iter().collect(),
+                    &new.comparison_operators.iter().collect(),
+                    |_| false,
+                    Self::definition,
+                );
+                this.map(
+                    &at(path, "aggregate_functions"),
+                    &old.aggregate_functions.iter().collect(),
+                    &new.aggregate_functions.iter().collect(),
+                    |_| false,
+                    Self::definition,
+                );
+                this.map(
+                    &at(path, "extraction_functions"),
+                    &old.extraction_functions.iter().collect(),
+                    &new.extraction_functions.iter().collect(),
+                    |_| false,
+                    Self::definition,
+                );
+            },
+        );
+
+        self.map(
+            &at(&path, "object_types"),
+            &old.object_types.iter().collect(),
+            &new.object_types.iter().collect(),
+            |_| false,
+            Self::object_type,
+        );
+
+        self.map(
+            &at(&path, "collections"),
+            &old.collections.iter().map(|c| (&c.name, c)).collect(),
+            &new.collections.iter().map(|c| (&c.name, c)).collect(),
+            |_| false,
+            Self::collection,
+        );
+
+        self.map(
+            &at(&path, "functions"),
+            &old.functions.iter().map(|f| (&f.name, f)).collect(),
+            &new.functions.iter().map(|f| (&f.name, f)).collect(),
+            |_| false,
+            |this, path, old, new| {
+                this.arguments(&at(path, "arguments"), &old.arguments, &new.arguments);
+                this.output_type(&at(path, "result_type"), &old.result_type, &new.result_type);
+            },
+        );
+
+        self.map(
+            &at(&path, "procedures"),
+            &old.procedures.iter().map(|p| (&p.name, p)).collect(),
+            &new.procedures.iter().map(|p| (&p.name, p)).collect(),
+            |_| false,
+            |this, path, old, new| {
+                this.arguments(&at(path, "arguments"), &old.arguments, &new.arguments);
+                this.output_type(&at(path, "result_type"), &old.result_type, &new.result_type);
+            },
+        );
+
+        self.definition(
+            &at(&at(&path, "capabilities"), "count_scalar_type"),
+            &count_scalar_type(old),
+            &count_scalar_type(new),
+        );
+
+        let request_arguments = at(&path, "request_arguments");
+        let default = crate::RequestLevelArguments::default();
+        let old_arguments = old.request_arguments.as_ref().unwrap_or(&default);
+        let new_arguments = new.request_arguments.as_ref().unwrap_or(&default);
+        self.arguments(
+            &at(&request_arguments, "query_arguments"),
+            &old_arguments.query_arguments,
+            &new_arguments.query_arguments,
+        );
+        self.arguments(
+            &at(&request_arguments, "mutation_arguments"),
+            &old_arguments.mutation_arguments,
+            &new_arguments.mutation_arguments,
+        );
+        self.arguments(
+            &at(&request_arguments, "relational_query_arguments"),
+            &old_arguments.relational_query_arguments,
+            &new_arguments.relational_query_arguments,
+        );
+    }
+
+    fn object_type(&mut self, path: &[String], old: &ObjectType, new: &ObjectType) {
+        let is_in = |types: &BTreeSet<&ObjectTypeName>| {
+            path.last()
+                .is_some_and(|name| types.iter().any(|t| t.as_str() == name))
+        };
+        let input = is_in(&self.input_types);
+        // Object types which are neither read nor written are compared as if
+        // they were read
+        let output = is_in(&self.output_types) || !input;
+
+        self.map(
+            &at(path, "fields"),
+            &old.fields.iter().collect(),
+            &new.fields.iter().collect(),
+            // Values written by clients must now provide the new field
+            |field: &ObjectField| input && !matches!(field.r#type, Type::Nullable { .. }),
+            |this, path, old, new| {
+                if old.r#type != new.r#type {
+                    let breaking = (output && !admits(&old.r#type, &new.r#type))
+                        || (input && !admits(&new.r#type, &old.r#type));
+                    this.change(
+                        path.to_vec(),
+                        SchemaChangeKind::TypeChanged {
+                            old: old.r#type.clone(),
+                            new: new.r#type.clone(),
+                        },
+                        breaking,
+                    );
+                }
+                this.arguments(&at(path, "arguments"), &old.arguments, &new.arguments);
+            },
+        );
+
+        self.map(
+            &at(path, "foreign_keys"),
+            &old.foreign_keys.iter().collect(),
+            &new.foreign_keys.iter().collect(),
+            |_| false,
+            Self::definition,
+        );
+    }
+
+    fn collection(&mut self, path: &[String], old: &CollectionInfo, new: &CollectionInfo) {
+        if old.collection_type != new.collection_type {
+            self.output_type(
+                &at(path, "type"),
+                &Type::Named {
+                    name: old.collection_type.inner().clone(),
+                },
+                &Type::Named {
+                    name: new.collection_type.inner().clone(),
+                },
+            );
+        }
+        self.arguments(&at(path, "arguments"), &old.arguments, &new.arguments);
+
+        self.map(
+            &at(path, "uniqueness_constraints"),
+            &old.uniqueness_constraints.iter().collect(),
+            &new.uniqueness_constraints.iter().collect(),
+            |_| false,
+            Self::definition,
+        );
+
+        // Allowing a mutation is not breaking, but disallowing one is
+        let mutations_path = at(path, "relational_mutations");
+        let flags = |info: Option<&crate::RelationalMutationInfo>| {
+            info.map_or([false; 3], |info| {
+                [info.insertable, info.updatable, info.deletable]
+            })
+        };
+        let old_flags = flags(old.relational_mutations.as_ref());
+        let new_flags = flags(new.relational_mutations.as_ref());
+        for ((name, was_allowed), is_allowed) in ["insertable", "updatable", "deletable"]
+            .into_iter()
+            .zip(old_flags)
+            .zip(new_flags)
+        {
+            if was_allowed != is_allowed {
+                self.change(
+                    at(&mutations_path, name),
+                    SchemaChangeKind::Changed,
+                    was_allowed,
+                );
+            }
+        }
+    }
+
+    fn arguments(
+        &mut self,
+        path: &[String],
+        old: &BTreeMap<ArgumentName, ArgumentInfo>,
+        new: &BTreeMap<ArgumentName, ArgumentInfo>,
+    ) {
+        self.map(
+            path,
+            &old.iter().collect(),
+            &new.iter().collect(),
+            // Requests must now provide a value for a new required argument
+            |argument: &ArgumentInfo| !matches!(argument.argument_type, Type::Nullable { .. }),
+            |this, path, old, new| {
+                this.input_type(path, &old.argument_type, &new.argument_type);
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema(value: serde_json::Value) -> SchemaResponse {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn changes(old: &SchemaResponse, new: &SchemaResponse) -> Vec<String> {
+        diff_schemas(old, new)
+            .into_iter()
+            .map(|change| change.to_string())
+            .collect()
+    }
+
+    fn base() -> serde_json::Value {
+        serde_json::json!({
+            "scalar_types": {
+                "Int": {
+                    "representation": { "type": "int32" },
+                    "aggregate_functions": {
+                        "max": { "type": "max" },
+                    },
+                    "comparison_operators": {
+                        "eq": { "type": "equal" },
+                        "lt": { "type": "less_than" },
+                    },
+                },
+            },
+            "object_types": {
+                "article": {
+                    "fields": {
+                        "id": { "type": { "type": "named", "name": "Int" } },
+                        "author_id": { "type": { "type": "named", "name": "Int" } },
+                    },
+                    "foreign_keys": {},
+                },
+                "article_input": {
+                    "fields": {
+                        "author_id": { "type": { "type": "named", "name": "Int" } },
+                    },
+                    "foreign_keys": {},
+                },
+            },
+            "collections": [
+                {
+                    "name": "articles",
+                    "arguments": {},
+                    "type": "article",
+                    "uniqueness_constraints": {},
+                },
+            ],
+            "functions": [],
+            "procedures": [
+                {
+                    "name": "insert_article",
+                    "arguments": {
+                        "article": { "type": { "type": "named", "name": "article_input" } },
+                    },
+                    "result_type": { "type": "named", "name": "article" },
+                },
+            ],
+        })
+    }
+
+    #[test]
+    fn test_identical_schemas() {
+        let schema = schema(base());
+        assert_eq!(changes(&schema, &schema), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_breaking_changes() {
+        let old = schema(base());
+        let mut new = base();
+        // Readers may now see null authors
+        new["object_types"]["article"]["fields"]["author_id"]["type"] = serde_json::json!({
+            "type": "nullable",
+            "underlying_type": { "type": "named", "name": "Int" },
+        });
+        // Writers must now provide a title
+        new["object_types"]["article_input"]["fields"]["title"] =
+            serde_json::json!({ "type": { "type": "named", "name": "Int" } });
+        new["scalar_types"]["Int"]["comparison_operators"]
+            .as_object_mut()
+            .unwrap()
+            .remove("lt");
+        new["collections"][0]["arguments"]["limit"] =
+            serde_json::json!({ "type": { "type": "named", "name": "Int" } });
+        let new = schema(new);
+
+        assert_eq!(
+            changes(&old, &new),
+            vec![
+                "breaking: $.scalar_types.Int.comparison_operators.lt was removed",
+                "breaking: the type of $.object_types.article.fields.author_id changed from Int to nullable Int",
+                "breaking: $.object_types.article_input.fields.title was added",
+                "breaking: $.collections.articles.arguments.limit was added",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_non_breaking_changes() {
+        let old = schema(base());
+        let mut new = base();
+        // Writers may now omit the author
+        new["object_types"]["article_input"]["fields"]["author_id"]["type"] = serde_json::json!({
+            "type": "nullable",
+            "underlying_type": { "type": "named", "name": "Int" },
+        });
+        new["object_types"]["article"]["fields"]["title"] =
+            serde_json::json!({ "type": { "type": "named", "name": "Int" } });
+        new["scalar_types"]["Int"]["aggregate_functions"]["min"] =
+            serde_json::json!({ "type": "min" });
+        new["collections"][0]["arguments"]["limit"] = serde_json::json!({
+            "type": {
+                "type": "nullable",
+                "underlying_type": { "type": "named", "name": "Int" },
+            },
+        });
+        let new = schema(new);
+
+        let changes = diff_schemas(&old, &new);
+        assert!(!is_breaking(&changes));
+        assert_eq!(
+            changes
+                .into_iter()
+                .map(|change| change.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "non-breaking: $.scalar_types.Int.aggregate_functions.min was added",
+                "non-breaking: $.object_types.article.fields.title was added",
+                "non-breaking: the type of $.object_types.article_input.fields.author_id changed from Int to nullable Int",
+                "non-breaking: $.collections.articles.arguments.limit was added",
+            ]
+        );
+    }
+
+    fn nullable(name: &str) -> serde_json::Value {
+        serde_json::json!({
+            "type": "nullable",
+            "underlying_type": { "type": "named", "name": name },
+        })
+    }
+
+    #[test]
+    fn test_removed_and_retyped_fields() {
+        let old = schema(base());
+        let mut new = base();
+        new["object_types"]["article"]["fields"]
+            .as_object_mut()
+            .unwrap()
+            .remove("author_id");
+        new["object_types"]["article"]["fields"]["id"]["type"] =
+            serde_json::json!({ "type": "named", "name": "String" });
+        new["object_types"]["article_input"]["fields"]
+            .as_object_mut()
+            .unwrap()
+            .remove("author_id");
+        let new = schema(new);
+
+        assert_eq!(
+            changes(&old, &new),
+            vec![
+                "breaking: $.object_types.article.fields.author_id was removed",
+                "breaking: the type of $.object_types.article.fields.id changed from Int to String",
+                "breaking: $.object_types.article_input.fields.author_id was removed",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nullability_of_output_types() {
+        let mut old = base();
+        old["object_types"]["article"]["fields"]["author_id"]["type"] = nullable("Int");
+        old["procedures"][0]["result_type"] = nullable("article");
+        let old = schema(old);
+
+        // Narrowing a value which is read by clients is not breaking
+        let narrowed = schema(base());
+        assert_eq!(
+            changes(&old, &narrowed),
+            vec![
+                "non-breaking: the type of $.object_types.article.fields.author_id changed from nullable Int to Int",
+                "non-breaking: the type of $.procedures.insert_article.result_type changed from nullable article to article",
+            ]
+        );
+
+        // Widening it is breaking
+        assert_eq!(
+            changes(&narrowed, &old),
+            vec![
+                "breaking: the type of $.object_types.article.fields.author_id changed from Int to nullable Int",
+                "breaking: the type of $.procedures.insert_article.result_type changed from article to nullable article",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nullability_of_input_types() {
+        let mut old = base();
+        old["object_types"]["article_input"]["fields"]["author_id"]["type"] = nullable("Int");
+        old["procedures"][0]["arguments"]["article"]["type"] = nullable("article_input");
+        let old = schema(old);
+
+        // Narrowing a value which is written by clients is breaking
+        let narrowed = schema(base());
+        assert_eq!(
+            changes(&old, &narrowed),
+            vec![
+                "breaking: the type of $.object_types.article_input.fields.author_id changed from nullable Int to Int",
+                "breaking: the type of $.procedures.insert_article.arguments.article changed from nullable article_input to article_input",
+            ]
+        );
+
+        // Widening it is not breaking
+        assert!(!is_breaking(&diff_schemas(&narrowed, &old)));
+    }
+
+    #[test]
+    fn test_operator_and_aggregate_changes() {
+        let old = schema(base());
+        let mut new = base();
+        new["scalar_types"]["Int"]["aggregate_functions"]
+            .as_object_mut()
+            .unwrap()
+            .remove("max");
+        new["scalar_types"]["Int"]["comparison_operators"]["eq"] = serde_json::json!({
+            "type": "custom",
+            "argument_type": { "type": "named", "name": "Int" },
+        });
+        new["scalar_types"]["Int"]["comparison_operators"]["gt"] =
+            serde_json::json!({ "type": "greater_than" });
+        let new = schema(new);
+
+        assert_eq!(
+            changes(&old, &new),
+            vec![
+                "breaking: $.scalar_types.Int.comparison_operators.eq was changed",
+                "non-breaking: $.scalar_types.Int.comparison_operators.gt was added",
+                "breaking: $.scalar_types.Int.aggregate_functions.max was removed",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_argument_changes() {
+        let mut old = base();
+        old["collections"][0]["arguments"]["limit"] =
+            serde_json::json!({ "type": { "type": "named", "name": "Int" } });
+        old["collections"][0]["arguments"]["offset"] =
+            serde_json::json!({ "type": { "type": "named", "name": "Int" } });
+        let old = schema(old);
+
+        let mut new = base();
+        // Requests may now omit the limit, and can no longer provide an offset
+        new["collections"][0]["arguments"]["limit"] =
+            serde_json::json!({ "type": nullable("Int") });
+        new["procedures"][0]["arguments"]["article"]["type"] =
+            serde_json::json!({ "type": "named", "name": "article" });
+        new["procedures"][0]["arguments"]["dry_run"] =
+            serde_json::json!({ "type": nullable("Bool") });
+        let new = schema(new);
+
+        assert_eq!(
+            changes(&old, &new),
+            vec![
+                "non-breaking: the type of $.collections.articles.arguments.limit changed from Int to nullable Int",
+                "breaking: $.collections.articles.arguments.offset was removed",
+                "breaking: the type of $.procedures.insert_article.arguments.article changed from article_input to article",
+                "non-breaking: $.procedures.insert_article.arguments.dry_run was added",
+            ]
+        );
+        assert_eq!(
+            changes(&new, &old),
+            vec![
+                "breaking: the type of $.collections.articles.arguments.limit changed from nullable Int to Int",
+                "breaking: $.collections.articles.arguments.offset was added",
+                "breaking: the type of $.procedures.insert_article.arguments.article changed from article to article_input",
+                "breaking: $.procedures.insert_article.arguments.dry_run was removed",
+            ]
+        );
+    }
+}
@@ -1,5 +1,6 @@
 mod aggregation;
 pub use aggregation::*;
+mod calendar;
 mod capabilities;
 pub use capabilities::*;
 mod expression;
@@ -8,8 +9,10 @@ mod fields;
 pub use fields::*;
 mod names;
 pub use names::*;
+mod normalize;
 mod ordering;
 pub use ordering::*;
+mod path;
 mod relational_query;
 pub use relational_query::*;
 mod relational_mutation;
@@ -18,6 +21,21 @@ mod requests;
 pub use requests::*;
 mod schema;
 pub use schema::*;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod builders;
+pub mod diff;
+pub mod lower;
+pub mod optimize;
+pub mod required_capabilities;
+#[cfg(feature = "sql")]
+pub mod sql;
+pub mod typecheck;
+pub mod validate;
+pub mod value;
+pub mod version;
+pub mod visit;
+pub mod visit_mut;
 
 pub const VERSION_HEADER_NAME: &str = "X-Hasura-NDC-Version";
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
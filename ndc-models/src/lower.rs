@@ -0,0 +1,3185 @@
+//! Lowering of query requests into relational plans.
+//!
+//! [`lower_query_request`] translates a [`QueryRequest`] into [`Relation`]s
+//! which compute the same rows, aggregates and groups, so that a connector
+//! which only implements the relational API can serve ordinary queries too.
+//!
+//! The translation follows the shape of a [`Query`]:
+//!
+//! * the collection and its arguments become a `From` relation,
+//! * `predicate` becomes a `Filter`, with top-level `EXISTS` predicates
+//!   becoming semi-joins (or anti-joins, when negated), and other `EXISTS`
+//!   predicates and comparisons against aggregates becoming left joins of
+//!   aggregates of the related rows,
+//! * object relationships used by fields, orderings and dimensions become
+//!   left joins, made once for each relationship path,
+//! * `order_by` becomes a `Sort`, `after` a `Filter` on the ordering, and
+//!   `limit` and `offset` a `Paginate`,
+//! * `distinct` becomes a `Filter` keeping the first row of each partition
+//!   of a `Window`,
+//! * `fields` becomes a `Project` of the paginated rows, and any other
+//!   relationship fields become plans of their own, which start with the
+//!   columns of the relationship so that they can be matched with the rows,
+//! * `aggregates` becomes an `Aggregate` of the paginated rows, and
+//! * `groups` becomes an `Aggregate` grouped by the dimensions, followed by
+//!   the grouping's own `Filter`, `Sort` and `Paginate`.
+//!
+//! Filtered aggregates aggregate a `CASE` expression which is null for the
+//! rows excluded by the filter, and distinct aggregates without a relational
+//! equivalent aggregate only the first row of each value of a `Window`.
+//!
+//! Anything without a relational equivalent, such as variables, nested field
+//! selections or custom operators, is reported as a [`LoweringError`] rather
+//! than approximated.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use indexmap::IndexMap;
+
+use crate::calendar;
+use crate::path::at;
+use crate::validate::{
+    aggregate_function_result_type, unwrap_nullable, value_object_type, ValidationErrorKind,
+};
+use crate::{
+    Aggregate, AggregateFunctionDefinition, Argument, ArgumentName, CaseWhen, CollectionName,
+    ComparisonOperatorDefinition, ComparisonOperatorName, ComparisonTarget, ComparisonValue,
+    DatePartUnit, Dimension, Distinct, ExistsInCollection, Expression,
+    ExtractionFunctionDefinition, Field, FieldName, GroupComparisonTarget, GroupComparisonValue,
+    GroupExpression, GroupOrderBy, GroupOrderByTarget, Grouping, JoinOn, JoinType, NullsSort,
+    ObjectType, OrderBy, OrderByTarget, OrderDirection, PathElement, Query, QueryRequest, Relation,
+    RelationalExpression, RelationalLiteral, Relationship, RelationshipArgument, RelationshipName,
+    RelationshipType, ScalarType, ScalarTypeName, SchemaResponse, Sort, Type, TypeRepresentation,
+    UnaryComparisonOperator,
+};
+
+/// An error found while lowering a request, along with the JSON path
+/// of the offending part of the request.
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+#[error("{kind} at path {}", .path.join("."))]
+pub struct LoweringError {
+    /// The path to the offending value, starting with `$` for the request itself
+    pub path: Vec<String>,
+    pub kind: LoweringErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum LoweringErrorKind {
+    /// The request is not valid for the schema
+    #[error(transparent)]
+    Invalid(#[from] ValidationErrorKind),
+    #[error("argument {0} is not defined")]
+    ArgumentIsNotDefined(ArgumentName),
+    /// The cursor is not an array of the ordering values and unique key of a row
+    #[error("invalid cursor")]
+    InvalidCursor,
+    /// The request is valid, but has no equivalent relational plan
+    #[error("{0} cannot be lowered to a relation")]
+    Unsupported(&'static str),
+}
+
+/// The relational plans equivalent to a query request.
+///
+/// The relations of the query of a relationship field start with one column
+/// for each column of the relationship, which identify the row (or rows) of
+/// the enclosing query they belong to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoweredQuery {
+    /// The selected rows, if the query requested `fields`
+    pub rows: Option<LoweredRows>,
+    /// A single row of aggregates, if the query requested `aggregates`. For a
+    /// relationship field, there is one row for each related row of the
+    /// enclosing query which has related rows: counts over a missing row are
+    /// zero, and other aggregates are null.
+    pub aggregates: Option<LoweredAggregates>,
+    /// One row per group, if the query requested `groups`
+    pub groups: Option<LoweredGroups>,
+}
+
+/// A relation producing the rows of a query
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoweredRows {
+    pub relation: Relation,
+    /// Where each column of the relation belongs in a response row: the name
+    /// of a field, preceded by the names of any object relationship fields
+    /// it is nested in. A missing related row produces null columns.
+    pub columns: Vec<Vec<FieldName>>,
+    /// The relationship fields which are not part of the relation
+    pub relationships: Vec<LoweredRelationship>,
+    /// The columns of the relation which make up `next_cursor` for a row, as
+    /// a JSON array: the ordering values followed by the columns of the first
+    /// uniqueness constraint of the collection. Empty unless the query was
+    /// paginated with `limit` or `after` over a collection with a uniqueness
+    /// constraint.
+    pub cursor: Vec<usize>,
+}
+
+/// A relationship field which is lowered to plans of its own
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoweredRelationship {
+    /// The name of the field, preceded by the names of any object
+    /// relationship fields it is nested in
+    pub field: Vec<FieldName>,
+    /// The columns of the enclosing rows whose values are equal to the
+    /// leading columns of the related plans, one for each column of the
+    /// relationship
+    pub keys: Vec<usize>,
+    pub query: LoweredQuery,
+}
+
+/// A relation producing a single row, with one column per aggregate
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoweredAggregates {
+    pub relation: Relation,
+    pub columns: Vec<FieldName>,
+}
+
+/// A relation producing one row per group, with a column for each dimension
+/// followed by a column for each aggregate
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoweredGroups {
+    pub relation: Relation,
+    pub dimensions: usize,
+    pub aggregates: Vec<FieldName>,
+}
+
+/// Lower a query request into relational plans, returning every part of the
+/// request which could not be lowered if there are any.
+///
+/// Requests should be validated with
+/// [`validate_query_request`](crate::validate::validate_query_request) first:
+/// lowering reports names which are not defined, but does not type-check the
+/// request in full.
+pub fn lower_query_request(
+    schema: &SchemaResponse,
+    request: &QueryRequest,
+) -> Result<LoweredQuery, Vec<LoweringError>> {
+    let mut lowering = Lowering {
+        schema,
+        collection_relationships: &request.collection_relationships,
+        errors: vec![],
+    };
+
+    let path = vec!["$".to_string()];
+
+    if request.variables.is_some() {
+        lowering.unsupported(&at(&path, "variables"), "a request with variables");
+    }
+
+    let mut arguments = BTreeMap::new();
+    for (name, argument) in &request.arguments {
+        match argument {
+            Argument::Literal { value } => {
+                arguments.insert(name, value);
+            }
+            Argument::Variable { .. } => {
+                lowering.unsupported(&at(&at(&path, "arguments"), name), "a variable argument");
+            }
+        }
+    }
+
+    let lowered = lowering
+        .source(&request.collection, &arguments, &at(&path, "collection"))
+        .map(|rows| lowering.query(rows, &request.query, &[], &at(&path, "query")));
+
+    match lowered {
+        Some(lowered) if lowering.errors.is_empty() => Ok(lowered),
+        _ => Err(lowering.errors),
+    }
+}
+
+/// A relation under construction, along with the collections whose columns
+/// it contains
+#[derive(Clone)]
+struct Rows<'a> {
+    relation: Relation,
+    /// The collections joined into the relation, starting with the root collection
+    tables: Vec<Table<'a>>,
+    /// The number of columns in the relation
+    width: usize,
+    /// The columns of the first uniqueness constraint of the root collection
+    unique_columns: Vec<FieldName>,
+    /// The aggregates joined into the relation, and the index of their first
+    /// aggregate column
+    aggregate_joins: Vec<(Vec<JoinOn>, Relation, usize)>,
+    /// The window functions added to the relation by aggregates, and their
+    /// columns. Rows are never filtered after these are added.
+    windows: Vec<(RelationalExpression, usize)>,
+}
+
+#[derive(Clone)]
+struct Table<'a> {
+    /// The relationships navigated from the root collection to this
+    /// collection, as normalized by [`join_key`]
+    path: Vec<PathElement>,
+    object_type: Cow<'a, ObjectType>,
+    /// The index of the collection's first column in the relation
+    offset: usize,
+}
+
+impl Rows<'_> {
+    fn wrap(&mut self, f: impl FnOnce(Relation) -> Relation) {
+        let input = std::mem::replace(&mut self.relation, Relation::Union { relations: vec![] });
+        self.relation = f(input);
+    }
+
+    fn filter(&mut self, predicate: RelationalExpression) {
+        self.wrap(|input| Relation::Filter {
+            input: input.into(),
+            predicate,
+        });
+    }
+
+    /// Append a window function column, and return the column
+    fn window(&mut self, expr: RelationalExpression) -> RelationalExpression {
+        let index = self.width;
+        self.wrap(|input| Relation::Window {
+            input: input.into(),
+            exprs: vec![expr],
+        });
+        self.width += 1;
+        RelationalExpression::Column {
+            index: index as u64,
+        }
+    }
+
+    /// Append a window function column for an aggregate, reusing an
+    /// identical one, and return the column
+    fn aggregate_window(&mut self, expr: RelationalExpression) -> RelationalExpression {
+        if let Some((_, index)) = self.windows.iter().find(|(e, _)| *e == expr) {
+            return RelationalExpression::Column {
+                index: *index as u64,
+            };
+        }
+        let column = self.window(expr.clone());
+        self.windows.push((expr, self.width - 1));
+        column
+    }
+
+    /// Left join an aggregate of `target` grouped by its columns in `on`,
+    /// reusing an identical join, and return the index of the first
+    /// aggregate column
+    fn join_aggregate(
+        &mut self,
+        target: Relation,
+        on: Vec<JoinOn>,
+        aggregates: Vec<RelationalExpression>,
+    ) -> usize {
+        // Group the related rows by their join columns, and join on those
+        let (on, group_by): (Vec<_>, Vec<_>) = on
+            .into_iter()
+            .enumerate()
+            .map(|(index, JoinOn { left, right })| {
+                (
+                    JoinOn {
+                        left,
+                        right: RelationalExpression::Column {
+                            index: index as u64,
+                        },
+                    },
+                    right,
+                )
+            })
+            .unzip();
+        let width = group_by.len() + aggregates.len();
+        let right = Relation::Aggregate {
+            input: target.into(),
+            group_by,
+            aggregates,
+        };
+        if let Some((_, _, index)) = self
+            .aggregate_joins
+            .iter()
+            .find(|(o, r, _)| *o == on && *r == right)
+        {
+            return *index;
+        }
+
+        let index = self.width + on.len();
+        self.aggregate_joins
+            .push((on.clone(), right.clone(), index));
+        self.wrap(|left| Relation::Join {
+            left: left.into(),
+            right: right.into(),
+            on,
+            join_type: JoinType::Left,
+        });
+        self.width += width;
+        index
+    }
+}
+
+/// A relationship field lowered to plans of its own, once the enclosing rows
+/// are complete
+struct PendingRelationship<'a, 'q> {
+    field: Vec<FieldName>,
+    /// The table of the enclosing rows the relationship starts from
+    table: usize,
+    relationship: &'a Relationship,
+    arguments: &'q BTreeMap<ArgumentName, RelationshipArgument>,
+    query: &'q Query,
+    path: Vec<String>,
+}
+
+/// The right-hand side of a comparison
+enum Operand<'r> {
+    Scalar(&'r serde_json::Value),
+    Expression(RelationalExpression),
+}
+
+struct Lowering<'a> {
+    schema: &'a SchemaResponse,
+    collection_relationships: &'a BTreeMap<RelationshipName, Relationship>,
+    errors: Vec<LoweringError>,
+}
+
+impl<'a> Lowering<'a> {
+    fn error(&mut self, path: &[String], kind: impl Into<LoweringErrorKind>) {
+        self.errors.push(LoweringError {
+            path: path.to_vec(),
+            kind: kind.into(),
+        });
+    }
+
+    fn unsupported(&mut self, path: &[String], construct: &'static str) {
+        self.error(path, LoweringErrorKind::Unsupported(construct));
+    }
+
+    /// Lower a query of the rows. For the query of a relationship field,
+    /// `keys` are the columns of the relationship, which every relation
+    /// starts with, and which partition the rows for pagination.
+    fn query(
+        &mut self,
+        mut rows: Rows<'a>,
+        query: &Query,
+        keys: &[RelationalExpression],
+        path: &[String],
+    ) -> LoweredQuery {
+        if let Some(predicate) = &query.predicate {
+            self.filter(&mut rows, predicate, &at(path, "predicate"));
+        }
+
+        // Every join is added before sorting and pagination, so that the
+        // joins cannot change the order or number of rows
+        let mut projection = vec![];
+        let mut columns = vec![];
+        let mut relationships = vec![];
+        if let Some(fields) = &query.fields {
+            self.fields(
+                &mut rows,
+                &[],
+                &[],
+                fields,
+                &at(path, "fields"),
+                &mut projection,
+                &mut columns,
+                &mut relationships,
+            );
+        }
+        let mut sorts = query
+            .order_by
+            .as_ref()
+            .map(|order_by| {
+                self.order_by(
+                    &mut rows,
+                    order_by,
+                    query.after.is_some(),
+                    &at(path, "order_by"),
+                )
+            })
+            .unwrap_or_default();
+        let dimensions = query.groups.as_ref().map(|grouping| {
+            self.dimensions(
+                &mut rows,
+                &grouping.dimensions,
+                &at(&at(path, "groups"), "dimensions"),
+            )
+        });
+
+        if let Some(distinct) = &query.distinct {
+            let path = at(path, "distinct");
+            let on = match distinct {
+                Distinct::Fields if !relationships.is_empty() => {
+                    self.unsupported(&path, "distinct rows with relationship fields");
+                    None
+                }
+                Distinct::Fields => Some(projection.clone()),
+                Distinct::On { columns } => columns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, column)| {
+                        self.column(
+                            &rows.tables[0],
+                            column,
+                            &[],
+                            &BTreeMap::new(),
+                            &at(&at(&path, "columns"), &i),
+                        )
+                        .map(|(expr, _)| expr)
+                    })
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .collect::<Option<Vec<_>>>(),
+            };
+            // Keep the first row of each set of equal rows, in order
+            if let Some(on) = on {
+                let row_number = rows.window(RelationalExpression::RowNumber {
+                    order_by: sorts.iter().map(|(sort, _)| sort.clone()).collect(),
+                    partition_by: keys.iter().cloned().chain(on).collect(),
+                });
+                rows.filter(RelationalExpression::Eq {
+                    left: Box::new(row_number),
+                    right: Box::new(int64(1)),
+                });
+            }
+        }
+
+        // A cursor identifies a row by its ordering values, made unique by
+        // the columns of a uniqueness constraint
+        let paginated = query.limit.is_some() || query.after.is_some();
+        let has_cursor = paginated && !rows.unique_columns.is_empty();
+        if has_cursor {
+            for column in rows.unique_columns.clone() {
+                let column = self.column(&rows.tables[0], &column, &[], &BTreeMap::new(), path);
+                sorts.extend(column.map(|(expr, ty)| (sort(expr, OrderDirection::Asc), Some(ty))));
+            }
+        }
+        if let Some(after) = &query.after {
+            let path = at(path, "after");
+            if has_cursor {
+                self.after(&mut rows, &sorts, after, &path);
+            } else {
+                self.unsupported(
+                    &path,
+                    "cursor pagination of a collection without a uniqueness constraint",
+                );
+            }
+        }
+
+        let sorts = sorts.into_iter().map(|(sort, _)| sort).collect::<Vec<_>>();
+        if (query.limit.is_some() || query.offset.is_some()) && !keys.is_empty() {
+            // Paginate the rows related to each row separately
+            let row_number = rows.window(RelationalExpression::RowNumber {
+                order_by: sorts.clone(),
+                partition_by: keys.to_vec(),
+            });
+            let skip = query.offset.map_or(0, i64::from);
+            let mut predicate = RelationalExpression::Gt {
+                left: Box::new(row_number.clone()),
+                right: Box::new(int64(skip)),
+            };
+            if let Some(limit) = query.limit {
+                predicate = and(
+                    predicate,
+                    RelationalExpression::LtEq {
+                        left: Box::new(row_number),
+                        right: Box::new(int64(skip + i64::from(limit))),
+                    },
+                );
+            }
+            rows.filter(predicate);
+        }
+        if !sorts.is_empty() {
+            let exprs = sorts.clone();
+            rows.wrap(|input| Relation::Sort {
+                input: input.into(),
+                exprs,
+            });
+        }
+        if (query.limit.is_some() || query.offset.is_some()) && keys.is_empty() {
+            rows.wrap(|input| Relation::Paginate {
+                input: input.into(),
+                fetch: query.limit.map(u64::from),
+                skip: query.offset.map_or(0, u64::from),
+            });
+        }
+
+        let aggregates = query.aggregates.as_ref().map(|aggregates| {
+            let path = at(path, "aggregates");
+            let mut input = rows.clone();
+            let exprs = aggregates
+                .iter()
+                .filter_map(|(alias, aggregate)| {
+                    self.aggregate(&mut input, keys, aggregate, &at(&path, alias))
+                })
+                .collect();
+            LoweredAggregates {
+                relation: Relation::Aggregate {
+                    input: input.relation.into(),
+                    group_by: keys.to_vec(),
+                    aggregates: exprs,
+                },
+                columns: aggregates.keys().cloned().collect(),
+            }
+        });
+
+        let groups = query
+            .groups
+            .as_ref()
+            .zip(dimensions)
+            .map(|(grouping, dimensions)| {
+                self.groups(
+                    rows.clone(),
+                    grouping,
+                    keys,
+                    dimensions,
+                    &at(path, "groups"),
+                )
+            });
+
+        let rows = query.fields.as_ref().map(|_| {
+            // The keys and cursor of the rows follow the selected columns
+            let mut exprs = keys.to_vec();
+            exprs.append(&mut projection);
+            let relationships = relationships
+                .iter()
+                .filter_map(|pending| self.relationship_field(&rows, pending, &mut exprs))
+                .collect();
+            let cursor = if has_cursor {
+                sorts
+                    .into_iter()
+                    .map(|Sort { expr, .. }| {
+                        exprs.push(expr);
+                        exprs.len() - 1
+                    })
+                    .collect()
+            } else {
+                vec![]
+            };
+            LoweredRows {
+                relation: Relation::Project {
+                    input: rows.relation.into(),
+                    exprs,
+                },
+                columns,
+                relationships,
+                cursor,
+            }
+        });
+
+        LoweredQuery {
+            rows,
+            aggregates,
+            groups,
+        }
+    }
+
+    /// Lower a relationship field to plans of its own, which are restricted
+    /// to the rows related to the enclosing rows, and add the columns of the
+    /// enclosing rows which identify the related rows to the projection
+    fn relationship_field(
+        &mut self,
+        rows: &Rows<'a>,
+        pending: &PendingRelationship<'a, '_>,
+        projection: &mut Vec<RelationalExpression>,
+    ) -> Option<LoweredRelationship> {
+        let PendingRelationship {
+            field,
+            table,
+            relationship,
+            arguments,
+            query,
+            path,
+        } = pending;
+        let mut target = self.relationship_source(relationship, arguments, None, path)?;
+        let on = self.join_on(rows, *table, &target, relationship, path);
+
+        let enclosing = rows.relation.clone();
+        let semi_join_on = on
+            .iter()
+            .map(|JoinOn { left, right }| JoinOn {
+                left: right.clone(),
+                right: left.clone(),
+            })
+            .collect();
+        target.wrap(|input| Relation::Join {
+            left: input.into(),
+            right: enclosing.into(),
+            on: semi_join_on,
+            join_type: JoinType::LeftSemi,
+        });
+
+        let (keys, related): (Vec<_>, Vec<_>) = on
+            .into_iter()
+            .map(|JoinOn { left, right }| {
+                projection.push(left);
+                (projection.len() - 1, right)
+            })
+            .unzip();
+        let query = self.query(target, query, &related, &at(path, "query"));
+        Some(LoweredRelationship {
+            field: field.clone(),
+            keys,
+            query,
+        })
+    }
+
+    /// Filter the sorted rows to those after the row identified by a cursor
+    fn after(
+        &mut self,
+        rows: &mut Rows<'a>,
+        sorts: &[(Sort, Option<Type>)],
+        after: &str,
+        path: &[String],
+    ) {
+        let values = match serde_json::from_str::<Vec<serde_json::Value>>(after) {
+            Ok(values) if values.len() == sorts.len() => values,
+            _ => {
+                self.error(path, LoweringErrorKind::InvalidCursor);
+                return;
+            }
+        };
+
+        // A row is after the cursor if it is after it in the first ordering,
+        // or equal in the first ordering and after it in the rest
+        let mut predicate = None;
+        for ((sort, ty), value) in sorts.iter().zip(&values).rev() {
+            let Some(literal) = ty.as_ref().and_then(|ty| self.literal(ty, value, path)) else {
+                return;
+            };
+            let expr = Box::new(sort.expr.clone());
+            let is_null = RelationalExpression::IsNull { expr: expr.clone() };
+            // Nulls are ordered before any other value
+            let (later, equal) = match (literal, sort.direction) {
+                (RelationalLiteral::Null, OrderDirection::Asc) => {
+                    (RelationalExpression::IsNotNull { expr }, is_null)
+                }
+                (RelationalLiteral::Null, OrderDirection::Desc) => (boolean(false), is_null),
+                (literal, direction) => {
+                    let value = Box::new(RelationalExpression::Literal { literal });
+                    let later = match direction {
+                        OrderDirection::Asc => RelationalExpression::Gt {
+                            left: expr.clone(),
+                            right: value.clone(),
+                        },
+                        OrderDirection::Desc => or(
+                            RelationalExpression::Lt {
+                                left: expr.clone(),
+                                right: value.clone(),
+                            },
+                            is_null,
+                        ),
+                    };
+                    (
+                        later,
+                        RelationalExpression::Eq {
+                            left: expr,
+                            right: value,
+                        },
+                    )
+                }
+            };
+            predicate = Some(match predicate {
+                None => later,
+                Some(rest) => or(later, and(equal, rest)),
+            });
+        }
+        if let Some(predicate) = predicate {
+            rows.filter(predicate);
+        }
+    }
+
+    /// A `From` relation selecting every column of a collection
+    fn source(
+        &mut self,
+        collection_name: &CollectionName,
+        arguments: &BTreeMap<&ArgumentName, &serde_json::Value>,
+        path: &[String],
+    ) -> Option<Rows<'a>> {
+        let schema = self.schema;
+
+        let (argument_types, object_type, unique_columns) = if let Some(collection) = schema
+            .collections
+            .iter()
+            .find(|c| &c.name == collection_name)
+        {
+            let Some(object_type) = schema.object_types.get(&collection.collection_type) else {
+                self.error(
+                    path,
+                    ValidationErrorKind::ObjectTypeIsNotDefined(collection.collection_type.clone()),
+                );
+                return None;
+            };
+            let unique_columns = collection
+                .uniqueness_constraints
+                .values()
+                .next()
+                .map(|constraint| constraint.unique_columns.clone())
+                .unwrap_or_default();
+            (
+                &collection.arguments,
+                Cow::Borrowed(object_type),
+                unique_columns,
+            )
+        } else if let Some(function) = schema
+            .functions
+            .iter()
+            .find(|f| f.name.inner() == collection_name)
+        {
+            (
+                &function.arguments,
+                Cow::Owned(value_object_type(function.result_type.clone())),
+                vec![],
+            )
+        } else {
+            self.error(
+                path,
+                ValidationErrorKind::CollectionIsNotDefined(collection_name.clone()),
+            );
+            return None;
+        };
+
+        let mut literals = BTreeMap::new();
+        for (name, value) in arguments {
+            let path = at(path, name);
+            if let Some(argument) = argument_types.get(*name) {
+                if let Some(literal) = self.literal(&argument.argument_type, value, &path) {
+                    literals.insert((*name).clone(), literal);
+                }
+            } else {
+                self.error(
+                    &path,
+                    LoweringErrorKind::ArgumentIsNotDefined((*name).clone()),
+                );
+            }
+        }
+
+        let width = object_type.fields.len();
+        Some(Rows {
+            relation: Relation::From {
+                collection: collection_name.clone(),
+                columns: object_type.fields.keys().cloned().collect(),
+                arguments: literals,
+            },
+            tables: vec![Table {
+                path: vec![],
+                object_type,
+                offset: 0,
+            }],
+            width,
+            unique_columns,
+            aggregate_joins: vec![],
+            windows: vec![],
+        })
+    }
+
+    /// The target collection of a relationship, filtered by `predicate`
+    fn relationship_source(
+        &mut self,
+        relationship: &Relationship,
+        arguments: &BTreeMap<ArgumentName, RelationshipArgument>,
+        predicate: Option<&Expression>,
+        path: &[String],
+    ) -> Option<Rows<'a>> {
+        let mut literals = BTreeMap::new();
+        for (name, argument) in relationship.arguments.iter().chain(arguments) {
+            match argument {
+                RelationshipArgument::Literal { value } => {
+                    literals.insert(name, value);
+                }
+                RelationshipArgument::Variable { .. } => {
+                    self.unsupported(&at(path, name), "a variable argument");
+                }
+                RelationshipArgument::Column { .. } => {
+                    self.unsupported(&at(path, name), "a column argument to a relationship");
+                }
+            }
+        }
+
+        let mut rows = self.source(&relationship.target_collection, &literals, path)?;
+        if let Some(predicate) = predicate {
+            self.filter(&mut rows, predicate, &at(path, "predicate"));
+        }
+        Some(rows)
+    }
+
+    fn relationship(
+        &mut self,
+        name: &RelationshipName,
+        path: &[String],
+    ) -> Option<&'a Relationship> {
+        let relationship = self.collection_relationships.get(name);
+        if relationship.is_none() {
+            self.error(
+                path,
+                ValidationErrorKind::RelationshipIsNotDefined(name.clone()),
+            );
+        }
+        relationship
+    }
+
+    /// The join condition for a relationship from a table of `rows` to the
+    /// root collection of `target`
+    fn join_on(
+        &mut self,
+        rows: &Rows<'a>,
+        table: usize,
+        target: &Rows<'a>,
+        relationship: &Relationship,
+        path: &[String],
+    ) -> Vec<JoinOn> {
+        let mut on = vec![];
+        for (source_column, target_path) in &relationship.column_mapping {
+            let left = self.column(
+                &rows.tables[table],
+                source_column,
+                &[],
+                &BTreeMap::new(),
+                path,
+            );
+            let Some((target_column, field_path)) = target_path.split_first() else {
+                self.error(path, ValidationErrorKind::ExpectedScalarType);
+                continue;
+            };
+            let right = self.column(
+                &target.tables[0],
+                target_column,
+                field_path,
+                &BTreeMap::new(),
+                path,
+            );
+            if let Some(((left, _), (right, _))) = left.zip(right) {
+                on.push(JoinOn { left, right });
+            }
+        }
+        on
+    }
+
+    /// Left join the object relationships along a path, reusing any joins
+    /// already made along equivalent paths, and return the index of the table
+    /// the path leads to
+    fn join(
+        &mut self,
+        rows: &mut Rows<'a>,
+        elements: &[PathElement],
+        path: &[String],
+    ) -> Option<usize> {
+        let elements = elements.iter().map(join_key).collect::<Vec<_>>();
+        let mut table = 0;
+        for (depth, element) in elements.iter().enumerate() {
+            let prefix = &elements[..=depth];
+            if let Some(existing) = rows.tables.iter().position(|t| t.path == prefix) {
+                table = existing;
+                continue;
+            }
+
+            let path = at(path, &depth);
+            if element.field_path.as_ref().is_some_and(|p| !p.is_empty()) {
+                self.unsupported(&path, "a relationship from a nested field");
+                return None;
+            }
+            let relationship = self.relationship(&element.relationship, &path)?;
+            if relationship.relationship_type == RelationshipType::Array {
+                self.unsupported(&path, "an array relationship outside of an aggregate");
+                return None;
+            }
+
+            let target = self.relationship_source(
+                relationship,
+                &element.arguments,
+                element.predicate.as_deref(),
+                &path,
+            )?;
+            let on = self.join_on(rows, table, &target, relationship, &path);
+            let Rows {
+                relation: right,
+                tables,
+                width,
+                ..
+            } = target;
+
+            rows.wrap(|left| Relation::Join {
+                left: left.into(),
+                right: right.into(),
+                on,
+                join_type: JoinType::Left,
+            });
+            rows.tables
+                .extend(tables.into_iter().take(1).map(|t| Table {
+                    path: prefix.to_vec(),
+                    object_type: t.object_type,
+                    offset: rows.width,
+                }));
+            rows.width += width;
+            table = rows.tables.len() - 1;
+        }
+        Some(table)
+    }
+
+    /// Resolve a column of a table, descending through nested object fields
+    fn column(
+        &mut self,
+        table: &Table<'a>,
+        name: &FieldName,
+        field_path: &[FieldName],
+        arguments: &BTreeMap<ArgumentName, Argument>,
+        path: &[String],
+    ) -> Option<(RelationalExpression, Type)> {
+        if !arguments.is_empty() {
+            self.unsupported(path, "a column with arguments");
+            return None;
+        }
+        let Some((index, (_, field))) = table
+            .object_type
+            .fields
+            .iter()
+            .enumerate()
+            .find(|(_, (field_name, _))| *field_name == name)
+        else {
+            self.error(path, ValidationErrorKind::FieldIsNotDefined(name.clone()));
+            return None;
+        };
+
+        let mut expr = RelationalExpression::Column {
+            index: (table.offset + index) as u64,
+        };
+        let mut ty = field.r#type.clone();
+        for field_name in field_path {
+            let object_type = self.object_type_of(&ty, path)?;
+            let Some(field) = object_type.fields.get(field_name) else {
+                self.error(
+                    path,
+                    ValidationErrorKind::FieldIsNotDefined(field_name.clone()),
+                );
+                return None;
+            };
+            ty = field.r#type.clone();
+            expr = RelationalExpression::GetField {
+                column: Box::new(expr),
+                field: field_name.to_string(),
+            };
+        }
+        Some((expr, ty))
+    }
+
+    fn object_type_of(&mut self, ty: &Type, path: &[String]) -> Option<&'a ObjectType> {
+        let schema = self.schema;
+        if let Type::Named { name } = unwrap_nullable(ty) {
+            if let Some(object_type) = schema.object_types.get(name.as_str()) {
+                return Some(object_type);
+            }
+        }
+        self.error(path, ValidationErrorKind::ExpectedObjectType);
+        None
+    }
+
+    fn scalar_type_of(
+        &mut self,
+        ty: &Type,
+        path: &[String],
+    ) -> Option<(&'a ScalarTypeName, &'a ScalarType)> {
+        let schema = self.schema;
+        if let Type::Named { name } = unwrap_nullable(ty) {
+            if let Some(scalar_type) = schema.scalar_types.get_key_value(name.as_str()) {
+                return Some(scalar_type);
+            }
+        }
+        self.error(path, ValidationErrorKind::ExpectedScalarType);
+        None
+    }
+
+    fn literal(
+        &mut self,
+        ty: &Type,
+        value: &serde_json::Value,
+        path: &[String],
+    ) -> Option<RelationalLiteral> {
+        if value.is_null() {
+            return Some(RelationalLiteral::Null);
+        }
+        let (_, scalar_type) = self.scalar_type_of(ty, path)?;
+        match literal(&scalar_type.representation, value) {
+            Ok(literal) => Some(literal),
+            Err(kind) => {
+                self.error(path, kind);
+                None
+            }
+        }
+    }
+
+    /// Filter the rows by a predicate. Top-level `EXISTS` predicates become
+    /// semi-joins, and negated ones anti-joins.
+    fn filter(&mut self, rows: &mut Rows<'a>, predicate: &Expression, path: &[String]) {
+        let mut conjuncts = vec![];
+        collect_conjuncts(predicate, path.to_vec(), &mut conjuncts);
+
+        let mut exprs = vec![];
+        for (conjunct, path) in conjuncts {
+            match conjunct {
+                Expression::Exists {
+                    in_collection,
+                    predicate,
+                } => self.semi_join(
+                    rows,
+                    in_collection,
+                    predicate.as_deref(),
+                    JoinType::LeftSemi,
+                    &path,
+                ),
+                Expression::Not { expression } => match expression.as_ref() {
+                    Expression::Exists {
+                        in_collection,
+                        predicate,
+                    } => self.semi_join(
+                        rows,
+                        in_collection,
+                        predicate.as_deref(),
+                        JoinType::LeftAnti,
+                        &at(&path, "expression"),
+                    ),
+                    _ => exprs.extend(self.expression(rows, conjunct, &path)),
+                },
+                _ => exprs.extend(self.expression(rows, conjunct, &path)),
+            }
+        }
+
+        if let Some(predicate) = exprs.into_iter().reduce(and) {
+            rows.wrap(|input| Relation::Filter {
+                input: input.into(),
+                predicate,
+            });
+        }
+    }
+
+    fn semi_join(
+        &mut self,
+        rows: &mut Rows<'a>,
+        in_collection: &ExistsInCollection,
+        predicate: Option<&Expression>,
+        join_type: JoinType,
+        path: &[String],
+    ) {
+        let Some((target, on)) = self.exists_target(rows, in_collection, predicate, path) else {
+            return;
+        };
+        rows.wrap(|left| Relation::Join {
+            left: left.into(),
+            right: target.relation.into(),
+            on,
+            join_type,
+        });
+    }
+
+    /// Lower an `EXISTS` predicate which cannot be a semi-join, by left
+    /// joining the number of related rows
+    fn exists(
+        &mut self,
+        rows: &mut Rows<'a>,
+        in_collection: &ExistsInCollection,
+        predicate: Option<&Expression>,
+        path: &[String],
+    ) -> Option<RelationalExpression> {
+        let (target, on) = self.exists_target(rows, in_collection, predicate, path)?;
+        let index = rows.join_aggregate(
+            target.relation,
+            on,
+            vec![RelationalExpression::Count {
+                expr: Box::new(RelationalExpression::Literal {
+                    literal: RelationalLiteral::Int32 { value: 1 },
+                }),
+                distinct: false,
+            }],
+        );
+        Some(RelationalExpression::Gt {
+            left: Box::new(RelationalExpression::Coalesce {
+                exprs: vec![
+                    RelationalExpression::Column {
+                        index: index as u64,
+                    },
+                    int64(0),
+                ],
+            }),
+            right: Box::new(int64(0)),
+        })
+    }
+
+    /// The rows of an `EXISTS` predicate, and the condition joining them to
+    /// the root collection of the rows
+    fn exists_target(
+        &mut self,
+        rows: &Rows<'a>,
+        in_collection: &ExistsInCollection,
+        predicate: Option<&Expression>,
+        path: &[String],
+    ) -> Option<(Rows<'a>, Vec<JoinOn>)> {
+        let in_collection_path = at(path, "in_collection");
+        match in_collection {
+            ExistsInCollection::Related {
+                field_path,
+                relationship,
+                arguments,
+            } => {
+                if field_path.as_ref().is_some_and(|p| !p.is_empty()) {
+                    self.unsupported(&in_collection_path, "a relationship from a nested field");
+                    return None;
+                }
+                let relationship = self.relationship(relationship, &in_collection_path)?;
+                let target = self.relationship_source(relationship, arguments, predicate, path)?;
+                let on = self.join_on(rows, 0, &target, relationship, &in_collection_path);
+                Some((target, on))
+            }
+            ExistsInCollection::Unrelated {
+                collection,
+                arguments,
+            } => {
+                let relationship = Relationship {
+                    column_mapping: BTreeMap::new(),
+                    relationship_type: RelationshipType::Array,
+                    target_collection: collection.clone(),
+                    arguments: BTreeMap::new(),
+                };
+                let target = self.relationship_source(&relationship, arguments, predicate, path)?;
+                Some((target, vec![]))
+            }
+            ExistsInCollection::NestedCollection { .. }
+            | ExistsInCollection::NestedScalarCollection { .. } => {
+                self.unsupported(&in_collection_path, "an EXISTS over a nested collection");
+                None
+            }
+        }
+    }
+
+    /// Lower a predicate of the root collection of the rows, joining any
+    /// related rows it refers to
+    fn expression(
+        &mut self,
+        rows: &mut Rows<'a>,
+        expression: &Expression,
+        path: &[String],
+    ) -> Option<RelationalExpression> {
+        match expression {
+            Expression::And { expressions } => {
+                let path = at(path, "expressions");
+                let exprs = expressions
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| self.expression(rows, e, &at(&path, &i)))
+                    .collect::<Vec<_>>();
+                connective(exprs, and, true)
+            }
+            Expression::Or { expressions } => {
+                let path = at(path, "expressions");
+                let exprs = expressions
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| self.expression(rows, e, &at(&path, &i)))
+                    .collect::<Vec<_>>();
+                connective(exprs, or, false)
+            }
+            Expression::Not { expression } => Some(RelationalExpression::Not {
+                expr: Box::new(self.expression(rows, expression, &at(path, "expression"))?),
+            }),
+            Expression::UnaryComparisonOperator { column, operator } => {
+                let (expr, _) = self.comparison_target(rows, column, &at(path, "column"))?;
+                match operator {
+                    UnaryComparisonOperator::IsNull => Some(RelationalExpression::IsNull {
+                        expr: Box::new(expr),
+                    }),
+                }
+            }
+            Expression::BinaryComparisonOperator {
+                column,
+                operator,
+                value,
+            } => {
+                let target = self.comparison_target(rows, column, &at(path, "column"));
+                let value_path = at(path, "value");
+                let operand = match value {
+                    ComparisonValue::Scalar { value } => Some(Operand::Scalar(value)),
+                    ComparisonValue::Variable { .. } => {
+                        self.unsupported(&value_path, "a variable");
+                        None
+                    }
+                    ComparisonValue::Column {
+                        path: elements,
+                        name,
+                        arguments,
+                        field_path,
+                        scope,
+                    } => {
+                        if scope.is_some_and(|scope| scope > 0) {
+                            self.unsupported(&value_path, "a column of an enclosing collection");
+                            None
+                        } else {
+                            self.join(rows, elements, &at(&value_path, "path"))
+                                .and_then(|table| {
+                                    self.column(
+                                        &rows.tables[table],
+                                        name,
+                                        field_path.as_deref().unwrap_or_default(),
+                                        arguments,
+                                        &value_path,
+                                    )
+                                })
+                                .map(|(expr, _)| Operand::Expression(expr))
+                        }
+                    }
+                };
+                let (expr, ty) = target?;
+                self.comparison(expr, &ty, operator, operand?, path)
+            }
+            Expression::ArrayComparison { .. } => {
+                self.unsupported(path, "an array comparison");
+                None
+            }
+            Expression::Exists {
+                in_collection,
+                predicate,
+            } => self.exists(rows, in_collection, predicate.as_deref(), path),
+        }
+    }
+
+    fn comparison_target(
+        &mut self,
+        rows: &mut Rows<'a>,
+        target: &ComparisonTarget,
+        path: &[String],
+    ) -> Option<(RelationalExpression, Type)> {
+        match target {
+            ComparisonTarget::Column {
+                name,
+                arguments,
+                field_path,
+            } => self.column(
+                &rows.tables[0],
+                name,
+                field_path.as_deref().unwrap_or_default(),
+                arguments,
+                path,
+            ),
+            ComparisonTarget::Aggregate {
+                path: elements,
+                aggregate,
+            } => {
+                let (expr, ty) = self.related_aggregate(rows, elements, aggregate, true, path)?;
+                Some((expr, ty?))
+            }
+        }
+    }
+
+    /// Apply a comparison operator defined on the scalar type `ty`
+    fn comparison(
+        &mut self,
+        left: RelationalExpression,
+        ty: &Type,
+        operator: &ComparisonOperatorName,
+        operand: Operand,
+        path: &[String],
+    ) -> Option<RelationalExpression> {
+        let (scalar_type_name, scalar_type) = self.scalar_type_of(ty, &at(path, "column"))?;
+        let Some(definition) = scalar_type.comparison_operators.get(operator) else {
+            self.error(
+                &at(path, "operator"),
+                ValidationErrorKind::ComparisonOperatorIsNotDefined {
+                    scalar_type: scalar_type_name.clone(),
+                    operator: operator.clone(),
+                },
+            );
+            return None;
+        };
+
+        let value_path = at(path, "value");
+        if let ComparisonOperatorDefinition::In = definition {
+            let list = match operand {
+                Operand::Scalar(serde_json::Value::Array(values)) => values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| self.literal(ty, value, &at(&value_path, &i)))
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .map(|literal| literal.map(|literal| RelationalExpression::Literal { literal }))
+                    .collect::<Option<Vec<_>>>()?,
+                Operand::Scalar(_) => {
+                    self.error(
+                        &value_path,
+                        ValidationErrorKind::InvalidLiteral(format!("array of {scalar_type_name}")),
+                    );
+                    return None;
+                }
+                Operand::Expression(_) => {
+                    self.unsupported(&value_path, "an IN comparison against a column");
+                    return None;
+                }
+            };
+            return Some(RelationalExpression::In {
+                expr: Box::new(left),
+                list,
+            });
+        }
+
+        let right = match operand {
+            Operand::Scalar(value) => RelationalExpression::Literal {
+                literal: self.literal(ty, value, &value_path)?,
+            },
+            Operand::Expression(expr) => expr,
+        };
+        let (left, right) = (Box::new(left), Box::new(right));
+        match definition {
+            ComparisonOperatorDefinition::Equal => Some(RelationalExpression::Eq { left, right }),
+            ComparisonOperatorDefinition::LessThan => {
+                Some(RelationalExpression::Lt { left, right })
+            }
+            ComparisonOperatorDefinition::LessThanOrEqual => {
+                Some(RelationalExpression::LtEq { left, right })
+            }
+            ComparisonOperatorDefinition::GreaterThan => {
+                Some(RelationalExpression::Gt { left, right })
+            }
+            ComparisonOperatorDefinition::GreaterThanOrEqual => {
+                Some(RelationalExpression::GtEq { left, right })
+            }
+            ComparisonOperatorDefinition::Contains => Some(RelationalExpression::Contains {
+                str: left,
+                search_str: right,
+            }),
+            ComparisonOperatorDefinition::ContainsInsensitive => {
+                Some(RelationalExpression::Contains {
+                    str: Box::new(RelationalExpression::ToLower { expr: left }),
+                    search_str: Box::new(RelationalExpression::ToLower { expr: right }),
+                })
+            }
+            ComparisonOperatorDefinition::StartsWith => Some(affix(left, right, true)),
+            ComparisonOperatorDefinition::StartsWithInsensitive => Some(affix(
+                Box::new(RelationalExpression::ToLower { expr: left }),
+                Box::new(RelationalExpression::ToLower { expr: right }),
+                true,
+            )),
+            ComparisonOperatorDefinition::EndsWith => Some(affix(left, right, false)),
+            ComparisonOperatorDefinition::EndsWithInsensitive => Some(affix(
+                Box::new(RelationalExpression::ToLower { expr: left }),
+                Box::new(RelationalExpression::ToLower { expr: right }),
+                false,
+            )),
+            ComparisonOperatorDefinition::In => unreachable!("handled above"),
+            ComparisonOperatorDefinition::FullTextSearch { .. } => {
+                self.unsupported(&at(path, "operator"), "a full-text search operator");
+                None
+            }
+            ComparisonOperatorDefinition::SpatialIntersects
+            | ComparisonOperatorDefinition::SpatialWithin
+            | ComparisonOperatorDefinition::SpatialContains
+            | ComparisonOperatorDefinition::SpatialWithinDistance { .. } => {
+                self.unsupported(&at(path, "operator"), "a geospatial operator");
+                None
+            }
+            ComparisonOperatorDefinition::Custom { .. } => {
+                self.unsupported(&at(path, "operator"), "a custom comparison operator");
+                None
+            }
+        }
+    }
+
+    /// Project the selected columns, joining the object relationship fields
+    /// whose queries only select fields, and collecting the other
+    /// relationship fields to be lowered separately
+    #[allow(clippy::too_many_arguments)]
+    fn fields<'q>(
+        &mut self,
+        rows: &mut Rows<'a>,
+        elements: &[PathElement],
+        names: &[FieldName],
+        fields: &'q IndexMap<FieldName, Field>,
+        path: &[String],
+        projection: &mut Vec<RelationalExpression>,
+        columns: &mut Vec<Vec<FieldName>>,
+        relationships: &mut Vec<PendingRelationship<'a, 'q>>,
+    ) {
+        let Some(table) = self.join(rows, elements, path) else {
+            return;
+        };
+
+        for (alias, field) in fields {
+            let path = at(path, alias);
+            let mut name = names.to_vec();
+            name.push(alias.clone());
+            match field {
+                Field::Column {
+                    column,
+                    fields: None,
+                    arguments,
+                } => {
+                    if let Some((expr, _)) =
+                        self.column(&rows.tables[table], column, &[], arguments, &path)
+                    {
+                        projection.push(expr);
+                        columns.push(name);
+                    }
+                }
+                Field::Column {
+                    fields: Some(_), ..
+                } => self.unsupported(&at(&path, "fields"), "a nested field selection"),
+                Field::Relationship {
+                    query,
+                    relationship,
+                    arguments,
+                } => {
+                    let Some(definition) = self.relationship(relationship, &path) else {
+                        continue;
+                    };
+                    // Other relationship fields can produce any number of rows
+                    // for each row, so they cannot be joined
+                    let joinable = definition.relationship_type == RelationshipType::Object
+                        && matches!(
+                            query.as_ref(),
+                            Query {
+                                aggregates: None,
+                                limit: None,
+                                offset: None,
+                                after: None,
+                                order_by: None,
+                                distinct: None,
+                                groups: None,
+                                ..
+                            }
+                        );
+                    if !joinable {
+                        relationships.push(PendingRelationship {
+                            field: name,
+                            table,
+                            relationship: definition,
+                            arguments,
+                            query,
+                            path,
+                        });
+                        continue;
+                    }
+                    let mut elements = elements.to_vec();
+                    elements.push(PathElement {
+                        field_path: None,
+                        relationship: relationship.clone(),
+                        arguments: arguments.clone(),
+                        predicate: query.predicate.clone().map(Box::new),
+                    });
+                    if let Some(fields) = &query.fields {
+                        self.fields(
+                            rows,
+                            &elements,
+                            &name,
+                            fields,
+                            &at(&at(&path, "query"), "fields"),
+                            projection,
+                            columns,
+                            relationships,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Lower the ordering of the rows, along with the type of each ordering
+    /// if `typed` is set, since the types are only needed to read a cursor
+    fn order_by(
+        &mut self,
+        rows: &mut Rows<'a>,
+        order_by: &OrderBy,
+        typed: bool,
+        path: &[String],
+    ) -> Vec<(Sort, Option<Type>)> {
+        let path = at(path, "elements");
+        let mut sorts = vec![];
+        for (i, element) in order_by.elements.iter().enumerate() {
+            let path = at(&at(&path, &i), "target");
+            let expr = match &element.target {
+                OrderByTarget::Column {
+                    path: elements,
+                    name,
+                    arguments,
+                    field_path,
+                } => self
+                    .join(rows, elements, &at(&path, "path"))
+                    .and_then(|table| {
+                        self.column(
+                            &rows.tables[table],
+                            name,
+                            field_path.as_deref().unwrap_or_default(),
+                            arguments,
+                            &path,
+                        )
+                    })
+                    .map(|(expr, ty)| (expr, Some(ty))),
+                OrderByTarget::Aggregate {
+                    path: elements,
+                    aggregate,
+                } => self.related_aggregate(rows, elements, aggregate, typed, &path),
+                OrderByTarget::Relevance { .. } => {
+                    self.unsupported(&path, "ordering by relevance");
+                    None
+                }
+                OrderByTarget::Distance { .. } => {
+                    self.unsupported(&path, "ordering by distance");
+                    None
+                }
+                OrderByTarget::Similarity { .. } => {
+                    self.unsupported(&path, "ordering by vector similarity");
+                    None
+                }
+            };
+            sorts.extend(expr.map(|(expr, ty)| (sort(expr, element.order_direction), ty)));
+        }
+        sorts
+    }
+
+    /// Left join an aggregate of the rows related by the last relationship in
+    /// a path, and return the column containing the aggregate, along with its
+    /// type if `typed` is set
+    fn related_aggregate(
+        &mut self,
+        rows: &mut Rows<'a>,
+        elements: &[PathElement],
+        aggregate: &Aggregate,
+        typed: bool,
+        path: &[String],
+    ) -> Option<(RelationalExpression, Option<Type>)> {
+        let elements_path = at(path, "path");
+        let Some((last, init)) = elements.split_last() else {
+            self.unsupported(path, "an aggregate of the collection being ordered");
+            return None;
+        };
+        let table = self.join(rows, init, &elements_path)?;
+
+        let last_path = at(&elements_path, &init.len());
+        if last.field_path.as_ref().is_some_and(|p| !p.is_empty()) {
+            self.unsupported(&last_path, "a relationship from a nested field");
+            return None;
+        }
+        let relationship = self.relationship(&last.relationship, &last_path)?;
+        let mut target = self.relationship_source(
+            relationship,
+            &last.arguments,
+            last.predicate.as_deref(),
+            &last_path,
+        )?;
+
+        let aggregate_path = at(path, "aggregate");
+        let on = self.join_on(rows, table, &target, relationship, &last_path);
+        let group_by = on.iter().map(|o| o.right.clone()).collect::<Vec<_>>();
+        let ty = if typed {
+            Some(self.aggregate_type(&target, aggregate, &aggregate_path)?)
+        } else {
+            None
+        };
+        let expr = self.aggregate(&mut target, &group_by, aggregate, &aggregate_path)?;
+        let index = rows.join_aggregate(target.relation, on, vec![expr]);
+
+        let column = RelationalExpression::Column {
+            index: index as u64,
+        };
+        let expr = match aggregate {
+            // Counts of missing related rows are zero rather than null
+            Aggregate::ColumnCount { .. } | Aggregate::StarCount { .. } => {
+                RelationalExpression::Coalesce {
+                    exprs: vec![column, int64(0)],
+                }
+            }
+            Aggregate::SingleColumn { .. } => column,
+        };
+        Some((expr, ty))
+    }
+
+    /// Lower an aggregate of the root collection of the rows, which are
+    /// grouped by `group_by`
+    fn aggregate(
+        &mut self,
+        rows: &mut Rows<'a>,
+        group_by: &[RelationalExpression],
+        aggregate: &Aggregate,
+        path: &[String],
+    ) -> Option<RelationalExpression> {
+        let (Aggregate::ColumnCount { filter, .. }
+        | Aggregate::SingleColumn { filter, .. }
+        | Aggregate::StarCount { filter }) = aggregate;
+        // Rows excluded by the filter contribute a null value, which is
+        // ignored by every aggregate function but `array_agg`
+        let filter = match filter {
+            Some(filter) => Some(self.expression(rows, filter, &at(path, "filter"))?),
+            None => None,
+        };
+        let filtered =
+            |expr: RelationalExpression, filter: Option<RelationalExpression>| match filter {
+                Some(when) => RelationalExpression::Case {
+                    scrutinee: None,
+                    when: vec![CaseWhen { when, then: expr }],
+                    default: None,
+                },
+                None => expr,
+            };
+
+        match aggregate {
+            Aggregate::ColumnCount {
+                column,
+                arguments,
+                field_path,
+                distinct,
+                ..
+            } => {
+                let (expr, _) = self.column(
+                    &rows.tables[0],
+                    column,
+                    field_path.as_deref().unwrap_or_default(),
+                    arguments,
+                    path,
+                )?;
+                Some(RelationalExpression::Count {
+                    expr: Box::new(filtered(expr, filter)),
+                    distinct: *distinct,
+                })
+            }
+            Aggregate::SingleColumn {
+                column,
+                arguments,
+                field_path,
+                function,
+                distinct,
+                ..
+            } => {
+                let distinct = distinct.unwrap_or_default();
+                let (expr, ty) = self.column(
+                    &rows.tables[0],
+                    column,
+                    field_path.as_deref().unwrap_or_default(),
+                    arguments,
+                    path,
+                )?;
+                let (scalar_type_name, scalar_type) = self.scalar_type_of(&ty, path)?;
+                let Some(definition) = scalar_type.aggregate_functions.get(function) else {
+                    self.error(
+                        path,
+                        ValidationErrorKind::AggregateFunctionIsNotDefined {
+                            scalar_type: scalar_type_name.clone(),
+                            function: function.clone(),
+                        },
+                    );
+                    return None;
+                };
+                if filter.is_some() && matches!(definition, AggregateFunctionDefinition::ArrayAgg) {
+                    self.unsupported(&at(path, "filter"), "a filtered array aggregate");
+                    return None;
+                }
+                // Duplicate values do not change the result of these functions,
+                // and string and array aggregates support DISTINCT directly.
+                // Other functions only aggregate the first row with each value.
+                let expr = if distinct
+                    && !matches!(
+                        definition,
+                        AggregateFunctionDefinition::Min
+                            | AggregateFunctionDefinition::Max
+                            | AggregateFunctionDefinition::BoolAnd
+                            | AggregateFunctionDefinition::BoolOr
+                            | AggregateFunctionDefinition::StringAgg { .. }
+                            | AggregateFunctionDefinition::ArrayAgg
+                            | AggregateFunctionDefinition::ApproxDistinct { .. }
+                    ) {
+                    let partition_by = group_by
+                        .iter()
+                        .cloned()
+                        .chain([expr.clone()])
+                        .chain(filter.clone())
+                        .collect();
+                    let row_number = rows.aggregate_window(RelationalExpression::RowNumber {
+                        order_by: vec![],
+                        partition_by,
+                    });
+                    let first = RelationalExpression::Eq {
+                        left: Box::new(row_number),
+                        right: Box::new(int64(1)),
+                    };
+                    filtered(
+                        expr,
+                        Some(filter.map_or(first.clone(), |filter| and(first, filter))),
+                    )
+                } else {
+                    filtered(expr, filter)
+                };
+                let expr = Box::new(expr);
+                match definition {
+                    AggregateFunctionDefinition::Min => Some(RelationalExpression::Min { expr }),
+                    AggregateFunctionDefinition::Max => Some(RelationalExpression::Max { expr }),
+                    AggregateFunctionDefinition::Sum { .. } => {
+                        Some(RelationalExpression::Sum { expr })
+                    }
+                    AggregateFunctionDefinition::Average { .. } => {
+                        Some(RelationalExpression::Average { expr })
+                    }
+                    AggregateFunctionDefinition::Median => {
+                        Some(RelationalExpression::Median { expr })
+                    }
+                    AggregateFunctionDefinition::Stddev { .. } => {
+                        Some(RelationalExpression::Stddev { expr })
+                    }
+                    AggregateFunctionDefinition::StddevPop { .. } => {
+                        Some(RelationalExpression::StddevPop { expr })
+                    }
+                    AggregateFunctionDefinition::Var { .. } => {
+                        Some(RelationalExpression::Var { expr })
+                    }
+                    AggregateFunctionDefinition::BoolAnd => {
+                        Some(RelationalExpression::BoolAnd { expr })
+                    }
+                    AggregateFunctionDefinition::BoolOr => {
+                        Some(RelationalExpression::BoolOr { expr })
+                    }
+                    AggregateFunctionDefinition::StringAgg { separator } => {
+                        Some(RelationalExpression::StringAgg {
+                            expr,
+                            separator: separator.clone(),
+                            distinct,
+                            order_by: None,
+                        })
+                    }
+                    AggregateFunctionDefinition::ArrayAgg => Some(RelationalExpression::ArrayAgg {
+                        expr,
+                        distinct,
+                        order_by: None,
+                    }),
+                    AggregateFunctionDefinition::ApproxDistinct { .. } => {
+                        Some(RelationalExpression::ApproxDistinct { expr })
+                    }
+                    AggregateFunctionDefinition::Custom { .. } => {
+                        self.unsupported(path, "a custom aggregate function");
+                        None
+                    }
+                }
+            }
+            Aggregate::StarCount { .. } => Some(RelationalExpression::Count {
+                expr: Box::new(filtered(
+                    RelationalExpression::Literal {
+                        literal: RelationalLiteral::Int32 { value: 1 },
+                    },
+                    filter,
+                )),
+                distinct: false,
+            }),
+        }
+    }
+
+    /// The result type of an aggregate of the root collection of the rows
+    fn aggregate_type(
+        &mut self,
+        rows: &Rows<'a>,
+        aggregate: &Aggregate,
+        path: &[String],
+    ) -> Option<Type> {
+        match aggregate {
+            Aggregate::SingleColumn {
+                column,
+                arguments,
+                field_path,
+                function,
+                ..
+            } => {
+                let (_, ty) = self.column(
+                    &rows.tables[0],
+                    column,
+                    field_path.as_deref().unwrap_or_default(),
+                    arguments,
+                    path,
+                )?;
+                let (scalar_type_name, scalar_type) = self.scalar_type_of(&ty, path)?;
+                let definition = scalar_type.aggregate_functions.get(function)?;
+                Some(aggregate_function_result_type(scalar_type_name, definition))
+            }
+            Aggregate::ColumnCount { .. } | Aggregate::StarCount { .. } => {
+                let count_scalar_type = self
+                    .schema
+                    .capabilities
+                    .as_ref()
+                    .and_then(|c| c.query.as_ref())
+                    .and_then(|q| q.aggregates.as_ref())
+                    .map(|a| a.count_scalar_type.clone());
+                if count_scalar_type.is_none() {
+                    self.unsupported(
+                        path,
+                        "a comparison against a count without a count scalar type",
+                    );
+                }
+                Some(Type::Named {
+                    name: count_scalar_type?.into_inner(),
+                })
+            }
+        }
+    }
+
+    fn dimensions(
+        &mut self,
+        rows: &mut Rows<'a>,
+        dimensions: &[Dimension],
+        path: &[String],
+    ) -> Vec<RelationalExpression> {
+        let mut exprs = vec![];
+        for (i, dimension) in dimensions.iter().enumerate() {
+            let path = at(path, &i);
+            let Dimension::Column {
+                path: elements,
+                column_name,
+                arguments,
+                field_path,
+                extraction,
+            } = dimension;
+            let Some(table) = self.join(rows, elements, &at(&path, "path")) else {
+                continue;
+            };
+            let Some((expr, ty)) = self.column(
+                &rows.tables[table],
+                column_name,
+                field_path.as_deref().unwrap_or_default(),
+                arguments,
+                &path,
+            ) else {
+                continue;
+            };
+            let Some(extraction) = extraction else {
+                exprs.push(expr);
+                continue;
+            };
+
+            let Some((scalar_type_name, scalar_type)) = self.scalar_type_of(&ty, &path) else {
+                continue;
+            };
+            let path = at(&path, "extraction");
+            let part = match scalar_type.extraction_functions.get(extraction) {
+                None => {
+                    self.error(
+                        &path,
+                        ValidationErrorKind::ExtractionFunctionIsNotDefined {
+                            scalar_type: scalar_type_name.clone(),
+                            function: extraction.clone(),
+                        },
+                    );
+                    continue;
+                }
+                Some(ExtractionFunctionDefinition::Custom { .. }) => {
+                    self.unsupported(&path, "a custom extraction function");
+                    continue;
+                }
+                Some(definition) => date_part(definition),
+            };
+            exprs.push(RelationalExpression::DatePart {
+                expr: Box::new(expr),
+                part,
+            });
+        }
+        exprs
+    }
+
+    /// Group the rows by the dimensions, preceded by the columns of the
+    /// relationship for a relationship field
+    fn groups(
+        &mut self,
+        mut rows: Rows<'a>,
+        grouping: &Grouping,
+        keys: &[RelationalExpression],
+        dimensions: Vec<RelationalExpression>,
+        path: &[String],
+    ) -> LoweredGroups {
+        let dimension_count = grouping.dimensions.len();
+        let requested = grouping.aggregates.len();
+
+        let group_by = keys.iter().cloned().chain(dimensions).collect::<Vec<_>>();
+        let mut columns = GroupColumns {
+            group_by,
+            aggregates: vec![],
+        };
+        let aggregates_path = at(path, "aggregates");
+        for (alias, aggregate) in &grouping.aggregates {
+            if let Some(expr) = self.aggregate(
+                &mut rows,
+                &columns.group_by,
+                aggregate,
+                &at(&aggregates_path, alias),
+            ) {
+                columns.aggregates.push(expr);
+            }
+        }
+
+        let predicate = grouping.predicate.as_ref().and_then(|predicate| {
+            self.group_expression(&mut rows, &mut columns, predicate, &at(path, "predicate"))
+        });
+        let sorts = grouping
+            .order_by
+            .as_ref()
+            .map(|order_by| {
+                self.group_order_by(
+                    &mut rows,
+                    &mut columns,
+                    keys.len(),
+                    order_by,
+                    &at(path, "order_by"),
+                )
+            })
+            .unwrap_or_default();
+
+        let width = columns.group_by.len() + columns.aggregates.len();
+        let mut hidden = columns.aggregates.len() > requested;
+        let mut relation = Relation::Aggregate {
+            input: rows.relation.into(),
+            group_by: columns.group_by,
+            aggregates: columns.aggregates,
+        };
+        if let Some(predicate) = predicate {
+            relation = Relation::Filter {
+                input: relation.into(),
+                predicate,
+            };
+        }
+        let paginated = grouping.limit.is_some() || grouping.offset.is_some();
+        if paginated && !keys.is_empty() {
+            // Paginate the groups of each related row separately
+            relation = Relation::Window {
+                input: relation.into(),
+                exprs: vec![RelationalExpression::RowNumber {
+                    order_by: sorts.clone(),
+                    partition_by: (0..keys.len())
+                        .map(|index| RelationalExpression::Column {
+                            index: index as u64,
+                        })
+                        .collect(),
+                }],
+            };
+            let row_number = RelationalExpression::Column {
+                index: width as u64,
+            };
+            let skip = grouping.offset.map_or(0, i64::from);
+            let mut predicate = RelationalExpression::Gt {
+                left: Box::new(row_number.clone()),
+                right: Box::new(int64(skip)),
+            };
+            if let Some(limit) = grouping.limit {
+                predicate = and(
+                    predicate,
+                    RelationalExpression::LtEq {
+                        left: Box::new(row_number),
+                        right: Box::new(int64(skip + i64::from(limit))),
+                    },
+                );
+            }
+            relation = Relation::Filter {
+                input: relation.into(),
+                predicate,
+            };
+            hidden = true;
+        }
+        if !sorts.is_empty() {
+            relation = Relation::Sort {
+                input: relation.into(),
+                exprs: sorts,
+            };
+        }
+        if paginated && keys.is_empty() {
+            relation = Relation::Paginate {
+                input: relation.into(),
+                fetch: grouping.limit.map(u64::from),
+                skip: grouping.offset.map_or(0, u64::from),
+            };
+        }
+        // Drop any columns which were only needed for filtering, sorting or
+        // pagination
+        if hidden {
+            relation = Relation::Project {
+                input: relation.into(),
+                exprs: (0..keys.len() + dimension_count + requested)
+                    .map(|index| RelationalExpression::Column {
+                        index: index as u64,
+                    })
+                    .collect(),
+            };
+        }
+
+        LoweredGroups {
+            relation,
+            dimensions: dimension_count,
+            aggregates: grouping.aggregates.keys().cloned().collect(),
+        }
+    }
+
+    /// The column of the grouped relation which contains an aggregate,
+    /// adding the aggregate if it was not requested
+    fn group_aggregate(
+        &mut self,
+        rows: &mut Rows<'a>,
+        columns: &mut GroupColumns,
+        aggregate: &Aggregate,
+        path: &[String],
+    ) -> Option<RelationalExpression> {
+        let expr = self.aggregate(rows, &columns.group_by, aggregate, path)?;
+        let position = columns
+            .aggregates
+            .iter()
+            .position(|e| *e == expr)
+            .unwrap_or_else(|| {
+                columns.aggregates.push(expr);
+                columns.aggregates.len() - 1
+            });
+        Some(RelationalExpression::Column {
+            index: (columns.group_by.len() + position) as u64,
+        })
+    }
+
+    fn group_expression(
+        &mut self,
+        rows: &mut Rows<'a>,
+        columns: &mut GroupColumns,
+        expression: &GroupExpression,
+        path: &[String],
+    ) -> Option<RelationalExpression> {
+        match expression {
+            GroupExpression::And { expressions } => {
+                let path = at(path, "expressions");
+                let exprs = expressions
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| self.group_expression(rows, columns, e, &at(&path, &i)))
+                    .collect::<Vec<_>>();
+                connective(exprs, and, true)
+            }
+            GroupExpression::Or { expressions } => {
+                let path = at(path, "expressions");
+                let exprs = expressions
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| self.group_expression(rows, columns, e, &at(&path, &i)))
+                    .collect::<Vec<_>>();
+                connective(exprs, or, false)
+            }
+            GroupExpression::Not { expression } => Some(RelationalExpression::Not {
+                expr: Box::new(self.group_expression(
+                    rows,
+                    columns,
+                    expression,
+                    &at(path, "expression"),
+                )?),
+            }),
+            GroupExpression::UnaryComparisonOperator { target, operator } => {
+                let GroupComparisonTarget::Aggregate { aggregate } = target;
+                let expr = self.group_aggregate(rows, columns, aggregate, &at(path, "target"))?;
+                match operator {
+                    UnaryComparisonOperator::IsNull => Some(RelationalExpression::IsNull {
+                        expr: Box::new(expr),
+                    }),
+                }
+            }
+            GroupExpression::BinaryComparisonOperator {
+                target,
+                operator,
+                value,
+            } => {
+                let GroupComparisonTarget::Aggregate { aggregate } = target;
+                let target_path = at(path, "target");
+                let expr = self.group_aggregate(rows, columns, aggregate, &target_path);
+                let ty = self.aggregate_type(rows, aggregate, &target_path);
+                let operand = match value {
+                    GroupComparisonValue::Scalar { value } => Some(Operand::Scalar(value)),
+                    GroupComparisonValue::Variable { .. } => {
+                        self.unsupported(&at(path, "value"), "a variable");
+                        None
+                    }
+                };
+                self.comparison(expr?, &ty?, operator, operand?, path)
+            }
+        }
+    }
+
+    /// Lower the ordering of the groups, whose dimensions follow `keys`
+    /// columns of the relationship
+    fn group_order_by(
+        &mut self,
+        rows: &mut Rows<'a>,
+        columns: &mut GroupColumns,
+        keys: usize,
+        order_by: &GroupOrderBy,
+        path: &[String],
+    ) -> Vec<Sort> {
+        let path = at(path, "elements");
+        let mut sorts = vec![];
+        for (i, element) in order_by.elements.iter().enumerate() {
+            let path = at(&at(&path, &i), "target");
+            let expr = match &element.target {
+                GroupOrderByTarget::Dimension { index }
+                    if keys + index < columns.group_by.len() =>
+                {
+                    Some(RelationalExpression::Column {
+                        index: (keys + index) as u64,
+                    })
+                }
+                GroupOrderByTarget::Dimension { index } => {
+                    self.error(&path, ValidationErrorKind::DimensionIsNotDefined(*index));
+                    None
+                }
+                GroupOrderByTarget::Aggregate { aggregate } => {
+                    self.group_aggregate(rows, columns, aggregate, &path)
+                }
+            };
+            sorts.extend(expr.map(|expr| sort(expr, element.order_direction)));
+        }
+        sorts
+    }
+}
+
+/// The columns of a grouped relation: the keys of the relationship and the
+/// dimensions, then the aggregates
+struct GroupColumns {
+    group_by: Vec<RelationalExpression>,
+    aggregates: Vec<RelationalExpression>,
+}
+
+/// Flatten nested conjunctions, keeping the path to each conjunct
+fn collect_conjuncts<'e>(
+    expression: &'e Expression,
+    path: Vec<String>,
+    conjuncts: &mut Vec<(&'e Expression, Vec<String>)>,
+) {
+    if let Expression::And { expressions } = expression {
+        let path = at(&path, "expressions");
+        for (i, expression) in expressions.iter().enumerate() {
+            collect_conjuncts(expression, at(&path, &i), conjuncts);
+        }
+    } else {
+        conjuncts.push((expression, path));
+    }
+}
+
+/// A path element with the same meaning as another, so that joins along
+/// equivalent paths are made once: an empty field path is omitted, and so is
+/// a predicate which is always true.
+fn join_key(element: &PathElement) -> PathElement {
+    PathElement {
+        field_path: element.field_path.clone().filter(|p| !p.is_empty()),
+        relationship: element.relationship.clone(),
+        arguments: element.arguments.clone(),
+        predicate: element
+            .predicate
+            .as_deref()
+            .map(|predicate| predicate.clone().simplify())
+            .filter(|predicate| {
+                !matches!(predicate, Expression::And { expressions } if expressions.is_empty())
+            })
+            .map(Box::new),
+    }
+}
+
+fn and(left: RelationalExpression, right: RelationalExpression) -> RelationalExpression {
+    RelationalExpression::And {
+        left: Box::new(left),
+        right: Box::new(right),
+    }
+}
+
+fn or(left: RelationalExpression, right: RelationalExpression) -> RelationalExpression {
+    RelationalExpression::Or {
+        left: Box::new(left),
+        right: Box::new(right),
+    }
+}
+
+/// Combine operands with `and` or `or`, or return `unit` if there are none.
+/// Fails if any operand could not be lowered.
+fn connective(
+    operands: Vec<Option<RelationalExpression>>,
+    combine: fn(RelationalExpression, RelationalExpression) -> RelationalExpression,
+    unit: bool,
+) -> Option<RelationalExpression> {
+    let operands = operands.into_iter().collect::<Option<Vec<_>>>()?;
+    Some(
+        operands
+            .into_iter()
+            .reduce(combine)
+            .unwrap_or_else(|| boolean(unit)),
+    )
+}
+
+fn int64(value: i64) -> RelationalExpression {
+    RelationalExpression::Literal {
+        literal: RelationalLiteral::Int64 { value },
+    }
+}
+
+fn boolean(value: bool) -> RelationalExpression {
+    RelationalExpression::Literal {
+        literal: RelationalLiteral::Boolean { value },
+    }
+}
+
+/// Test whether `str` starts (or ends) with `affix`, by comparing it with
+/// the prefix (or suffix) of `str` of the same length
+fn affix(
+    str: Box<RelationalExpression>,
+    affix: Box<RelationalExpression>,
+    prefix: bool,
+) -> RelationalExpression {
+    let n = Box::new(RelationalExpression::CharacterLength { str: affix.clone() });
+    let left = if prefix {
+        RelationalExpression::Left { str, n }
+    } else {
+        RelationalExpression::Right { str, n }
+    };
+    RelationalExpression::Eq {
+        left: Box::new(left),
+        right: affix,
+    }
+}
+
+/// Nulls compare lower than any other value, as in the reference connector
+fn sort(expr: RelationalExpression, direction: OrderDirection) -> Sort {
+    let nulls_sort = match direction {
+        OrderDirection::Asc => NullsSort::NullsFirst,
+        OrderDirection::Desc => NullsSort::NullsLast,
+    };
+    Sort {
+        expr,
+        direction,
+        nulls_sort,
+    }
+}
+
+fn date_part(definition: &ExtractionFunctionDefinition) -> DatePartUnit {
+    match definition {
+        ExtractionFunctionDefinition::Nanosecond { .. } => DatePartUnit::Nanosecond,
+        ExtractionFunctionDefinition::Microsecond { .. } => DatePartUnit::Microsecond,
+        ExtractionFunctionDefinition::Millisecond { .. } => DatePartUnit::Millisecond,
+        ExtractionFunctionDefinition::Second { .. } => DatePartUnit::Second,
+        ExtractionFunctionDefinition::Minute { .. } => DatePartUnit::Minute,
+        ExtractionFunctionDefinition::Hour { .. } => DatePartUnit::Hour,
+        ExtractionFunctionDefinition::Day { .. } => DatePartUnit::Day,
+        ExtractionFunctionDefinition::Week { .. } => DatePartUnit::Week,
+        ExtractionFunctionDefinition::Month { .. } => DatePartUnit::Month,
+        ExtractionFunctionDefinition::Quarter { .. } => DatePartUnit::Quarter,
+        ExtractionFunctionDefinition::Year { .. } => DatePartUnit::Year,
+        ExtractionFunctionDefinition::DayOfWeek { .. } => DatePartUnit::DayOfWeek,
+        ExtractionFunctionDefinition::DayOfYear { .. } => DatePartUnit::DayOfYear,
+        ExtractionFunctionDefinition::Custom { .. } => {
+            unreachable!("custom extraction functions are reported")
+        }
+    }
+}
+
+/// Convert a JSON value into a literal of a scalar type's representation
+fn literal(
+    representation: &TypeRepresentation,
+    value: &serde_json::Value,
+) -> Result<RelationalLiteral, LoweringErrorKind> {
+    fn invalid(expected: &str) -> LoweringErrorKind {
+        ValidationErrorKind::InvalidLiteral(expected.into()).into()
+    }
+    fn integer<T: TryFrom<i64>>(value: &serde_json::Value) -> Option<T> {
+        T::try_from(value.as_i64()?).ok()
+    }
+
+    match representation {
+        TypeRepresentation::Boolean => value
+            .as_bool()
+            .map(|value| RelationalLiteral::Boolean { value })
+            .ok_or_else(|| invalid("boolean")),
+        TypeRepresentation::String | TypeRepresentation::UUID => value
+            .as_str()
+            .map(|value| RelationalLiteral::String {
+                value: value.into(),
+            })
+            .ok_or_else(|| invalid("string")),
+        TypeRepresentation::Enum { one_of } => value
+            .as_str()
+            .filter(|s| one_of.iter().any(|v| v == s))
+            .map(|value| RelationalLiteral::String {
+                value: value.into(),
+            })
+            .ok_or_else(|| invalid(&format!("one of {}", one_of.join(", ")))),
+        TypeRepresentation::Int8 => integer(value)
+            .map(|value| RelationalLiteral::Int8 { value })
+            .ok_or_else(|| invalid("int8")),
+        TypeRepresentation::Int16 => integer(value)
+            .map(|value| RelationalLiteral::Int16 { value })
+            .ok_or_else(|| invalid("int16")),
+        TypeRepresentation::Int32 => integer(value)
+            .map(|value| RelationalLiteral::Int32 { value })
+            .ok_or_else(|| invalid("int32")),
+        TypeRepresentation::Int64 => value
+            .as_i64()
+            .or_else(|| value.as_str()?.parse().ok())
+            .map(|value| RelationalLiteral::Int64 { value })
+            .ok_or_else(|| invalid("int64")),
+        #[allow(clippy::cast_possible_truncation)]
+        TypeRepresentation::Float32 => value
+            .as_f64()
+            .map(|value| RelationalLiteral::Float32 {
+                value: crate::Float32(value as f32),
+            })
+            .ok_or_else(|| invalid("float32")),
+        TypeRepresentation::Float64 => value
+            .as_f64()
+            .map(|value| RelationalLiteral::Float64 {
+                value: crate::Float64(value),
+            })
+            .ok_or_else(|| invalid("float64")),
+        TypeRepresentation::BigInteger => decimal(value)
+            .filter(|literal| matches!(literal, RelationalLiteral::Decimal256 { scale: 0, .. }))
+            .ok_or_else(|| invalid("biginteger")),
+        TypeRepresentation::BigDecimal => decimal(value).ok_or_else(|| invalid("bigdecimal")),
+        TypeRepresentation::Date => value
+            .as_str()
+            .and_then(calendar::parse_date)
+            .and_then(|days| i32::try_from(days).ok())
+            .map(|value| RelationalLiteral::Date32 { value })
+            .ok_or_else(|| invalid("date")),
+        TypeRepresentation::Timestamp | TypeRepresentation::TimestampTZ => {
+            Err(LoweringErrorKind::Unsupported("a timestamp literal"))
+        }
+        TypeRepresentation::Geography | TypeRepresentation::Geometry => {
+            Err(LoweringErrorKind::Unsupported("a geospatial literal"))
+        }
+        TypeRepresentation::Bytes => Err(LoweringErrorKind::Unsupported("a bytes literal")),
+        TypeRepresentation::JSON => Err(LoweringErrorKind::Unsupported("a JSON literal")),
+        TypeRepresentation::Vector { .. } => {
+            Err(LoweringErrorKind::Unsupported("a vector literal"))
+        }
+    }
+}
+
+/// Parse a decimal number, given as a string or a JSON number, and optionally
+/// in exponent notation
+fn decimal(value: &serde_json::Value) -> Option<RelationalLiteral> {
+    let text = match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Number(number) => number.to_string(),
+        _ => return None,
+    };
+    let (negative, unsigned) = match text.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, text.as_str()),
+    };
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i16>().ok()?),
+        None => (unsigned, 0),
+    };
+    let (whole, fraction) = match mantissa.split_once('.') {
+        Some((_, "")) => return None,
+        Some(parts) => parts,
+        None => (mantissa, ""),
+    };
+    if whole.is_empty()
+        || !whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let digits = format!("{whole}{fraction}");
+    let mut digits = match digits.trim_start_matches('0') {
+        "" => "0",
+        digits => digits,
+    }
+    .to_string();
+    // A positive exponent may move the decimal point past the last digit, in
+    // which case the number is an integer
+    let scale = i16::try_from(fraction.len()).ok()?.checked_sub(exponent)?;
+    if scale < 0 && digits != "0" {
+        digits.push_str(&"0".repeat(usize::from(scale.unsigned_abs())));
+    }
+    let scale = u8::try_from(scale.max(0)).ok()?;
+    let prec = u8::try_from(digits.len().max(usize::from(scale)))
+        .ok()
+        .filter(|prec| *prec <= 76)?;
+    let value = if negative && digits != "0" {
+        format!("-{digits}")
+    } else {
+        digits
+    };
+    Some(RelationalLiteral::Decimal256 {
+        value,
+        scale: i8::try_from(scale).ok()?,
+        prec,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::{col, exists_related, lit, star_count};
+    use crate::{Grouping, Query, QueryRequest};
+
+    fn schema() -> SchemaResponse {
+        serde_json::from_value(serde_json::json!({
+            "scalar_types": {
+                "Int": {
+                    "representation": { "type": "int32" },
+                    "aggregate_functions": {
+                        "sum": { "type": "sum", "result_type": "Int" },
+                        "custom": { "type": "custom", "result_type": { "type": "named", "name": "Int" } },
+                    },
+                    "comparison_operators": {
+                        "eq": { "type": "equal" },
+                        "gt": { "type": "greater_than" },
+                        "custom": {
+                            "type": "custom",
+                            "argument_type": { "type": "named", "name": "Int" },
+                        },
+                    },
+                },
+                "String": {
+                    "representation": { "type": "string" },
+                    "aggregate_functions": {
+                        "string_agg": { "type": "string_agg", "separator": ", " },
+                    },
+                    "comparison_operators": {
+                        "starts_with": { "type": "starts_with" },
+                    },
+                },
+            },
+            "object_types": {
+                "article": {
+                    "fields": {
+                        "id": { "type": { "type": "named", "name": "Int" } },
+                        "title": { "type": { "type": "named", "name": "String" } },
+                        "author_id": { "type": { "type": "named", "name": "Int" } },
+                    },
+                    "foreign_keys": {},
+                },
+                "author": {
+                    "fields": {
+                        "id": { "type": { "type": "named", "name": "Int" } },
+                        "name": { "type": { "type": "named", "name": "String" } },
+                    },
+                    "foreign_keys": {},
+                },
+            },
+            "collections": [
+                { "name": "articles", "arguments": {}, "type": "article", "uniqueness_constraints": {} },
+                { "name": "authors", "arguments": {}, "type": "author", "uniqueness_constraints": {} },
+            ],
+            "functions": [],
+            "procedures": [],
+        }))
+        .unwrap()
+    }
+
+    fn author_relationship() -> Relationship {
+        serde_json::from_value(serde_json::json!({
+            "column_mapping": { "author_id": ["id"] },
+            "relationship_type": "object",
+            "target_collection": "authors",
+            "arguments": {},
+        }))
+        .unwrap()
+    }
+
+    fn articles_relationship() -> Relationship {
+        serde_json::from_value(serde_json::json!({
+            "column_mapping": { "id": ["author_id"] },
+            "relationship_type": "array",
+            "target_collection": "articles",
+            "arguments": {},
+        }))
+        .unwrap()
+    }
+
+    fn relation(value: serde_json::Value) -> Relation {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn column(index: u64) -> serde_json::Value {
+        serde_json::json!({ "type": "column", "index": index })
+    }
+
+    fn int32(value: i32) -> serde_json::Value {
+        serde_json::json!({ "type": "literal", "literal": { "type": "Int32", "value": value } })
+    }
+
+    fn int64(value: i64) -> serde_json::Value {
+        serde_json::json!({ "type": "literal", "literal": { "type": "Int64", "value": value } })
+    }
+
+    fn from(collection: &str) -> serde_json::Value {
+        let columns = match collection {
+            "articles" => serde_json::json!(["author_id", "id", "title"]),
+            _ => serde_json::json!(["id", "name"]),
+        };
+        serde_json::json!({ "type": "from", "collection": collection, "columns": columns })
+    }
+
+    #[test]
+    fn test_lower_rows() {
+        let request = QueryRequest::collection("articles")
+            .relationship("author", author_relationship())
+            .query(
+                Query::new()
+                    .field("id")
+                    .field("title")
+                    .field_as(
+                        "author",
+                        Field::relationship("author", Query::new().field("name")),
+                    )
+                    .filter(col("id").op("gt", lit(1)))
+                    .filter(exists_related(
+                        "author",
+                        Some(col("name").op("starts_with", lit("A"))),
+                    ))
+                    .order_by(col("id").desc())
+                    .limit(2)
+                    .offset(1),
+            );
+
+        let lowered = lower_query_request(&schema(), &request).unwrap();
+        let rows = lowered.rows.unwrap();
+
+        let column = |index: u64| serde_json::json!({ "type": "column", "index": index });
+        let authors = serde_json::json!({
+            "type": "from",
+            "collection": "authors",
+            "columns": ["id", "name"],
+        });
+        let expected = relation(serde_json::json!({
+            "type": "project",
+            "input": {
+                "type": "paginate",
+                "input": {
+                    "type": "sort",
+                    "input": {
+                        "type": "join",
+                        "left": {
+                            "type": "filter",
+                            "input": {
+                                "type": "join",
+                                "left": {
+                                    "type": "from",
+                                    "collection": "articles",
+                                    "columns": ["author_id", "id", "title"],
+                                },
+                                "right": {
+                                    "type": "filter",
+                                    "input": authors,
+                                    "predicate": {
+                                        "type": "eq",
+                                        "left": {
+                                            "type": "left",
+                                            "str": column(1),
+                                            "n": {
+                                                "type": "character_length",
+                                                "str": {
+                                                    "type": "literal",
+                                                    "literal": { "type": "String", "value": "A" },
+                                                },
+                                            },
+                                        },
+                                        "right": {
+                                            "type": "literal",
+                                            "literal": { "type": "String", "value": "A" },
+                                        },
+                                    },
+                                },
+                                "on": [{ "left": column(0), "right": column(0) }],
+                                "join_type": "left_semi",
+                            },
+                            "predicate": {
+                                "type": "gt",
+                                "left": column(1),
+                                "right": {
+                                    "type": "literal",
+                                    "literal": { "type": "Int32", "value": 1 },
+                                },
+                            },
+                        },
+                        "right": authors,
+                        "on": [{ "left": column(0), "right": column(0) }],
+                        "join_type": "left",
+                    },
+                    "exprs": [{ "expr": column(1), "direction": "desc", "nulls_sort": "nulls_last" }],
+                },
+                "fetch": 2,
+                "skip": 1,
+            },
+            "exprs": [column(1), column(2), column(4)],
+        }));
+
+        assert_eq!(rows.relation, expected);
+        assert_eq!(
+            rows.columns,
+            vec![
+                vec![FieldName::from("id")],
+                vec![FieldName::from("title")],
+                vec![FieldName::from("author"), FieldName::from("name")],
+            ]
+        );
+        assert_eq!(lowered.aggregates, None);
+        assert_eq!(lowered.groups, None);
+    }
+
+    #[test]
+    fn test_lower_groups() {
+        let request = QueryRequest::collection("articles").group_by(
+            Grouping::new()
+                .dimension(crate::builders::dim("author_id"))
+                .aggregate("count", star_count())
+                .filter(col("id").aggregate("sum").op("gt", 10))
+                .order_by(crate::GroupOrderByElement {
+                    order_direction: OrderDirection::Asc,
+                    target: GroupOrderByTarget::Dimension { index: 0 },
+                }),
+        );
+
+        let groups = lower_query_request(&schema(), &request)
+            .unwrap()
+            .groups
+            .unwrap();
+
+        let column = |index: u64| serde_json::json!({ "type": "column", "index": index });
+        let expected = relation(serde_json::json!({
+            "type": "project",
+            "input": {
+                "type": "sort",
+                "input": {
+                    "type": "filter",
+                    "input": {
+                        "type": "aggregate",
+                        "input": {
+                            "type": "from",
+                            "collection": "articles",
+                            "columns": ["author_id", "id", "title"],
+                        },
+                        "group_by": [column(0)],
+                        "aggregates": [
+                            {
+                                "type": "count",
+                                "expr": { "type": "literal", "literal": { "type": "Int32", "value": 1 } },
+                                "distinct": false,
+                            },
+                            { "type": "sum", "expr": column(1) },
+                        ],
+                    },
+                    "predicate": {
+                        "type": "gt",
+                        "left": column(2),
+                        "right": { "type": "literal", "literal": { "type": "Int32", "value": 10 } },
+                    },
+                },
+                "exprs": [{ "expr": column(0), "direction": "asc", "nulls_sort": "nulls_first" }],
+            },
+            "exprs": [column(0), column(1)],
+        }));
+
+        assert_eq!(groups.relation, expected);
+        assert_eq!(groups.dimensions, 1);
+        assert_eq!(groups.aggregates, vec![FieldName::from("count")]);
+    }
+
+    #[test]
+    fn test_lower_standard_aggregates() {
+        let request = QueryRequest::collection("articles")
+            .aggregate("titles", col("title").aggregate("string_agg"));
+
+        let aggregates = lower_query_request(&schema(), &request)
+            .unwrap()
+            .aggregates
+            .unwrap();
+
+        let expected = relation(serde_json::json!({
+            "type": "aggregate",
+            "input": {
+                "type": "from",
+                "collection": "articles",
+                "columns": ["author_id", "id", "title"],
+            },
+            "group_by": [],
+            "aggregates": [{
+                "type": "string_agg",
+                "expr": { "type": "column", "index": 2 },
+                "separator": ", ",
+                "distinct": false,
+            }],
+        }));
+
+        assert_eq!(aggregates.relation, expected);
+        assert_eq!(aggregates.columns, vec![FieldName::from("titles")]);
+    }
+
+    #[test]
+    fn test_lower_array_relationship() {
+        let request = QueryRequest::collection("authors")
+            .relationship("articles", articles_relationship())
+            .field("name")
+            .field_as(
+                "articles",
+                Field::relationship(
+                    "articles",
+                    Query::new()
+                        .field("title")
+                        .order_by(col("id").asc())
+                        .limit(2),
+                ),
+            );
+
+        let rows = lower_query_request(&schema(), &request)
+            .unwrap()
+            .rows
+            .unwrap();
+
+        // The author IDs follow the selected columns
+        let expected = relation(serde_json::json!({
+            "type": "project",
+            "input": from("authors"),
+            "exprs": [column(1), column(0)],
+        }));
+        assert_eq!(rows.relation, expected);
+        assert_eq!(rows.columns, vec![vec![FieldName::from("name")]]);
+        let [articles] = rows.relationships.as_slice() else {
+            panic!("expected one relationship");
+        };
+        assert_eq!(articles.field, vec![FieldName::from("articles")]);
+        assert_eq!(articles.keys, vec![1]);
+
+        // The articles of the selected authors, starting with their author IDs,
+        // are numbered and paginated for each author
+        let order_by = serde_json::json!([
+            { "expr": column(1), "direction": "asc", "nulls_sort": "nulls_first" },
+        ]);
+        let expected = relation(serde_json::json!({
+            "type": "project",
+            "input": {
+                "type": "sort",
+                "input": {
+                    "type": "filter",
+                    "input": {
+                        "type": "window",
+                        "input": {
+                            "type": "join",
+                            "left": from("articles"),
+                            "right": from("authors"),
+                            "on": [{ "left": column(0), "right": column(0) }],
+                            "join_type": "left_semi",
+                        },
+                        "exprs": [{
+                            "type": "row_number",
+                            "order_by": order_by,
+                            "partition_by": [column(0)],
+                        }],
+                    },
+                    "predicate": {
+                        "type": "and",
+                        "left": { "type": "gt", "left": column(3), "right": int64(0) },
+                        "right": { "type": "lt_eq", "left": column(3), "right": int64(2) },
+                    },
+                },
+                "exprs": order_by,
+            },
+            "exprs": [column(0), column(2)],
+        }));
+        let related = articles.query.rows.as_ref().unwrap();
+        assert_eq!(related.relation, expected);
+        assert_eq!(related.columns, vec![vec![FieldName::from("title")]]);
+    }
+
+    #[test]
+    fn test_lower_filtered_and_distinct_aggregates() {
+        let request = QueryRequest::collection("articles")
+            .aggregate(
+                "count",
+                star_count().filter(col("author_id").op("eq", lit(1))),
+            )
+            .aggregate(
+                "sum",
+                col("id")
+                    .aggregate("sum")
+                    .distinct()
+                    .filter(col("author_id").op("eq", lit(1))),
+            );
+
+        let aggregates = lower_query_request(&schema(), &request)
+            .unwrap()
+            .aggregates
+            .unwrap();
+
+        let filter = serde_json::json!({ "type": "eq", "left": column(0), "right": int32(1) });
+        let expected = relation(serde_json::json!({
+            "type": "aggregate",
+            "input": {
+                "type": "window",
+                "input": from("articles"),
+                "exprs": [{
+                    "type": "row_number",
+                    "order_by": [],
+                    "partition_by": [column(1), filter],
+                }],
+            },
+            "group_by": [],
+            "aggregates": [
+                {
+                    "type": "count",
+                    "expr": { "type": "case", "when": [{ "when": filter, "then": int32(1) }] },
+                    "distinct": false,
+                },
+                {
+                    "type": "sum",
+                    "expr": {
+                        "type": "case",
+                        "when": [{
+                            "when": {
+                                "type": "and",
+                                "left": { "type": "eq", "left": column(3), "right": int64(1) },
+                                "right": filter,
+                            },
+                            "then": column(1),
+                        }],
+                    },
+                },
+            ],
+        }));
+
+        assert_eq!(aggregates.relation, expected);
+    }
+
+    #[test]
+    fn test_lower_distinct_rows_after_cursor() {
+        let mut schema = schema();
+        schema.collections[0].uniqueness_constraints.insert(
+            "ArticleByID".into(),
+            crate::UniquenessConstraint {
+                unique_columns: vec!["id".into()],
+            },
+        );
+        let request = QueryRequest::collection("articles").query(
+            Query::new()
+                .field("title")
+                .order_by(col("title").desc())
+                .distinct_on(["author_id"])
+                .after(r#"["Functional Programming", 2]"#)
+                .limit(1),
+        );
+
+        let rows = lower_query_request(&schema, &request)
+            .unwrap()
+            .rows
+            .unwrap();
+
+        let title = serde_json::json!({
+            "type": "literal",
+            "literal": { "type": "String", "value": "Functional Programming" },
+        });
+        let expected = relation(serde_json::json!({
+            "type": "project",
+            "input": {
+                "type": "paginate",
+                "input": {
+                    "type": "sort",
+                    "input": {
+                        "type": "filter",
+                        "input": {
+                            "type": "filter",
+                            "input": {
+                                "type": "window",
+                                "input": from("articles"),
+                                "exprs": [{
+                                    "type": "row_number",
+                                    "order_by": [
+                                        { "expr": column(2), "direction": "desc", "nulls_sort": "nulls_last" },
+                                    ],
+                                    "partition_by": [column(0)],
+                                }],
+                            },
+                            "predicate": { "type": "eq", "left": column(3), "right": int64(1) },
+                        },
+                        // Rows after the cursor have an earlier title, or the
+                        // same title and a greater ID
+                        "predicate": {
+                            "type": "or",
+                            "left": {
+                                "type": "or",
+                                "left": { "type": "lt", "left": column(2), "right": title },
+                                "right": { "type": "is_null", "expr": column(2) },
+                            },
+                            "right": {
+                                "type": "and",
+                                "left": { "type": "eq", "left": column(2), "right": title },
+                                "right": { "type": "gt", "left": column(1), "right": int32(2) },
+                            },
+                        },
+                    },
+                    "exprs": [
+                        { "expr": column(2), "direction": "desc", "nulls_sort": "nulls_last" },
+                        { "expr": column(1), "direction": "asc", "nulls_sort": "nulls_first" },
+                    ],
+                },
+                "fetch": 1,
+                "skip": 0,
+            },
+            "exprs": [column(2), column(2), column(1)],
+        }));
+
+        assert_eq!(rows.relation, expected);
+        assert_eq!(rows.cursor, vec![1, 2]);
+
+        let request =
+            QueryRequest::collection("articles").query(Query::new().field("title").after("[1, 2]"));
+        let errors = lower_query_request(&schema, &request).unwrap_err();
+        assert_eq!(
+            errors[0].to_string(),
+            "invalid cursor at path $.query.after"
+        );
+    }
+
+    #[test]
+    fn test_lower_exists_below_disjunction() {
+        let request = QueryRequest::collection("authors")
+            .relationship("articles", articles_relationship())
+            .field("name")
+            .filter(crate::builders::or([
+                exists_related("articles", Some(col("id").op("gt", lit(1)))),
+                serde_json::from_value(serde_json::json!({
+                    "type": "binary_comparison_operator",
+                    "column": {
+                        "type": "aggregate",
+                        "path": [{ "relationship": "articles", "arguments": {} }],
+                        "aggregate": { "type": "single_column", "column": "id", "function": "sum" },
+                    },
+                    "operator": "gt",
+                    "value": { "type": "scalar", "value": 10 },
+                }))
+                .unwrap(),
+            ]));
+
+        let rows = lower_query_request(&schema(), &request)
+            .unwrap()
+            .rows
+            .unwrap();
+
+        // The number of matching articles and the sum of all article IDs are
+        // joined to each author
+        let expected = relation(serde_json::json!({
+            "type": "project",
+            "input": {
+                "type": "filter",
+                "input": {
+                    "type": "join",
+                    "left": {
+                        "type": "join",
+                        "left": from("authors"),
+                        "right": {
+                            "type": "aggregate",
+                            "input": {
+                                "type": "filter",
+                                "input": from("articles"),
+                                "predicate": { "type": "gt", "left": column(1), "right": int32(1) },
+                            },
+                            "group_by": [column(0)],
+                            "aggregates": [{ "type": "count", "expr": int32(1), "distinct": false }],
+                        },
+                        "on": [{ "left": column(0), "right": column(0) }],
+                        "join_type": "left",
+                    },
+                    "right": {
+                        "type": "aggregate",
+                        "input": from("articles"),
+                        "group_by": [column(0)],
+                        "aggregates": [{ "type": "sum", "expr": column(1) }],
+                    },
+                    "on": [{ "left": column(0), "right": column(0) }],
+                    "join_type": "left",
+                },
+                "predicate": {
+                    "type": "or",
+                    "left": {
+                        "type": "gt",
+                        "left": { "type": "coalesce", "exprs": [column(3), int64(0)] },
+                        "right": int64(0),
+                    },
+                    "right": { "type": "gt", "left": column(5), "right": int32(10) },
+                },
+            },
+            "exprs": [column(1)],
+        }));
+
+        assert_eq!(rows.relation, expected);
+    }
+
+    #[test]
+    fn test_reuse_joins() {
+        // A path element with an empty predicate and field path leads to the
+        // same join as one without
+        let author = serde_json::json!({
+            "relationship": "author",
+            "arguments": {},
+            "field_path": [],
+            "predicate": { "type": "and", "expressions": [] },
+        });
+        let request = QueryRequest::collection("articles")
+            .relationship("author", author_relationship())
+            .field_as(
+                "author",
+                Field::relationship("author", Query::new().field("name")),
+            )
+            .order_by(
+                serde_json::from_value(serde_json::json!({
+                    "order_direction": "asc",
+                    "target": { "type": "column", "path": [author], "name": "name" },
+                }))
+                .unwrap(),
+            );
+
+        let rows = lower_query_request(&schema(), &request)
+            .unwrap()
+            .rows
+            .unwrap();
+
+        let expected = relation(serde_json::json!({
+            "type": "project",
+            "input": {
+                "type": "sort",
+                "input": {
+                    "type": "join",
+                    "left": from("articles"),
+                    "right": from("authors"),
+                    "on": [{ "left": column(0), "right": column(0) }],
+                    "join_type": "left",
+                },
+                "exprs": [{ "expr": column(4), "direction": "asc", "nulls_sort": "nulls_first" }],
+            },
+            "exprs": [column(4)],
+        }));
+
+        assert_eq!(rows.relation, expected);
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let request = QueryRequest::collection("articles")
+            .relationship("author", author_relationship())
+            .variables(BTreeMap::new())
+            .field("id")
+            .aggregate("custom", col("id").aggregate("custom"))
+            .filter(crate::builders::or([
+                col("id").op("custom", lit(1)),
+                exists_related("author", None),
+            ]));
+
+        let errors = lower_query_request(&schema(), &request)
+            .unwrap_err()
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec![
+                "a request with variables cannot be lowered to a relation at path $.variables",
+                "a custom comparison operator cannot be lowered to a relation at path $.query.predicate.expressions.0.operator",
+                "a custom aggregate function cannot be lowered to a relation at path $.query.aggregates.custom",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_operators() {
+        let mut schema = schema();
+        let string = schema.scalar_types.get_mut("String").unwrap();
+        string.comparison_operators.insert(
+            "intersects".into(),
+            crate::ComparisonOperatorDefinition::SpatialIntersects,
+        );
+        string.comparison_operators.insert(
+            "search".into(),
+            serde_json::from_value(
+                serde_json::json!({ "type": "full_text_search", "syntax": "plain" }),
+            )
+            .unwrap(),
+        );
+        let request =
+            QueryRequest::collection("articles")
+                .field("id")
+                .filter(crate::builders::and([
+                    col("title").op("intersects", lit("POINT(0 0)")),
+                    col("title").op("search", lit("rust")),
+                ]));
+
+        let errors = lower_query_request(&schema, &request)
+            .unwrap_err()
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec![
+                "a geospatial operator cannot be lowered to a relation at path $.query.predicate.expressions.0.operator",
+                "a full-text search operator cannot be lowered to a relation at path $.query.predicate.expressions.1.operator",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_literals() {
+        let decimal = |value: &str, scale: i8, prec: u8| RelationalLiteral::Decimal256 {
+            value: value.into(),
+            scale,
+            prec,
+        };
+        let big_decimal =
+            |value: serde_json::Value| literal(&TypeRepresentation::BigDecimal, &value);
+
+        assert_eq!(
+            big_decimal(serde_json::json!("-012.50")),
+            Ok(decimal("-1250", 2, 4))
+        );
+        assert_eq!(
+            big_decimal(serde_json::json!("0.001")),
+            Ok(decimal("1", 3, 3))
+        );
+        assert_eq!(
+            big_decimal(serde_json::json!("-0.0")),
+            Ok(decimal("0", 1, 1))
+        );
+        assert_eq!(
+            big_decimal(serde_json::json!("1e3")),
+            Ok(decimal("1000", 0, 4))
+        );
+        assert_eq!(
+            big_decimal(serde_json::json!("1.25E+1")),
+            Ok(decimal("125", 1, 3))
+        );
+        assert_eq!(
+            big_decimal(serde_json::json!("-1.5e-2")),
+            Ok(decimal("-15", 3, 3))
+        );
+        assert_eq!(
+            big_decimal(serde_json::json!(1e300)),
+            Err(ValidationErrorKind::InvalidLiteral("bigdecimal".into()).into())
+        );
+        assert_eq!(
+            big_decimal(serde_json::json!(2.5e-7)),
+            Ok(decimal("25", 8, 8))
+        );
+        for value in ["", "1.", ".5", "1e", "1e1.5", "+1", "1_000", "0x10"] {
+            assert!(big_decimal(serde_json::json!(value)).is_err(), "{value}");
+        }
+
+        assert_eq!(
+            literal(&TypeRepresentation::BigInteger, &serde_json::json!("2e2")),
+            Ok(decimal("200", 0, 3))
+        );
+        assert!(literal(&TypeRepresentation::BigInteger, &serde_json::json!("2.5e0")).is_err());
+        assert_eq!(
+            literal(
+                &TypeRepresentation::Int64,
+                &serde_json::json!("-9007199254740993")
+            ),
+            Ok(RelationalLiteral::Int64 {
+                value: -9_007_199_254_740_993
+            })
+        );
+        assert!(literal(&TypeRepresentation::Int8, &serde_json::json!(128)).is_err());
+        assert_eq!(
+            literal(&TypeRepresentation::Date, &serde_json::json!("2024-02-29")),
+            Ok(RelationalLiteral::Date32 { value: 19_782 })
+        );
+        assert!(literal(&TypeRepresentation::Date, &serde_json::json!("2023-02-29")).is_err());
+        assert_eq!(
+            literal(
+                &TypeRepresentation::Enum {
+                    one_of: vec!["draft".into(), "published".into()]
+                },
+                &serde_json::json!("archived")
+            ),
+            Err(ValidationErrorKind::InvalidLiteral("one of draft, published".into()).into())
+        );
+        assert_eq!(
+            literal(&TypeRepresentation::JSON, &serde_json::json!({})),
+            Err(LoweringErrorKind::Unsupported("a JSON literal"))
+        );
+    }
+}
@@ -0,0 +1,436 @@
+//! Simplification and normal forms for boolean expressions.
+//!
+//! Following the conventions used throughout the specification, an empty
+//! `and` expression is treated as the constant `true`, and an empty `or`
+//! expression as the constant `false`.
+
+use crate::{Expression, GroupExpression};
+
+impl Expression {
+    /// Simplify an expression, without changing its meaning:
+    ///
+    /// * nested `and` and `or` expressions are flattened,
+    /// * double negations are removed,
+    /// * constant `true` (empty `and`) and `false` (empty `or`) subexpressions are folded,
+    /// * repeated operands of `and` and `or` are removed, and
+    /// * `and` and `or` expressions with a single operand are replaced by that operand.
+    ///
+    /// The predicates of `exists` expressions are simplified recursively.
+    #[must_use]
+    pub fn simplify(self) -> Self {
+        simplify(self)
+    }
+
+    /// Simplify an expression and convert it to conjunctive normal form: an `and` of
+    /// `or`s of comparisons, possibly negated.
+    ///
+    /// Note that the size of the result can be exponential in the size of the input.
+    #[must_use]
+    pub fn to_cnf(self) -> Self {
+        normal_form(self, Connective::And)
+    }
+
+    /// Simplify an expression and convert it to disjunctive normal form: an `or` of
+    /// `and`s of comparisons, possibly negated.
+    ///
+    /// Note that the size of the result can be exponential in the size of the input.
+    #[must_use]
+    pub fn to_dnf(self) -> Self {
+        normal_form(self, Connective::Or)
+    }
+}
+
+impl GroupExpression {
+    /// Simplify a group expression, without changing its meaning.
+    ///
+    /// See [`Expression::simplify`].
+    #[must_use]
+    pub fn simplify(self) -> Self {
+        simplify(self)
+    }
+
+    /// Simplify a group expression and convert it to conjunctive normal form.
+    ///
+    /// See [`Expression::to_cnf`].
+    #[must_use]
+    pub fn to_cnf(self) -> Self {
+        normal_form(self, Connective::And)
+    }
+
+    /// Simplify a group expression and convert it to disjunctive normal form.
+    ///
+    /// See [`Expression::to_dnf`].
+    #[must_use]
+    pub fn to_dnf(self) -> Self {
+        normal_form(self, Connective::Or)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Connective {
+    And,
+    Or,
+}
+
+impl Connective {
+    fn dual(self) -> Self {
+        match self {
+            Connective::And => Connective::Or,
+            Connective::Or => Connective::And,
+        }
+    }
+}
+
+enum Shape<E> {
+    Junction(Connective, Vec<E>),
+    Not(E),
+    Atom(E),
+}
+
+/// The common structure of [`Expression`] and [`GroupExpression`]
+trait Boolean: Sized + PartialEq {
+    fn into_shape(self) -> Shape<Self>;
+
+    fn junction(connective: Connective, operands: Vec<Self>) -> Self;
+
+    fn not(operand: Self) -> Self;
+
+    /// Simplify any expressions nested inside an atomic expression
+    fn simplify_atom(self) -> Self {
+        self
+    }
+}
+
+impl Boolean for Expression {
+    fn into_shape(self) -> Shape<Self> {
+        match self {
+            Expression::And { expressions } => Shape::Junction(Connective::And, expressions),
+            Expression::Or { expressions } => Shape::Junction(Connective::Or, expressions),
+            Expression::Not { expression } => Shape::Not(*expression),
+            atom => Shape::Atom(atom),
+        }
+    }
+
+    fn junction(connective: Connective, expressions: Vec<Self>) -> Self {
+        match connective {
+            Connective::And => Expression::And { expressions },
+            Connective::Or => Expression::Or { expressions },
+        }
+    }
+
+    fn not(expression: Self) -> Self {
+        Expression::Not {
+            expression: Box::new(expression),
+        }
+    }
+
+    fn simplify_atom(self) -> Self {
+        match self {
+            Expression::Exists {
+                in_collection,
+                predicate,
+            } => Expression::Exists {
+                in_collection,
+                predicate: predicate.map(|predicate| Box::new(simplify(*predicate))),
+            },
+            atom => atom,
+        }
+    }
+}
+
+impl Boolean for GroupExpression {
+    fn into_shape(self) -> Shape<Self> {
+        match self {
+            GroupExpression::And { expressions } => Shape::Junction(Connective::And, expressions),
+            GroupExpression::Or { expressions } => Shape::Junction(Connective::Or, expressions),
+            GroupExpression::Not { expression } => Shape::Not(*expression),
+            atom => Shape::Atom(atom),
+        }
+    }
+
+    fn junction(connective: Connective, expressions: Vec<Self>) -> Self {
+        match connective {
+            Connective::And => GroupExpression::And { expressions },
+            Connective::Or => GroupExpression::Or { expressions },
+        }
+    }
+
+    fn not(expression: Self) -> Self {
+        GroupExpression::Not {
+            expression: Box::new(expression),
+        }
+    }
+}
+
+fn from_shape<E: Boolean>(shape: Shape<E>) -> E {
+    match shape {
+        Shape::Junction(connective, operands) => E::junction(connective, operands),
+        Shape::Not(operand) => E::not(operand),
+        Shape::Atom(atom) => atom,
+    }
+}
+
+fn simplify<E: Boolean>(expression: E) -> E {
+    match expression.into_shape() {
+        Shape::Atom(atom) => atom.simplify_atom(),
+        Shape::Not(operand) => match simplify(operand).into_shape() {
+            Shape::Not(inner) => inner,
+            // not(true) = false, not(false) = true
+            Shape::Junction(connective, operands) if operands.is_empty() => {
+                E::junction(connective.dual(), vec![])
+            }
+            shape => E::not(from_shape(shape)),
+        },
+        Shape::Junction(connective, operands) => {
+            let mut simplified: Vec<E> = vec![];
+            for operand in operands {
+                match simplify(operand).into_shape() {
+                    Shape::Junction(inner, inner_operands) if inner == connective => {
+                        for inner_operand in inner_operands {
+                            push_unique(&mut simplified, inner_operand);
+                        }
+                    }
+                    // The dual constant absorbs everything: and(false, ...) = false
+                    Shape::Junction(inner, inner_operands) if inner_operands.is_empty() => {
+                        return E::junction(inner, vec![]);
+                    }
+                    shape => push_unique(&mut simplified, from_shape(shape)),
+                }
+            }
+            if simplified.len() == 1 {
+                simplified.remove(0)
+            } else {
+                E::junction(connective, simplified)
+            }
+        }
+    }
+}
+
+fn push_unique<E: PartialEq>(operands: &mut Vec<E>, operand: E) {
+    if !operands.contains(&operand) {
+        operands.push(operand);
+    }
+}
+
+/// Push negations inwards, so that they are only applied to atomic expressions
+fn negation_normal_form<E: Boolean>(expression: E, negate: bool) -> E {
+    match expression.into_shape() {
+        Shape::Atom(atom) => {
+            if negate {
+                E::not(atom)
+            } else {
+                atom
+            }
+        }
+        Shape::Not(operand) => negation_normal_form(operand, !negate),
+        Shape::Junction(connective, operands) => E::junction(
+            if negate {
+                connective.dual()
+            } else {
+                connective
+            },
+            operands
+                .into_iter()
+                .map(|operand| negation_normal_form(operand, negate))
+                .collect(),
+        ),
+    }
+}
+
+/// Convert an expression to a normal form whose outermost connective is `outer`
+fn normal_form<E: Boolean + Clone>(expression: E, outer: Connective) -> E {
+    let nnf = negation_normal_form(simplify(expression), false);
+    let clauses = clauses(nnf, outer);
+    simplify(E::junction(
+        outer,
+        clauses
+            .into_iter()
+            .map(|clause| E::junction(outer.dual(), clause))
+            .collect(),
+    ))
+}
+
+/// Compute the clauses of an expression in negation normal form, where the outermost
+/// connective is `outer`, and each clause is combined using the dual connective
+fn clauses<E: Boolean + Clone>(expression: E, outer: Connective) -> Vec<Vec<E>> {
+    match expression.into_shape() {
+        Shape::Junction(connective, operands) if connective == outer => operands
+            .into_iter()
+            .flat_map(|operand| clauses(operand, outer))
+            .collect(),
+        Shape::Junction(_, operands) => {
+            // Distribute the dual connective over the outer connective
+            let mut product: Vec<Vec<E>> = vec![vec![]];
+            for operand in operands {
+                let operand_clauses = clauses(operand, outer);
+                product = product
+                    .iter()
+                    .flat_map(|left| {
+                        operand_clauses
+                            .iter()
+                            .map(move |right| left.iter().chain(right.iter()).cloned().collect())
+                    })
+                    .collect();
+            }
+            product
+        }
+        literal => vec![vec![from_shape(literal)]],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builders::{and, col, exists_related, lit, not, or, star_count};
+    use crate::GroupExpression;
+
+    #[test]
+    fn test_simplify() {
+        let a = col("a").equals(lit(1));
+        let b = col("b").equals(lit(2));
+
+        assert_eq!(
+            and([a.clone(), and([b.clone(), a.clone()]), not(not(b.clone()))]).simplify(),
+            and([a.clone(), b])
+        );
+        assert_eq!(and([a.clone(), or([])]).simplify(), or([]));
+        assert_eq!(or([a.clone(), and([])]).simplify(), and([]));
+        assert_eq!(and([and([]), a.clone()]).simplify(), a);
+        assert_eq!(not(and([])).simplify(), or([]));
+    }
+
+    #[test]
+    fn test_normal_forms() {
+        let a = col("a").equals(lit(1));
+        let b = col("b").equals(lit(2));
+        let c = col("c").equals(lit(3));
+
+        let expression = or([a.clone(), and([b.clone(), not(or([c.clone()]))])]);
+
+        assert_eq!(
+            expression.clone().to_cnf(),
+            and([or([a.clone(), b.clone()]), or([a.clone(), not(c.clone())])])
+        );
+        assert_eq!(
+            expression.to_dnf(),
+            or([a.clone(), and([b.clone(), not(c)])])
+        );
+        assert_eq!(
+            not(or([a.clone(), b.clone()])).to_dnf(),
+            and([not(a), not(b)])
+        );
+    }
+
+    #[test]
+    fn test_double_negation() {
+        let a = col("a").equals(lit(1));
+
+        assert_eq!(not(not(a.clone())).simplify(), a);
+        assert_eq!(not(not(not(a.clone()))).simplify(), not(a.clone()));
+        assert_eq!(not(not(not(not(a.clone())))).simplify(), a);
+        assert_eq!(or([not(not(a.clone())), not(not(a.clone()))]).simplify(), a);
+    }
+
+    #[test]
+    fn test_de_morgan() {
+        let a = col("a").equals(lit(1));
+        let b = col("b").equals(lit(2));
+
+        // Simplification alone does not push negations inwards
+        assert_eq!(
+            not(and([a.clone(), b.clone()])).simplify(),
+            not(and([a.clone(), b.clone()]))
+        );
+        assert_eq!(
+            not(and([a.clone(), b.clone()])).to_dnf(),
+            or([not(a.clone()), not(b.clone())])
+        );
+        assert_eq!(
+            not(and([a.clone(), b.clone()])).to_cnf(),
+            or([not(a.clone()), not(b.clone())])
+        );
+        assert_eq!(
+            not(or([a.clone(), not(b.clone())])).to_cnf(),
+            and([not(a), b])
+        );
+    }
+
+    #[test]
+    fn test_constant_folding() {
+        let a = col("a").equals(lit(1));
+
+        assert_eq!(and([]).simplify(), and([]));
+        assert_eq!(or([]).simplify(), or([]));
+        assert_eq!(not(or([])).simplify(), and([]));
+        assert_eq!(not(not(and([]))).simplify(), and([]));
+        assert_eq!(or([or([]), a.clone()]).simplify(), a);
+        assert_eq!(and([and([]), and([])]).simplify(), and([]));
+        assert_eq!(or([or([]), or([])]).simplify(), or([]));
+        assert_eq!(and([a.clone(), not(and([]))]).simplify(), or([]));
+        assert_eq!(or([not(or([])), a]).simplify(), and([]));
+    }
+
+    #[test]
+    fn test_flatten() {
+        let a = col("a").equals(lit(1));
+        let b = col("b").equals(lit(2));
+        let c = col("c").equals(lit(3));
+        let d = col("d").equals(lit(4));
+
+        assert_eq!(
+            and([a.clone(), and([b.clone(), and([c.clone(), d.clone()])])]).simplify(),
+            and([a.clone(), b.clone(), c.clone(), d.clone()])
+        );
+        assert_eq!(
+            or([or([a.clone(), b.clone()]), or([or([c.clone()]), d.clone()])]).simplify(),
+            or([a.clone(), b.clone(), c.clone(), d.clone()])
+        );
+        // Operands with a different connective are not flattened
+        assert_eq!(
+            and([a.clone(), or([b.clone(), and([c.clone(), d.clone()])])]).simplify(),
+            and([a.clone(), or([b.clone(), and([c, d])])])
+        );
+        assert_eq!(
+            and([or([a.clone()]), and([b.clone()])]).simplify(),
+            and([a, b])
+        );
+    }
+
+    #[test]
+    fn test_simplify_exists_predicate() {
+        let a = col("a").equals(lit(1));
+
+        assert_eq!(
+            not(not(exists_related(
+                "author_articles",
+                Some(and([and([a.clone()]), not(not(a.clone()))]))
+            )))
+            .simplify(),
+            exists_related("author_articles", Some(a))
+        );
+    }
+
+    #[test]
+    fn test_simplify_group_expression() {
+        let a = star_count().op("gt", 1);
+        let b = star_count().op("lt", 10);
+
+        assert_eq!(
+            GroupExpression::Not {
+                expression: Box::new(GroupExpression::Not {
+                    expression: Box::new(GroupExpression::And {
+                        expressions: vec![
+                            a.clone(),
+                            GroupExpression::And {
+                                expressions: vec![b.clone(), a.clone()],
+                            },
+                        ],
+                    }),
+                }),
+            }
+            .simplify(),
+            GroupExpression::And {
+                expressions: vec![a, b],
+            }
+        );
+    }
+}
@@ -0,0 +1,955 @@
+//! A rule-based optimizer for relational query plans.
+//!
+//! [`optimize`] rewrites a [`Relation`] into an equivalent plan which is usually
+//! cheaper to evaluate, and which may be supported by connectors that only
+//! implement part of the relational query capabilities:
+//!
+//! * filters are pushed down through projections and joins,
+//! * adjacent filters are merged, if `relational_query.filter.scalar.and` is supported,
+//! * adjacent pagination nodes are merged,
+//! * sorts which cannot affect the result of an aggregation are removed, and
+//! * unused columns are pruned from projections and from `from` nodes.
+//!
+//! A rule which would introduce an expression that the connector does not
+//! support in the relevant context is not applied.
+
+use std::collections::BTreeSet;
+
+use crate::required_capabilities::expression_is_supported;
+use crate::visit::{self, Visit};
+use crate::visit_mut::{self, VisitMut};
+use crate::{JoinOn, JoinType, Relation, RelationalExpression, RelationalQueryCapabilities, Sort};
+
+/// The maximum number of rewriting passes, after which the plan is returned as-is
+const MAX_PASSES: usize = 16;
+
+/// Optimize a relational query plan, using only the capabilities which the
+/// connector has advertised.
+///
+/// Columns are not pruned from plans which refer to columns that their inputs
+/// do not produce, so that such plans are still rejected by the connector.
+pub fn optimize(relation: Relation, capabilities: &RelationalQueryCapabilities) -> Relation {
+    let mut relation = relation;
+    for _ in 0..MAX_PASSES {
+        let rewritten = rewrite(relation.clone(), capabilities);
+        if rewritten == relation {
+            break;
+        }
+        relation = rewritten;
+    }
+
+    if !column_refs_in_range(&relation) {
+        return relation;
+    }
+    let required = (0..arity(&relation)).collect();
+    prune(relation, &required).0
+}
+
+/// The number of columns produced by a relation
+pub(crate) fn arity(relation: &Relation) -> usize {
+    match relation {
+        Relation::From { columns, .. } => columns.len(),
+        Relation::Paginate { input, .. }
+        | Relation::Filter { input, .. }
+        | Relation::Sort { input, .. } => arity(input),
+        Relation::Project { exprs, .. } => exprs.len(),
+        Relation::Join {
+            left,
+            right,
+            join_type,
+            ..
+        } => match join_type {
+            JoinType::LeftSemi | JoinType::LeftAnti => arity(left),
+            JoinType::RightSemi | JoinType::RightAnti => arity(right),
+            JoinType::Left | JoinType::Right | JoinType::Inner | JoinType::Full => {
+                arity(left) + arity(right)
+            }
+        },
+        Relation::Aggregate {
+            group_by,
+            aggregates,
+            ..
+        } => group_by.len() + aggregates.len(),
+        Relation::Window { input, exprs } => arity(input) + exprs.len(),
+        Relation::Union { relations } => relations.first().map_or(0, arity),
+    }
+}
+
+/// Whether every column reference in a plan refers to a column of the
+/// relevant input
+fn column_refs_in_range(relation: &Relation) -> bool {
+    let within = |exprs: BTreeSet<usize>, input: &Relation| {
+        let input_arity = arity(input);
+        exprs.iter().all(|index| *index < input_arity)
+    };
+    match relation {
+        Relation::From { .. } => true,
+        Relation::Paginate { input, .. } => column_refs_in_range(input),
+        Relation::Project { input, exprs } | Relation::Window { input, exprs } => {
+            within(refs_of(exprs), input) && column_refs_in_range(input)
+        }
+        Relation::Filter { input, predicate } => {
+            within(column_refs(predicate), input) && column_refs_in_range(input)
+        }
+        Relation::Sort { input, exprs } => {
+            within(refs_of(exprs.iter().map(|sort| &sort.expr)), input)
+                && column_refs_in_range(input)
+        }
+        Relation::Join {
+            left, right, on, ..
+        } => {
+            within(refs_of(on.iter().map(|join_on| &join_on.left)), left)
+                && within(refs_of(on.iter().map(|join_on| &join_on.right)), right)
+                && column_refs_in_range(left)
+                && column_refs_in_range(right)
+        }
+        Relation::Aggregate {
+            input,
+            group_by,
+            aggregates,
+        } => {
+            within(refs_of(group_by.iter().chain(aggregates)), input) && column_refs_in_range(input)
+        }
+        Relation::Union { relations } => relations.iter().all(column_refs_in_range),
+    }
+}
+
+#[cfg(not(feature = "arc-relation"))]
+#[allow(clippy::boxed_local)]
+fn take(input: Box<Relation>) -> Relation {
+    *input
+}
+
+#[cfg(feature = "arc-relation")]
+fn take(input: std::sync::Arc<Relation>) -> Relation {
+    std::sync::Arc::unwrap_or_clone(input)
+}
+
+/// Apply a function to each of the immediate inputs of a relation
+fn map_inputs(relation: Relation, f: &mut impl FnMut(Relation) -> Relation) -> Relation {
+    match relation {
+        Relation::From { .. } => relation,
+        Relation::Paginate { input, fetch, skip } => Relation::Paginate {
+            input: f(take(input)).into(),
+            fetch,
+            skip,
+        },
+        Relation::Project { input, exprs } => Relation::Project {
+            input: f(take(input)).into(),
+            exprs,
+        },
+        Relation::Filter { input, predicate } => Relation::Filter {
+            input: f(take(input)).into(),
+            predicate,
+        },
+        Relation::Sort { input, exprs } => Relation::Sort {
+            input: f(take(input)).into(),
+            exprs,
+        },
+        Relation::Join {
+            left,
+            right,
+            on,
+            join_type,
+        } => Relation::Join {
+            left: f(take(left)).into(),
+            right: f(take(right)).into(),
+            on,
+            join_type,
+        },
+        Relation::Aggregate {
+            input,
+            group_by,
+            aggregates,
+        } => Relation::Aggregate {
+            input: f(take(input)).into(),
+            group_by,
+            aggregates,
+        },
+        Relation::Window { input, exprs } => Relation::Window {
+            input: f(take(input)).into(),
+            exprs,
+        },
+        Relation::Union { relations } => Relation::Union {
+            relations: relations.into_iter().map(f).collect(),
+        },
+    }
+}
+
+/// Apply the rewriting rules once, bottom-up
+fn rewrite(relation: Relation, capabilities: &RelationalQueryCapabilities) -> Relation {
+    let relation = map_inputs(relation, &mut |input| rewrite(input, capabilities));
+
+    match relation {
+        Relation::Filter { input, predicate } => {
+            push_down_filter(take(input), predicate, capabilities)
+        }
+        Relation::Paginate { input, fetch, skip } => match take(input) {
+            Relation::Paginate {
+                input,
+                fetch: inner_fetch,
+                skip: inner_skip,
+            } => Relation::Paginate {
+                input,
+                fetch: match inner_fetch {
+                    None => fetch,
+                    Some(inner_fetch) => {
+                        let remaining = inner_fetch.saturating_sub(skip);
+                        Some(fetch.map_or(remaining, |fetch| fetch.min(remaining)))
+                    }
+                },
+                skip: inner_skip.saturating_add(skip),
+            },
+            input => Relation::Paginate {
+                input: input.into(),
+                fetch,
+                skip,
+            },
+        },
+        Relation::Aggregate {
+            input,
+            group_by,
+            aggregates,
+        } => match take(input) {
+            Relation::Sort { input, .. } if !aggregates.iter().any(is_order_sensitive) => {
+                Relation::Aggregate {
+                    input,
+                    group_by,
+                    aggregates,
+                }
+            }
+            input => Relation::Aggregate {
+                input: input.into(),
+                group_by,
+                aggregates,
+            },
+        },
+        relation => relation,
+    }
+}
+
+/// Place a filter above the given input, pushing it as far down as possible
+fn push_down_filter(
+    input: Relation,
+    predicate: RelationalExpression,
+    capabilities: &RelationalQueryCapabilities,
+) -> Relation {
+    let Some(filter_capabilities) = &capabilities.filter else {
+        return Relation::Filter {
+            input: input.into(),
+            predicate,
+        };
+    };
+
+    match input {
+        Relation::Filter {
+            input,
+            predicate: inner_predicate,
+        } if filter_capabilities.scalar.and.is_some() => Relation::Filter {
+            input,
+            predicate: RelationalExpression::And {
+                left: Box::new(inner_predicate),
+                right: Box::new(predicate),
+            },
+        },
+        Relation::Project { input, exprs } => {
+            let referenced = column_refs(&predicate);
+            let substituted = substitute(predicate.clone(), &exprs);
+            if referenced
+                .iter()
+                .all(|index| exprs.get(*index).is_some_and(|expr| !is_volatile(expr)))
+                && expression_is_supported(&substituted, filter_capabilities)
+            {
+                Relation::Project {
+                    input: push_down_filter(take(input), substituted, capabilities).into(),
+                    exprs,
+                }
+            } else {
+                Relation::Filter {
+                    input: Relation::Project { input, exprs }.into(),
+                    predicate,
+                }
+            }
+        }
+        Relation::Join {
+            left,
+            right,
+            on,
+            join_type,
+        } => push_down_filter_through_join(
+            take(left),
+            take(right),
+            on,
+            join_type,
+            predicate,
+            capabilities,
+        ),
+        input => Relation::Filter {
+            input: input.into(),
+            predicate,
+        },
+    }
+}
+
+fn push_down_filter_through_join(
+    left: Relation,
+    right: Relation,
+    on: Vec<JoinOn>,
+    join_type: JoinType,
+    predicate: RelationalExpression,
+    capabilities: &RelationalQueryCapabilities,
+) -> Relation {
+    // A filter can be moved to one side of a join if that side's rows are
+    // not padded with nulls, and it only refers to that side's columns.
+    let (to_left, to_right) = match join_type {
+        JoinType::Inner => (true, true),
+        JoinType::Left | JoinType::LeftSemi | JoinType::LeftAnti => (true, false),
+        JoinType::Right | JoinType::RightSemi | JoinType::RightAnti => (false, true),
+        JoinType::Full => (false, false),
+    };
+    let left_arity = arity(&left);
+    let right_offset = match join_type {
+        JoinType::RightSemi | JoinType::RightAnti => 0,
+        _ => left_arity,
+    };
+
+    let mut left_predicates = vec![];
+    let mut right_predicates = vec![];
+    let mut remaining = vec![];
+
+    for conjunct in conjuncts(predicate) {
+        let referenced = column_refs(&conjunct);
+        if to_left && referenced.iter().all(|index| *index < left_arity) {
+            left_predicates.push(conjunct);
+        } else if to_right && referenced.iter().all(|index| *index >= right_offset) {
+            right_predicates.push(remap(conjunct, &|index| index - right_offset));
+        } else {
+            remaining.push(conjunct);
+        }
+    }
+
+    let left = match conjunction(left_predicates) {
+        Some(predicate) => push_down_filter(left, predicate, capabilities),
+        None => left,
+    };
+    let right = match conjunction(right_predicates) {
+        Some(predicate) => push_down_filter(right, predicate, capabilities),
+        None => right,
+    };
+
+    let join = Relation::Join {
+        left: left.into(),
+        right: right.into(),
+        on,
+        join_type,
+    };
+
+    match conjunction(remaining) {
+        Some(predicate) => Relation::Filter {
+            input: join.into(),
+            predicate,
+        },
+        None => join,
+    }
+}
+
+/// Split a predicate into the operands of its top-level conjunction
+fn conjuncts(predicate: RelationalExpression) -> Vec<RelationalExpression> {
+    match predicate {
+        RelationalExpression::And { left, right } => {
+            let mut conjuncts = conjuncts(*left);
+            conjuncts.extend(self::conjuncts(*right));
+            conjuncts
+        }
+        predicate => vec![predicate],
+    }
+}
+
+fn conjunction(predicates: Vec<RelationalExpression>) -> Option<RelationalExpression> {
+    predicates
+        .into_iter()
+        .reduce(|left, right| RelationalExpression::And {
+            left: Box::new(left),
+            right: Box::new(right),
+        })
+}
+
+/// Whether the result of an aggregate expression can depend on the order of its input rows
+fn is_order_sensitive(expr: &RelationalExpression) -> bool {
+    struct OrderSensitive(bool);
+
+    impl Visit for OrderSensitive {
+        fn visit_relational_expression(&mut self, expr: &RelationalExpression) {
+            match expr {
+                RelationalExpression::FirstValue { order_by, .. }
+                | RelationalExpression::LastValue { order_by, .. }
+                | RelationalExpression::StringAgg { order_by, .. }
+                | RelationalExpression::ArrayAgg { order_by, .. }
+                    if order_by.as_ref().is_none_or(Vec::is_empty) =>
+                {
+                    self.0 = true;
+                }
+                _ => visit::visit_relational_expression(self, expr),
+            }
+        }
+    }
+
+    let mut visitor = OrderSensitive(false);
+    visitor.visit_relational_expression(expr);
+    visitor.0
+}
+
+/// Whether an expression can produce a different value each time it is evaluated,
+/// so that it cannot be duplicated
+pub(crate) fn is_volatile(expr: &RelationalExpression) -> bool {
+    struct Volatile(bool);
+
+    impl Visit for Volatile {
+        fn visit_relational_expression(&mut self, expr: &RelationalExpression) {
+            if let RelationalExpression::Random = expr {
+                self.0 = true;
+            }
+            visit::visit_relational_expression(self, expr);
+        }
+    }
+
+    let mut visitor = Volatile(false);
+    visitor.visit_relational_expression(expr);
+    visitor.0
+}
+
+/// The indices of the input columns referred to by an expression
+fn column_refs(expr: &RelationalExpression) -> BTreeSet<usize> {
+    struct ColumnRefs(BTreeSet<usize>);
+
+    impl Visit for ColumnRefs {
+        fn visit_relational_expression(&mut self, expr: &RelationalExpression) {
+            if let RelationalExpression::Column { index } = expr {
+                self.0.insert(column_index(*index));
+            }
+            visit::visit_relational_expression(self, expr);
+        }
+    }
+
+    let mut visitor = ColumnRefs(BTreeSet::new());
+    visitor.visit_relational_expression(expr);
+    visitor.0
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn column_index(index: u64) -> usize {
+    index as usize
+}
+
+/// Replace each column reference in an expression with the corresponding expression
+fn substitute(expr: RelationalExpression, exprs: &[RelationalExpression]) -> RelationalExpression {
+    struct Substitute<'a>(&'a [RelationalExpression]);
+
+    impl VisitMut for Substitute<'_> {
+        fn visit_relational_expression_mut(&mut self, expr: &mut RelationalExpression) {
+            if let RelationalExpression::Column { index } = expr {
+                if let Some(replacement) = self.0.get(column_index(*index)) {
+                    *expr = replacement.clone();
+                }
+            } else {
+                visit_mut::visit_relational_expression_mut(self, expr);
+            }
+        }
+    }
+
+    let mut expr = expr;
+    Substitute(exprs).visit_relational_expression_mut(&mut expr);
+    expr
+}
+
+/// Renumber the column references in an expression
+fn remap(expr: RelationalExpression, f: &dyn Fn(usize) -> usize) -> RelationalExpression {
+    struct Remap<'a>(&'a dyn Fn(usize) -> usize);
+
+    impl VisitMut for Remap<'_> {
+        fn visit_relational_expression_mut(&mut self, expr: &mut RelationalExpression) {
+            if let RelationalExpression::Column { index } = expr {
+                *index = (self.0)(column_index(*index)) as u64;
+            }
+            visit_mut::visit_relational_expression_mut(self, expr);
+        }
+    }
+
+    let mut expr = expr;
+    Remap(f).visit_relational_expression_mut(&mut expr);
+    expr
+}
+
+/// A mapping from the column indices of a relation to those of its pruned
+/// replacement, which is `None` for columns which were removed
+type Mapping = Vec<Option<usize>>;
+
+fn remap_with(expr: RelationalExpression, mapping: &Mapping) -> RelationalExpression {
+    remap(expr, &|index| {
+        mapping
+            .get(index)
+            .copied()
+            .flatten()
+            .expect("column references are checked before pruning")
+    })
+}
+
+fn remap_sort(sort: Sort, mapping: &Mapping) -> Sort {
+    Sort {
+        expr: remap_with(sort.expr, mapping),
+        ..sort
+    }
+}
+
+fn refs_of<'a>(exprs: impl IntoIterator<Item = &'a RelationalExpression>) -> BTreeSet<usize> {
+    exprs.into_iter().flat_map(column_refs).collect()
+}
+
+/// Keep the required indices out of `len`, or the first index if none are required,
+/// since relations must produce at least one column
+fn keep(required: &BTreeSet<usize>, len: usize) -> Vec<usize> {
+    let kept: Vec<usize> = required.iter().copied().filter(|i| *i < len).collect();
+    if kept.is_empty() && len > 0 {
+        vec![0]
+    } else {
+        kept
+    }
+}
+
+fn mapping_from_kept(kept: &[usize], len: usize) -> Mapping {
+    let mut mapping = vec![None; len];
+    for (new_index, old_index) in kept.iter().enumerate() {
+        mapping[*old_index] = Some(new_index);
+    }
+    mapping
+}
+
+/// Remove columns which are not required from projections and `from` nodes,
+/// returning the pruned relation and the mapping from old to new column indices
+fn prune(relation: Relation, required: &BTreeSet<usize>) -> (Relation, Mapping) {
+    match relation {
+        Relation::From {
+            collection,
+            columns,
+            arguments,
+        } => {
+            let kept = keep(required, columns.len());
+            let mapping = mapping_from_kept(&kept, columns.len());
+            let columns = kept.iter().map(|i| columns[*i].clone()).collect();
+            (
+                Relation::From {
+                    collection,
+                    columns,
+                    arguments,
+                },
+                mapping,
+            )
+        }
+        Relation::Project { input, exprs } => {
+            let kept = keep(required, exprs.len());
+            let mapping = mapping_from_kept(&kept, exprs.len());
+            let exprs: Vec<RelationalExpression> = kept.iter().map(|i| exprs[*i].clone()).collect();
+            let (input, input_mapping) = prune(take(input), &refs_of(&exprs));
+            (
+                Relation::Project {
+                    input: input.into(),
+                    exprs: exprs
+                        .into_iter()
+                        .map(|expr| remap_with(expr, &input_mapping))
+                        .collect(),
+                },
+                mapping,
+            )
+        }
+        Relation::Filter { input, predicate } => {
+            let mut input_required = required.clone();
+            input_required.extend(column_refs(&predicate));
+            let (input, mapping) = prune(take(input), &input_required);
+            let predicate = remap_with(predicate, &mapping);
+            (
+                Relation::Filter {
+                    input: input.into(),
+                    predicate,
+                },
+                mapping,
+            )
+        }
+        Relation::Sort { input, exprs } => {
+            let mut input_required = required.clone();
+            input_required.extend(refs_of(exprs.iter().map(|sort| &sort.expr)));
+            let (input, mapping) = prune(take(input), &input_required);
+            let exprs = exprs
+                .into_iter()
+                .map(|sort| remap_sort(sort, &mapping))
+                .collect();
+            (
+                Relation::Sort {
+                    input: input.into(),
+                    exprs,
+                },
+                mapping,
+            )
+        }
+        Relation::Paginate { input, fetch, skip } => {
+            let (input, mapping) = prune(take(input), required);
+            (
+                Relation::Paginate {
+                    input: input.into(),
+                    fetch,
+                    skip,
+                },
+                mapping,
+            )
+        }
+        Relation::Join {
+            left,
+            right,
+            on,
+            join_type,
+        } => prune_join(take(left), take(right), on, join_type, required),
+        Relation::Aggregate {
+            input,
+            group_by,
+            aggregates,
+        } => {
+            let output_arity = group_by.len() + aggregates.len();
+            let (input, input_mapping) =
+                prune(take(input), &refs_of(group_by.iter().chain(&aggregates)));
+            (
+                Relation::Aggregate {
+                    input: input.into(),
+                    group_by: group_by
+                        .into_iter()
+                        .map(|expr| remap_with(expr, &input_mapping))
+                        .collect(),
+                    aggregates: aggregates
+                        .into_iter()
+                        .map(|expr| remap_with(expr, &input_mapping))
+                        .collect(),
+                },
+                (0..output_arity).map(Some).collect(),
+            )
+        }
+        Relation::Window { input, exprs } => {
+            let input_arity = arity(&input);
+            let mut input_required: BTreeSet<usize> = required
+                .iter()
+                .copied()
+                .filter(|i| *i < input_arity)
+                .collect();
+            input_required.extend(refs_of(&exprs));
+            let (input, input_mapping) = prune(take(input), &input_required);
+            let new_input_arity = arity(&input);
+            let mapping = input_mapping
+                .iter()
+                .copied()
+                .chain((0..exprs.len()).map(|i| Some(new_input_arity + i)))
+                .collect();
+            (
+                Relation::Window {
+                    input: input.into(),
+                    exprs: exprs
+                        .into_iter()
+                        .map(|expr| remap_with(expr, &input_mapping))
+                        .collect(),
+                },
+                mapping,
+            )
+        }
+        Relation::Union { relations } => {
+            // Every branch of a union must produce the same columns, so
+            // columns are only pruned within each branch.
+            let output_arity = relations.first().map_or(0, arity);
+            let relations = relations
+                .into_iter()
+                .map(|relation| {
+                    let required = (0..arity(&relation)).collect();
+                    prune(relation, &required).0
+                })
+                .collect();
+            (
+                Relation::Union { relations },
+                (0..output_arity).map(Some).collect(),
+            )
+        }
+    }
+}
+
+fn prune_join(
+    left: Relation,
+    right: Relation,
+    on: Vec<JoinOn>,
+    join_type: JoinType,
+    required: &BTreeSet<usize>,
+) -> (Relation, Mapping) {
+    let outputs_left = !matches!(join_type, JoinType::RightSemi | JoinType::RightAnti);
+    let outputs_right = !matches!(join_type, JoinType::LeftSemi | JoinType::LeftAnti);
+    let left_arity = arity(&left);
+    let right_arity = arity(&right);
+    let right_offset = if outputs_left { left_arity } else { 0 };
+
+    let mut left_required = refs_of(on.iter().map(|join_on| &join_on.left));
+    if outputs_left {
+        left_required.extend(required.iter().copied().filter(|i| *i < left_arity));
+    }
+    let mut right_required = refs_of(on.iter().map(|join_on| &join_on.right));
+    if outputs_right {
+        right_required.extend(
+            required
+                .iter()
+                .copied()
+                .filter(|i| *i >= right_offset && *i < right_offset + right_arity)
+                .map(|i| i - right_offset),
+        );
+    }
+
+    let (left, left_mapping) = prune(left, &left_required);
+    let (right, right_mapping) = prune(right, &right_required);
+    let new_right_offset = if outputs_left { arity(&left) } else { 0 };
+
+    let mut mapping = vec![];
+    if outputs_left {
+        mapping.extend(left_mapping.iter().copied());
+    }
+    if outputs_right {
+        mapping.extend(
+            right_mapping
+                .iter()
+                .map(|index| index.map(|index| index + new_right_offset)),
+        );
+    }
+
+    let on = on
+        .into_iter()
+        .map(|join_on| JoinOn {
+            left: remap_with(join_on.left, &left_mapping),
+            right: remap_with(join_on.right, &right_mapping),
+        })
+        .collect();
+
+    (
+        Relation::Join {
+            left: left.into(),
+            right: right.into(),
+            on,
+            join_type,
+        },
+        mapping,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        LeafCapability, RelationalExpressionCapabilities, RelationalJoinCapabilities,
+        RelationalScalarExpressionCapabilities,
+    };
+
+    fn relation(value: serde_json::Value) -> Relation {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_push_filter_through_project_and_prune() {
+        let plan = relation(serde_json::json!({
+            "type": "filter",
+            "input": {
+                "type": "project",
+                "input": {
+                    "type": "from",
+                    "collection": "articles",
+                    "columns": ["id", "title", "author_id", "published"],
+                },
+                "exprs": [
+                    { "type": "column", "index": 1 },
+                    { "type": "column", "index": 2 },
+                ],
+            },
+            "predicate": {
+                "type": "eq",
+                "left": { "type": "column", "index": 1 },
+                "right": { "type": "literal", "literal": { "type": "Int64", "value": 1 } },
+            },
+        }));
+
+        let expected = relation(serde_json::json!({
+            "type": "project",
+            "input": {
+                "type": "filter",
+                "input": {
+                    "type": "from",
+                    "collection": "articles",
+                    "columns": ["title", "author_id"],
+                },
+                "predicate": {
+                    "type": "eq",
+                    "left": { "type": "column", "index": 1 },
+                    "right": { "type": "literal", "literal": { "type": "Int64", "value": 1 } },
+                },
+            },
+            "exprs": [
+                { "type": "column", "index": 0 },
+                { "type": "column", "index": 1 },
+            ],
+        }));
+
+        let capabilities = RelationalQueryCapabilities {
+            filter: Some(RelationalExpressionCapabilities::default()),
+            ..Default::default()
+        };
+
+        assert_eq!(optimize(plan, &capabilities), expected);
+    }
+
+    #[test]
+    fn test_push_filter_through_left_join() {
+        let eq = |index: u64, value: i64| {
+            serde_json::json!({
+                "type": "eq",
+                "left": { "type": "column", "index": index },
+                "right": { "type": "literal", "literal": { "type": "Int64", "value": value } },
+            })
+        };
+
+        let plan = relation(serde_json::json!({
+            "type": "project",
+            "input": {
+                "type": "filter",
+                "input": {
+                    "type": "join",
+                    "left": {
+                        "type": "from",
+                        "collection": "articles",
+                        "columns": ["id", "author_id"],
+                    },
+                    "right": {
+                        "type": "from",
+                        "collection": "authors",
+                        "columns": ["id", "name"],
+                    },
+                    "on": [{
+                        "left": { "type": "column", "index": 1 },
+                        "right": { "type": "column", "index": 0 },
+                    }],
+                    "join_type": "left",
+                },
+                "predicate": { "type": "and", "left": eq(0, 1), "right": eq(2, 2) },
+            },
+            "exprs": [
+                { "type": "column", "index": 0 },
+                { "type": "column", "index": 2 },
+            ],
+        }));
+
+        // Only the predicate on the left side can be pushed down, and the
+        // unused `name` column is pruned.
+        let expected = relation(serde_json::json!({
+            "type": "project",
+            "input": {
+                "type": "filter",
+                "input": {
+                    "type": "join",
+                    "left": {
+                        "type": "filter",
+                        "input": {
+                            "type": "from",
+                            "collection": "articles",
+                            "columns": ["id", "author_id"],
+                        },
+                        "predicate": eq(0, 1),
+                    },
+                    "right": { "type": "from", "collection": "authors", "columns": ["id"] },
+                    "on": [{
+                        "left": { "type": "column", "index": 1 },
+                        "right": { "type": "column", "index": 0 },
+                    }],
+                    "join_type": "left",
+                },
+                "predicate": eq(2, 2),
+            },
+            "exprs": [
+                { "type": "column", "index": 0 },
+                { "type": "column", "index": 2 },
+            ],
+        }));
+
+        let capabilities = RelationalQueryCapabilities {
+            filter: Some(RelationalExpressionCapabilities {
+                scalar: RelationalScalarExpressionCapabilities {
+                    and: Some(LeafCapability {}),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            join: Some(RelationalJoinCapabilities::default()),
+            ..Default::default()
+        };
+
+        assert_eq!(optimize(plan, &capabilities), expected);
+    }
+
+    #[test]
+    fn test_merge_paginate_and_remove_sort() {
+        let plan = relation(serde_json::json!({
+            "type": "paginate",
+            "input": {
+                "type": "paginate",
+                "input": {
+                    "type": "aggregate",
+                    "input": {
+                        "type": "sort",
+                        "input": { "type": "from", "collection": "articles", "columns": ["id"] },
+                        "exprs": [{
+                            "expr": { "type": "column", "index": 0 },
+                            "direction": "asc",
+                            "nulls_sort": "nulls_last",
+                        }],
+                    },
+                    "group_by": [],
+                    "aggregates": [{ "type": "max", "expr": { "type": "column", "index": 0 } }],
+                },
+                "fetch": 10,
+                "skip": 5,
+            },
+            "fetch": 20,
+            "skip": 3,
+        }));
+
+        let expected = relation(serde_json::json!({
+            "type": "paginate",
+            "input": {
+                "type": "aggregate",
+                "input": { "type": "from", "collection": "articles", "columns": ["id"] },
+                "group_by": [],
+                "aggregates": [{ "type": "max", "expr": { "type": "column", "index": 0 } }],
+            },
+            "fetch": 7,
+            "skip": 8,
+        }));
+
+        let capabilities = RelationalQueryCapabilities::default();
+
+        assert_eq!(optimize(plan, &capabilities), expected);
+    }
+
+    #[test]
+    fn test_out_of_range_column_is_not_pruned() {
+        let plan = relation(serde_json::json!({
+            "type": "project",
+            "input": {
+                "type": "filter",
+                "input": { "type": "from", "collection": "articles", "columns": ["id", "title"] },
+                "predicate": {
+                    "type": "is_null",
+                    "expr": { "type": "column", "index": 5 },
+                },
+            },
+            "exprs": [{ "type": "column", "index": 0 }],
+        }));
+
+        let capabilities = RelationalQueryCapabilities::default();
+
+        assert_eq!(optimize(plan.clone(), &capabilities), plan);
+    }
+}
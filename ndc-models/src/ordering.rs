@@ -3,7 +3,10 @@ use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use std::collections::BTreeMap;
 
-use crate::{Aggregate, Argument, ArgumentName, FieldName, PathElement};
+use crate::{
+    Aggregate, Argument, ArgumentName, ComparisonOperatorName, ComparisonValue, FieldName,
+    PathElement,
+};
 
 // ANCHOR: OrderBy
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -54,9 +57,67 @@ pub enum OrderByTarget {
         /// The aggregation method to use
         aggregate: Aggregate,
     },
+    /// The ordering is performed over the relevance score of a full-text search.
+    /// Only used if the 'query.full_text_search.order_by_relevance' capability is supported.
+    Relevance {
+        /// The name of the column being searched
+        name: FieldName,
+        /// Path to a nested field within an object column.
+        /// Only non-empty if the 'query.nested_fields.order_by' capability is supported.
+        field_path: Option<Vec<FieldName>>,
+        /// The name of the search operator, which must be defined as a
+        /// 'full_text_search' operator on the type of the column
+        operator: ComparisonOperatorName,
+        /// The search query to score rows against
+        value: ComparisonValue,
+    },
+    /// The ordering is performed over the distance between a geospatial column and a value.
+    /// Only used if the 'query.geospatial.order_by_distance' capability is supported.
+    Distance {
+        /// The name of the column, whose scalar type must have the type
+        /// representation Geography or Geometry
+        name: FieldName,
+        /// Path to a nested field within an object column.
+        /// Only non-empty if the 'query.nested_fields.order_by' capability is supported.
+        field_path: Option<Vec<FieldName>>,
+        /// The value to measure the distance to, of the same type as the column
+        value: ComparisonValue,
+    },
+    /// The ordering is performed over the similarity between a vector column and a vector value.
+    /// Only used if the 'query.vector_search' capability is supported.
+    Similarity {
+        /// The name of the column, whose scalar type must have the type
+        /// representation Vector
+        name: FieldName,
+        /// Path to a nested field within an object column.
+        /// Only non-empty if the 'query.nested_fields.order_by' capability is supported.
+        field_path: Option<Vec<FieldName>>,
+        /// The metric used to compare vectors
+        metric: SimilarityMetric,
+        /// The vector to compare against, of the same type as the column
+        value: ComparisonValue,
+    },
 }
 // ANCHOR_END: OrderByTarget
 
+// ANCHOR: SimilarityMetric
+/// A metric used to compare vectors. Rows are ordered by their similarity
+/// score, which is greater for more similar vectors.
+#[derive(
+    Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[schemars(title = "Similarity Metric")]
+#[serde(rename_all = "snake_case")]
+pub enum SimilarityMetric {
+    /// The cosine of the angle between the vectors
+    Cosine,
+    /// The negated Euclidean distance between the vectors
+    L2,
+    /// The inner (dot) product of the vectors
+    InnerProduct,
+}
+// ANCHOR_END: SimilarityMetric
+
 // ANCHOR: OrderDirection
 #[derive(
     Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, JsonSchema,
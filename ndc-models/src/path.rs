@@ -0,0 +1,9 @@
+//! Paths to the parts of a request or schema, as reported in errors and
+//! schema changes.
+
+/// Extend a path with one more segment, such as a field name or an index
+pub(crate) fn at<S: ToString + ?Sized>(path: &[String], segment: &S) -> Vec<String> {
+    let mut path = path.to_vec();
+    path.push(segment.to_string());
+    path
+}
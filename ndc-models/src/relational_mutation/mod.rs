@@ -8,11 +8,15 @@ use crate::LeafCapability;
 /// Describes which features of the relational mutation API are supported by the connector.
 /// This feature is experimental and subject to breaking changes within minor versions.
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Mutation Capabilities")]
 pub struct RelationalMutationCapabilities {
     pub insert: Option<LeafCapability>,
     pub update: Option<LeafCapability>,
     pub delete: Option<LeafCapability>,
+    /// Does the connector support inserts which update or skip conflicting rows
+    pub upsert: Option<LeafCapability>,
+    /// Does the connector support returning columns of the affected rows
+    pub returning: Option<LeafCapability>,
 }
 // ANCHOR_END: RelationalMutationCapabilities
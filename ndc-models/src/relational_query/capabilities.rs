@@ -8,7 +8,7 @@ use crate::LeafCapability;
 /// Describes which features of the relational query API are supported by the connector.
 /// This feature is experimental and subject to breaking changes within minor versions.
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Query Capabilities")]
 pub struct RelationalQueryCapabilities {
     pub project: RelationalProjectionCapabilities,
@@ -19,12 +19,15 @@ pub struct RelationalQueryCapabilities {
     pub window: Option<RelationalWindowCapabilities>,
     pub union: Option<LeafCapability>,
     pub streaming: Option<LeafCapability>,
+    /// Does the connector describe the columns of its results in
+    /// `RelationalQueryResponse.columns`?
+    pub column_metadata: Option<LeafCapability>,
 }
 // ANCHOR_END: RelationalQueryCapabilities
 
 // ANCHOR: RelationalProjectionCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Projection Capabilities")]
 pub struct RelationalProjectionCapabilities {
     pub expression: RelationalExpressionCapabilities,
@@ -33,7 +36,7 @@ pub struct RelationalProjectionCapabilities {
 
 // ANCHOR: RelationalSortCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Sort Capabilities")]
 pub struct RelationalSortCapabilities {
     pub expression: RelationalExpressionCapabilities,
@@ -42,7 +45,7 @@ pub struct RelationalSortCapabilities {
 
 // ANCHOR: RelationalJoinCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Join Capabilities")]
 pub struct RelationalJoinCapabilities {
     pub expression: RelationalExpressionCapabilities,
@@ -52,7 +55,7 @@ pub struct RelationalJoinCapabilities {
 
 // ANCHOR: RelationalJoinTypeCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Join Type Capabilities")]
 pub struct RelationalJoinTypeCapabilities {
     pub left: Option<LeafCapability>,
@@ -68,7 +71,7 @@ pub struct RelationalJoinTypeCapabilities {
 
 // ANCHOR: RelationalAggregateCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Aggregate Capabilities")]
 pub struct RelationalAggregateCapabilities {
     pub expression: RelationalExpressionCapabilities,
@@ -78,7 +81,7 @@ pub struct RelationalAggregateCapabilities {
 
 // ANCHOR: RelationalWindowCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Window Capabilities")]
 pub struct RelationalWindowCapabilities {
     pub expression: RelationalExpressionCapabilities,
@@ -87,7 +90,7 @@ pub struct RelationalWindowCapabilities {
 
 // ANCHOR: RelationalExpressionCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Expression Capabilities")]
 pub struct RelationalExpressionCapabilities {
     pub conditional: RelationalConditionalExpressionCapabilities,
@@ -101,7 +104,7 @@ pub struct RelationalExpressionCapabilities {
 
 // ANCHOR: RelationalConditionalExpressionCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Conditional Expression Capabilities")]
 pub struct RelationalConditionalExpressionCapabilities {
     pub case: Option<RelationalCaseCapabilities>,
@@ -111,7 +114,7 @@ pub struct RelationalConditionalExpressionCapabilities {
 
 // ANCHOR: RelationalCaseCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Case Capabilities")]
 pub struct RelationalCaseCapabilities {
     pub scrutinee: Option<LeafCapability>,
@@ -120,7 +123,7 @@ pub struct RelationalCaseCapabilities {
 
 // ANCHOR: RelationalFilterExpressionCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Filter Expression Capabilities")]
 pub struct RelationalComparisonExpressionCapabilities {
     pub between: Option<LeafCapability>,
@@ -143,7 +146,7 @@ pub struct RelationalComparisonExpressionCapabilities {
 
 // ANCHOR: RelationalScalarExpressionCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Scalar Expression Capabilities")]
 pub struct RelationalScalarExpressionCapabilities {
     pub abs: Option<LeafCapability>,
@@ -214,7 +217,7 @@ pub struct RelationalScalarExpressionCapabilities {
 
 // ANCHOR: DatePartScalarExpressionCapability
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Date Part Scalar Expression Capability")]
 pub struct DatePartScalarExpressionCapability {
     pub year: Option<LeafCapability>,
@@ -236,7 +239,7 @@ pub struct DatePartScalarExpressionCapability {
 
 // ANCHOR: RelationalAggregateExpressionCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Aggregate Expression Capabilities")]
 pub struct RelationalAggregateExpressionCapabilities {
     pub avg: Option<LeafCapability>,
@@ -264,7 +267,7 @@ pub struct RelationalAggregateExpressionCapabilities {
 
 // ANCHOR: RelationalAggregateFunctionCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Aggregate Function Capabilities")]
 pub struct RelationalAggregateFunctionCapabilities {
     pub distinct: Option<LeafCapability>,
@@ -273,7 +276,7 @@ pub struct RelationalAggregateFunctionCapabilities {
 
 // ANCHOR: RelationalOrderedAggregateFunctionCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Ordered Aggregate Function Capabilities")]
 pub struct RelationalOrderedAggregateFunctionCapabilities {
     pub distinct: Option<LeafCapability>,
@@ -283,7 +286,7 @@ pub struct RelationalOrderedAggregateFunctionCapabilities {
 
 // ANCHOR: RelationalWindowExpressionCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Window Expression Capabilities")]
 pub struct RelationalWindowExpressionCapabilities {
     pub row_number: Option<LeafCapability>,
@@ -297,7 +300,7 @@ pub struct RelationalWindowExpressionCapabilities {
 
 // ANCHOR: RelationalScalarTypeCapabilities
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Scalar Type Capabilities")]
 pub struct RelationalScalarTypeCapabilities {
     /// Does the connector support the INTERVAL scalar type?
@@ -1165,6 +1165,131 @@ pub enum RelationalExpression {
     // nth_value
 }
 
+impl RelationalExpression {
+    /// The name of the expression, as given by its `type` tag in JSON
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Literal { .. } => "literal",
+            Self::Column { .. } => "column",
+            Self::Case { .. } => "case",
+            Self::And { .. } => "and",
+            Self::Or { .. } => "or",
+            Self::Not { .. } => "not",
+            Self::Eq { .. } => "eq",
+            Self::NotEq { .. } => "not_eq",
+            Self::IsDistinctFrom { .. } => "is_distinct_from",
+            Self::IsNotDistinctFrom { .. } => "is_not_distinct_from",
+            Self::Lt { .. } => "lt",
+            Self::LtEq { .. } => "lt_eq",
+            Self::Gt { .. } => "gt",
+            Self::GtEq { .. } => "gt_eq",
+            Self::IsNotNull { .. } => "is_not_null",
+            Self::IsNull { .. } => "is_null",
+            Self::IsTrue { .. } => "is_true",
+            Self::IsFalse { .. } => "is_false",
+            Self::IsNotTrue { .. } => "is_not_true",
+            Self::IsNotFalse { .. } => "is_not_false",
+            Self::In { .. } => "in",
+            Self::NotIn { .. } => "not_in",
+            Self::Like { .. } => "like",
+            Self::NotLike { .. } => "not_like",
+            Self::ILike { .. } => "i_like",
+            Self::NotILike { .. } => "not_i_like",
+            Self::Between { .. } => "between",
+            Self::NotBetween { .. } => "not_between",
+            Self::Contains { .. } => "contains",
+            Self::IsNaN { .. } => "is_na_n",
+            Self::IsZero { .. } => "is_zero",
+            Self::Plus { .. } => "plus",
+            Self::Minus { .. } => "minus",
+            Self::Multiply { .. } => "multiply",
+            Self::Divide { .. } => "divide",
+            Self::Modulo { .. } => "modulo",
+            Self::Negate { .. } => "negate",
+            Self::Cast { .. } => "cast",
+            Self::TryCast { .. } => "try_cast",
+            Self::Abs { .. } => "abs",
+            Self::ArrayElement { .. } => "array_element",
+            Self::BTrim { .. } => "b_trim",
+            Self::Ceil { .. } => "ceil",
+            Self::CharacterLength { .. } => "character_length",
+            Self::Coalesce { .. } => "coalesce",
+            Self::Concat { .. } => "concat",
+            Self::Cos { .. } => "cos",
+            Self::CurrentDate => "current_date",
+            Self::CurrentTime => "current_time",
+            Self::CurrentTimestamp => "current_timestamp",
+            Self::DatePart { .. } => "date_part",
+            Self::DateTrunc { .. } => "date_trunc",
+            Self::Exp { .. } => "exp",
+            Self::Floor { .. } => "floor",
+            Self::GetField { .. } => "get_field",
+            Self::Greatest { .. } => "greatest",
+            Self::Least { .. } => "least",
+            Self::Left { .. } => "left",
+            Self::Ln { .. } => "ln",
+            Self::Log { .. } => "log",
+            Self::Log10 { .. } => "log10",
+            Self::Log2 { .. } => "log2",
+            Self::LPad { .. } => "l_pad",
+            Self::LTrim { .. } => "l_trim",
+            Self::NullIf { .. } => "null_if",
+            Self::Nvl { .. } => "nvl",
+            Self::Power { .. } => "power",
+            Self::Random => "random",
+            Self::Replace { .. } => "replace",
+            Self::Reverse { .. } => "reverse",
+            Self::Right { .. } => "right",
+            Self::Round { .. } => "round",
+            Self::RPad { .. } => "r_pad",
+            Self::RTrim { .. } => "r_trim",
+            Self::Sqrt { .. } => "sqrt",
+            Self::StrPos { .. } => "str_pos",
+            Self::Substr { .. } => "substr",
+            Self::SubstrIndex { .. } => "substr_index",
+            Self::Tan { .. } => "tan",
+            Self::ToDate { .. } => "to_date",
+            Self::ToTimestamp { .. } => "to_timestamp",
+            Self::Trunc { .. } => "trunc",
+            Self::ToLower { .. } => "to_lower",
+            Self::ToUpper { .. } => "to_upper",
+            Self::BinaryConcat { .. } => "binary_concat",
+            Self::JsonContains { .. } => "json_contains",
+            Self::JsonGet { .. } => "json_get",
+            Self::JsonGetStr { .. } => "json_get_str",
+            Self::JsonGetInt { .. } => "json_get_int",
+            Self::JsonGetFloat { .. } => "json_get_float",
+            Self::JsonGetBool { .. } => "json_get_bool",
+            Self::JsonGetJson { .. } => "json_get_json",
+            Self::JsonAsText { .. } => "json_as_text",
+            Self::JsonLength { .. } => "json_length",
+            Self::Average { .. } => "average",
+            Self::BoolAnd { .. } => "bool_and",
+            Self::BoolOr { .. } => "bool_or",
+            Self::Count { .. } => "count",
+            Self::FirstValue { .. } => "first_value",
+            Self::LastValue { .. } => "last_value",
+            Self::Max { .. } => "max",
+            Self::Median { .. } => "median",
+            Self::Min { .. } => "min",
+            Self::StringAgg { .. } => "string_agg",
+            Self::Sum { .. } => "sum",
+            Self::Var { .. } => "var",
+            Self::Stddev { .. } => "stddev",
+            Self::StddevPop { .. } => "stddev_pop",
+            Self::ApproxPercentileCont { .. } => "approx_percentile_cont",
+            Self::ArrayAgg { .. } => "array_agg",
+            Self::ApproxDistinct { .. } => "approx_distinct",
+            Self::RowNumber { .. } => "row_number",
+            Self::DenseRank { .. } => "dense_rank",
+            Self::NTile { .. } => "n_tile",
+            Self::Rank { .. } => "rank",
+            Self::CumeDist { .. } => "cume_dist",
+            Self::PercentRank { .. } => "percent_rank",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 #[schemars(title = "CaseWhen")]
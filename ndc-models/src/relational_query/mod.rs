@@ -8,6 +8,8 @@ mod capabilities;
 pub use capabilities::*;
 mod expression;
 pub use expression::*;
+mod stream;
+pub use stream::*;
 mod types;
 pub use types::*;
 
@@ -23,10 +25,14 @@ pub struct RelationalQuery {
     pub request_arguments: Option<BTreeMap<ArgumentName, serde_json::Value>>,
 }
 
+#[skip_serializing_none]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "RelationalQueryResponse")]
 #[serde(rename_all = "snake_case")]
 pub struct RelationalQueryResponse {
+    /// The name, type and nullability of each column of the rows, in order.
+    /// Only provided if the `relational_query.column_metadata` capability is supported.
+    pub columns: Option<Vec<RelationalColumn>>,
     pub rows: Vec<Vec<serde_json::Value>>,
 }
 
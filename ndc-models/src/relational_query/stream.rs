@@ -0,0 +1,590 @@
+use std::io::{BufRead, Write};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use super::RelationalColumn;
+use crate::ErrorResponse;
+
+/// The content type of a streamed relational query response. Only used when
+/// the `relational_query.streaming` capability is supported.
+pub const RELATIONAL_QUERY_STREAM_CONTENT_TYPE: &str = "application/x-ndjson";
+
+// ANCHOR: RelationalQueryStreamChunk
+/// A single line of a streamed relational query response.
+///
+/// A stream consists of exactly one `header` chunk, followed by any number of
+/// `rows` chunks, followed by exactly one `trailer` or `error` chunk. Each
+/// chunk is serialized as JSON on a single line.
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[schemars(title = "RelationalQueryStreamChunk")]
+pub enum RelationalQueryStreamChunk {
+    Header {
+        /// The name, type and nullability of each column of the rows, in order.
+        /// Only provided if the `relational_query.column_metadata` capability is supported.
+        columns: Option<Vec<RelationalColumn>>,
+    },
+    Rows {
+        rows: Vec<Vec<serde_json::Value>>,
+    },
+    Trailer {
+        /// The total number of rows sent in `rows` chunks
+        row_count: u64,
+    },
+    /// The query failed after the header was sent. No chunks follow.
+    Error {
+        error: ErrorResponse,
+    },
+}
+// ANCHOR_END: RelationalQueryStreamChunk
+
+impl RelationalQueryStreamChunk {
+    fn name(&self) -> &'static str {
+        match self {
+            RelationalQueryStreamChunk::Header { .. } => "header",
+            RelationalQueryStreamChunk::Rows { .. } => "rows",
+            RelationalQueryStreamChunk::Trailer { .. } => "trailer",
+            RelationalQueryStreamChunk::Error { .. } => "error",
+        }
+    }
+}
+
+/// An error found while reading or writing a streamed relational query response
+#[derive(Debug, thiserror::Error)]
+pub enum RelationalQueryStreamError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid chunk: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("expected {expected} chunk, found {found} chunk")]
+    UnexpectedChunk {
+        expected: &'static str,
+        found: &'static str,
+    },
+    #[error("the stream ended before the trailer")]
+    UnexpectedEnd,
+    #[error("found {0} chunk after the trailer")]
+    ChunkAfterTrailer(&'static str),
+    #[error("the trailer reports {reported} rows, but {received} rows were received")]
+    RowCountMismatch { reported: u64, received: u64 },
+    #[error("the connector reported an error: {}", .0.message)]
+    Connector(ErrorResponse),
+}
+
+/// A batch of rows, as sent in a `rows` chunk
+type Rows = Vec<Vec<serde_json::Value>>;
+
+/// Serialize a chunk as a single line of JSON
+fn line(chunk: &RelationalQueryStreamChunk) -> Result<Vec<u8>, RelationalQueryStreamError> {
+    let mut line = serde_json::to_vec(chunk)?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+/// Parse a line of a stream, skipping blank lines
+fn parse_line(
+    line: &str,
+) -> Result<Option<RelationalQueryStreamChunk>, RelationalQueryStreamError> {
+    if line.trim().is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(serde_json::from_str(line)?))
+    }
+}
+
+/// The chunks read so far, which are checked in the same way by the blocking
+/// and async readers. A chunk of `None` marks the end of the stream.
+struct ReadState {
+    columns: Option<Vec<RelationalColumn>>,
+    row_count: u64,
+    done: bool,
+}
+
+impl ReadState {
+    fn new(header: Option<RelationalQueryStreamChunk>) -> Result<Self, RelationalQueryStreamError> {
+        match header {
+            Some(RelationalQueryStreamChunk::Header { columns }) => Ok(ReadState {
+                columns,
+                row_count: 0,
+                done: false,
+            }),
+            Some(chunk) => Err(RelationalQueryStreamError::UnexpectedChunk {
+                expected: "header",
+                found: chunk.name(),
+            }),
+            None => Err(RelationalQueryStreamError::UnexpectedEnd),
+        }
+    }
+
+    /// Handle the chunk after the header or a `rows` chunk, returning `None`
+    /// for a valid trailer
+    fn next(
+        &mut self,
+        chunk: Option<RelationalQueryStreamChunk>,
+    ) -> Result<Option<Rows>, RelationalQueryStreamError> {
+        match chunk {
+            Some(RelationalQueryStreamChunk::Rows { rows }) => {
+                self.row_count += rows.len() as u64;
+                Ok(Some(rows))
+            }
+            Some(RelationalQueryStreamChunk::Trailer { row_count }) => {
+                if row_count == self.row_count {
+                    Ok(None)
+                } else {
+                    Err(RelationalQueryStreamError::RowCountMismatch {
+                        reported: row_count,
+                        received: self.row_count,
+                    })
+                }
+            }
+            Some(RelationalQueryStreamChunk::Error { error }) => {
+                Err(RelationalQueryStreamError::Connector(error))
+            }
+            Some(RelationalQueryStreamChunk::Header { .. }) => {
+                Err(RelationalQueryStreamError::UnexpectedChunk {
+                    expected: "rows or trailer",
+                    found: "header",
+                })
+            }
+            None => Err(RelationalQueryStreamError::UnexpectedEnd),
+        }
+    }
+
+    /// Check the chunk after the trailer, which must be the end of the stream
+    fn end(chunk: Option<RelationalQueryStreamChunk>) -> Result<(), RelationalQueryStreamError> {
+        match chunk {
+            Some(chunk) => Err(RelationalQueryStreamError::ChunkAfterTrailer(chunk.name())),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Writes a streamed relational query response, one chunk at a time.
+///
+/// The header is written on construction. Rows are written in batches as they
+/// become available, and the stream is completed by calling
+/// [`RelationalQueryStreamWriter::finish`] or [`RelationalQueryStreamWriter::fail`].
+///
+/// See `AsyncRelationalQueryStreamWriter` for a writer which does not block,
+/// with the `tokio` feature.
+pub struct RelationalQueryStreamWriter<W: Write> {
+    writer: W,
+    row_count: u64,
+}
+
+impl<W: Write> RelationalQueryStreamWriter<W> {
+    pub fn new(
+        writer: W,
+        columns: Option<Vec<RelationalColumn>>,
+    ) -> Result<Self, RelationalQueryStreamError> {
+        let mut stream = RelationalQueryStreamWriter {
+            writer,
+            row_count: 0,
+        };
+        stream.write_chunk(&RelationalQueryStreamChunk::Header { columns })?;
+        Ok(stream)
+    }
+
+    /// Write a batch of rows. Empty batches are not written.
+    pub fn write_rows(&mut self, rows: Rows) -> Result<(), RelationalQueryStreamError> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        self.row_count += rows.len() as u64;
+        self.write_chunk(&RelationalQueryStreamChunk::Rows { rows })
+    }
+
+    /// Write the trailer, and return the underlying writer
+    pub fn finish(mut self) -> Result<W, RelationalQueryStreamError> {
+        let row_count = self.row_count;
+        self.write_chunk(&RelationalQueryStreamChunk::Trailer { row_count })?;
+        Ok(self.writer)
+    }
+
+    /// Report an error instead of a trailer, and return the underlying writer
+    pub fn fail(mut self, error: ErrorResponse) -> Result<W, RelationalQueryStreamError> {
+        self.write_chunk(&RelationalQueryStreamChunk::Error { error })?;
+        Ok(self.writer)
+    }
+
+    fn write_chunk(
+        &mut self,
+        chunk: &RelationalQueryStreamChunk,
+    ) -> Result<(), RelationalQueryStreamError> {
+        self.writer.write_all(&line(chunk)?)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Reads a streamed relational query response, one chunk at a time.
+///
+/// The header is read on construction, and batches of rows are returned by
+/// iterating over the reader. The order of chunks and the row count in the
+/// trailer are checked, and an `error` chunk is returned as
+/// [`RelationalQueryStreamError::Connector`].
+///
+/// See `AsyncRelationalQueryStreamReader` for a reader which does not block,
+/// with the `tokio` feature.
+pub struct RelationalQueryStreamReader<R: BufRead> {
+    reader: R,
+    state: ReadState,
+    line: String,
+}
+
+impl<R: BufRead> RelationalQueryStreamReader<R> {
+    pub fn new(mut reader: R) -> Result<Self, RelationalQueryStreamError> {
+        let mut line = String::new();
+        let header = Self::read_chunk(&mut reader, &mut line)?;
+        Ok(RelationalQueryStreamReader {
+            reader,
+            state: ReadState::new(header)?,
+            line,
+        })
+    }
+
+    /// The columns described in the header, if any
+    pub fn columns(&self) -> Option<&[RelationalColumn]> {
+        self.state.columns.as_deref()
+    }
+
+    /// The number of rows read so far
+    pub fn row_count(&self) -> u64 {
+        self.state.row_count
+    }
+
+    fn read_chunk(
+        reader: &mut R,
+        line: &mut String,
+    ) -> Result<Option<RelationalQueryStreamChunk>, RelationalQueryStreamError> {
+        loop {
+            line.clear();
+            if reader.read_line(line)? == 0 {
+                return Ok(None);
+            }
+            if let Some(chunk) = parse_line(line)? {
+                return Ok(Some(chunk));
+            }
+        }
+    }
+
+    fn next_rows(&mut self) -> Result<Option<Rows>, RelationalQueryStreamError> {
+        let chunk = Self::read_chunk(&mut self.reader, &mut self.line)?;
+        let rows = self.state.next(chunk)?;
+        if rows.is_none() {
+            ReadState::end(Self::read_chunk(&mut self.reader, &mut self.line)?)?;
+        }
+        Ok(rows)
+    }
+}
+
+impl<R: BufRead> Iterator for RelationalQueryStreamReader<R> {
+    type Item = Result<Rows, RelationalQueryStreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.state.done {
+            return None;
+        }
+        let result = self.next_rows().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.state.done = true;
+        }
+        result
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub use async_io::*;
+
+/// Async versions of the reader and writer, for use with Tokio
+#[cfg(feature = "tokio")]
+mod async_io {
+    use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+    use super::{
+        line, parse_line, ReadState, RelationalColumn, RelationalQueryStreamChunk,
+        RelationalQueryStreamError, Rows,
+    };
+    use crate::ErrorResponse;
+
+    /// Writes a streamed relational query response without blocking. See
+    /// [`RelationalQueryStreamWriter`](super::RelationalQueryStreamWriter).
+    pub struct AsyncRelationalQueryStreamWriter<W: AsyncWrite + Unpin> {
+        writer: W,
+        row_count: u64,
+    }
+
+    impl<W: AsyncWrite + Unpin> AsyncRelationalQueryStreamWriter<W> {
+        pub async fn new(
+            writer: W,
+            columns: Option<Vec<RelationalColumn>>,
+        ) -> Result<Self, RelationalQueryStreamError> {
+            let mut stream = AsyncRelationalQueryStreamWriter {
+                writer,
+                row_count: 0,
+            };
+            stream
+                .write_chunk(&RelationalQueryStreamChunk::Header { columns })
+                .await?;
+            Ok(stream)
+        }
+
+        /// Write a batch of rows. Empty batches are not written.
+        pub async fn write_rows(&mut self, rows: Rows) -> Result<(), RelationalQueryStreamError> {
+            if rows.is_empty() {
+                return Ok(());
+            }
+            self.row_count += rows.len() as u64;
+            self.write_chunk(&RelationalQueryStreamChunk::Rows { rows })
+                .await
+        }
+
+        /// Write the trailer, and return the underlying writer
+        pub async fn finish(mut self) -> Result<W, RelationalQueryStreamError> {
+            let row_count = self.row_count;
+            self.write_chunk(&RelationalQueryStreamChunk::Trailer { row_count })
+                .await?;
+            Ok(self.writer)
+        }
+
+        /// Report an error instead of a trailer, and return the underlying writer
+        pub async fn fail(mut self, error: ErrorResponse) -> Result<W, RelationalQueryStreamError> {
+            self.write_chunk(&RelationalQueryStreamChunk::Error { error })
+                .await?;
+            Ok(self.writer)
+        }
+
+        async fn write_chunk(
+            &mut self,
+            chunk: &RelationalQueryStreamChunk,
+        ) -> Result<(), RelationalQueryStreamError> {
+            self.writer.write_all(&line(chunk)?).await?;
+            self.writer.flush().await?;
+            Ok(())
+        }
+    }
+
+    /// Reads a streamed relational query response without blocking. See
+    /// [`RelationalQueryStreamReader`](super::RelationalQueryStreamReader).
+    pub struct AsyncRelationalQueryStreamReader<R: AsyncBufRead + Unpin> {
+        reader: R,
+        state: ReadState,
+        line: String,
+    }
+
+    impl<R: AsyncBufRead + Unpin> AsyncRelationalQueryStreamReader<R> {
+        pub async fn new(mut reader: R) -> Result<Self, RelationalQueryStreamError> {
+            let mut line = String::new();
+            let header = Self::read_chunk(&mut reader, &mut line).await?;
+            Ok(AsyncRelationalQueryStreamReader {
+                reader,
+                state: ReadState::new(header)?,
+                line,
+            })
+        }
+
+        /// The columns described in the header, if any
+        pub fn columns(&self) -> Option<&[RelationalColumn]> {
+            self.state.columns.as_deref()
+        }
+
+        /// The number of rows read so far
+        pub fn row_count(&self) -> u64 {
+            self.state.row_count
+        }
+
+        /// Read the next batch of rows, or `None` after the trailer, or after
+        /// an error has been returned
+        pub async fn next_rows(&mut self) -> Result<Option<Rows>, RelationalQueryStreamError> {
+            if self.state.done {
+                return Ok(None);
+            }
+            let result = self.read_rows().await;
+            if !matches!(result, Ok(Some(_))) {
+                self.state.done = true;
+            }
+            result
+        }
+
+        async fn read_rows(&mut self) -> Result<Option<Rows>, RelationalQueryStreamError> {
+            let chunk = Self::read_chunk(&mut self.reader, &mut self.line).await?;
+            let rows = self.state.next(chunk)?;
+            if rows.is_none() {
+                ReadState::end(Self::read_chunk(&mut self.reader, &mut self.line).await?)?;
+            }
+            Ok(rows)
+        }
+
+        async fn read_chunk(
+            reader: &mut R,
+            line: &mut String,
+        ) -> Result<Option<RelationalQueryStreamChunk>, RelationalQueryStreamError> {
+            loop {
+                line.clear();
+                if reader.read_line(line).await? == 0 {
+                    return Ok(None);
+                }
+                if let Some(chunk) = parse_line(line)? {
+                    return Ok(Some(chunk));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut writer = RelationalQueryStreamWriter::new(vec![], None).unwrap();
+        writer
+            .write_rows(vec![vec![1.into(), "a".into()], vec![2.into(), "b".into()]])
+            .unwrap();
+        writer.write_rows(vec![]).unwrap();
+        writer.write_rows(vec![vec![3.into(), "c".into()]]).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(bytes.clone()).unwrap(),
+            concat!(
+                "{\"type\":\"header\"}\n",
+                "{\"type\":\"rows\",\"rows\":[[1,\"a\"],[2,\"b\"]]}\n",
+                "{\"type\":\"rows\",\"rows\":[[3,\"c\"]]}\n",
+                "{\"type\":\"trailer\",\"row_count\":3}\n",
+            )
+        );
+
+        let reader = RelationalQueryStreamReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.columns(), None);
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(
+            batches[1],
+            vec![vec![serde_json::json!(3), serde_json::json!("c")]]
+        );
+    }
+
+    fn read(input: &str) -> Result<usize, RelationalQueryStreamError> {
+        RelationalQueryStreamReader::new(input.as_bytes())
+            .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+            .map(|batches| batches.len())
+    }
+
+    #[test]
+    fn test_misordered_streams() {
+        assert!(matches!(
+            read("{\"type\":\"rows\",\"rows\":[]}\n"),
+            Err(RelationalQueryStreamError::UnexpectedChunk {
+                expected: "header",
+                found: "rows"
+            })
+        ));
+        assert!(matches!(
+            read("{\"type\":\"header\"}\n{\"type\":\"header\"}\n"),
+            Err(RelationalQueryStreamError::UnexpectedChunk {
+                expected: "rows or trailer",
+                found: "header"
+            })
+        ));
+        assert!(matches!(
+            read("{\"type\":\"header\"}\n{\"type\":\"trailer\",\"row_count\":0}\n{\"type\":\"rows\",\"rows\":[[1]]}\n"),
+            Err(RelationalQueryStreamError::ChunkAfterTrailer("rows"))
+        ));
+        assert!(matches!(
+            read("{\"type\":\"header\"}\n{\"type\":\"trailer\",\"row_count\":1}\n"),
+            Err(RelationalQueryStreamError::RowCountMismatch {
+                reported: 1,
+                received: 0
+            })
+        ));
+        assert!(matches!(
+            read("{\"type\":\"header\"}\n{\"type\":\"rows\",\"rows\":[[1]]}\n{\"type\":\"error\",\"error\":{\"message\":\"oops\",\"details\":null}}\n"),
+            Err(RelationalQueryStreamError::Connector(error)) if error.message == "oops"
+        ));
+
+        // Blank lines are ignored, including after the trailer
+        assert_eq!(
+            read("\n{\"type\":\"header\"}\n\n{\"type\":\"trailer\",\"row_count\":0}\n\n").unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_truncated_streams() {
+        assert!(matches!(
+            read(""),
+            Err(RelationalQueryStreamError::UnexpectedEnd)
+        ));
+        assert!(matches!(
+            read("{\"type\":\"header\"}\n"),
+            Err(RelationalQueryStreamError::UnexpectedEnd)
+        ));
+        assert!(matches!(
+            read("{\"type\":\"header\"}\n{\"type\":\"rows\",\"rows\":[[1]]}\n"),
+            Err(RelationalQueryStreamError::UnexpectedEnd)
+        ));
+        assert!(matches!(
+            read("{\"type\":\"header\"}\n{\"type\":\"rows\",\"rows\":[[1],"),
+            Err(RelationalQueryStreamError::Json(_))
+        ));
+
+        // Batches read before the stream was cut off are still returned
+        let mut reader = RelationalQueryStreamReader::new(
+            "{\"type\":\"header\"}\n{\"type\":\"rows\",\"rows\":[[1]]}\n".as_bytes(),
+        )
+        .unwrap();
+        assert!(matches!(reader.next(), Some(Ok(rows)) if rows.len() == 1));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(RelationalQueryStreamError::UnexpectedEnd))
+        ));
+        assert!(reader.next().is_none());
+        assert_eq!(reader.row_count(), 1);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_async_round_trip() {
+        tokio_test::block_on(async {
+            let mut writer = AsyncRelationalQueryStreamWriter::new(vec![], None)
+                .await
+                .unwrap();
+            writer.write_rows(vec![vec![1.into()]]).await.unwrap();
+            writer.write_rows(vec![vec![2.into()]]).await.unwrap();
+            let bytes = writer.finish().await.unwrap();
+
+            let mut reader = AsyncRelationalQueryStreamReader::new(bytes.as_slice())
+                .await
+                .unwrap();
+            assert_eq!(reader.columns(), None);
+            assert_eq!(
+                reader.next_rows().await.unwrap(),
+                Some(vec![vec![serde_json::json!(1)]])
+            );
+            assert_eq!(
+                reader.next_rows().await.unwrap(),
+                Some(vec![vec![serde_json::json!(2)]])
+            );
+            assert_eq!(reader.next_rows().await.unwrap(), None);
+            assert_eq!(reader.row_count(), 2);
+
+            let mut reader = AsyncRelationalQueryStreamReader::new(&bytes[..bytes.len() - 10])
+                .await
+                .unwrap();
+            // The trailer is cut off
+            for _ in 0..2 {
+                reader.next_rows().await.unwrap();
+            }
+            assert!(matches!(
+                reader.next_rows().await,
+                Err(RelationalQueryStreamError::Json(_))
+            ));
+            assert_eq!(reader.next_rows().await.unwrap(), None);
+        });
+    }
+}
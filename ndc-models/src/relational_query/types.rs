@@ -1,6 +1,8 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::{ObjectTypeName, ScalarTypeName};
+
 // Add newtypes for f32 and f64, to derive Hash and PartialEq
 #[derive(Debug, Copy, Clone, PartialOrd, Serialize, Deserialize)]
 pub struct Float32(pub f32);
@@ -251,3 +253,77 @@ pub enum RelationalLiteral {
         nanoseconds: i64,
     },
 }
+
+/// The type of a column produced by a relation
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[schemars(title = "RelationalType")]
+pub enum RelationalType {
+    /// A scalar type defined in the schema
+    Scalar { name: ScalarTypeName },
+    /// An object type defined in the schema
+    Object { name: ObjectTypeName },
+    /// An array of values of the element type
+    Array { element_type: Box<RelationalType> },
+    /// A built-in type, such as the result of a cast or of an arithmetic expression
+    Builtin { cast_type: CastType },
+    /// Arbitrary JSON, such as the result of `json_get`
+    Json,
+    /// The type of a null literal, which is compatible with every other type
+    Null,
+}
+
+impl std::fmt::Display for RelationalType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RelationalType::Scalar { name } => write!(f, "{name}"),
+            RelationalType::Object { name } => write!(f, "{name}"),
+            RelationalType::Array { element_type } => write!(f, "array of {element_type}"),
+            RelationalType::Builtin { cast_type } => match cast_type {
+                CastType::Decimal128 { scale, prec } | CastType::Decimal256 { scale, prec } => {
+                    write!(f, "{}({prec}, {scale})", cast_type_name(*cast_type))
+                }
+                _ => write!(f, "{}", cast_type_name(*cast_type)),
+            },
+            RelationalType::Json => write!(f, "json"),
+            RelationalType::Null => write!(f, "null"),
+        }
+    }
+}
+
+fn cast_type_name(cast_type: CastType) -> &'static str {
+    match cast_type {
+        CastType::Boolean => "boolean",
+        CastType::Utf8 => "utf8",
+        CastType::Int8 => "int8",
+        CastType::Int16 => "int16",
+        CastType::Int32 => "int32",
+        CastType::Int64 => "int64",
+        CastType::UInt8 => "uint8",
+        CastType::UInt16 => "uint16",
+        CastType::UInt32 => "uint32",
+        CastType::UInt64 => "uint64",
+        CastType::Float32 => "float32",
+        CastType::Float64 => "float64",
+        CastType::Decimal128 { .. } => "decimal128",
+        CastType::Decimal256 { .. } => "decimal256",
+        CastType::Date => "date",
+        CastType::Time => "time",
+        CastType::Timestamp => "timestamp",
+        CastType::Duration => "duration",
+        CastType::Interval => "interval",
+    }
+}
+
+/// The name, type and nullability of a column produced by a relation
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[schemars(title = "RelationalColumn")]
+pub struct RelationalColumn {
+    /// The name of the collection column, for columns which are passed through
+    /// unchanged, or otherwise the name of the expression which computes the column
+    pub name: String,
+    #[serde(rename = "type")]
+    pub r#type: RelationalType,
+    pub nullable: bool,
+}
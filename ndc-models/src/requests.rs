@@ -35,7 +35,7 @@ pub struct QueryRequest {
 
 // ANCHOR: Query
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Query")]
 pub struct Query {
     /// Aggregate fields of the query.
@@ -47,16 +47,37 @@ pub struct Query {
     pub limit: Option<u32>,
     /// Optionally offset from the Nth result
     pub offset: Option<u32>,
+    /// Optionally return only the rows after the row identified by a cursor,
+    /// which was returned as `next_cursor` by a query with the same `order_by`.
+    /// Only used if the 'query.pagination.cursor' capability is supported.
+    pub after: Option<String>,
     /// Optionally specify how rows should be ordered
     pub order_by: Option<OrderBy>,
     /// Optionally specify a predicate to apply to the rows
     pub predicate: Option<Expression>,
+    /// Optionally return only distinct rows.
+    /// Only used if the 'query.distinct' capability is supported.
+    pub distinct: Option<Distinct>,
     /// Optionally group and aggregate the selected rows.
     /// Only used if the 'query.aggregates.group_by' capability is supported.
     pub groups: Option<Grouping>,
 }
 // ANCHOR_END: Query
 
+// ANCHOR: Distinct
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[schemars(title = "Distinct")]
+pub enum Distinct {
+    /// Return one row from each set of rows with equal values for every selected field
+    Fields,
+    /// Return the first row, according to `order_by`, from each set of rows
+    /// with equal values for the given columns.
+    /// Only used if the 'query.distinct.on' capability is supported.
+    On { columns: Vec<FieldName> },
+}
+// ANCHOR_END: Distinct
+
 // ANCHOR: Argument
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -142,6 +163,11 @@ pub struct RowSet {
     pub rows: Option<Vec<IndexMap<FieldName, RowFieldValue>>>,
     /// The results of any grouping operation
     pub groups: Option<Vec<Group>>,
+    /// An opaque cursor identifying the last row in `rows`, which can be passed
+    /// as `after` to fetch the following rows. Only returned if the
+    /// 'query.pagination.cursor' capability is supported and more rows follow
+    /// the returned rows.
+    pub next_cursor: Option<String>,
 }
 // ANCHOR_END: RowSet
 
@@ -216,6 +242,14 @@ pub enum MutationOperation {
         /// The fields to return from the result, or null to return everything
         fields: Option<NestedField>,
     },
+    /// Only used if the 'relational_mutation.insert' capability is supported.
+    RelationalInsert(RelationalInsertRequest),
+    /// Only used if the 'relational_mutation.upsert' capability is supported.
+    RelationalUpsert(RelationalUpsertRequest),
+    /// Only used if the 'relational_mutation.update' capability is supported.
+    RelationalUpdate(RelationalUpdateRequest),
+    /// Only used if the 'relational_mutation.delete' capability is supported.
+    RelationalDelete(RelationalDeleteRequest),
 }
 // ANCHOR_END: MutationOperation
 
@@ -234,6 +268,10 @@ pub struct MutationResponse {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum MutationOperationResults {
     Procedure { result: serde_json::Value },
+    RelationalInsert(RelationalInsertResponse),
+    RelationalUpsert(RelationalUpsertResponse),
+    RelationalUpdate(RelationalUpdateResponse),
+    RelationalDelete(RelationalDeleteResponse),
 }
 // ANCHOR_END: MutationOperationResults
 
@@ -250,6 +288,7 @@ pub struct ErrorResponse {
 // ANCHOR_END: ErrorResponse
 
 // ANCHOR: RelationalInsertRequest
+#[skip_serializing_none]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Insert Request")]
 pub struct RelationalInsertRequest {
@@ -261,10 +300,54 @@ pub struct RelationalInsertRequest {
     pub columns: Vec<FieldName>,
     /// The rows to insert, each row containing values for the specified columns
     pub rows: Vec<Vec<serde_json::Value>>,
+    /// The columns to return from each affected row. Only used if the
+    /// `relational_mutation.returning` capability is supported.
+    pub returning: Option<Vec<FieldName>>,
 }
 // ANCHOR_END: RelationalInsertRequest
 
+// ANCHOR: RelationalUpsertRequest
+#[skip_serializing_none]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(title = "Relational Upsert Request")]
+pub struct RelationalUpsertRequest {
+    /// The name of the collection to insert into
+    pub collection: CollectionName,
+    /// Values to be provided to any collection arguments
+    pub arguments: BTreeMap<ArgumentName, Argument>,
+    /// The columns to insert values for
+    pub columns: Vec<FieldName>,
+    /// The rows to insert, each row containing values for the specified columns
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// The name of a uniqueness constraint of the collection, as defined in
+    /// `uniqueness_constraints`. A row conflicts with an existing row if they
+    /// have the same values for the columns of this constraint.
+    pub conflict_target: String,
+    /// What to do with rows which conflict with an existing row
+    pub on_conflict: OnConflictAction,
+    /// The columns to return from each affected row. Only used if the
+    /// `relational_mutation.returning` capability is supported.
+    pub returning: Option<Vec<FieldName>>,
+}
+// ANCHOR_END: RelationalUpsertRequest
+
+// ANCHOR: OnConflictAction
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[schemars(title = "On Conflict Action")]
+pub enum OnConflictAction {
+    /// Skip the conflicting row, leaving the existing row unchanged
+    DoNothing,
+    /// Update the existing row with the values of the conflicting row
+    Update {
+        /// The columns to update, which must be a subset of the inserted columns
+        columns: Vec<FieldName>,
+    },
+}
+// ANCHOR_END: OnConflictAction
+
 // ANCHOR: RelationalUpdateRequest
+#[skip_serializing_none]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Update Request")]
 pub struct RelationalUpdateRequest {
@@ -274,10 +357,14 @@ pub struct RelationalUpdateRequest {
     pub arguments: BTreeMap<ArgumentName, Argument>,
     /// The relation that identifies which rows to update
     pub relation: Relation,
+    /// The columns to return from each affected row. Only used if the
+    /// `relational_mutation.returning` capability is supported.
+    pub returning: Option<Vec<FieldName>>,
 }
 // ANCHOR_END: RelationalUpdateRequest
 
 // ANCHOR: RelationalDeleteRequest
+#[skip_serializing_none]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Delete Request")]
 pub struct RelationalDeleteRequest {
@@ -287,32 +374,63 @@ pub struct RelationalDeleteRequest {
     pub arguments: BTreeMap<ArgumentName, Argument>,
     /// The relation that identifies which rows to delete
     pub relation: Relation,
+    /// The columns to return from each affected row. Only used if the
+    /// `relational_mutation.returning` capability is supported.
+    pub returning: Option<Vec<FieldName>>,
 }
 // ANCHOR_END: RelationalDeleteRequest
 
 // ANCHOR: RelationalInsertResponse
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[skip_serializing_none]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Insert Response")]
 pub struct RelationalInsertResponse {
     /// The number of rows that were inserted
     pub affected_rows: u64,
+    /// The values of the `returning` columns of the inserted rows, in the order
+    /// the columns were requested. Only provided if `returning` was requested.
+    pub returning: Option<Vec<Vec<serde_json::Value>>>,
 }
 // ANCHOR_END: RelationalInsertResponse
 
+// ANCHOR: RelationalUpsertResponse
+#[skip_serializing_none]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(title = "Relational Upsert Response")]
+pub struct RelationalUpsertResponse {
+    /// The number of rows that were inserted or updated. Rows skipped
+    /// because of a conflict are not counted.
+    pub affected_rows: u64,
+    /// The values of the `returning` columns of the inserted or updated rows,
+    /// in the order the columns were requested. Only provided if `returning`
+    /// was requested.
+    pub returning: Option<Vec<Vec<serde_json::Value>>>,
+}
+// ANCHOR_END: RelationalUpsertResponse
+
 // ANCHOR: RelationalUpdateResponse
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[skip_serializing_none]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Update Response")]
 pub struct RelationalUpdateResponse {
     /// The number of rows that were updated
     pub affected_rows: u64,
+    /// The values of the `returning` columns of the updated rows, after the
+    /// update, in the order the columns were requested. Only provided if
+    /// `returning` was requested.
+    pub returning: Option<Vec<Vec<serde_json::Value>>>,
 }
 // ANCHOR_END: RelationalUpdateResponse
 
 // ANCHOR: RelationalDeleteResponse
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[skip_serializing_none]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(title = "Relational Delete Response")]
 pub struct RelationalDeleteResponse {
     /// The number of rows that were deleted
     pub affected_rows: u64,
+    /// The values of the `returning` columns of the deleted rows, in the order
+    /// the columns were requested. Only provided if `returning` was requested.
+    pub returning: Option<Vec<Vec<serde_json::Value>>>,
 }
 // ANCHOR_END: RelationalDeleteResponse
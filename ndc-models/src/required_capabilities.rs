@@ -0,0 +1,1207 @@
+//! Computing the capabilities which a request depends on.
+//!
+//! Each function in this module walks a request and returns the smallest
+//! [`Capabilities`] value which would permit it. The result can be compared
+//! against a connector's advertised capabilities using [`missing_capabilities`].
+
+use std::collections::BTreeMap;
+
+use crate::visit::{self, Visit};
+use crate::{
+    Aggregate, Argument, ArgumentName, ArrayComparison, Capabilities, CapabilitiesResponse,
+    CastType, ComparisonTarget, ComparisonValue, Dimension, Distinct, ExistsInCollection,
+    Expression, Field, GroupExpression, GroupOrderByTarget, Grouping, JoinType, LeafCapability,
+    MutationOperation, MutationRequest, NestedField, OrderByTarget, PathElement, Query,
+    QueryRequest, Relation, RelationalExpression, RelationalExpressionCapabilities,
+    RelationalLiteral, RelationalMutationCapabilities,
+    RelationalOrderedAggregateFunctionCapabilities, RelationalQuery, RelationalQueryCapabilities,
+    RelationshipArgument, SimilarityMetric, Sort,
+};
+
+/// Compute the capabilities required to execute a query request
+pub fn for_query_request(request: &QueryRequest) -> Capabilities {
+    let mut capabilities = Capabilities::default();
+
+    if request.variables.as_ref().is_some_and(|v| !v.is_empty()) {
+        capabilities.query.variables = LEAF;
+    }
+
+    arguments(&mut capabilities, &request.arguments);
+    query(&mut capabilities, &request.query);
+
+    capabilities
+}
+
+/// Compute the capabilities required to execute a mutation request
+pub fn for_mutation_request(request: &MutationRequest) -> Capabilities {
+    let mut capabilities = Capabilities::default();
+
+    if request.operations.len() > 1 {
+        capabilities.mutation.transactional = LEAF;
+    }
+
+    for operation in &request.operations {
+        match operation {
+            MutationOperation::Procedure { fields, .. } => {
+                if let Some(fields) = fields {
+                    nested_field(&mut capabilities, fields, Nesting::None);
+                }
+            }
+            MutationOperation::RelationalInsert(request) => {
+                arguments(&mut capabilities, &request.arguments);
+                relational_mutation(&mut capabilities, request.returning.is_some()).insert = LEAF;
+            }
+            MutationOperation::RelationalUpsert(request) => {
+                arguments(&mut capabilities, &request.arguments);
+                relational_mutation(&mut capabilities, request.returning.is_some()).upsert = LEAF;
+            }
+            MutationOperation::RelationalUpdate(request) => {
+                arguments(&mut capabilities, &request.arguments);
+                relational_mutation(&mut capabilities, request.returning.is_some()).update = LEAF;
+                relation(
+                    capabilities
+                        .relational_query
+                        .get_or_insert_with(Default::default),
+                    &request.relation,
+                );
+            }
+            MutationOperation::RelationalDelete(request) => {
+                arguments(&mut capabilities, &request.arguments);
+                relational_mutation(&mut capabilities, request.returning.is_some()).delete = LEAF;
+                relation(
+                    capabilities
+                        .relational_query
+                        .get_or_insert_with(Default::default),
+                    &request.relation,
+                );
+            }
+        }
+    }
+
+    capabilities
+}
+
+/// Compute the capabilities required to execute a relational query
+pub fn for_relational_query(query: &RelationalQuery) -> Capabilities {
+    let mut capabilities = Capabilities::default();
+    relation(
+        capabilities
+            .relational_query
+            .get_or_insert_with(Default::default),
+        &query.root_relation,
+    );
+    capabilities
+}
+
+/// List the capabilities in `required` which are not advertised in `response`,
+/// as dotted paths such as `query.exists.unrelated`.
+///
+/// Only the outermost missing capability is listed: if `relationships` is not
+/// supported at all, then `relationships.relation_comparisons` is not listed separately.
+pub fn missing_capabilities(
+    required: &Capabilities,
+    response: &CapabilitiesResponse,
+) -> Vec<String> {
+    // Capabilities are nested objects whose leaves are empty objects, so it
+    // suffices to compare the serialized forms key by key.
+    let required = serde_json::to_value(required).unwrap_or_default();
+    let supported = serde_json::to_value(&response.capabilities).unwrap_or_default();
+
+    let mut missing = vec![];
+    missing_in(&required, &supported, &mut vec![], &mut missing);
+    missing
+}
+
+fn missing_in(
+    required: &serde_json::Value,
+    supported: &serde_json::Value,
+    path: &mut Vec<String>,
+    missing: &mut Vec<String>,
+) {
+    let Some(required) = required.as_object() else {
+        return;
+    };
+
+    for (key, required_value) in required {
+        if required_value.is_null() {
+            continue;
+        }
+
+        path.push(key.clone());
+        match supported.get(key) {
+            None | Some(serde_json::Value::Null) => missing.push(path.join(".")),
+            Some(supported_value) => missing_in(required_value, supported_value, path, missing),
+        }
+        path.pop();
+    }
+}
+
+const LEAF: Option<LeafCapability> = Some(LeafCapability {});
+
+/// Where a field or path is found, relative to nested fields of the enclosing collection
+#[derive(Clone, Copy, PartialEq)]
+enum Nesting {
+    None,
+    Object,
+    Array,
+}
+
+/// The context in which a relationship path is navigated
+#[derive(Clone, Copy, PartialEq)]
+enum PathContext {
+    Filtering,
+    Ordering,
+    Other,
+}
+
+fn query(capabilities: &mut Capabilities, query: &Query) {
+    // An empty map of aggregates does not require any aggregation
+    if let Some(aggregates) = query
+        .aggregates
+        .as_ref()
+        .filter(|aggregates| !aggregates.is_empty())
+    {
+        capabilities
+            .query
+            .aggregates
+            .get_or_insert_with(Default::default);
+        for aggregate in aggregates.values() {
+            self::aggregate(capabilities, aggregate);
+        }
+    }
+
+    if let Some(fields) = &query.fields {
+        for field in fields.values() {
+            self::field(capabilities, field, Nesting::None);
+        }
+    }
+
+    if let Some(order_by) = &query.order_by {
+        for element in &order_by.elements {
+            order_by_target(capabilities, &element.target);
+        }
+    }
+
+    if let Some(distinct) = &query.distinct {
+        let distinct_capabilities = capabilities
+            .query
+            .distinct
+            .get_or_insert_with(Default::default);
+        match distinct {
+            Distinct::Fields => {}
+            Distinct::On { .. } => distinct_capabilities.on = LEAF,
+        }
+    }
+
+    if query.after.is_some() {
+        capabilities.query.pagination.cursor = LEAF;
+    }
+
+    if let Some(predicate) = &query.predicate {
+        expression(capabilities, predicate);
+    }
+
+    if let Some(grouping) = &query.groups {
+        self::grouping(capabilities, grouping);
+    }
+}
+
+fn field(capabilities: &mut Capabilities, field: &Field, nesting: Nesting) {
+    match field {
+        Field::Column {
+            fields, arguments, ..
+        } => {
+            self::arguments(capabilities, arguments);
+            if let Some(fields) = fields {
+                nested_field(capabilities, fields, nesting);
+            }
+        }
+        Field::Relationship {
+            query, arguments, ..
+        } => {
+            let relationships = capabilities
+                .relationships
+                .get_or_insert_with(Default::default);
+            match nesting {
+                Nesting::None => {}
+                Nesting::Object => {
+                    relationships.nested.get_or_insert_with(Default::default);
+                }
+                Nesting::Array => {
+                    relationships
+                        .nested
+                        .get_or_insert_with(Default::default)
+                        .array = LEAF;
+                }
+            }
+            relationship_arguments(capabilities, arguments);
+            self::query(capabilities, query);
+        }
+    }
+}
+
+fn nested_field(capabilities: &mut Capabilities, nested_field: &NestedField, nesting: Nesting) {
+    match nested_field {
+        NestedField::Object(nested_object) => {
+            let nesting = if nesting == Nesting::Array {
+                Nesting::Array
+            } else {
+                Nesting::Object
+            };
+            for field in nested_object.fields.values() {
+                self::field(capabilities, field, nesting);
+            }
+        }
+        NestedField::Array(nested_array) => {
+            self::nested_field(capabilities, &nested_array.fields, Nesting::Array);
+        }
+        NestedField::Collection(nested_collection) => {
+            capabilities.query.nested_fields.nested_collections = LEAF;
+            query(capabilities, &nested_collection.query);
+        }
+    }
+}
+
+fn arguments(capabilities: &mut Capabilities, arguments: &BTreeMap<ArgumentName, Argument>) {
+    for argument in arguments.values() {
+        match argument {
+            Argument::Variable { .. } => capabilities.query.variables = LEAF,
+            Argument::Literal { .. } => {}
+        }
+    }
+}
+
+/// The relational mutation capabilities, including `returning` if the operation returns rows
+fn relational_mutation(
+    capabilities: &mut Capabilities,
+    returning: bool,
+) -> &mut RelationalMutationCapabilities {
+    let mutation = capabilities
+        .relational_mutation
+        .get_or_insert_with(Default::default);
+    if returning {
+        mutation.returning = LEAF;
+    }
+    mutation
+}
+
+fn relationship_arguments(
+    capabilities: &mut Capabilities,
+    arguments: &BTreeMap<ArgumentName, RelationshipArgument>,
+) {
+    for argument in arguments.values() {
+        match argument {
+            RelationshipArgument::Variable { .. } => capabilities.query.variables = LEAF,
+            RelationshipArgument::Literal { .. } | RelationshipArgument::Column { .. } => {}
+        }
+    }
+}
+
+fn path(capabilities: &mut Capabilities, path: &[PathElement], context: PathContext) {
+    if path.is_empty() {
+        return;
+    }
+
+    let relationships = capabilities
+        .relationships
+        .get_or_insert_with(Default::default);
+
+    if path
+        .iter()
+        .any(|element| element.field_path.as_ref().is_some_and(|p| !p.is_empty()))
+    {
+        let nested = relationships.nested.get_or_insert_with(Default::default);
+        match context {
+            PathContext::Filtering => nested.filtering = LEAF,
+            PathContext::Ordering => nested.ordering = LEAF,
+            PathContext::Other => {}
+        }
+    }
+
+    for element in path {
+        relationship_arguments(capabilities, &element.arguments);
+        if let Some(predicate) = &element.predicate {
+            expression(capabilities, predicate);
+        }
+    }
+}
+
+fn aggregate(capabilities: &mut Capabilities, aggregate: &Aggregate) {
+    let (Aggregate::ColumnCount { filter, .. }
+    | Aggregate::SingleColumn { filter, .. }
+    | Aggregate::StarCount { filter }) = aggregate;
+    if let Some(filter) = filter {
+        capabilities
+            .query
+            .aggregates
+            .get_or_insert_with(Default::default)
+            .filter = LEAF;
+        expression(capabilities, filter);
+    }
+    if let Aggregate::SingleColumn {
+        distinct: Some(true),
+        ..
+    } = aggregate
+    {
+        capabilities
+            .query
+            .aggregates
+            .get_or_insert_with(Default::default)
+            .distinct = LEAF;
+    }
+
+    match aggregate {
+        Aggregate::ColumnCount {
+            arguments,
+            field_path,
+            ..
+        }
+        | Aggregate::SingleColumn {
+            arguments,
+            field_path,
+            ..
+        } => {
+            if field_path.as_ref().is_some_and(|p| !p.is_empty()) {
+                capabilities.query.nested_fields.aggregates = LEAF;
+            }
+            self::arguments(capabilities, arguments);
+        }
+        Aggregate::StarCount { .. } => {}
+    }
+}
+
+fn order_by_target(capabilities: &mut Capabilities, target: &OrderByTarget) {
+    match target {
+        OrderByTarget::Column {
+            path,
+            arguments,
+            field_path,
+            ..
+        } => {
+            if field_path.as_ref().is_some_and(|p| !p.is_empty()) {
+                capabilities.query.nested_fields.order_by = LEAF;
+            }
+            self::arguments(capabilities, arguments);
+            self::path(capabilities, path, PathContext::Ordering);
+        }
+        OrderByTarget::Aggregate { path, aggregate } => {
+            capabilities
+                .relationships
+                .get_or_insert_with(Default::default)
+                .order_by_aggregate = LEAF;
+            self::path(capabilities, path, PathContext::Ordering);
+            self::aggregate(capabilities, aggregate);
+        }
+        OrderByTarget::Relevance {
+            field_path, value, ..
+        } => {
+            if field_path.as_ref().is_some_and(|p| !p.is_empty()) {
+                capabilities.query.nested_fields.order_by = LEAF;
+            }
+            capabilities
+                .query
+                .full_text_search
+                .get_or_insert_with(Default::default)
+                .order_by_relevance = LEAF;
+            comparison_value(capabilities, value);
+        }
+        OrderByTarget::Distance {
+            field_path, value, ..
+        } => {
+            if field_path.as_ref().is_some_and(|p| !p.is_empty()) {
+                capabilities.query.nested_fields.order_by = LEAF;
+            }
+            capabilities
+                .query
+                .geospatial
+                .get_or_insert_with(Default::default)
+                .order_by_distance = LEAF;
+            comparison_value(capabilities, value);
+        }
+        OrderByTarget::Similarity {
+            field_path,
+            metric,
+            value,
+            ..
+        } => {
+            if field_path.as_ref().is_some_and(|p| !p.is_empty()) {
+                capabilities.query.nested_fields.order_by = LEAF;
+            }
+            let vector_search = capabilities
+                .query
+                .vector_search
+                .get_or_insert_with(Default::default);
+            match metric {
+                SimilarityMetric::Cosine => vector_search.cosine = LEAF,
+                SimilarityMetric::L2 => vector_search.l2 = LEAF,
+                SimilarityMetric::InnerProduct => vector_search.inner_product = LEAF,
+            }
+            comparison_value(capabilities, value);
+        }
+    }
+}
+
+fn expression(capabilities: &mut Capabilities, expression: &Expression) {
+    match expression {
+        Expression::And { expressions } | Expression::Or { expressions } => {
+            for expression in expressions {
+                self::expression(capabilities, expression);
+            }
+        }
+        Expression::Not { expression } => self::expression(capabilities, expression),
+        Expression::UnaryComparisonOperator { column, .. } => {
+            comparison_target(capabilities, column);
+        }
+        Expression::BinaryComparisonOperator { column, value, .. } => {
+            comparison_target(capabilities, column);
+            comparison_value(capabilities, value);
+        }
+        Expression::ArrayComparison { column, comparison } => {
+            comparison_target(capabilities, column);
+            let nested_arrays = capabilities
+                .query
+                .nested_fields
+                .filter_by
+                .get_or_insert_with(Default::default)
+                .nested_arrays
+                .get_or_insert_with(Default::default);
+            match comparison {
+                ArrayComparison::Contains { value } => {
+                    nested_arrays.contains = LEAF;
+                    comparison_value(capabilities, value);
+                }
+                ArrayComparison::IsEmpty => nested_arrays.is_empty = LEAF,
+            }
+        }
+        Expression::Exists {
+            in_collection,
+            predicate,
+        } => {
+            self::in_collection(capabilities, in_collection);
+            if let Some(predicate) = predicate {
+                self::expression(capabilities, predicate);
+            }
+        }
+    }
+}
+
+fn in_collection(capabilities: &mut Capabilities, in_collection: &ExistsInCollection) {
+    match in_collection {
+        ExistsInCollection::Related {
+            field_path,
+            arguments,
+            ..
+        } => {
+            let relationships = capabilities
+                .relationships
+                .get_or_insert_with(Default::default);
+            if field_path.as_ref().is_some_and(|p| !p.is_empty()) {
+                relationships
+                    .nested
+                    .get_or_insert_with(Default::default)
+                    .filtering = LEAF;
+            }
+            relationship_arguments(capabilities, arguments);
+        }
+        ExistsInCollection::Unrelated { arguments, .. } => {
+            capabilities.query.exists.unrelated = LEAF;
+            relationship_arguments(capabilities, arguments);
+        }
+        ExistsInCollection::NestedCollection { arguments, .. } => {
+            capabilities.query.exists.nested_collections = LEAF;
+            self::arguments(capabilities, arguments);
+        }
+        ExistsInCollection::NestedScalarCollection { arguments, .. } => {
+            capabilities.query.exists.nested_scalar_collections = LEAF;
+            self::arguments(capabilities, arguments);
+        }
+    }
+}
+
+fn comparison_target(capabilities: &mut Capabilities, target: &ComparisonTarget) {
+    match target {
+        ComparisonTarget::Column {
+            arguments,
+            field_path,
+            ..
+        } => {
+            if field_path.as_ref().is_some_and(|p| !p.is_empty()) {
+                capabilities
+                    .query
+                    .nested_fields
+                    .filter_by
+                    .get_or_insert_with(Default::default);
+            }
+            self::arguments(capabilities, arguments);
+        }
+        ComparisonTarget::Aggregate { path, aggregate } => {
+            capabilities
+                .query
+                .aggregates
+                .get_or_insert_with(Default::default)
+                .filter_by = LEAF;
+            self::path(capabilities, path, PathContext::Filtering);
+            self::aggregate(capabilities, aggregate);
+        }
+    }
+}
+
+fn comparison_value(capabilities: &mut Capabilities, value: &ComparisonValue) {
+    match value {
+        ComparisonValue::Column {
+            path,
+            arguments,
+            field_path,
+            scope,
+            ..
+        } => {
+            if !path.is_empty() {
+                capabilities
+                    .relationships
+                    .get_or_insert_with(Default::default)
+                    .relation_comparisons = LEAF;
+            }
+            if field_path.as_ref().is_some_and(|p| !p.is_empty()) {
+                capabilities
+                    .query
+                    .nested_fields
+                    .filter_by
+                    .get_or_insert_with(Default::default);
+            }
+            if scope.is_some_and(|scope| scope > 0) {
+                capabilities.query.exists.named_scopes = LEAF;
+            }
+            self::arguments(capabilities, arguments);
+            self::path(capabilities, path, PathContext::Filtering);
+        }
+        ComparisonValue::Scalar { .. } => {}
+        ComparisonValue::Variable { .. } => capabilities.query.variables = LEAF,
+    }
+}
+
+fn grouping(capabilities: &mut Capabilities, grouping: &Grouping) {
+    let group_by = capabilities
+        .query
+        .aggregates
+        .get_or_insert_with(Default::default)
+        .group_by
+        .get_or_insert_with(Default::default);
+
+    if grouping.predicate.is_some() {
+        group_by.filter = LEAF;
+    }
+    if grouping.order_by.is_some() {
+        group_by.order = LEAF;
+    }
+    if grouping.limit.is_some() || grouping.offset.is_some() {
+        group_by.paginate = LEAF;
+    }
+
+    for dimension in &grouping.dimensions {
+        match dimension {
+            Dimension::Column {
+                path,
+                arguments,
+                field_path,
+                ..
+            } => {
+                if field_path.as_ref().is_some_and(|p| !p.is_empty()) {
+                    capabilities.query.nested_fields.aggregates = LEAF;
+                }
+                self::arguments(capabilities, arguments);
+                self::path(capabilities, path, PathContext::Other);
+            }
+        }
+    }
+
+    for aggregate in grouping.aggregates.values() {
+        self::aggregate(capabilities, aggregate);
+    }
+
+    if let Some(predicate) = &grouping.predicate {
+        group_expression(capabilities, predicate);
+    }
+
+    if let Some(order_by) = &grouping.order_by {
+        for element in &order_by.elements {
+            match &element.target {
+                GroupOrderByTarget::Dimension { .. } => {}
+                GroupOrderByTarget::Aggregate { aggregate } => {
+                    self::aggregate(capabilities, aggregate);
+                }
+            }
+        }
+    }
+}
+
+fn group_expression(capabilities: &mut Capabilities, expression: &GroupExpression) {
+    match expression {
+        GroupExpression::And { expressions } | GroupExpression::Or { expressions } => {
+            for expression in expressions {
+                group_expression(capabilities, expression);
+            }
+        }
+        GroupExpression::Not { expression } => group_expression(capabilities, expression),
+        GroupExpression::UnaryComparisonOperator { target, .. } => {
+            let crate::GroupComparisonTarget::Aggregate { aggregate } = target;
+            self::aggregate(capabilities, aggregate);
+        }
+        GroupExpression::BinaryComparisonOperator { target, value, .. } => {
+            let crate::GroupComparisonTarget::Aggregate { aggregate } = target;
+            self::aggregate(capabilities, aggregate);
+            if let crate::GroupComparisonValue::Variable { .. } = value {
+                capabilities.query.variables = LEAF;
+            }
+        }
+    }
+}
+
+fn relation(capabilities: &mut RelationalQueryCapabilities, relation: &Relation) {
+    match relation {
+        Relation::From { arguments, .. } => {
+            for argument in arguments.values() {
+                literal(&mut capabilities.project.expression, argument);
+            }
+        }
+        Relation::Paginate { input, .. } => self::relation(capabilities, input),
+        Relation::Project { input, exprs } => {
+            for expr in exprs {
+                expression_capabilities(&mut capabilities.project.expression, expr);
+            }
+            self::relation(capabilities, input);
+        }
+        Relation::Filter { input, predicate } => {
+            expression_capabilities(
+                capabilities.filter.get_or_insert_with(Default::default),
+                predicate,
+            );
+            self::relation(capabilities, input);
+        }
+        Relation::Sort { input, exprs } => {
+            let sort = capabilities.sort.get_or_insert_with(Default::default);
+            for sort_expr in exprs {
+                expression_capabilities(&mut sort.expression, &sort_expr.expr);
+            }
+            self::relation(capabilities, input);
+        }
+        Relation::Join {
+            left,
+            right,
+            on,
+            join_type,
+        } => {
+            let join = capabilities.join.get_or_insert_with(Default::default);
+            for join_on in on {
+                expression_capabilities(&mut join.expression, &join_on.left);
+                expression_capabilities(&mut join.expression, &join_on.right);
+            }
+            let join_types = &mut join.join_types;
+            match join_type {
+                JoinType::Left => join_types.left = LEAF,
+                JoinType::Right => join_types.right = LEAF,
+                JoinType::Inner => join_types.inner = LEAF,
+                JoinType::Full => join_types.full = LEAF,
+                JoinType::LeftAnti => join_types.left_anti = LEAF,
+                JoinType::LeftSemi => join_types.left_semi = LEAF,
+                JoinType::RightAnti => join_types.right_anti = LEAF,
+                JoinType::RightSemi => join_types.right_semi = LEAF,
+            }
+            self::relation(capabilities, left);
+            self::relation(capabilities, right);
+        }
+        Relation::Aggregate {
+            input,
+            group_by,
+            aggregates,
+        } => {
+            let aggregate = capabilities.aggregate.get_or_insert_with(Default::default);
+            if !group_by.is_empty() {
+                aggregate.group_by = LEAF;
+            }
+            for expr in group_by.iter().chain(aggregates) {
+                expression_capabilities(&mut aggregate.expression, expr);
+            }
+            self::relation(capabilities, input);
+        }
+        Relation::Window { input, exprs } => {
+            let window = capabilities.window.get_or_insert_with(Default::default);
+            for expr in exprs {
+                expression_capabilities(&mut window.expression, expr);
+            }
+            self::relation(capabilities, input);
+        }
+        Relation::Union { relations } => {
+            capabilities.union = LEAF;
+            for relation in relations {
+                self::relation(capabilities, relation);
+            }
+        }
+    }
+}
+
+fn literal(capabilities: &mut RelationalExpressionCapabilities, literal: &RelationalLiteral) {
+    if let RelationalLiteral::Interval { .. } = literal {
+        capabilities
+            .scalar_types
+            .get_or_insert_with(Default::default)
+            .interval = LEAF;
+    }
+}
+
+fn ordered_aggregate(
+    capability: &mut Option<RelationalOrderedAggregateFunctionCapabilities>,
+    distinct: bool,
+    order_by: Option<&Vec<Sort>>,
+) {
+    let capability = capability.get_or_insert_with(Default::default);
+    if distinct {
+        capability.distinct = LEAF;
+    }
+    if order_by.is_some_and(|o| !o.is_empty()) {
+        capability.order_by = LEAF;
+    }
+}
+
+fn expression_capabilities(
+    capabilities: &mut RelationalExpressionCapabilities,
+    expr: &RelationalExpression,
+) {
+    ExpressionCapabilities(capabilities).visit_relational_expression(expr);
+}
+
+/// Whether a relational expression can be evaluated in a context which supports
+/// the given expression capabilities
+pub(crate) fn expression_is_supported(
+    expr: &RelationalExpression,
+    supported: &RelationalExpressionCapabilities,
+) -> bool {
+    let mut required = RelationalExpressionCapabilities::default();
+    expression_capabilities(&mut required, expr);
+
+    let required = serde_json::to_value(&required).unwrap_or_default();
+    let supported = serde_json::to_value(supported).unwrap_or_default();
+
+    let mut missing = vec![];
+    missing_in(&required, &supported, &mut vec![], &mut missing);
+    missing.is_empty()
+}
+
+/// Records the capabilities required by relational expressions, in the context
+/// described by the wrapped capabilities (projection, filtering, sorting and so on)
+struct ExpressionCapabilities<'a>(&'a mut RelationalExpressionCapabilities);
+
+impl Visit for ExpressionCapabilities<'_> {
+    fn visit_relational_literal(&mut self, literal: &RelationalLiteral) {
+        self::literal(self.0, literal);
+    }
+
+    #[allow(clippy::match_same_arms, clippy::too_many_lines)]
+    fn visit_relational_expression(&mut self, expr: &RelationalExpression) {
+        let capabilities = &mut *self.0;
+        let conditional = &mut capabilities.conditional;
+        let comparison = &mut capabilities.comparison;
+        let scalar = &mut capabilities.scalar;
+        let aggregate = &mut capabilities.aggregate;
+        let window = &mut capabilities.window;
+
+        match expr {
+            RelationalExpression::Literal { .. } | RelationalExpression::Column { .. } => {}
+            RelationalExpression::Case {
+                scrutinee,
+                when: _,
+                default: _,
+            } => {
+                let case = conditional.case.get_or_insert_with(Default::default);
+                if scrutinee.is_some() {
+                    case.scrutinee = LEAF;
+                }
+            }
+            RelationalExpression::And { .. } => scalar.and = LEAF,
+            RelationalExpression::Or { .. } => scalar.or = LEAF,
+            RelationalExpression::Not { .. } => scalar.not = LEAF,
+            RelationalExpression::Eq { .. } | RelationalExpression::NotEq { .. } => {}
+            RelationalExpression::IsDistinctFrom { .. }
+            | RelationalExpression::IsNotDistinctFrom { .. } => comparison.is_distinct_from = LEAF,
+            RelationalExpression::Lt { .. } => comparison.less_than = LEAF,
+            RelationalExpression::LtEq { .. } => comparison.less_than_eq = LEAF,
+            RelationalExpression::Gt { .. } => comparison.greater_than = LEAF,
+            RelationalExpression::GtEq { .. } => comparison.greater_than_eq = LEAF,
+            RelationalExpression::IsNotNull { .. } | RelationalExpression::IsNull { .. } => {
+                comparison.is_null = LEAF;
+            }
+            RelationalExpression::IsTrue { .. } | RelationalExpression::IsNotTrue { .. } => {
+                comparison.is_true = LEAF;
+            }
+            RelationalExpression::IsFalse { .. } | RelationalExpression::IsNotFalse { .. } => {
+                comparison.is_false = LEAF;
+            }
+            RelationalExpression::In { .. } | RelationalExpression::NotIn { .. } => {
+                comparison.in_list = LEAF;
+            }
+            RelationalExpression::Like { .. } | RelationalExpression::NotLike { .. } => {
+                comparison.like = LEAF;
+            }
+            RelationalExpression::ILike { .. } | RelationalExpression::NotILike { .. } => {
+                comparison.ilike = LEAF;
+            }
+            RelationalExpression::Between { .. } | RelationalExpression::NotBetween { .. } => {
+                comparison.between = LEAF;
+            }
+            RelationalExpression::Contains { .. } => comparison.contains = LEAF,
+            RelationalExpression::IsNaN { .. } => comparison.is_nan = LEAF,
+            RelationalExpression::IsZero { .. } => comparison.is_zero = LEAF,
+            RelationalExpression::Plus { .. } => scalar.plus = LEAF,
+            RelationalExpression::Minus { .. } => scalar.minus = LEAF,
+            RelationalExpression::Multiply { .. } => scalar.multiply = LEAF,
+            RelationalExpression::Divide { .. } => scalar.divide = LEAF,
+            RelationalExpression::Modulo { .. } => scalar.modulo = LEAF,
+            RelationalExpression::Negate { .. } => scalar.negate = LEAF,
+            RelationalExpression::Cast {
+                from_type, as_type, ..
+            }
+            | RelationalExpression::TryCast {
+                from_type, as_type, ..
+            } => {
+                if from_type.is_some() || matches!(as_type, CastType::Interval) {
+                    let scalar_types = capabilities
+                        .scalar_types
+                        .get_or_insert_with(Default::default);
+                    if from_type.is_some() {
+                        scalar_types.from_type = LEAF;
+                    }
+                    if matches!(as_type, CastType::Interval)
+                        || matches!(from_type, Some(CastType::Interval))
+                    {
+                        scalar_types.interval = LEAF;
+                    }
+                }
+            }
+            RelationalExpression::Abs { .. } => scalar.abs = LEAF,
+            RelationalExpression::ArrayElement { .. } => scalar.array_element = LEAF,
+            RelationalExpression::BTrim { .. } => scalar.btrim = LEAF,
+            RelationalExpression::Ceil { .. } => scalar.ceil = LEAF,
+            RelationalExpression::CharacterLength { .. } => scalar.character_length = LEAF,
+            RelationalExpression::Coalesce { .. } => scalar.coalesce = LEAF,
+            RelationalExpression::Concat { .. } => scalar.concat = LEAF,
+            RelationalExpression::Cos { .. } => scalar.cos = LEAF,
+            RelationalExpression::CurrentDate => scalar.current_date = LEAF,
+            RelationalExpression::CurrentTime => scalar.current_time = LEAF,
+            RelationalExpression::CurrentTimestamp => scalar.current_timestamp = LEAF,
+            RelationalExpression::DatePart { part, .. } => {
+                let date_part = scalar.date_part.get_or_insert_with(Default::default);
+                let unit = match part {
+                    crate::DatePartUnit::Year => &mut date_part.year,
+                    crate::DatePartUnit::Quarter => &mut date_part.quarter,
+                    crate::DatePartUnit::Month => &mut date_part.month,
+                    crate::DatePartUnit::Week => &mut date_part.week,
+                    crate::DatePartUnit::DayOfWeek => &mut date_part.day_of_week,
+                    crate::DatePartUnit::DayOfYear => &mut date_part.day_of_year,
+                    crate::DatePartUnit::Day => &mut date_part.day,
+                    crate::DatePartUnit::Hour => &mut date_part.hour,
+                    crate::DatePartUnit::Minute => &mut date_part.minute,
+                    crate::DatePartUnit::Second => &mut date_part.second,
+                    crate::DatePartUnit::Microsecond => &mut date_part.microsecond,
+                    crate::DatePartUnit::Millisecond => &mut date_part.millisecond,
+                    crate::DatePartUnit::Nanosecond => &mut date_part.nanosecond,
+                    crate::DatePartUnit::Epoch => &mut date_part.epoch,
+                };
+                *unit = LEAF;
+            }
+            RelationalExpression::DateTrunc { .. } => scalar.date_trunc = LEAF,
+            RelationalExpression::Exp { .. } => scalar.exp = LEAF,
+            RelationalExpression::Floor { .. } => scalar.floor = LEAF,
+            RelationalExpression::GetField { .. } => scalar.get_field = LEAF,
+            RelationalExpression::Greatest { .. } => scalar.greatest = LEAF,
+            RelationalExpression::Least { .. } => scalar.least = LEAF,
+            RelationalExpression::Left { .. } => scalar.left = LEAF,
+            RelationalExpression::Ln { .. } => scalar.ln = LEAF,
+            RelationalExpression::Log { .. } => scalar.log = LEAF,
+            RelationalExpression::Log10 { .. } => scalar.log10 = LEAF,
+            RelationalExpression::Log2 { .. } => scalar.log2 = LEAF,
+            RelationalExpression::LPad { .. } => scalar.lpad = LEAF,
+            RelationalExpression::LTrim { .. } => scalar.ltrim = LEAF,
+            RelationalExpression::NullIf { .. } => conditional.nullif = LEAF,
+            RelationalExpression::Nvl { .. } => scalar.nvl = LEAF,
+            RelationalExpression::Power { .. } => scalar.power = LEAF,
+            RelationalExpression::Random => scalar.random = LEAF,
+            RelationalExpression::Replace { .. } => scalar.replace = LEAF,
+            RelationalExpression::Reverse { .. } => scalar.reverse = LEAF,
+            RelationalExpression::Right { .. } => scalar.right = LEAF,
+            RelationalExpression::Round { .. } => scalar.round = LEAF,
+            RelationalExpression::RPad { .. } => scalar.rpad = LEAF,
+            RelationalExpression::RTrim { .. } => scalar.rtrim = LEAF,
+            RelationalExpression::Sqrt { .. } => scalar.sqrt = LEAF,
+            RelationalExpression::StrPos { .. } => scalar.str_pos = LEAF,
+            RelationalExpression::Substr { .. } => scalar.substr = LEAF,
+            RelationalExpression::SubstrIndex { .. } => scalar.substr_index = LEAF,
+            RelationalExpression::Tan { .. } => scalar.tan = LEAF,
+            RelationalExpression::ToDate { .. } => scalar.to_date = LEAF,
+            RelationalExpression::ToTimestamp { .. } => scalar.to_timestamp = LEAF,
+            RelationalExpression::Trunc { .. } => scalar.trunc = LEAF,
+            RelationalExpression::ToLower { .. } => scalar.to_lower = LEAF,
+            RelationalExpression::ToUpper { .. } => scalar.to_upper = LEAF,
+            RelationalExpression::BinaryConcat { .. } => scalar.binary_concat = LEAF,
+            RelationalExpression::JsonContains { .. } => scalar.json_contains = LEAF,
+            RelationalExpression::JsonGet { .. } => scalar.json_get = LEAF,
+            RelationalExpression::JsonGetStr { .. } => scalar.json_get_str = LEAF,
+            RelationalExpression::JsonGetInt { .. } => scalar.json_get_int = LEAF,
+            RelationalExpression::JsonGetFloat { .. } => scalar.json_get_float = LEAF,
+            RelationalExpression::JsonGetBool { .. } => scalar.json_get_bool = LEAF,
+            RelationalExpression::JsonGetJson { .. } => scalar.json_get_json = LEAF,
+            RelationalExpression::JsonAsText { .. } => scalar.json_as_text = LEAF,
+            RelationalExpression::JsonLength { .. } => scalar.json_length = LEAF,
+            RelationalExpression::Average { .. } => aggregate.avg = LEAF,
+            RelationalExpression::BoolAnd { .. } => aggregate.bool_and = LEAF,
+            RelationalExpression::BoolOr { .. } => aggregate.bool_or = LEAF,
+            RelationalExpression::Count { distinct, .. } => {
+                let count = aggregate.count.get_or_insert_with(Default::default);
+                if *distinct {
+                    count.distinct = LEAF;
+                }
+            }
+            RelationalExpression::FirstValue { order_by, .. } => {
+                ordered_aggregate(&mut aggregate.first_value, false, order_by.as_ref());
+            }
+            RelationalExpression::LastValue { order_by, .. } => {
+                ordered_aggregate(&mut aggregate.last_value, false, order_by.as_ref());
+            }
+            RelationalExpression::Max { .. } => aggregate.max = LEAF,
+            RelationalExpression::Median { .. } => aggregate.median = LEAF,
+            RelationalExpression::Min { .. } => aggregate.min = LEAF,
+            RelationalExpression::StringAgg {
+                distinct, order_by, ..
+            } => {
+                ordered_aggregate(
+                    &mut aggregate.string_agg_with_separator,
+                    *distinct,
+                    order_by.as_ref(),
+                );
+            }
+            RelationalExpression::Sum { .. } => aggregate.sum = LEAF,
+            RelationalExpression::Var { .. } => aggregate.var = LEAF,
+            RelationalExpression::Stddev { .. } => aggregate.stddev = LEAF,
+            RelationalExpression::StddevPop { .. } => aggregate.stddev_pop = LEAF,
+            RelationalExpression::ApproxPercentileCont { .. } => {
+                aggregate.approx_percentile_cont = LEAF;
+            }
+            RelationalExpression::ArrayAgg {
+                distinct, order_by, ..
+            } => ordered_aggregate(&mut aggregate.array_agg, *distinct, order_by.as_ref()),
+            RelationalExpression::ApproxDistinct { .. } => aggregate.approx_distinct = LEAF,
+            RelationalExpression::RowNumber { .. } => window.row_number = LEAF,
+            RelationalExpression::DenseRank { .. } => window.dense_rank = LEAF,
+            RelationalExpression::NTile { .. } => window.ntile = LEAF,
+            RelationalExpression::Rank { .. } => window.rank = LEAF,
+            RelationalExpression::CumeDist { .. } => window.cume_dist = LEAF,
+            RelationalExpression::PercentRank { .. } => window.percent_rank = LEAF,
+        }
+
+        visit::visit_relational_expression(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_query_capabilities() {
+        let request: QueryRequest = serde_json::from_value(serde_json::json!({
+            "collection": "authors",
+            "arguments": {},
+            "collection_relationships": {},
+            "variables": [{ "name": "Peter" }],
+            "query": {
+                "predicate": {
+                    "type": "exists",
+                    "in_collection": {
+                        "type": "unrelated",
+                        "collection": "articles",
+                        "arguments": {},
+                    },
+                    "predicate": {
+                        "type": "binary_comparison_operator",
+                        "column": { "type": "column", "name": "author" },
+                        "operator": "eq",
+                        "value": { "type": "variable", "name": "name" },
+                    },
+                },
+            },
+        }))
+        .unwrap();
+
+        let required = for_query_request(&request);
+        assert!(required.query.variables.is_some());
+        assert!(required.query.exists.unrelated.is_some());
+        assert!(required.relationships.is_none());
+
+        let response = CapabilitiesResponse {
+            version: crate::VERSION.into(),
+            capabilities: Capabilities {
+                query: crate::QueryCapabilities {
+                    variables: LEAF,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        };
+
+        assert_eq!(
+            missing_capabilities(&required, &response),
+            vec!["query.exists.unrelated".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_distinct_capabilities() {
+        let request: QueryRequest = serde_json::from_value(serde_json::json!({
+            "collection": "articles",
+            "arguments": {},
+            "collection_relationships": {},
+            "query": {
+                "fields": { "author_id": { "type": "column", "column": "author_id" } },
+                "distinct": { "type": "fields" },
+            },
+        }))
+        .unwrap();
+
+        let required = for_query_request(&request);
+        assert_eq!(
+            required.query.distinct,
+            Some(crate::DistinctCapabilities { on: None })
+        );
+
+        let request = QueryRequest {
+            query: request.query.distinct_on(["author_id"]),
+            ..request
+        };
+        let required = for_query_request(&request);
+        assert!(required.query.distinct.as_ref().unwrap().on.is_some());
+
+        let response = CapabilitiesResponse {
+            version: crate::VERSION.into(),
+            capabilities: Capabilities {
+                query: crate::QueryCapabilities {
+                    distinct: Some(crate::DistinctCapabilities { on: None }),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        };
+        assert_eq!(
+            missing_capabilities(&required, &response),
+            vec!["query.distinct.on".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_aggregates_capabilities() {
+        let request: QueryRequest = serde_json::from_value(serde_json::json!({
+            "collection": "articles",
+            "arguments": {},
+            "collection_relationships": {},
+            "query": {
+                "fields": { "id": { "type": "column", "column": "id" } },
+                "aggregates": {},
+            },
+        }))
+        .unwrap();
+
+        let required = for_query_request(&request);
+        assert!(required.query.aggregates.is_none());
+
+        let request = QueryRequest {
+            query: request
+                .query
+                .aggregate("count", crate::builders::star_count()),
+            ..request
+        };
+        let required = for_query_request(&request);
+        assert!(required.query.aggregates.is_some());
+    }
+
+    #[test]
+    fn test_missing_relational_capabilities() {
+        let query: RelationalQuery = serde_json::from_value(serde_json::json!({
+            "root_relation": {
+                "type": "window",
+                "input": {
+                    "type": "from",
+                    "collection": "articles",
+                    "columns": ["id", "title"],
+                },
+                "exprs": [{
+                    "type": "row_number",
+                    "order_by": [],
+                    "partition_by": [{ "type": "column", "index": 0 }],
+                }],
+            },
+        }))
+        .unwrap();
+
+        let response = CapabilitiesResponse {
+            version: crate::VERSION.into(),
+            capabilities: Capabilities {
+                relational_query: Some(RelationalQueryCapabilities::default()),
+                ..Default::default()
+            },
+        };
+
+        assert_eq!(
+            missing_capabilities(&for_relational_query(&query), &response),
+            vec!["relational_query.window".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_missing_mutation_capabilities() {
+        let request: MutationRequest = serde_json::from_value(serde_json::json!({
+            "operations": [
+                {
+                    "type": "relational_insert",
+                    "collection": "authors",
+                    "arguments": {},
+                    "columns": ["id", "name"],
+                    "rows": [[1, "Peter"]],
+                    "returning": ["id"],
+                },
+                {
+                    "type": "relational_delete",
+                    "collection": "articles",
+                    "arguments": {},
+                    "relation": {
+                        "type": "from",
+                        "collection": "articles",
+                        "columns": ["id"],
+                    },
+                },
+            ],
+            "collection_relationships": {},
+        }))
+        .unwrap();
+
+        let response = CapabilitiesResponse {
+            version: crate::VERSION.into(),
+            capabilities: Capabilities {
+                mutation: crate::MutationCapabilities {
+                    transactional: LEAF,
+                    ..Default::default()
+                },
+                relational_query: Some(RelationalQueryCapabilities::default()),
+                relational_mutation: Some(RelationalMutationCapabilities {
+                    insert: LEAF,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        };
+
+        assert_eq!(
+            missing_capabilities(&for_mutation_request(&request), &response),
+            vec![
+                "relational_mutation.delete".to_string(),
+                "relational_mutation.returning".to_string()
+            ]
+        );
+    }
+}
@@ -100,6 +100,9 @@ pub enum TypeRepresentation {
     JSON,
     /// One of the specified string values
     Enum { one_of: Vec<String> },
+    /// A vector of floating-point numbers with a fixed number of dimensions,
+    /// such as an embedding
+    Vector { dimensions: u32 },
 }
 // ANCHOR_END: TypeRepresentation
 
@@ -168,6 +171,7 @@ pub enum Type {
 
 // ANCHOR: ComparisonOperatorDefinition
 /// The definition of a comparison operator on a scalar type
+#[skip_serializing_none]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[schemars(title = "Comparison Operator Definition")]
@@ -184,6 +188,25 @@ pub enum ComparisonOperatorDefinition {
     StartsWithInsensitive,
     EndsWith,
     EndsWithInsensitive,
+    FullTextSearch {
+        /// The language used to stem and tokenize the column and the search
+        /// query, if the connector supports more than one
+        language: Option<String>,
+        /// The syntax used to interpret the search query, which defaults to
+        /// `plain`
+        #[serde(default)]
+        syntax: FullTextSearchSyntax,
+    },
+    SpatialIntersects,
+    SpatialWithin,
+    SpatialContains,
+    SpatialWithinDistance {
+        /// The object type of the argument to this operator, which should
+        /// have a field `geometry` of the scalar type on which this operator
+        /// is defined, and a field `distance` of a scalar type with the type
+        /// representation Float64
+        argument_type: ObjectTypeName,
+    },
     Custom {
         /// The type of the argument to this operator
         argument_type: Type,
@@ -191,6 +214,23 @@ pub enum ComparisonOperatorDefinition {
 }
 // ANCHOR_END: ComparisonOperatorDefinition
 
+// ANCHOR: FullTextSearchSyntax
+/// The syntax used to interpret the argument of a full-text search operator
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[schemars(title = "Full Text Search Syntax")]
+pub enum FullTextSearchSyntax {
+    /// All words in the query must match, and any operators are ignored
+    #[default]
+    Plain,
+    /// The words in the query must match in order, as a phrase
+    Phrase,
+    /// The query uses web search syntax: quoted phrases, `or`, and `-` to
+    /// negate a word
+    Websearch,
+}
+// ANCHOR_END: FullTextSearchSyntax
+
 // ANCHOR: AggregateFunctionDefinition
 /// The definition of an aggregation function on a scalar type
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -211,6 +251,47 @@ pub enum AggregateFunctionDefinition {
         /// the type representation Float64
         result_type: ScalarTypeName,
     },
+    /// The middle value, which is of the scalar type on which this function
+    /// is defined. Only defined on types with a numeric representation.
+    Median,
+    /// The sample standard deviation
+    Stddev {
+        /// The scalar type of the result of this function, which should have
+        /// the type representation Float64
+        result_type: ScalarTypeName,
+    },
+    /// The population standard deviation
+    StddevPop {
+        /// The scalar type of the result of this function, which should have
+        /// the type representation Float64
+        result_type: ScalarTypeName,
+    },
+    /// The sample variance
+    Var {
+        /// The scalar type of the result of this function, which should have
+        /// the type representation Float64
+        result_type: ScalarTypeName,
+    },
+    /// True if every value is true. Only defined on types with the Boolean
+    /// representation.
+    BoolAnd,
+    /// True if any value is true. Only defined on types with the Boolean
+    /// representation.
+    BoolOr,
+    /// The values concatenated into a single string. Only defined on types
+    /// with the String representation.
+    StringAgg {
+        /// The string placed between consecutive values
+        separator: String,
+    },
+    /// The values collected into an array, including any nulls
+    ArrayAgg,
+    /// An approximation of the number of distinct non-null values
+    ApproxDistinct {
+        /// The scalar type of the result of this function, which should have
+        /// one of the type representations Int32 or Int64
+        result_type: ScalarTypeName,
+    },
     Custom {
         /// The scalar or object type of the result of this function
         result_type: Type,
@@ -433,3 +514,21 @@ pub struct RequestLevelArguments {
     pub relational_query_arguments: BTreeMap<ArgumentName, ArgumentInfo>,
 }
 // ANCHOR_END: RequestLevelArguments
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_text_search_syntax_defaults_to_plain() {
+        let definition: ComparisonOperatorDefinition =
+            serde_json::from_value(serde_json::json!({ "type": "full_text_search" })).unwrap();
+        assert_eq!(
+            definition,
+            ComparisonOperatorDefinition::FullTextSearch {
+                language: None,
+                syntax: FullTextSearchSyntax::Plain,
+            }
+        );
+    }
+}
@@ -0,0 +1,761 @@
+//! The points at which SQL dialects differ.
+//!
+//! Every hook receives already-rendered SQL fragments, and returns either a
+//! fragment, or a [`RenderError::Unsupported`] error if the dialect has no
+//! equivalent construct.
+
+use crate::{CastType, DatePartUnit};
+
+use super::RenderError;
+
+/// A SQL dialect.
+///
+/// The default implementations of the hooks follow the SQL standard, except
+/// where noted.
+pub trait Dialect {
+    /// The name of the dialect, for use in error messages
+    fn name(&self) -> &'static str;
+
+    /// An error for a construct which this dialect cannot express
+    fn unsupported(&self, construct: &str) -> RenderError {
+        RenderError::Unsupported {
+            dialect: self.name(),
+            construct: construct.to_string(),
+        }
+    }
+
+    /// Quote an identifier, such as a table or column name
+    fn quote_identifier(&self, identifier: &str) -> String {
+        format!("\"{}\"", identifier.replace('"', "\"\""))
+    }
+
+    /// Quote a string literal
+    fn quote_string(&self, value: &str) -> String {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+
+    /// The clauses which skip the first `skip` rows and return at most `fetch`
+    /// rows, or the empty string if no rows are skipped and `fetch` is `None`.
+    ///
+    /// The default implementation uses `LIMIT` and `OFFSET`.
+    fn pagination(&self, fetch: Option<u64>, skip: u64) -> String {
+        limit_offset(fetch, skip)
+    }
+
+    /// A case-insensitive `LIKE` comparison.
+    ///
+    /// The default implementation uses `ILIKE`.
+    fn ilike(&self, expr: &str, pattern: &str, negated: bool) -> String {
+        let not = if negated { "NOT " } else { "" };
+        format!("({expr} {not}ILIKE {pattern})")
+    }
+
+    /// Extract a component of a date or timestamp as an integer
+    fn date_part(&self, expr: &str, part: DatePartUnit) -> Result<String, RenderError> {
+        let field = match part {
+            DatePartUnit::Year => "YEAR",
+            DatePartUnit::Month => "MONTH",
+            DatePartUnit::Day => "DAY",
+            DatePartUnit::Hour => "HOUR",
+            DatePartUnit::Minute => "MINUTE",
+            DatePartUnit::Second => "SECOND",
+            _ => return Err(self.unsupported(&format!("date part {part:?}"))),
+        };
+        Ok(format!("EXTRACT({field} FROM {expr})"))
+    }
+
+    fn is_distinct_from(&self, left: &str, right: &str, negated: bool) -> String {
+        let not = if negated { "NOT " } else { "" };
+        format!("({left} IS {not}DISTINCT FROM {right})")
+    }
+
+    fn modulo(&self, left: &str, right: &str) -> String {
+        format!("MOD({left}, {right})")
+    }
+
+    fn is_nan(&self, expr: &str) -> String {
+        format!("({expr} <> {expr})")
+    }
+
+    /// The name of the SQL type corresponding to a cast type
+    fn type_name(&self, as_type: CastType) -> Result<String, RenderError> {
+        Ok(match as_type {
+            CastType::Boolean => "BOOLEAN".into(),
+            CastType::Utf8 => "VARCHAR".into(),
+            CastType::Int8 | CastType::Int16 | CastType::UInt8 => "SMALLINT".into(),
+            CastType::Int32 | CastType::UInt16 => "INTEGER".into(),
+            CastType::Int64 | CastType::UInt32 => "BIGINT".into(),
+            CastType::UInt64 => "DECIMAL(20)".into(),
+            CastType::Float32 => "REAL".into(),
+            CastType::Float64 => "DOUBLE PRECISION".into(),
+            CastType::Decimal128 { scale, prec } | CastType::Decimal256 { scale, prec } => {
+                format!("DECIMAL({prec}, {scale})")
+            }
+            CastType::Date => "DATE".into(),
+            CastType::Time => "TIME".into(),
+            CastType::Timestamp => "TIMESTAMP".into(),
+            CastType::Duration | CastType::Interval => {
+                return Err(self.unsupported(&format!("cast to {as_type:?}")))
+            }
+        })
+    }
+
+    fn cast(&self, expr: &str, as_type: CastType) -> Result<String, RenderError> {
+        Ok(format!("CAST({expr} AS {})", self.type_name(as_type)?))
+    }
+
+    /// A cast which evaluates to `NULL` instead of failing
+    fn try_cast(&self, _expr: &str, _as_type: CastType) -> Result<String, RenderError> {
+        Err(self.unsupported("TRY_CAST"))
+    }
+
+    /// A date literal, given in the form `YYYY-MM-DD`
+    fn date_literal(&self, date: &str) -> String {
+        format!("DATE '{date}'")
+    }
+
+    /// A time literal, given in the form `HH:MM:SS[.fffffffff]`
+    fn time_literal(&self, time: &str) -> String {
+        format!("TIME '{time}'")
+    }
+
+    /// A timestamp literal, given in the form `YYYY-MM-DD HH:MM:SS[.fffffffff]`
+    fn timestamp_literal(&self, timestamp: &str) -> String {
+        format!("TIMESTAMP '{timestamp}'")
+    }
+
+    /// An interval literal.
+    ///
+    /// The SQL standard separates year-month intervals from day-time intervals,
+    /// so the default implementation only supports intervals of one kind or the
+    /// other.
+    fn interval_literal(
+        &self,
+        months: i32,
+        days: i32,
+        nanoseconds: i128,
+    ) -> Result<String, RenderError> {
+        match (months, days, nanoseconds) {
+            (months, 0, 0) => Ok(format!("INTERVAL '{months}' MONTH")),
+            (0, days, 0) => Ok(format!("INTERVAL '{days}' DAY")),
+            (0, 0, nanoseconds) => Ok(format!("INTERVAL '{}' SECOND", seconds(nanoseconds))),
+            _ => Err(self.unsupported("mixed intervals")),
+        }
+    }
+
+    /// A call to a scalar function.
+    ///
+    /// The renderer uses the PostgreSQL names for scalar functions, so the
+    /// default implementation only rejects `SUBSTR_INDEX`, which has no
+    /// standard equivalent.
+    fn function(&self, name: &str, args: Vec<String>) -> Result<String, RenderError> {
+        match name {
+            "SUBSTR_INDEX" => Err(self.unsupported(name)),
+            _ => Ok(call(name, &args)),
+        }
+    }
+
+    /// A call to an aggregate function.
+    ///
+    /// Order-sensitive aggregates are passed `order_by`, and `FIRST_VALUE` and
+    /// `LAST_VALUE` are passed to this hook when used as aggregates, which the
+    /// default implementation does not support.
+    fn aggregate(
+        &self,
+        name: &str,
+        args: Vec<String>,
+        distinct: bool,
+        order_by: &[String],
+    ) -> Result<String, RenderError> {
+        match name {
+            "FIRST_VALUE" | "LAST_VALUE" => Err(self.unsupported(name)),
+            _ => Ok(aggregate_call(name, &args, distinct, order_by)),
+        }
+    }
+
+    /// A continuous percentile of an expression, such as the median
+    fn percentile(&self, expr: &str, percentile: f64) -> Result<String, RenderError> {
+        Ok(format!(
+            "PERCENTILE_CONT({percentile:?}) WITHIN GROUP (ORDER BY {expr})"
+        ))
+    }
+
+    /// Follow a path of object keys and array indices into a JSON value,
+    /// returning either JSON, or text if `as_text` is set
+    fn json_path(
+        &self,
+        _json: &str,
+        _keys: &[String],
+        as_text: bool,
+    ) -> Result<String, RenderError> {
+        Err(self.unsupported(if as_text {
+            "JSON text extraction"
+        } else {
+            "JSON extraction"
+        }))
+    }
+
+    /// The number of elements of a JSON array, or entries of a JSON object
+    fn json_length(&self, _json: &str) -> Result<String, RenderError> {
+        Err(self.unsupported("JSON length"))
+    }
+
+    /// A field of a structured value
+    fn get_field(&self, expr: &str, field: &str) -> Result<String, RenderError> {
+        Ok(format!("({expr}).{}", self.quote_identifier(field)))
+    }
+
+    /// An element of an array, where the first element has index 1
+    fn array_element(&self, expr: &str, index: usize) -> Result<String, RenderError> {
+        Ok(format!("({expr})[{index}]"))
+    }
+}
+
+/// Render a function call
+pub fn call(name: &str, args: &[String]) -> String {
+    format!("{name}({})", args.join(", "))
+}
+
+/// Render a call to an aggregate function, using the standard syntax for
+/// `DISTINCT` and `ORDER BY`
+pub fn aggregate_call(name: &str, args: &[String], distinct: bool, order_by: &[String]) -> String {
+    let distinct = if distinct { "DISTINCT " } else { "" };
+    let order_by = if order_by.is_empty() {
+        String::new()
+    } else {
+        format!(" ORDER BY {}", order_by.join(", "))
+    };
+    format!("{name}({distinct}{}{order_by})", args.join(", "))
+}
+
+fn limit_offset(fetch: Option<u64>, skip: u64) -> String {
+    let mut clauses = vec![];
+    if let Some(fetch) = fetch {
+        clauses.push(format!("LIMIT {fetch}"));
+    }
+    if skip > 0 {
+        clauses.push(format!("OFFSET {skip}"));
+    }
+    clauses.join(" ")
+}
+
+/// Format a number of nanoseconds as a decimal number of seconds
+pub(super) fn seconds(nanoseconds: i128) -> String {
+    let sign = if nanoseconds < 0 { "-" } else { "" };
+    let nanoseconds = nanoseconds.unsigned_abs();
+    let whole = nanoseconds / 1_000_000_000;
+    let fraction = nanoseconds % 1_000_000_000;
+    if fraction == 0 {
+        format!("{sign}{whole}")
+    } else {
+        let fraction = format!("{fraction:09}");
+        format!("{sign}{whole}.{}", fraction.trim_end_matches('0'))
+    }
+}
+
+/// PostgreSQL.
+///
+/// JSON values are assumed to have type `jsonb`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PostgreSql;
+
+impl Dialect for PostgreSql {
+    fn name(&self) -> &'static str {
+        "PostgreSQL"
+    }
+
+    fn date_part(&self, expr: &str, part: DatePartUnit) -> Result<String, RenderError> {
+        let field = match part {
+            DatePartUnit::Year => "YEAR",
+            DatePartUnit::Quarter => "QUARTER",
+            DatePartUnit::Month => "MONTH",
+            DatePartUnit::Week => "WEEK",
+            DatePartUnit::DayOfWeek => "DOW",
+            DatePartUnit::DayOfYear => "DOY",
+            DatePartUnit::Day => "DAY",
+            DatePartUnit::Hour => "HOUR",
+            DatePartUnit::Minute => "MINUTE",
+            DatePartUnit::Second => "SECOND",
+            DatePartUnit::Millisecond => "MILLISECONDS",
+            DatePartUnit::Microsecond => "MICROSECONDS",
+            DatePartUnit::Nanosecond => {
+                return Ok(format!("(EXTRACT(MICROSECONDS FROM {expr}) * 1000)"))
+            }
+            DatePartUnit::Epoch => "EPOCH",
+        };
+        Ok(format!("EXTRACT({field} FROM {expr})"))
+    }
+
+    fn modulo(&self, left: &str, right: &str) -> String {
+        format!("({left} % {right})")
+    }
+
+    fn is_nan(&self, expr: &str) -> String {
+        // NaN is equal to itself in PostgreSQL
+        format!("({expr} = CAST('NaN' AS DOUBLE PRECISION))")
+    }
+
+    fn type_name(&self, as_type: CastType) -> Result<String, RenderError> {
+        Ok(match as_type {
+            CastType::Utf8 => "TEXT".into(),
+            CastType::UInt64 => "NUMERIC(20)".into(),
+            CastType::Decimal128 { scale, prec } | CastType::Decimal256 { scale, prec } => {
+                format!("NUMERIC({prec}, {scale})")
+            }
+            CastType::Duration | CastType::Interval => "INTERVAL".into(),
+            _ => Ansi.type_name(as_type)?,
+        })
+    }
+
+    fn interval_literal(
+        &self,
+        months: i32,
+        days: i32,
+        nanoseconds: i128,
+    ) -> Result<String, RenderError> {
+        Ok(format!(
+            "INTERVAL '{months} months {days} days {} seconds'",
+            seconds(nanoseconds)
+        ))
+    }
+
+    fn function(&self, name: &str, args: Vec<String>) -> Result<String, RenderError> {
+        match (name, args.as_slice()) {
+            ("SUBSTR_INDEX", [str, delim, count]) => {
+                let parts = format!("STRING_TO_ARRAY({str}, {delim})");
+                Ok(format!(
+                    "CASE WHEN {count} >= 0 \
+                     THEN ARRAY_TO_STRING(({parts})[1:{count}], {delim}) \
+                     ELSE ARRAY_TO_STRING(({parts})[CARDINALITY({parts}) + {count} + 1:], {delim}) \
+                     END"
+                ))
+            }
+            _ => Ok(call(name, &args)),
+        }
+    }
+
+    fn aggregate(
+        &self,
+        name: &str,
+        args: Vec<String>,
+        distinct: bool,
+        order_by: &[String],
+    ) -> Result<String, RenderError> {
+        match name {
+            "FIRST_VALUE" => Ok(format!(
+                "({})[1]",
+                aggregate_call("ARRAY_AGG", &args, distinct, order_by)
+            )),
+            "LAST_VALUE" => {
+                // With DISTINCT, the array may have fewer elements than there
+                // are rows, so index it by its own length
+                let array = aggregate_call("ARRAY_AGG", &args, distinct, order_by);
+                Ok(format!("({array})[ARRAY_UPPER({array}, 1)]"))
+            }
+            _ => Ok(aggregate_call(name, &args, distinct, order_by)),
+        }
+    }
+
+    fn json_path(&self, json: &str, keys: &[String], as_text: bool) -> Result<String, RenderError> {
+        Ok(json_arrows(json, keys, as_text, "#>> '{}'"))
+    }
+
+    fn json_length(&self, json: &str) -> Result<String, RenderError> {
+        Ok(format!(
+            "CASE JSONB_TYPEOF({json}) \
+             WHEN 'array' THEN JSONB_ARRAY_LENGTH({json}) \
+             WHEN 'object' THEN (SELECT COUNT(*) FROM JSONB_OBJECT_KEYS({json})) \
+             END"
+        ))
+    }
+}
+
+/// SQLite, version 3.44 or later, with the math functions enabled.
+///
+/// SQLite has no date, time or interval types, so temporal values are
+/// represented as ISO 8601 strings, and arrays are represented as JSON.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sqlite;
+
+impl Dialect for Sqlite {
+    fn name(&self) -> &'static str {
+        "SQLite"
+    }
+
+    fn pagination(&self, fetch: Option<u64>, skip: u64) -> String {
+        match (fetch, skip) {
+            // OFFSET is only allowed after LIMIT
+            (None, skip) if skip > 0 => format!("LIMIT -1 OFFSET {skip}"),
+            (fetch, skip) => limit_offset(fetch, skip),
+        }
+    }
+
+    fn ilike(&self, expr: &str, pattern: &str, negated: bool) -> String {
+        let not = if negated { "NOT " } else { "" };
+        format!("(LOWER({expr}) {not}LIKE LOWER({pattern}))")
+    }
+
+    fn date_part(&self, expr: &str, part: DatePartUnit) -> Result<String, RenderError> {
+        let strftime = |format: &str| format!("CAST(STRFTIME('{format}', {expr}) AS INTEGER)");
+        let fraction = |scale: &str| format!("CAST(STRFTIME('%f', {expr}) * {scale} AS INTEGER)");
+        Ok(match part {
+            DatePartUnit::Year => strftime("%Y"),
+            DatePartUnit::Quarter => format!("(({} + 2) / 3)", strftime("%m")),
+            DatePartUnit::Month => strftime("%m"),
+            DatePartUnit::DayOfWeek => strftime("%w"),
+            DatePartUnit::DayOfYear => strftime("%j"),
+            DatePartUnit::Day => strftime("%d"),
+            DatePartUnit::Hour => strftime("%H"),
+            DatePartUnit::Minute => strftime("%M"),
+            DatePartUnit::Second => strftime("%S"),
+            DatePartUnit::Millisecond => fraction("1000"),
+            DatePartUnit::Microsecond => fraction("1000000"),
+            DatePartUnit::Nanosecond => fraction("1000000000"),
+            DatePartUnit::Epoch => strftime("%s"),
+            DatePartUnit::Week => return Err(self.unsupported("date part Week")),
+        })
+    }
+
+    fn is_distinct_from(&self, left: &str, right: &str, negated: bool) -> String {
+        let operator = if negated { "IS" } else { "IS NOT" };
+        format!("({left} {operator} {right})")
+    }
+
+    fn modulo(&self, left: &str, right: &str) -> String {
+        format!("({left} % {right})")
+    }
+
+    fn type_name(&self, as_type: CastType) -> Result<String, RenderError> {
+        Ok(match as_type {
+            CastType::Boolean
+            | CastType::Int8
+            | CastType::Int16
+            | CastType::Int32
+            | CastType::Int64
+            | CastType::UInt8
+            | CastType::UInt16
+            | CastType::UInt32
+            | CastType::UInt64 => "INTEGER".into(),
+            CastType::Utf8 | CastType::Date | CastType::Time | CastType::Timestamp => "TEXT".into(),
+            CastType::Float32 | CastType::Float64 => "REAL".into(),
+            CastType::Decimal128 { .. } | CastType::Decimal256 { .. } => "NUMERIC".into(),
+            CastType::Duration | CastType::Interval => {
+                return Err(self.unsupported(&format!("cast to {as_type:?}")))
+            }
+        })
+    }
+
+    fn cast(&self, expr: &str, as_type: CastType) -> Result<String, RenderError> {
+        match as_type {
+            CastType::Date => Ok(format!("DATE({expr})")),
+            CastType::Time => Ok(format!("TIME({expr})")),
+            CastType::Timestamp => Ok(format!("DATETIME({expr})")),
+            _ => Ok(format!("CAST({expr} AS {})", self.type_name(as_type)?)),
+        }
+    }
+
+    fn try_cast(&self, expr: &str, as_type: CastType) -> Result<String, RenderError> {
+        // Casts never fail in SQLite
+        self.cast(expr, as_type)
+    }
+
+    fn date_literal(&self, date: &str) -> String {
+        self.quote_string(date)
+    }
+
+    fn time_literal(&self, time: &str) -> String {
+        self.quote_string(time)
+    }
+
+    fn timestamp_literal(&self, timestamp: &str) -> String {
+        self.quote_string(timestamp)
+    }
+
+    fn interval_literal(&self, _: i32, _: i32, _: i128) -> Result<String, RenderError> {
+        Err(self.unsupported("intervals"))
+    }
+
+    fn function(&self, name: &str, args: Vec<String>) -> Result<String, RenderError> {
+        match (name, args.as_slice()) {
+            ("CHARACTER_LENGTH", _) => Ok(call("LENGTH", &args)),
+            ("STRPOS", _) => Ok(call("INSTR", &args)),
+            ("BTRIM", _) => Ok(call("TRIM", &args)),
+            ("GREATEST" | "LEAST", [arg]) => Ok(arg.clone()),
+            ("GREATEST", _) => Ok(call("MAX", &args)),
+            ("LEAST", _) => Ok(call("MIN", &args)),
+            ("LEFT", [str, n]) => Ok(format!("SUBSTR({str}, 1, {n})")),
+            ("RIGHT", [str, n]) => Ok(format!("SUBSTR({str}, MAX(LENGTH({str}) - ({n}) + 1, 1))")),
+            // RANDOM returns a 64-bit signed integer
+            ("RANDOM", []) => Ok("(RANDOM() / 18446744073709551616.0 + 0.5)".into()),
+            ("TRUNC", [_, _])
+            | ("DATE_TRUNC" | "LPAD" | "RPAD" | "REVERSE" | "SUBSTR_INDEX", _) => {
+                Err(self.unsupported(name))
+            }
+            _ => Ok(call(name, &args)),
+        }
+    }
+
+    fn aggregate(
+        &self,
+        name: &str,
+        args: Vec<String>,
+        distinct: bool,
+        order_by: &[String],
+    ) -> Result<String, RenderError> {
+        match name {
+            "BOOL_AND" => Ok(aggregate_call("MIN", &args, distinct, order_by)),
+            "BOOL_OR" => Ok(aggregate_call("MAX", &args, distinct, order_by)),
+            "ARRAY_AGG" => Ok(aggregate_call(
+                "JSON_GROUP_ARRAY",
+                &args,
+                distinct,
+                order_by,
+            )),
+            "FIRST_VALUE" => Ok(format!(
+                "({} ->> '$[0]')",
+                aggregate_call("JSON_GROUP_ARRAY", &args, distinct, order_by)
+            )),
+            "LAST_VALUE" => Ok(format!(
+                "({} ->> '$[#-1]')",
+                aggregate_call("JSON_GROUP_ARRAY", &args, distinct, order_by)
+            )),
+            // DISTINCT aggregates may only have a single argument
+            "STRING_AGG" if distinct => Err(self.unsupported("distinct STRING_AGG")),
+            "VAR_SAMP" | "STDDEV_SAMP" | "STDDEV_POP" => Err(self.unsupported(name)),
+            _ => Ok(aggregate_call(name, &args, distinct, order_by)),
+        }
+    }
+
+    fn percentile(&self, _expr: &str, _percentile: f64) -> Result<String, RenderError> {
+        Err(self.unsupported("percentiles"))
+    }
+
+    fn json_path(&self, json: &str, keys: &[String], as_text: bool) -> Result<String, RenderError> {
+        Ok(json_arrows(json, keys, as_text, "->> '$'"))
+    }
+
+    fn json_length(&self, json: &str) -> Result<String, RenderError> {
+        Ok(format!(
+            "CASE JSON_TYPE({json}) \
+             WHEN 'array' THEN JSON_ARRAY_LENGTH({json}) \
+             WHEN 'object' THEN (SELECT COUNT(*) FROM JSON_EACH({json})) \
+             END"
+        ))
+    }
+
+    fn get_field(&self, expr: &str, field: &str) -> Result<String, RenderError> {
+        let path = format!("$.\"{}\"", field.replace('"', "\\\""));
+        Ok(format!("({expr} -> {})", self.quote_string(&path)))
+    }
+
+    fn array_element(&self, expr: &str, index: usize) -> Result<String, RenderError> {
+        Ok(match index.checked_sub(1) {
+            Some(index) => format!("({expr} -> {index})"),
+            None => "NULL".into(),
+        })
+    }
+}
+
+/// Standard SQL, as of SQL:2016.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ansi;
+
+impl Dialect for Ansi {
+    fn name(&self) -> &'static str {
+        "ANSI"
+    }
+
+    fn pagination(&self, fetch: Option<u64>, skip: u64) -> String {
+        let mut clauses = vec![];
+        if skip > 0 {
+            clauses.push(format!("OFFSET {skip} ROWS"));
+        }
+        if let Some(fetch) = fetch {
+            clauses.push(format!("FETCH FIRST {fetch} ROWS ONLY"));
+        }
+        clauses.join(" ")
+    }
+
+    fn ilike(&self, expr: &str, pattern: &str, negated: bool) -> String {
+        let not = if negated { "NOT " } else { "" };
+        format!("(UPPER({expr}) {not}LIKE UPPER({pattern}))")
+    }
+
+    fn function(&self, name: &str, args: Vec<String>) -> Result<String, RenderError> {
+        let trim = |side: &str| match args.as_slice() {
+            [str] => Ok(format!("TRIM({side} FROM {str})")),
+            [str, trim_str] => Ok(format!("TRIM({side} {trim_str} FROM {str})")),
+            _ => Err(self.unsupported(name)),
+        };
+        match (name, args.as_slice()) {
+            ("BTRIM", _) => trim("BOTH"),
+            ("LTRIM", _) => trim("LEADING"),
+            ("RTRIM", _) => trim("TRAILING"),
+            ("STRPOS", [str, substr]) => Ok(format!("POSITION({substr} IN {str})")),
+            ("SUBSTR", [str, start]) => Ok(format!("SUBSTRING({str} FROM {start})")),
+            ("SUBSTR", [str, start, len]) => Ok(format!("SUBSTRING({str} FROM {start} FOR {len})")),
+            ("LEFT", [str, n]) => Ok(format!("SUBSTRING({str} FROM 1 FOR {n})")),
+            ("CONCAT", _) => Ok(format!("({})", args.join(" || "))),
+            (
+                "RIGHT" | "RANDOM" | "DATE_TRUNC" | "LPAD" | "RPAD" | "REVERSE" | "SUBSTR_INDEX",
+                _,
+            ) => Err(self.unsupported(name)),
+            _ => Ok(call(name, &args)),
+        }
+    }
+
+    fn aggregate(
+        &self,
+        name: &str,
+        args: Vec<String>,
+        distinct: bool,
+        order_by: &[String],
+    ) -> Result<String, RenderError> {
+        match name {
+            "BOOL_AND" => Ok(aggregate_call("EVERY", &args, distinct, order_by)),
+            "BOOL_OR" => Ok(aggregate_call("SOME", &args, distinct, order_by)),
+            "STRING_AGG" => {
+                let listagg = aggregate_call("LISTAGG", &args, distinct, &[]);
+                if order_by.is_empty() {
+                    Ok(listagg)
+                } else {
+                    Ok(format!(
+                        "{listagg} WITHIN GROUP (ORDER BY {})",
+                        order_by.join(", ")
+                    ))
+                }
+            }
+            "FIRST_VALUE" | "LAST_VALUE" => Err(self.unsupported(name)),
+            _ => Ok(aggregate_call(name, &args, distinct, order_by)),
+        }
+    }
+}
+
+/// Follow a JSON path using the `->` and `->>` operators, which PostgreSQL and
+/// SQLite share. `text` is the suffix which converts a whole JSON value to text.
+fn json_arrows(json: &str, keys: &[String], as_text: bool, text: &str) -> String {
+    match keys.split_last() {
+        None if as_text => format!("({json} {text})"),
+        None => json.to_string(),
+        Some((last, init)) => {
+            let mut path = json.to_string();
+            for key in init {
+                path = format!("{path} -> {key}");
+            }
+            let arrow = if as_text { "->>" } else { "->" };
+            format!("({path} {arrow} {last})")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postgresql_first_and_last_value() {
+        let args = vec![r#""t0"."title""#.to_string()];
+        let order_by = vec![r#""t0"."id" ASC NULLS LAST"#.to_string()];
+        assert_eq!(
+            PostgreSql
+                .aggregate("FIRST_VALUE", args.clone(), false, &order_by)
+                .unwrap(),
+            r#"(ARRAY_AGG("t0"."title" ORDER BY "t0"."id" ASC NULLS LAST))[1]"#
+        );
+        assert_eq!(
+            PostgreSql.aggregate("LAST_VALUE", args, true, &[]).unwrap(),
+            r#"(ARRAY_AGG(DISTINCT "t0"."title"))[ARRAY_UPPER(ARRAY_AGG(DISTINCT "t0"."title"), 1)]"#
+        );
+    }
+
+    #[test]
+    fn test_quoting() {
+        for dialect in [&PostgreSql as &dyn Dialect, &Sqlite, &Ansi] {
+            assert_eq!(dialect.quote_identifier(r#"a"b"#), r#""a""b""#);
+            assert_eq!(dialect.quote_string("o'brien"), "'o''brien'");
+        }
+    }
+
+    #[test]
+    fn test_postgresql_substr_index_and_json_length() {
+        let args = vec!["'a.b.c'".to_string(), "'.'".to_string(), "2".to_string()];
+        assert_eq!(
+            PostgreSql.function("SUBSTR_INDEX", args.clone()).unwrap(),
+            "CASE WHEN 2 >= 0 \
+             THEN ARRAY_TO_STRING((STRING_TO_ARRAY('a.b.c', '.'))[1:2], '.') \
+             ELSE ARRAY_TO_STRING((STRING_TO_ARRAY('a.b.c', '.'))[CARDINALITY(STRING_TO_ARRAY('a.b.c', '.')) + 2 + 1:], '.') \
+             END"
+        );
+        assert_eq!(
+            Ansi.function("SUBSTR_INDEX", args),
+            Err(RenderError::Unsupported {
+                dialect: "ANSI",
+                construct: "SUBSTR_INDEX".into()
+            })
+        );
+        assert_eq!(
+            PostgreSql.json_length(r#""t0"."doc""#).unwrap(),
+            r#"CASE JSONB_TYPEOF("t0"."doc") WHEN 'array' THEN JSONB_ARRAY_LENGTH("t0"."doc") WHEN 'object' THEN (SELECT COUNT(*) FROM JSONB_OBJECT_KEYS("t0"."doc")) END"#
+        );
+    }
+
+    #[test]
+    fn test_json_paths() {
+        let keys = vec!["'tags'".to_string(), "0".to_string()];
+        assert_eq!(
+            PostgreSql.json_path(r#""t0"."doc""#, &keys, true).unwrap(),
+            r#"("t0"."doc" -> 'tags' ->> 0)"#
+        );
+        assert_eq!(
+            Sqlite.json_path(r#""t0"."doc""#, &keys, false).unwrap(),
+            r#"("t0"."doc" -> 'tags' -> 0)"#
+        );
+        assert_eq!(
+            PostgreSql.json_path(r#""t0"."doc""#, &[], true).unwrap(),
+            r#"("t0"."doc" #>> '{}')"#
+        );
+        assert_eq!(
+            Sqlite.json_path(r#""t0"."doc""#, &[], true).unwrap(),
+            r#"("t0"."doc" ->> '$')"#
+        );
+        assert_eq!(
+            Sqlite.json_path(r#""t0"."doc""#, &[], false).unwrap(),
+            r#""t0"."doc""#
+        );
+        assert_eq!(
+            Ansi.json_path(r#""t0"."doc""#, &keys, true),
+            Err(RenderError::Unsupported {
+                dialect: "ANSI",
+                construct: "JSON text extraction".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_sqlite_json_length_field_and_array_access() {
+        assert_eq!(
+            Sqlite.json_length(r#""t0"."doc""#).unwrap(),
+            r#"CASE JSON_TYPE("t0"."doc") WHEN 'array' THEN JSON_ARRAY_LENGTH("t0"."doc") WHEN 'object' THEN (SELECT COUNT(*) FROM JSON_EACH("t0"."doc")) END"#
+        );
+        assert_eq!(
+            Sqlite
+                .get_field(r#""t0"."address""#, r#"it's "here""#)
+                .unwrap(),
+            r#"("t0"."address" -> '$."it''s \"here\""')"#
+        );
+        assert_eq!(
+            PostgreSql.get_field(r#""t0"."address""#, "city").unwrap(),
+            r#"("t0"."address")."city""#
+        );
+        // SQLite arrays are JSON, which is indexed from zero
+        assert_eq!(
+            Sqlite.array_element(r#""t0"."tags""#, 1).unwrap(),
+            r#"("t0"."tags" -> 0)"#
+        );
+        assert_eq!(Sqlite.array_element(r#""t0"."tags""#, 0).unwrap(), "NULL");
+        assert_eq!(
+            PostgreSql.array_element(r#""t0"."tags""#, 1).unwrap(),
+            r#"("t0"."tags")[1]"#
+        );
+    }
+}
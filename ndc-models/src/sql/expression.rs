@@ -0,0 +1,607 @@
+use crate::{CastType, NullsSort, OrderDirection, RelationalExpression, Sort};
+
+use super::literal::literal;
+use super::{Dialect, RenderError};
+
+/// The clause in which an expression appears, which determines whether it may
+/// use aggregate and window functions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Context {
+    Scalar,
+    /// The aggregates of an `Aggregate` relation
+    Aggregate,
+    /// The expressions of a `Window` relation
+    Window,
+}
+
+/// The input columns and context of an expression
+pub(super) struct Scope<'a> {
+    pub dialect: &'a dyn Dialect,
+    /// The rendered expression for each input column
+    pub columns: &'a [String],
+    pub context: Context,
+}
+
+type Expr = RelationalExpression;
+
+impl Scope<'_> {
+    /// The scope for the arguments of an aggregate or window function
+    fn inner(&self) -> Scope<'_> {
+        Scope {
+            dialect: self.dialect,
+            columns: self.columns,
+            context: Context::Scalar,
+        }
+    }
+
+    pub fn exprs<'e>(
+        &self,
+        exprs: impl IntoIterator<Item = &'e Expr>,
+    ) -> Result<Vec<String>, RenderError> {
+        exprs.into_iter().map(|expr| self.expr(expr)).collect()
+    }
+
+    pub fn sorts<'e>(
+        &self,
+        sorts: impl IntoIterator<Item = &'e Sort>,
+    ) -> Result<Vec<String>, RenderError> {
+        sorts
+            .into_iter()
+            .map(|sort| {
+                let direction = match sort.direction {
+                    OrderDirection::Asc => "ASC",
+                    OrderDirection::Desc => "DESC",
+                };
+                let nulls = match sort.nulls_sort {
+                    NullsSort::NullsFirst => "NULLS FIRST",
+                    NullsSort::NullsLast => "NULLS LAST",
+                };
+                Ok(format!("{} {direction} {nulls}", self.expr(&sort.expr)?))
+            })
+            .collect()
+    }
+
+    fn binary(&self, left: &Expr, operator: &str, right: &Expr) -> Result<String, RenderError> {
+        Ok(format!(
+            "({} {operator} {})",
+            self.expr(left)?,
+            self.expr(right)?
+        ))
+    }
+
+    fn postfix(&self, expr: &Expr, operator: &str) -> Result<String, RenderError> {
+        Ok(format!("({} {operator})", self.expr(expr)?))
+    }
+
+    fn function<'e>(
+        &self,
+        name: &str,
+        args: impl IntoIterator<Item = &'e Expr>,
+    ) -> Result<String, RenderError> {
+        self.dialect.function(name, self.exprs(args)?)
+    }
+
+    fn in_list(&self, expr: &Expr, list: &[Expr], negated: bool) -> Result<String, RenderError> {
+        if list.is_empty() {
+            return Ok(if negated { "TRUE" } else { "FALSE" }.into());
+        }
+        let not = if negated { "NOT " } else { "" };
+        Ok(format!(
+            "({} {not}IN ({}))",
+            self.expr(expr)?,
+            self.exprs(list)?.join(", ")
+        ))
+    }
+
+    fn between(
+        &self,
+        low: &Expr,
+        expr: &Expr,
+        high: &Expr,
+        negated: bool,
+    ) -> Result<String, RenderError> {
+        let not = if negated { "NOT " } else { "" };
+        Ok(format!(
+            "({} {not}BETWEEN {} AND {})",
+            self.expr(expr)?,
+            self.expr(low)?,
+            self.expr(high)?
+        ))
+    }
+
+    fn json_path(&self, json: &Expr, keys: &[Expr], as_text: bool) -> Result<String, RenderError> {
+        self.dialect
+            .json_path(&self.expr(json)?, &self.exprs(keys)?, as_text)
+    }
+
+    fn aggregate(
+        &self,
+        name: &str,
+        expr: &Expr,
+        distinct: bool,
+        order_by: Option<&[Sort]>,
+    ) -> Result<String, RenderError> {
+        self.aggregate_with(name, expr, vec![], distinct, order_by)
+    }
+
+    /// An aggregate function, with extra arguments after the aggregated expression
+    fn aggregate_with(
+        &self,
+        name: &str,
+        expr: &Expr,
+        extra_args: Vec<String>,
+        distinct: bool,
+        order_by: Option<&[Sort]>,
+    ) -> Result<String, RenderError> {
+        let inner = self.inner();
+        let mut args = vec![inner.expr(expr)?];
+        args.extend(extra_args);
+        let order_by = inner.sorts(order_by.unwrap_or_default())?;
+        match self.context {
+            Context::Scalar => Err(RenderError::InvalidExpression(format!(
+                "aggregate function {name} used outside of an aggregation or window"
+            ))),
+            Context::Aggregate => self.dialect.aggregate(name, args, distinct, &order_by),
+            Context::Window if name == "FIRST_VALUE" || name == "LAST_VALUE" => {
+                let order_by = if order_by.is_empty() {
+                    String::new()
+                } else {
+                    format!("ORDER BY {} ", order_by.join(", "))
+                };
+                Ok(format!(
+                    "{name}({}) OVER ({order_by}ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING)",
+                    args.join(", ")
+                ))
+            }
+            Context::Window if distinct || !order_by.is_empty() => Err(self
+                .dialect
+                .unsupported("distinct or ordered aggregates over windows")),
+            Context::Window => Ok(format!(
+                "{} OVER ()",
+                self.dialect.aggregate(name, args, false, &[])?
+            )),
+        }
+    }
+
+    fn percentile(&self, expr: &Expr, percentile: f64) -> Result<String, RenderError> {
+        match self.context {
+            Context::Scalar => Err(RenderError::InvalidExpression(
+                "percentile used outside of an aggregation".into(),
+            )),
+            Context::Aggregate => self
+                .dialect
+                .percentile(&self.inner().expr(expr)?, percentile),
+            Context::Window => Err(self.dialect.unsupported("percentiles over windows")),
+        }
+    }
+
+    fn window(
+        &self,
+        name: &str,
+        args: &[String],
+        partition_by: &[Expr],
+        order_by: &[Sort],
+    ) -> Result<String, RenderError> {
+        if self.context != Context::Window {
+            return Err(RenderError::InvalidExpression(format!(
+                "window function {name} used outside of a window"
+            )));
+        }
+        let inner = self.inner();
+        let mut clauses = vec![];
+        if !partition_by.is_empty() {
+            clauses.push(format!(
+                "PARTITION BY {}",
+                inner.exprs(partition_by)?.join(", ")
+            ));
+        }
+        if !order_by.is_empty() {
+            clauses.push(format!("ORDER BY {}", inner.sorts(order_by)?.join(", ")));
+        }
+        Ok(format!(
+            "{name}({}) OVER ({})",
+            args.join(", "),
+            clauses.join(" ")
+        ))
+    }
+
+    #[allow(clippy::too_many_lines)]
+    pub fn expr(&self, expr: &Expr) -> Result<String, RenderError> {
+        match expr {
+            Expr::Literal { literal: value } => literal(self.dialect, value),
+            Expr::Column { index } => usize::try_from(*index)
+                .ok()
+                .and_then(|column| self.columns.get(column))
+                .cloned()
+                .ok_or(RenderError::ColumnOutOfRange(*index)),
+            Expr::Case {
+                scrutinee,
+                when,
+                default,
+            } => {
+                if when.is_empty() {
+                    return default
+                        .as_deref()
+                        .map_or_else(|| Ok("NULL".into()), |default| self.expr(default));
+                }
+                let mut parts = vec!["CASE".to_string()];
+                if let Some(scrutinee) = scrutinee {
+                    parts.push(self.expr(scrutinee)?);
+                }
+                for case in when {
+                    parts.push(format!(
+                        "WHEN {} THEN {}",
+                        self.expr(&case.when)?,
+                        self.expr(&case.then)?
+                    ));
+                }
+                if let Some(default) = default {
+                    parts.push(format!("ELSE {}", self.expr(default)?));
+                }
+                parts.push("END".into());
+                Ok(parts.join(" "))
+            }
+
+            // Logical operators
+            Expr::And { left, right } => self.binary(left, "AND", right),
+            Expr::Or { left, right } => self.binary(left, "OR", right),
+            Expr::Not { expr } => Ok(format!("(NOT {})", self.expr(expr)?)),
+
+            // Comparison operators
+            Expr::Eq { left, right } => self.binary(left, "=", right),
+            Expr::NotEq { left, right } => self.binary(left, "<>", right),
+            Expr::IsDistinctFrom { left, right } => {
+                Ok(self
+                    .dialect
+                    .is_distinct_from(&self.expr(left)?, &self.expr(right)?, false))
+            }
+            Expr::IsNotDistinctFrom { left, right } => {
+                Ok(self
+                    .dialect
+                    .is_distinct_from(&self.expr(left)?, &self.expr(right)?, true))
+            }
+            Expr::Lt { left, right } => self.binary(left, "<", right),
+            Expr::LtEq { left, right } => self.binary(left, "<=", right),
+            Expr::Gt { left, right } => self.binary(left, ">", right),
+            Expr::GtEq { left, right } => self.binary(left, ">=", right),
+            Expr::IsNotNull { expr } => self.postfix(expr, "IS NOT NULL"),
+            Expr::IsNull { expr } => self.postfix(expr, "IS NULL"),
+            Expr::IsTrue { expr } => self.postfix(expr, "IS TRUE"),
+            Expr::IsFalse { expr } => self.postfix(expr, "IS FALSE"),
+            Expr::IsNotTrue { expr } => self.postfix(expr, "IS NOT TRUE"),
+            Expr::IsNotFalse { expr } => self.postfix(expr, "IS NOT FALSE"),
+            Expr::In { expr, list } => self.in_list(expr, list, false),
+            Expr::NotIn { expr, list } => self.in_list(expr, list, true),
+            Expr::Like { expr, pattern } => self.binary(expr, "LIKE", pattern),
+            Expr::NotLike { expr, pattern } => self.binary(expr, "NOT LIKE", pattern),
+            Expr::ILike { expr, pattern } => {
+                Ok(self
+                    .dialect
+                    .ilike(&self.expr(expr)?, &self.expr(pattern)?, false))
+            }
+            Expr::NotILike { expr, pattern } => {
+                Ok(self
+                    .dialect
+                    .ilike(&self.expr(expr)?, &self.expr(pattern)?, true))
+            }
+            Expr::Between { low, expr, high } => self.between(low, expr, high, false),
+            Expr::NotBetween { low, expr, high } => self.between(low, expr, high, true),
+            Expr::Contains { str, search_str } => Ok(format!(
+                "({} > 0)",
+                self.function("STRPOS", [&**str, &**search_str])?
+            )),
+            Expr::IsNaN { expr } => Ok(self.dialect.is_nan(&self.expr(expr)?)),
+            Expr::IsZero { expr } => self.postfix(expr, "= 0"),
+
+            // Arithmetic operators
+            Expr::Plus { left, right } => self.binary(left, "+", right),
+            Expr::Minus { left, right } => self.binary(left, "-", right),
+            Expr::Multiply { left, right } => self.binary(left, "*", right),
+            Expr::Divide { left, right } => self.binary(left, "/", right),
+            Expr::Modulo { left, right } => {
+                Ok(self.dialect.modulo(&self.expr(left)?, &self.expr(right)?))
+            }
+            Expr::Negate { expr } => Ok(format!("(- {})", self.expr(expr)?)),
+
+            // Scalar functions
+            Expr::Cast { expr, as_type, .. } => self.dialect.cast(&self.expr(expr)?, *as_type),
+            Expr::TryCast { expr, as_type, .. } => {
+                self.dialect.try_cast(&self.expr(expr)?, *as_type)
+            }
+            Expr::Abs { expr } => self.function("ABS", [&**expr]),
+            Expr::ArrayElement { column, index } => {
+                self.dialect.array_element(&self.expr(column)?, *index)
+            }
+            Expr::BTrim { str, trim_str } => {
+                self.function("BTRIM", std::iter::once(&**str).chain(trim_str.as_deref()))
+            }
+            Expr::Ceil { expr } => self.function("CEIL", [&**expr]),
+            Expr::CharacterLength { str } => self.function("CHARACTER_LENGTH", [&**str]),
+            Expr::Coalesce { exprs } if exprs.is_empty() => Ok("NULL".into()),
+            Expr::Coalesce { exprs } => self.function("COALESCE", exprs),
+            Expr::Concat { exprs } if exprs.is_empty() => Ok(self.dialect.quote_string("")),
+            Expr::Concat { exprs } => self.function("CONCAT", exprs),
+            Expr::Cos { expr } => self.function("COS", [&**expr]),
+            Expr::CurrentDate => Ok("CURRENT_DATE".into()),
+            Expr::CurrentTime => Ok("CURRENT_TIME".into()),
+            Expr::CurrentTimestamp => Ok("CURRENT_TIMESTAMP".into()),
+            Expr::DatePart { expr, part } => self.dialect.date_part(&self.expr(expr)?, *part),
+            Expr::DateTrunc { expr, part } => self.function("DATE_TRUNC", [&**part, &**expr]),
+            Expr::Exp { expr } => self.function("EXP", [&**expr]),
+            Expr::Floor { expr } => self.function("FLOOR", [&**expr]),
+            Expr::GetField { column, field } => self.dialect.get_field(&self.expr(column)?, field),
+            Expr::Greatest { exprs } => self.function("GREATEST", exprs),
+            Expr::Least { exprs } => self.function("LEAST", exprs),
+            Expr::Left { str, n } => self.function("LEFT", [&**str, &**n]),
+            Expr::Ln { expr } => self.function("LN", [&**expr]),
+            Expr::Log {
+                expr,
+                base: Some(base),
+            } => self.function("LOG", [&**base, &**expr]),
+            Expr::Log { expr, base: None } | Expr::Log10 { expr } => {
+                self.function("LOG10", [&**expr])
+            }
+            Expr::Log2 { expr } => self
+                .dialect
+                .function("LOG", vec!["2".into(), self.expr(expr)?]),
+            Expr::LPad {
+                str,
+                n,
+                padding_str,
+            } => self.function(
+                "LPAD",
+                [&**str, &**n].into_iter().chain(padding_str.as_deref()),
+            ),
+            Expr::LTrim { str, trim_str } => {
+                self.function("LTRIM", std::iter::once(&**str).chain(trim_str.as_deref()))
+            }
+            Expr::NullIf { expr1, expr2 } => self.function("NULLIF", [&**expr1, &**expr2]),
+            Expr::Nvl { expr1, expr2 } => self.function("COALESCE", [&**expr1, &**expr2]),
+            Expr::Power { base, exp } => self.function("POWER", [&**base, &**exp]),
+            Expr::Random => self.dialect.function("RANDOM", vec![]),
+            Expr::Replace {
+                str,
+                substr,
+                replacement,
+            } => self.function("REPLACE", [&**str, &**substr, &**replacement]),
+            Expr::Reverse { str } => self.function("REVERSE", [&**str]),
+            Expr::Right { str, n } => self.function("RIGHT", [&**str, &**n]),
+            Expr::Round { expr, prec } => {
+                self.function("ROUND", std::iter::once(&**expr).chain(prec.as_deref()))
+            }
+            Expr::RPad {
+                str,
+                n,
+                padding_str,
+            } => self.function(
+                "RPAD",
+                [&**str, &**n].into_iter().chain(padding_str.as_deref()),
+            ),
+            Expr::RTrim { str, trim_str } => {
+                self.function("RTRIM", std::iter::once(&**str).chain(trim_str.as_deref()))
+            }
+            Expr::Sqrt { expr } => self.function("SQRT", [&**expr]),
+            Expr::StrPos { str, substr } => self.function("STRPOS", [&**str, &**substr]),
+            Expr::Substr {
+                str,
+                start_pos,
+                len,
+            } => self.function(
+                "SUBSTR",
+                [&**str, &**start_pos].into_iter().chain(len.as_deref()),
+            ),
+            Expr::SubstrIndex { str, delim, count } => {
+                self.function("SUBSTR_INDEX", [&**str, &**delim, &**count])
+            }
+            Expr::Tan { expr } => self.function("TAN", [&**expr]),
+            Expr::ToDate { expr } => self.dialect.cast(&self.expr(expr)?, CastType::Date),
+            Expr::ToTimestamp { expr } => self.dialect.cast(&self.expr(expr)?, CastType::Timestamp),
+            Expr::Trunc { expr, prec } => {
+                self.function("TRUNC", std::iter::once(&**expr).chain(prec.as_deref()))
+            }
+            Expr::ToLower { expr } => self.function("LOWER", [&**expr]),
+            Expr::ToUpper { expr } => self.function("UPPER", [&**expr]),
+            Expr::BinaryConcat { left, right } => self.binary(left, "||", right),
+
+            // JSON functions
+            Expr::JsonContains { json, keys } => Ok(format!(
+                "({} IS NOT NULL)",
+                self.json_path(json, keys, false)?
+            )),
+            Expr::JsonGet { json, keys } => self.json_path(json, keys, false),
+            Expr::JsonGetStr { json, keys } | Expr::JsonAsText { json, keys } => {
+                self.json_path(json, keys, true)
+            }
+            Expr::JsonGetInt { json, keys } => self
+                .dialect
+                .cast(&self.json_path(json, keys, true)?, CastType::Int64),
+            Expr::JsonGetFloat { json, keys } => self
+                .dialect
+                .cast(&self.json_path(json, keys, true)?, CastType::Float64),
+            Expr::JsonGetBool { json, keys } => self
+                .dialect
+                .cast(&self.json_path(json, keys, true)?, CastType::Boolean),
+            Expr::JsonGetJson { json, keys } => self
+                .dialect
+                .cast(&self.json_path(json, keys, false)?, CastType::Utf8),
+            Expr::JsonLength { json, keys } => self
+                .dialect
+                .json_length(&self.json_path(json, keys, false)?),
+
+            // Aggregate functions
+            Expr::Average { expr } => self.aggregate("AVG", expr, false, None),
+            Expr::BoolAnd { expr } => self.aggregate("BOOL_AND", expr, false, None),
+            Expr::BoolOr { expr } => self.aggregate("BOOL_OR", expr, false, None),
+            Expr::Count { expr, distinct } => self.aggregate("COUNT", expr, *distinct, None),
+            Expr::FirstValue { expr, order_by } => {
+                self.aggregate("FIRST_VALUE", expr, false, order_by.as_deref())
+            }
+            Expr::LastValue { expr, order_by } => {
+                self.aggregate("LAST_VALUE", expr, false, order_by.as_deref())
+            }
+            Expr::Max { expr } => self.aggregate("MAX", expr, false, None),
+            Expr::Median { expr } => self.percentile(expr, 0.5),
+            Expr::Min { expr } => self.aggregate("MIN", expr, false, None),
+            Expr::StringAgg {
+                expr,
+                separator,
+                distinct,
+                order_by,
+            } => self.aggregate_with(
+                "STRING_AGG",
+                expr,
+                vec![self.dialect.quote_string(separator)],
+                *distinct,
+                order_by.as_deref(),
+            ),
+            Expr::Sum { expr } => self.aggregate("SUM", expr, false, None),
+            Expr::Var { expr } => self.aggregate("VAR_SAMP", expr, false, None),
+            Expr::Stddev { expr } => self.aggregate("STDDEV_SAMP", expr, false, None),
+            Expr::StddevPop { expr } => self.aggregate("STDDEV_POP", expr, false, None),
+            Expr::ApproxPercentileCont { expr, percentile } => self.percentile(expr, percentile.0),
+            Expr::ArrayAgg {
+                expr,
+                distinct,
+                order_by,
+            } => self.aggregate("ARRAY_AGG", expr, *distinct, order_by.as_deref()),
+            Expr::ApproxDistinct { expr } => self.aggregate("COUNT", expr, true, None),
+
+            // Window functions
+            Expr::RowNumber {
+                order_by,
+                partition_by,
+            } => self.window("ROW_NUMBER", &[], partition_by, order_by),
+            Expr::DenseRank {
+                order_by,
+                partition_by,
+            } => self.window("DENSE_RANK", &[], partition_by, order_by),
+            Expr::NTile {
+                order_by,
+                partition_by,
+                n,
+            } => self.window("NTILE", &[n.to_string()], partition_by, order_by),
+            Expr::Rank {
+                order_by,
+                partition_by,
+            } => self.window("RANK", &[], partition_by, order_by),
+            Expr::CumeDist {
+                order_by,
+                partition_by,
+            } => self.window("CUME_DIST", &[], partition_by, order_by),
+            Expr::PercentRank {
+                order_by,
+                partition_by,
+            } => self.window("PERCENT_RANK", &[], partition_by, order_by),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::{Ansi, PostgreSql, Sqlite};
+
+    fn expr(value: serde_json::Value) -> Expr {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn column(index: u64) -> serde_json::Value {
+        serde_json::json!({ "type": "column", "index": index })
+    }
+
+    fn string(value: &str) -> serde_json::Value {
+        serde_json::json!({ "type": "literal", "literal": { "type": "String", "value": value } })
+    }
+
+    fn render(dialect: &dyn Dialect, context: Context, expr: &Expr) -> Result<String, RenderError> {
+        let columns = [r#""t0"."id""#.to_string(), r#""t0"."title""#.to_string()];
+        Scope {
+            dialect,
+            columns: &columns,
+            context,
+        }
+        .expr(expr)
+    }
+
+    #[test]
+    fn test_case() {
+        let searched = expr(serde_json::json!({
+            "type": "case",
+            "when": [{
+                "when": { "type": "is_null", "expr": column(1) },
+                "then": string("untitled"),
+            }],
+            "default": column(1),
+        }));
+        let simple = expr(serde_json::json!({
+            "type": "case",
+            "scrutinee": column(0),
+            "when": [
+                { "when": column(0), "then": string("first") },
+                { "when": column(0), "then": string("second") },
+            ],
+        }));
+        for dialect in [&PostgreSql as &dyn Dialect, &Sqlite, &Ansi] {
+            assert_eq!(
+                render(dialect, Context::Scalar, &searched).unwrap(),
+                r#"CASE WHEN ("t0"."title" IS NULL) THEN 'untitled' ELSE "t0"."title" END"#
+            );
+            assert_eq!(
+                render(dialect, Context::Scalar, &simple).unwrap(),
+                r#"CASE "t0"."id" WHEN "t0"."id" THEN 'first' WHEN "t0"."id" THEN 'second' END"#
+            );
+        }
+
+        // A CASE without any branches is just its default
+        let empty = expr(serde_json::json!({ "type": "case", "when": [], "default": column(0) }));
+        assert_eq!(
+            render(&Sqlite, Context::Scalar, &empty).unwrap(),
+            r#""t0"."id""#
+        );
+        let empty = expr(serde_json::json!({ "type": "case", "when": [] }));
+        assert_eq!(render(&Sqlite, Context::Scalar, &empty).unwrap(), "NULL");
+    }
+
+    #[test]
+    fn test_first_and_last_value_over_windows() {
+        let first_value = expr(serde_json::json!({
+            "type": "first_value",
+            "expr": column(1),
+            "order_by": [{
+                "expr": column(0),
+                "direction": "asc",
+                "nulls_sort": "nulls_last",
+            }],
+        }));
+        let last_value = expr(serde_json::json!({ "type": "last_value", "expr": column(1) }));
+
+        // Window frames are standard, so every dialect renders them the same
+        for dialect in [&PostgreSql as &dyn Dialect, &Sqlite, &Ansi] {
+            assert_eq!(
+                render(dialect, Context::Window, &first_value).unwrap(),
+                r#"FIRST_VALUE("t0"."title") OVER (ORDER BY "t0"."id" ASC NULLS LAST ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING)"#
+            );
+            assert_eq!(
+                render(dialect, Context::Window, &last_value).unwrap(),
+                r#"LAST_VALUE("t0"."title") OVER (ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING)"#
+            );
+        }
+
+        // As aggregates, they depend on the dialect
+        assert_eq!(
+            render(&Sqlite, Context::Aggregate, &first_value).unwrap(),
+            r#"(JSON_GROUP_ARRAY("t0"."title" ORDER BY "t0"."id" ASC NULLS LAST) ->> '$[0]')"#
+        );
+        assert_eq!(
+            render(&Ansi, Context::Aggregate, &last_value),
+            Err(RenderError::Unsupported {
+                dialect: "ANSI",
+                construct: "LAST_VALUE".into()
+            })
+        );
+        assert!(matches!(
+            render(&PostgreSql, Context::Scalar, &first_value),
+            Err(RenderError::InvalidExpression(_))
+        ));
+    }
+}
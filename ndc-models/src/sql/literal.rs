@@ -0,0 +1,243 @@
+use crate::{calendar, RelationalLiteral};
+
+use super::{Dialect, RenderError};
+
+const NANOSECONDS_PER_SECOND: i128 = 1_000_000_000;
+const NANOSECONDS_PER_DAY: i128 = 86_400 * NANOSECONDS_PER_SECOND;
+
+pub(super) fn literal(
+    dialect: &dyn Dialect,
+    literal: &RelationalLiteral,
+) -> Result<String, RenderError> {
+    match literal {
+        RelationalLiteral::Null => Ok("NULL".into()),
+        RelationalLiteral::Boolean { value } => Ok(if *value { "TRUE" } else { "FALSE" }.into()),
+        RelationalLiteral::String { value } => Ok(dialect.quote_string(value)),
+        RelationalLiteral::Int8 { value } => Ok(value.to_string()),
+        RelationalLiteral::Int16 { value } => Ok(value.to_string()),
+        RelationalLiteral::Int32 { value } => Ok(value.to_string()),
+        RelationalLiteral::Int64 { value } => Ok(value.to_string()),
+        RelationalLiteral::UInt8 { value } => Ok(value.to_string()),
+        RelationalLiteral::UInt16 { value } => Ok(value.to_string()),
+        RelationalLiteral::UInt32 { value } => Ok(value.to_string()),
+        RelationalLiteral::UInt64 { value } => Ok(value.to_string()),
+        RelationalLiteral::Float32 { value } => float(dialect, f64::from(value.0)),
+        RelationalLiteral::Float64 { value } => float(dialect, value.0),
+        RelationalLiteral::Decimal128 { value, scale, .. } => {
+            Ok(decimal(&value.to_string(), *scale))
+        }
+        RelationalLiteral::Decimal256 { value, scale, .. } => {
+            let digits = value.strip_prefix('-').unwrap_or(value);
+            if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+                return Err(RenderError::InvalidLiteral(format!(
+                    "{value} is not a decimal integer"
+                )));
+            }
+            Ok(decimal(value, *scale))
+        }
+        RelationalLiteral::Date32 { value } => Ok(dialect.date_literal(&date(i128::from(*value))?)),
+        RelationalLiteral::Date64 { value } => {
+            Ok(dialect.date_literal(&date(i128::from(*value).div_euclid(86_400_000))?))
+        }
+        RelationalLiteral::Time32Second { value } => {
+            time_literal(dialect, i128::from(*value) * NANOSECONDS_PER_SECOND)
+        }
+        RelationalLiteral::Time32Millisecond { value } => {
+            time_literal(dialect, i128::from(*value) * 1_000_000)
+        }
+        RelationalLiteral::Time64Microsecond { value } => {
+            time_literal(dialect, i128::from(*value) * 1_000)
+        }
+        RelationalLiteral::Time64Nanosecond { value } => time_literal(dialect, i128::from(*value)),
+        RelationalLiteral::TimestampSecond { value } => {
+            timestamp_literal(dialect, i128::from(*value) * NANOSECONDS_PER_SECOND)
+        }
+        RelationalLiteral::TimestampMillisecond { value } => {
+            timestamp_literal(dialect, i128::from(*value) * 1_000_000)
+        }
+        RelationalLiteral::TimestampMicrosecond { value } => {
+            timestamp_literal(dialect, i128::from(*value) * 1_000)
+        }
+        RelationalLiteral::TimestampNanosecond { value } => {
+            timestamp_literal(dialect, i128::from(*value))
+        }
+        RelationalLiteral::DurationSecond { value } => {
+            dialect.interval_literal(0, 0, i128::from(*value) * NANOSECONDS_PER_SECOND)
+        }
+        RelationalLiteral::DurationMillisecond { value } => {
+            dialect.interval_literal(0, 0, i128::from(*value) * 1_000_000)
+        }
+        RelationalLiteral::DurationMicrosecond { value } => {
+            dialect.interval_literal(0, 0, i128::from(*value) * 1_000)
+        }
+        RelationalLiteral::DurationNanosecond { value } => {
+            dialect.interval_literal(0, 0, i128::from(*value))
+        }
+        RelationalLiteral::Interval {
+            months,
+            days,
+            nanoseconds,
+        } => dialect.interval_literal(*months, *days, i128::from(*nanoseconds)),
+    }
+}
+
+fn float(dialect: &dyn Dialect, value: f64) -> Result<String, RenderError> {
+    if value.is_finite() {
+        Ok(format!("{value:?}"))
+    } else {
+        Err(dialect.unsupported("non-finite floating point literals"))
+    }
+}
+
+/// Format an integer number of units of `10^-scale` as a decimal number
+fn decimal(value: &str, scale: i8) -> String {
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", value),
+    };
+    let shift = usize::from(scale.unsigned_abs());
+    if scale <= 0 {
+        let zeros = if digits.bytes().all(|byte| byte == b'0') {
+            0
+        } else {
+            shift
+        };
+        format!("{sign}{digits}{}", "0".repeat(zeros))
+    } else {
+        let digits = format!("{digits:0>width$}", width = shift + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - shift);
+        format!("{sign}{whole}.{fraction}")
+    }
+}
+
+fn time_literal(dialect: &dyn Dialect, nanoseconds: i128) -> Result<String, RenderError> {
+    if (0..NANOSECONDS_PER_DAY).contains(&nanoseconds) {
+        Ok(dialect.time_literal(&time(nanoseconds)))
+    } else {
+        Err(RenderError::InvalidLiteral(format!(
+            "{nanoseconds}ns is not a time of day"
+        )))
+    }
+}
+
+fn timestamp_literal(dialect: &dyn Dialect, nanoseconds: i128) -> Result<String, RenderError> {
+    let date = date(nanoseconds.div_euclid(NANOSECONDS_PER_DAY))?;
+    let time = time(nanoseconds.rem_euclid(NANOSECONDS_PER_DAY));
+    Ok(dialect.timestamp_literal(&format!("{date} {time}")))
+}
+
+/// Format a number of days since 1970-01-01 as `YYYY-MM-DD`
+fn date(days: i128) -> Result<String, RenderError> {
+    i64::try_from(days)
+        .ok()
+        .and_then(calendar::format_date)
+        .ok_or_else(|| {
+            RenderError::InvalidLiteral(format!("{days} days since 1970-01-01 is out of range"))
+        })
+}
+
+/// Format a number of nanoseconds since midnight as `HH:MM:SS[.fffffffff]`
+fn time(nanoseconds: i128) -> String {
+    // Callers check that the time is within a single day
+    calendar::format_time(i64::try_from(nanoseconds).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::{Ansi, PostgreSql, Sqlite};
+
+    #[test]
+    fn test_string_escaping() {
+        let value = RelationalLiteral::String {
+            value: r#"it's a "quote" \ 'twice'"#.into(),
+        };
+        for dialect in [&PostgreSql as &dyn Dialect, &Sqlite, &Ansi] {
+            assert_eq!(
+                literal(dialect, &value).unwrap(),
+                r#"'it''s a "quote" \ ''twice'''"#
+            );
+        }
+    }
+
+    #[test]
+    fn test_temporal_literals() {
+        let time = RelationalLiteral::Time64Microsecond {
+            value: 45_296_500_000,
+        };
+        let timestamp = RelationalLiteral::TimestampSecond { value: 0 };
+        assert_eq!(literal(&PostgreSql, &time).unwrap(), "TIME '12:34:56.5'");
+        assert_eq!(literal(&Sqlite, &time).unwrap(), "'12:34:56.5'");
+        assert_eq!(
+            literal(&Ansi, &timestamp).unwrap(),
+            "TIMESTAMP '1970-01-01 00:00:00'"
+        );
+        assert_eq!(
+            literal(&Sqlite, &timestamp).unwrap(),
+            "'1970-01-01 00:00:00'"
+        );
+        assert_eq!(
+            literal(&Sqlite, &RelationalLiteral::Date32 { value: -1 }).unwrap(),
+            "'1969-12-31'"
+        );
+        assert_eq!(
+            literal(&Sqlite, &RelationalLiteral::DurationSecond { value: 1 }),
+            Err(RenderError::Unsupported {
+                dialect: "SQLite",
+                construct: "intervals".into()
+            })
+        );
+        assert_eq!(
+            literal(
+                &Ansi,
+                &RelationalLiteral::Interval {
+                    months: 1,
+                    days: 1,
+                    nanoseconds: 0
+                }
+            ),
+            Err(RenderError::Unsupported {
+                dialect: "ANSI",
+                construct: "mixed intervals".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_literals() {
+        assert!(matches!(
+            literal(
+                &PostgreSql,
+                &RelationalLiteral::Time32Second { value: 86_400 }
+            ),
+            Err(RenderError::InvalidLiteral(_))
+        ));
+        assert!(matches!(
+            literal(&PostgreSql, &RelationalLiteral::Date32 { value: i32::MAX }),
+            Err(RenderError::InvalidLiteral(_))
+        ));
+        assert!(matches!(
+            literal(
+                &PostgreSql,
+                &RelationalLiteral::Decimal256 {
+                    value: "1; DROP TABLE articles".into(),
+                    scale: 0,
+                    prec: 10
+                }
+            ),
+            Err(RenderError::InvalidLiteral(_))
+        ));
+        assert_eq!(
+            literal(
+                &Sqlite,
+                &RelationalLiteral::Float64 {
+                    value: crate::Float64(f64::NAN)
+                }
+            ),
+            Err(RenderError::Unsupported {
+                dialect: "SQLite",
+                construct: "non-finite floating point literals".into()
+            })
+        );
+    }
+}
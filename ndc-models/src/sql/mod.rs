@@ -0,0 +1,819 @@
+//! Rendering of relational query plans as SQL text.
+//!
+//! [`render`] translates a [`RelationalQuery`] into a single `SELECT` statement,
+//! whose output columns are named `c0`, `c1`, and so on, in the order of the
+//! columns of the root relation. Adjacent relations are merged into a single
+//! `SELECT` wherever that does not change their meaning, and are otherwise
+//! nested as subqueries.
+//!
+//! The differences between SQL dialects are captured by the [`Dialect`] trait,
+//! which is implemented for [`PostgreSql`], [`Sqlite`] and standard SQL
+//! ([`Ansi`]).
+//!
+//! This module is only available with the `sql` feature.
+//!
+//! ```
+//! use ndc_models::sql::{render_relation, PostgreSql};
+//! use ndc_models::{NullsSort, OrderDirection, Relation, RelationalExpression, Sort};
+//!
+//! let relation = Relation::Sort {
+//!     input: Relation::From {
+//!         collection: "articles".into(),
+//!         columns: vec!["id".into(), "title".into()],
+//!         arguments: Default::default(),
+//!     }
+//!     .into(),
+//!     exprs: vec![Sort {
+//!         expr: RelationalExpression::Column { index: 1 },
+//!         direction: OrderDirection::Asc,
+//!         nulls_sort: NullsSort::NullsLast,
+//!     }],
+//! };
+//!
+//! assert_eq!(
+//!     render_relation(&relation, &PostgreSql).unwrap(),
+//!     r#"SELECT "t0"."id" AS "c0", "t0"."title" AS "c1" FROM "articles" AS "t0" ORDER BY "t0"."title" ASC NULLS LAST"#
+//! );
+//! ```
+
+mod dialect;
+mod expression;
+mod literal;
+
+pub use dialect::*;
+
+use crate::optimize::is_volatile;
+#[cfg(test)]
+use crate::RelationalLiteral;
+use crate::{JoinOn, JoinType, Relation, RelationalExpression, RelationalQuery};
+
+use expression::{Context, Scope};
+
+/// An error encountered while rendering a relational query as SQL
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum RenderError {
+    #[error("{construct} is not supported by the {dialect} dialect")]
+    Unsupported {
+        dialect: &'static str,
+        construct: String,
+    },
+    #[error("column index {0} is out of range")]
+    ColumnOutOfRange(u64),
+    #[error("invalid relation: {0}")]
+    InvalidRelation(String),
+    #[error("invalid expression: {0}")]
+    InvalidExpression(String),
+    #[error("invalid literal: {0}")]
+    InvalidLiteral(String),
+}
+
+/// Render a relational query as a SQL `SELECT` statement
+pub fn render(query: &RelationalQuery, dialect: &dyn Dialect) -> Result<String, RenderError> {
+    render_relation(&query.root_relation, dialect)
+}
+
+/// Render a relation as a SQL `SELECT` statement
+pub fn render_relation(relation: &Relation, dialect: &dyn Dialect) -> Result<String, RenderError> {
+    let mut renderer = Renderer {
+        dialect,
+        aliases: 0,
+    };
+    let select = renderer.select(relation)?;
+    renderer.statement(select)
+}
+
+struct Renderer<'a> {
+    dialect: &'a dyn Dialect,
+    /// The number of table aliases used so far
+    aliases: usize,
+}
+
+/// A `SELECT` statement under construction.
+///
+/// The clauses are all rendered in terms of the sources in `from`, so each
+/// output column is stored as the expression which computes it.
+#[derive(Default)]
+struct Select {
+    columns: Vec<String>,
+    from: String,
+    /// Whether `from` is a join, rather than a single table or subquery
+    joined: bool,
+    filters: Vec<String>,
+    group_by: Option<Vec<String>>,
+    having: Vec<String>,
+    /// Whether any of the columns are computed by window functions
+    windowed: bool,
+    /// Whether any of the columns can produce a different value each time
+    /// they are evaluated
+    volatile: bool,
+    order_by: Vec<String>,
+    fetch: Option<u64>,
+    skip: u64,
+}
+
+impl Select {
+    fn is_paginated(&self) -> bool {
+        self.fetch.is_some() || self.skip > 0
+    }
+
+    /// Whether the statement does no more than filter the rows of its sources
+    fn is_simple(&self) -> bool {
+        self.group_by.is_none()
+            && !self.windowed
+            && self.order_by.is_empty()
+            && !self.is_paginated()
+    }
+}
+
+impl<'a> Renderer<'a> {
+    fn scope<'b>(&self, columns: &'b [String], context: Context) -> Scope<'b>
+    where
+        'a: 'b,
+    {
+        Scope {
+            dialect: self.dialect,
+            columns,
+            context,
+        }
+    }
+
+    fn alias(&mut self) -> String {
+        let alias = self.dialect.quote_identifier(&format!("t{}", self.aliases));
+        self.aliases += 1;
+        alias
+    }
+
+    fn column_name(&self, index: usize) -> String {
+        self.dialect.quote_identifier(&format!("c{index}"))
+    }
+
+    fn select(&mut self, relation: &Relation) -> Result<Select, RenderError> {
+        match relation {
+            Relation::From {
+                collection,
+                columns,
+                arguments,
+            } => {
+                if !arguments.is_empty() {
+                    return Err(self.dialect.unsupported("collection arguments"));
+                }
+                let alias = self.alias();
+                Ok(Select {
+                    columns: columns
+                        .iter()
+                        .map(|column| {
+                            format!("{alias}.{}", self.dialect.quote_identifier(column.as_str()))
+                        })
+                        .collect(),
+                    from: format!(
+                        "{} AS {alias}",
+                        self.dialect.quote_identifier(collection.as_str())
+                    ),
+                    ..Select::default()
+                })
+            }
+            Relation::Paginate { input, fetch, skip } => {
+                let select = self.select(input)?;
+                let wrap = select.is_paginated();
+                let mut select = self.wrap_if(select, wrap)?;
+                select.fetch = *fetch;
+                select.skip = *skip;
+                Ok(select)
+            }
+            Relation::Project { input, exprs } => {
+                let select = self.select(input)?;
+                let mut select = self.wrap_if(select, false)?;
+                select.columns = self.scope(&select.columns, Context::Scalar).exprs(exprs)?;
+                select.volatile = exprs.iter().any(is_volatile);
+                Ok(select)
+            }
+            Relation::Filter { input, predicate } => {
+                let select = self.select(input)?;
+                let wrap = select.windowed || select.is_paginated();
+                let mut select = self.wrap_if(select, wrap)?;
+                let predicate = self
+                    .scope(&select.columns, Context::Scalar)
+                    .expr(predicate)?;
+                if select.group_by.is_some() {
+                    select.having.push(predicate);
+                } else {
+                    select.filters.push(predicate);
+                }
+                Ok(select)
+            }
+            Relation::Sort { input, exprs } => {
+                let select = self.select(input)?;
+                let wrap = select.is_paginated();
+                let mut select = self.wrap_if(select, wrap)?;
+                let mut order_by = self
+                    .scope(&select.columns, Context::Scalar)
+                    .sorts(exprs.iter().filter(|sort| !is_literal(&sort.expr)))?;
+                // Any existing order only breaks ties
+                order_by.append(&mut select.order_by);
+                select.order_by = order_by;
+                Ok(select)
+            }
+            Relation::Join {
+                left,
+                right,
+                on,
+                join_type,
+            } => {
+                let left = self.select(left)?;
+                let right = self.select(right)?;
+                match join_type {
+                    JoinType::LeftSemi => self.semi_join(left, right, on, false),
+                    JoinType::LeftAnti => self.semi_join(left, right, on, true),
+                    JoinType::RightSemi => self.semi_join(right, left, &flip(on), false),
+                    JoinType::RightAnti => self.semi_join(right, left, &flip(on), true),
+                    JoinType::Left | JoinType::Right | JoinType::Inner | JoinType::Full => {
+                        self.join(left, right, on, *join_type)
+                    }
+                }
+            }
+            Relation::Aggregate {
+                input,
+                group_by,
+                aggregates,
+            } => {
+                let select = self.select(input)?;
+                let wrap = !select.is_simple();
+                let mut select = self.wrap_if(select, wrap)?;
+                let scope = self.scope(&select.columns, Context::Scalar);
+                let keys = scope.exprs(group_by)?;
+                let aggregates = self
+                    .scope(&select.columns, Context::Aggregate)
+                    .exprs(aggregates)?;
+                // Integer constants would be interpreted as column positions, so
+                // constant keys are omitted, since they do not affect the grouping
+                // alongside other keys. If every key is constant, one group is
+                // still produced for a non-empty input, but no groups for an empty
+                // input, unlike an aggregate without keys, so a constant key which
+                // is not a column position is used instead. Note that `GROUP BY ()`
+                // is equivalent to an aggregate without keys.
+                let mut group_by_keys: Vec<String> = group_by
+                    .iter()
+                    .zip(&keys)
+                    .filter(|(key, _)| !is_literal(key))
+                    .map(|(_, key)| key.clone())
+                    .collect();
+                if group_by_keys.is_empty() && !group_by.is_empty() {
+                    group_by_keys.push(CONSTANT_GROUP_KEY.into());
+                }
+                select.group_by = Some(group_by_keys);
+                select.columns = keys.into_iter().chain(aggregates).collect();
+                Ok(select)
+            }
+            Relation::Window { input, exprs } => {
+                let select = self.select(input)?;
+                let wrap = select.windowed || select.is_paginated();
+                let mut select = self.wrap_if(select, wrap)?;
+                let exprs = self.scope(&select.columns, Context::Window).exprs(exprs)?;
+                select.columns.extend(exprs);
+                select.windowed = true;
+                Ok(select)
+            }
+            Relation::Union { relations } => {
+                let mut branches = vec![];
+                let mut arity = None;
+                for relation in relations {
+                    let select = self.select(relation)?;
+                    // Only whole compound statements can be sorted or paginated
+                    let wrap = !select.order_by.is_empty() || select.is_paginated();
+                    let select = self.wrap_if(select, wrap)?;
+                    arity.get_or_insert(select.columns.len());
+                    branches.push(self.statement(select)?);
+                }
+                let arity =
+                    arity.ok_or_else(|| RenderError::InvalidRelation("empty union".into()))?;
+                let alias = self.alias();
+                Ok(Select {
+                    columns: (0..arity)
+                        .map(|index| format!("{alias}.{}", self.column_name(index)))
+                        .collect(),
+                    from: format!("({}) AS {alias}", branches.join(" UNION ALL ")),
+                    ..Select::default()
+                })
+            }
+        }
+    }
+
+    fn join(
+        &mut self,
+        left: Select,
+        right: Select,
+        on: &[JoinOn],
+        join_type: JoinType,
+    ) -> Result<Select, RenderError> {
+        let (keyword, left_preserved, right_preserved) = match join_type {
+            JoinType::Inner => ("INNER JOIN", false, false),
+            JoinType::Left => ("LEFT OUTER JOIN", true, false),
+            JoinType::Right => ("RIGHT OUTER JOIN", false, true),
+            _ => ("FULL OUTER JOIN", true, true),
+        };
+
+        // Filters on a side whose rows are preserved are applied after the join,
+        // and filters on the other side become part of the join condition. This
+        // is impossible when both sides are preserved.
+        let both_preserved = left_preserved && right_preserved;
+        let wrap = !left.is_simple() || (both_preserved && !left.filters.is_empty());
+        let mut left = self.wrap_if(left, wrap)?;
+        let wrap =
+            !right.is_simple() || right.joined || (both_preserved && !right.filters.is_empty());
+        let mut right = self.wrap_if(right, wrap)?;
+
+        let mut conditions = join_conditions(
+            &self.scope(&left.columns, Context::Scalar),
+            &self.scope(&right.columns, Context::Scalar),
+            on,
+        )?;
+        let mut filters = vec![];
+        for (side, preserved) in [(&mut left, left_preserved), (&mut right, right_preserved)] {
+            if preserved || join_type == JoinType::Inner {
+                filters.append(&mut side.filters);
+            } else {
+                conditions.append(&mut side.filters);
+            }
+        }
+
+        let condition = if conditions.is_empty() {
+            "TRUE".to_string()
+        } else {
+            conditions.join(" AND ")
+        };
+        Ok(Select {
+            columns: left.columns.into_iter().chain(right.columns).collect(),
+            from: format!("{} {keyword} {} ON {condition}", left.from, right.from),
+            joined: true,
+            filters,
+            ..Select::default()
+        })
+    }
+
+    /// Render a semi-join or anti-join as an `EXISTS` condition. The columns on
+    /// the left of `on` refer to `kept`, and those on the right to `other`.
+    fn semi_join(
+        &mut self,
+        kept: Select,
+        other: Select,
+        on: &[JoinOn],
+        anti: bool,
+    ) -> Result<Select, RenderError> {
+        let wrap = !kept.is_simple();
+        let mut kept = self.wrap_if(kept, wrap)?;
+        let wrap = !other.is_simple();
+        let mut other = self.wrap_if(other, wrap)?;
+
+        let mut conditions = join_conditions(
+            &self.scope(&kept.columns, Context::Scalar),
+            &self.scope(&other.columns, Context::Scalar),
+            on,
+        )?;
+        conditions.append(&mut other.filters);
+        let condition = if conditions.is_empty() {
+            "TRUE".to_string()
+        } else {
+            conditions.join(" AND ")
+        };
+        let not = if anti { "NOT " } else { "" };
+        kept.filters.push(format!(
+            "{not}EXISTS (SELECT 1 FROM {} WHERE {condition})",
+            other.from
+        ));
+        Ok(kept)
+    }
+
+    /// Nest a statement as a subquery if `wrap` is set, or if its columns cannot
+    /// safely be evaluated more than once
+    fn wrap_if(&mut self, select: Select, wrap: bool) -> Result<Select, RenderError> {
+        if !wrap && !select.volatile {
+            return Ok(select);
+        }
+        let arity = select.columns.len();
+        let statement = self.statement(select)?;
+        let alias = self.alias();
+        Ok(Select {
+            columns: (0..arity)
+                .map(|index| format!("{alias}.{}", self.column_name(index)))
+                .collect(),
+            from: format!("({statement}) AS {alias}"),
+            ..Select::default()
+        })
+    }
+
+    fn statement(&self, select: Select) -> Result<String, RenderError> {
+        if select.columns.is_empty() {
+            return Err(self.dialect.unsupported("relations without columns"));
+        }
+        let columns = select
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| format!("{column} AS {}", self.column_name(index)))
+            .collect::<Vec<_>>();
+        let mut clauses = vec![format!(
+            "SELECT {} FROM {}",
+            columns.join(", "),
+            select.from
+        )];
+        if !select.filters.is_empty() {
+            clauses.push(format!("WHERE {}", select.filters.join(" AND ")));
+        }
+        if let Some(group_by) = select.group_by.filter(|group_by| !group_by.is_empty()) {
+            clauses.push(format!("GROUP BY {}", group_by.join(", ")));
+        }
+        if !select.having.is_empty() {
+            clauses.push(format!("HAVING {}", select.having.join(" AND ")));
+        }
+        if !select.order_by.is_empty() {
+            clauses.push(format!("ORDER BY {}", select.order_by.join(", ")));
+        }
+        let pagination = self.dialect.pagination(select.fetch, select.skip);
+        if !pagination.is_empty() {
+            clauses.push(pagination);
+        }
+        Ok(clauses.join(" "))
+    }
+}
+
+/// A constant expression to group by, which is not an integer literal
+const CONSTANT_GROUP_KEY: &str = "(1 + 0)";
+
+fn is_literal(expr: &RelationalExpression) -> bool {
+    matches!(expr, RelationalExpression::Literal { .. })
+}
+
+fn flip(on: &[JoinOn]) -> Vec<JoinOn> {
+    on.iter()
+        .map(|join_on| JoinOn {
+            left: join_on.right.clone(),
+            right: join_on.left.clone(),
+        })
+        .collect()
+}
+
+fn join_conditions(
+    left: &Scope<'_>,
+    right: &Scope<'_>,
+    on: &[JoinOn],
+) -> Result<Vec<String>, RenderError> {
+    on.iter()
+        .map(|join_on| {
+            Ok(format!(
+                "({} = {})",
+                left.expr(&join_on.left)?,
+                right.expr(&join_on.right)?
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relation(value: serde_json::Value) -> Relation {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn from_articles() -> serde_json::Value {
+        serde_json::json!({
+            "type": "from",
+            "collection": "articles",
+            "columns": ["id", "title", "author_id"],
+        })
+    }
+
+    fn from_authors() -> serde_json::Value {
+        serde_json::json!({
+            "type": "from",
+            "collection": "authors",
+            "columns": ["id", "name"],
+        })
+    }
+
+    fn column(index: u64) -> serde_json::Value {
+        serde_json::json!({ "type": "column", "index": index })
+    }
+
+    fn int(value: i64) -> serde_json::Value {
+        serde_json::json!({ "type": "literal", "literal": { "type": "Int64", "value": value } })
+    }
+
+    #[test]
+    fn test_single_select() {
+        let plan = relation(serde_json::json!({
+            "type": "paginate",
+            "input": {
+                "type": "sort",
+                "input": {
+                    "type": "project",
+                    "input": {
+                        "type": "filter",
+                        "input": from_articles(),
+                        "predicate": { "type": "gt", "left": column(0), "right": int(10) },
+                    },
+                    "exprs": [
+                        column(1),
+                        { "type": "to_upper", "expr": column(1) },
+                    ],
+                },
+                "exprs": [{
+                    "expr": column(1),
+                    "direction": "desc",
+                    "nulls_sort": "nulls_first",
+                }],
+            },
+            "fetch": 10,
+            "skip": 20,
+        }));
+
+        let select = r#"SELECT "t0"."title" AS "c0", UPPER("t0"."title") AS "c1" FROM "articles" AS "t0" WHERE ("t0"."id" > 10) ORDER BY UPPER("t0"."title") DESC NULLS FIRST"#;
+        assert_eq!(
+            render_relation(&plan, &PostgreSql).unwrap(),
+            format!("{select} LIMIT 10 OFFSET 20")
+        );
+        assert_eq!(
+            render_relation(&plan, &Sqlite).unwrap(),
+            format!("{select} LIMIT 10 OFFSET 20")
+        );
+        assert_eq!(
+            render_relation(&plan, &Ansi).unwrap(),
+            format!("{select} OFFSET 20 ROWS FETCH FIRST 10 ROWS ONLY")
+        );
+    }
+
+    #[test]
+    fn test_joins() {
+        let plan = relation(serde_json::json!({
+            "type": "join",
+            "left": {
+                "type": "filter",
+                "input": from_articles(),
+                "predicate": { "type": "is_not_null", "expr": column(1) },
+            },
+            "right": {
+                "type": "filter",
+                "input": from_authors(),
+                "predicate": {
+                    "type": "i_like",
+                    "expr": column(1),
+                    "pattern": { "type": "literal", "literal": { "type": "String", "value": "%o'brien%" } },
+                },
+            },
+            "on": [{ "left": column(2), "right": column(0) }],
+            "join_type": "left",
+        }));
+
+        // The filter on the right-hand side must become part of the join condition
+        assert_eq!(
+            render_relation(&plan, &PostgreSql).unwrap(),
+            r#"SELECT "t0"."id" AS "c0", "t0"."title" AS "c1", "t0"."author_id" AS "c2", "t1"."id" AS "c3", "t1"."name" AS "c4" FROM "articles" AS "t0" LEFT OUTER JOIN "authors" AS "t1" ON ("t0"."author_id" = "t1"."id") AND ("t1"."name" ILIKE '%o''brien%') WHERE ("t0"."title" IS NOT NULL)"#
+        );
+
+        let plan = relation(serde_json::json!({
+            "type": "join",
+            "left": from_authors(),
+            "right": {
+                "type": "paginate",
+                "input": from_articles(),
+                "fetch": 5,
+                "skip": 0,
+            },
+            "on": [{ "left": column(0), "right": column(2) }],
+            "join_type": "left_anti",
+        }));
+
+        assert_eq!(
+            render_relation(&plan, &Sqlite).unwrap(),
+            r#"SELECT "t0"."id" AS "c0", "t0"."name" AS "c1" FROM "authors" AS "t0" WHERE NOT EXISTS (SELECT 1 FROM (SELECT "t1"."id" AS "c0", "t1"."title" AS "c1", "t1"."author_id" AS "c2" FROM "articles" AS "t1" LIMIT 5) AS "t2" WHERE ("t0"."id" = "t2"."c2"))"#
+        );
+    }
+
+    #[test]
+    fn test_right_and_full_joins() {
+        let join = |join_type: &str| {
+            relation(serde_json::json!({
+                "type": "join",
+                "left": {
+                    "type": "filter",
+                    "input": from_articles(),
+                    "predicate": { "type": "gt", "left": column(0), "right": int(10) },
+                },
+                "right": {
+                    "type": "filter",
+                    "input": from_authors(),
+                    "predicate": { "type": "is_not_null", "expr": column(1) },
+                },
+                "on": [{ "left": column(2), "right": column(0) }],
+                "join_type": join_type,
+            }))
+        };
+
+        // The filter on the left-hand side must become part of the join condition
+        let right = r#"SELECT "t0"."id" AS "c0", "t0"."title" AS "c1", "t0"."author_id" AS "c2", "t1"."id" AS "c3", "t1"."name" AS "c4" FROM "articles" AS "t0" RIGHT OUTER JOIN "authors" AS "t1" ON ("t0"."author_id" = "t1"."id") AND ("t0"."id" > 10) WHERE ("t1"."name" IS NOT NULL)"#;
+        assert_eq!(render_relation(&join("right"), &PostgreSql).unwrap(), right);
+        assert_eq!(render_relation(&join("right"), &Sqlite).unwrap(), right);
+        assert_eq!(render_relation(&join("right"), &Ansi).unwrap(), right);
+
+        // Both sides are preserved, so both filters must be applied before the join
+        let full = r#"SELECT "t2"."c0" AS "c0", "t2"."c1" AS "c1", "t2"."c2" AS "c2", "t3"."c0" AS "c3", "t3"."c1" AS "c4" FROM (SELECT "t0"."id" AS "c0", "t0"."title" AS "c1", "t0"."author_id" AS "c2" FROM "articles" AS "t0" WHERE ("t0"."id" > 10)) AS "t2" FULL OUTER JOIN (SELECT "t1"."id" AS "c0", "t1"."name" AS "c1" FROM "authors" AS "t1" WHERE ("t1"."name" IS NOT NULL)) AS "t3" ON ("t2"."c2" = "t3"."c0")"#;
+        assert_eq!(render_relation(&join("full"), &PostgreSql).unwrap(), full);
+        assert_eq!(render_relation(&join("full"), &Sqlite).unwrap(), full);
+        assert_eq!(render_relation(&join("full"), &Ansi).unwrap(), full);
+    }
+
+    #[test]
+    fn test_aggregate_and_window() {
+        let plan = relation(serde_json::json!({
+            "type": "window",
+            "input": {
+                "type": "filter",
+                "input": {
+                    "type": "aggregate",
+                    "input": from_articles(),
+                    "group_by": [column(2)],
+                    "aggregates": [
+                        { "type": "count", "expr": column(0), "distinct": false },
+                        {
+                            "type": "string_agg",
+                            "expr": column(1),
+                            "separator": ", ",
+                            "distinct": false,
+                            "order_by": [{
+                                "expr": column(1),
+                                "direction": "asc",
+                                "nulls_sort": "nulls_last",
+                            }],
+                        },
+                    ],
+                },
+                "predicate": { "type": "gt_eq", "left": column(1), "right": int(2) },
+            },
+            "exprs": [{
+                "type": "rank",
+                "order_by": [{
+                    "expr": column(1),
+                    "direction": "desc",
+                    "nulls_sort": "nulls_last",
+                }],
+                "partition_by": [],
+            }],
+        }));
+
+        assert_eq!(
+            render_relation(&plan, &PostgreSql).unwrap(),
+            r#"SELECT "t0"."author_id" AS "c0", COUNT("t0"."id") AS "c1", STRING_AGG("t0"."title", ', ' ORDER BY "t0"."title" ASC NULLS LAST) AS "c2", RANK() OVER (ORDER BY COUNT("t0"."id") DESC NULLS LAST) AS "c3" FROM "articles" AS "t0" GROUP BY "t0"."author_id" HAVING (COUNT("t0"."id") >= 2)"#
+        );
+        assert_eq!(
+            render_relation(&plan, &Ansi).unwrap(),
+            r#"SELECT "t0"."author_id" AS "c0", COUNT("t0"."id") AS "c1", LISTAGG("t0"."title", ', ') WITHIN GROUP (ORDER BY "t0"."title" ASC NULLS LAST) AS "c2", RANK() OVER (ORDER BY COUNT("t0"."id") DESC NULLS LAST) AS "c3" FROM "articles" AS "t0" GROUP BY "t0"."author_id" HAVING (COUNT("t0"."id") >= 2)"#
+        );
+    }
+
+    #[test]
+    fn test_aggregate_with_constant_keys() {
+        // An empty input, which has no groups when grouped by a constant
+        let empty_articles = serde_json::json!({
+            "type": "filter",
+            "input": from_articles(),
+            "predicate": { "type": "lt", "left": column(0), "right": int(0) },
+        });
+        let plan = relation(serde_json::json!({
+            "type": "aggregate",
+            "input": empty_articles,
+            "group_by": [int(1)],
+            "aggregates": [{ "type": "count", "expr": column(0), "distinct": false }],
+        }));
+
+        assert_eq!(
+            render_relation(&plan, &PostgreSql).unwrap(),
+            r#"SELECT 1 AS "c0", COUNT("t0"."id") AS "c1" FROM "articles" AS "t0" WHERE ("t0"."id" < 0) GROUP BY (1 + 0)"#
+        );
+        assert_eq!(
+            render_relation(&plan, &Sqlite).unwrap(),
+            r#"SELECT 1 AS "c0", COUNT("t0"."id") AS "c1" FROM "articles" AS "t0" WHERE ("t0"."id" < 0) GROUP BY (1 + 0)"#
+        );
+
+        // Constant keys are omitted alongside other keys
+        let plan = relation(serde_json::json!({
+            "type": "aggregate",
+            "input": from_articles(),
+            "group_by": [int(1), column(2)],
+            "aggregates": [{ "type": "count", "expr": column(0), "distinct": false }],
+        }));
+
+        assert_eq!(
+            render_relation(&plan, &PostgreSql).unwrap(),
+            r#"SELECT 1 AS "c0", "t0"."author_id" AS "c1", COUNT("t0"."id") AS "c2" FROM "articles" AS "t0" GROUP BY "t0"."author_id""#
+        );
+
+        // Without keys, the aggregate is global
+        let plan = relation(serde_json::json!({
+            "type": "aggregate",
+            "input": from_articles(),
+            "group_by": [],
+            "aggregates": [{ "type": "count", "expr": column(0), "distinct": false }],
+        }));
+
+        assert_eq!(
+            render_relation(&plan, &PostgreSql).unwrap(),
+            r#"SELECT COUNT("t0"."id") AS "c0" FROM "articles" AS "t0""#
+        );
+    }
+
+    #[test]
+    fn test_union_and_dialect_functions() {
+        let plan = relation(serde_json::json!({
+            "type": "project",
+            "input": {
+                "type": "union",
+                "relations": [from_authors(), from_authors()],
+            },
+            "exprs": [
+                { "type": "date_part", "expr": column(1), "part": "day_of_week" },
+                { "type": "str_pos", "str": column(1), "substr": column(1) },
+            ],
+        }));
+
+        let union = r#"FROM (SELECT "t0"."id" AS "c0", "t0"."name" AS "c1" FROM "authors" AS "t0" UNION ALL SELECT "t1"."id" AS "c0", "t1"."name" AS "c1" FROM "authors" AS "t1") AS "t2""#;
+        assert_eq!(
+            render_relation(&plan, &PostgreSql).unwrap(),
+            format!(
+                r#"SELECT EXTRACT(DOW FROM "t2"."c1") AS "c0", STRPOS("t2"."c1", "t2"."c1") AS "c1" {union}"#
+            )
+        );
+        assert_eq!(
+            render_relation(&plan, &Sqlite).unwrap(),
+            format!(
+                r#"SELECT CAST(STRFTIME('%w', "t2"."c1") AS INTEGER) AS "c0", INSTR("t2"."c1", "t2"."c1") AS "c1" {union}"#
+            )
+        );
+        assert_eq!(
+            render_relation(&plan, &Ansi),
+            Err(RenderError::Unsupported {
+                dialect: "ANSI",
+                construct: "date part DayOfWeek".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_literals() {
+        let render = |dialect: &dyn Dialect, value: RelationalLiteral| {
+            literal::literal(dialect, &value).unwrap()
+        };
+
+        assert_eq!(
+            render(
+                &Ansi,
+                RelationalLiteral::Decimal128 {
+                    value: -12345,
+                    scale: 3,
+                    prec: 10
+                }
+            ),
+            "-12.345"
+        );
+        assert_eq!(
+            render(
+                &Ansi,
+                RelationalLiteral::Decimal256 {
+                    value: "12".into(),
+                    scale: -2,
+                    prec: 10
+                }
+            ),
+            "1200"
+        );
+        assert_eq!(
+            render(&PostgreSql, RelationalLiteral::Date32 { value: 19_000 }),
+            "DATE '2022-01-08'"
+        );
+        assert_eq!(
+            render(
+                &Sqlite,
+                RelationalLiteral::TimestampNanosecond { value: -1 }
+            ),
+            "'1969-12-31 23:59:59.999999999'"
+        );
+        assert_eq!(
+            render(
+                &PostgreSql,
+                RelationalLiteral::Interval {
+                    months: 1,
+                    days: -2,
+                    nanoseconds: 1_500_000_000
+                }
+            ),
+            "INTERVAL '1 months -2 days 1.5 seconds'"
+        );
+        assert_eq!(
+            render(
+                &Ansi,
+                RelationalLiteral::DurationMillisecond { value: -1500 }
+            ),
+            "INTERVAL '-1.5' SECOND"
+        );
+    }
+}
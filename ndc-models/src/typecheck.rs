@@ -0,0 +1,1728 @@
+//! Type inference for relational query plans.
+//!
+//! [`relation_columns`] computes the name, type and nullability of every column
+//! produced by a [`Relation`], and type-checks every expression in the plan
+//! along the way, so that mistakes such as adding two strings are reported
+//! before a plan is sent to a connector.
+//!
+//! Columns read from collections have the scalar or object types declared in
+//! the schema. Computed columns keep a schema type where the result type of an
+//! expression is the type of one of its operands (for example `abs`, `max` or
+//! `coalesce`), and otherwise have a [`RelationalType::Builtin`] type.
+
+use std::borrow::Cow;
+
+use crate::path::at;
+use crate::validate::{value_object_type, ValidationErrorKind};
+use crate::{
+    CastType, CollectionName, DatePartUnit, FieldName, JoinType, ObjectType, Relation,
+    RelationalColumn, RelationalExpression, RelationalLiteral, RelationalType, ScalarTypeName,
+    SchemaResponse, Sort, Type, TypeRepresentation,
+};
+
+/// An error found while inferring the type of a relation, along with the JSON
+/// path of the offending part of the relation.
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+#[error("{kind} at path {}", .path.join("."))]
+pub struct TypeError {
+    /// The path to the offending value, starting with `$` for the root relation
+    pub path: Vec<String>,
+    pub kind: TypeErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum TypeErrorKind {
+    /// The relation refers to a name which is not defined in the schema
+    #[error(transparent)]
+    Invalid(#[from] ValidationErrorKind),
+    #[error("column index {index} is out of range for a relation with {width} columns")]
+    ColumnIndexOutOfRange { index: u64, width: usize },
+    #[error("expected {expected}, found {found}")]
+    UnexpectedType {
+        expected: &'static str,
+        found: RelationalType,
+    },
+    #[error("types {0} and {1} are not compatible")]
+    IncompatibleTypes(RelationalType, RelationalType),
+    #[error("values of type {0} cannot be cast")]
+    InvalidCast(RelationalType),
+    #[error("aggregate functions can only be used in aggregate and window relations")]
+    UnexpectedAggregate,
+    #[error("window functions can only be used in window relations")]
+    UnexpectedWindowFunction,
+    #[error("expected {expected} columns, found {found}")]
+    ColumnCountMismatch { expected: usize, found: usize },
+    #[error("predicate types cannot be used as column types")]
+    PredicateColumn,
+}
+
+/// Compute the columns produced by a relation, returning every type error
+/// found in the relation if there are any
+pub fn relation_columns(
+    schema: &SchemaResponse,
+    relation: &Relation,
+) -> Result<Vec<RelationalColumn>, Vec<TypeError>> {
+    let mut checker = Checker {
+        schema,
+        errors: vec![],
+    };
+    let columns = checker.relation(relation, &["$".to_string()]);
+    match columns {
+        Some(columns) if checker.errors.is_empty() => Ok(columns),
+        _ => Err(checker.errors),
+    }
+}
+
+/// Compute the type of an expression evaluated over rows with the given
+/// columns. Aggregate and window functions are permitted.
+pub fn expression_column(
+    schema: &SchemaResponse,
+    columns: &[RelationalColumn],
+    expr: &RelationalExpression,
+) -> Result<RelationalColumn, Vec<TypeError>> {
+    let mut checker = Checker {
+        schema,
+        errors: vec![],
+    };
+    let context = Context {
+        aggregates: true,
+        windows: true,
+    };
+    let typed = checker.expression(columns, expr, context, &["$".to_string()]);
+    match typed {
+        Some(typed) if checker.errors.is_empty() => Ok(column(expr, columns, typed)),
+        _ => Err(checker.errors),
+    }
+}
+
+/// The type and nullability of an expression
+#[derive(Clone, Debug)]
+struct Typed {
+    ty: RelationalType,
+    nullable: bool,
+}
+
+impl Typed {
+    fn builtin(cast_type: CastType, nullable: bool) -> Self {
+        Typed {
+            ty: RelationalType::Builtin { cast_type },
+            nullable,
+        }
+    }
+}
+
+fn column(
+    expr: &RelationalExpression,
+    columns: &[RelationalColumn],
+    typed: Typed,
+) -> RelationalColumn {
+    let name = match expr {
+        RelationalExpression::Column { index } => usize::try_from(*index)
+            .ok()
+            .and_then(|index| columns.get(index))
+            .map(|column| column.name.clone()),
+        _ => Some(expr.name().to_owned()),
+    };
+    RelationalColumn {
+        name: name.unwrap_or_default(),
+        r#type: typed.ty,
+        nullable: typed.nullable,
+    }
+}
+
+/// The expressions which are permitted in some part of a relation
+#[derive(Clone, Copy)]
+struct Context {
+    aggregates: bool,
+    windows: bool,
+}
+
+const SCALAR: Context = Context {
+    aggregates: false,
+    windows: false,
+};
+
+/// A classification of types, which determines the operations they support
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Null,
+    Boolean,
+    String,
+    /// An integer with the given number of bits
+    Integer(u8),
+    Decimal,
+    /// A floating-point number with the given number of bits
+    Float(u8),
+    Date,
+    Time,
+    Timestamp,
+    Interval,
+    Bytes,
+    Json,
+    Object,
+    Array,
+    /// Values which only support equality, such as geometries
+    Opaque,
+}
+
+impl Kind {
+    fn is_numeric(self) -> bool {
+        matches!(self, Kind::Integer(_) | Kind::Decimal | Kind::Float(_))
+    }
+
+    fn is_temporal(self) -> bool {
+        matches!(self, Kind::Date | Kind::Time | Kind::Timestamp)
+    }
+
+    /// The order in which numeric types are widened
+    fn numeric_rank(self) -> Option<(u8, u8)> {
+        match self {
+            Kind::Integer(bits) => Some((0, bits)),
+            Kind::Decimal => Some((1, 0)),
+            Kind::Float(bits) => Some((2, bits)),
+            _ => None,
+        }
+    }
+}
+
+fn is_boolean(kind: Kind) -> bool {
+    kind == Kind::Boolean
+}
+
+fn is_string(kind: Kind) -> bool {
+    kind == Kind::String
+}
+
+fn is_integer(kind: Kind) -> bool {
+    matches!(kind, Kind::Integer(_))
+}
+
+fn is_numeric(kind: Kind) -> bool {
+    kind.is_numeric()
+}
+
+fn is_temporal(kind: Kind) -> bool {
+    kind.is_temporal()
+}
+
+fn is_json(kind: Kind) -> bool {
+    matches!(kind, Kind::Json | Kind::String)
+}
+
+fn is_json_key(kind: Kind) -> bool {
+    matches!(kind, Kind::String | Kind::Integer(_))
+}
+
+fn is_any(_: Kind) -> bool {
+    true
+}
+
+fn cast_type_kind(cast_type: CastType) -> Kind {
+    match cast_type {
+        CastType::Boolean => Kind::Boolean,
+        CastType::Utf8 => Kind::String,
+        CastType::Int8 | CastType::UInt8 => Kind::Integer(8),
+        CastType::Int16 | CastType::UInt16 => Kind::Integer(16),
+        CastType::Int32 | CastType::UInt32 => Kind::Integer(32),
+        CastType::Int64 | CastType::UInt64 => Kind::Integer(64),
+        CastType::Float32 => Kind::Float(32),
+        CastType::Float64 => Kind::Float(64),
+        CastType::Decimal128 { .. } | CastType::Decimal256 { .. } => Kind::Decimal,
+        CastType::Date => Kind::Date,
+        CastType::Time => Kind::Time,
+        CastType::Timestamp => Kind::Timestamp,
+        CastType::Duration | CastType::Interval => Kind::Interval,
+    }
+}
+
+fn representation_kind(representation: &TypeRepresentation) -> Kind {
+    match representation {
+        TypeRepresentation::Boolean => Kind::Boolean,
+        TypeRepresentation::String | TypeRepresentation::UUID | TypeRepresentation::Enum { .. } => {
+            Kind::String
+        }
+        TypeRepresentation::Int8 => Kind::Integer(8),
+        TypeRepresentation::Int16 => Kind::Integer(16),
+        TypeRepresentation::Int32 => Kind::Integer(32),
+        TypeRepresentation::Int64 => Kind::Integer(64),
+        TypeRepresentation::BigInteger => Kind::Integer(128),
+        TypeRepresentation::Float32 => Kind::Float(32),
+        TypeRepresentation::Float64 => Kind::Float(64),
+        TypeRepresentation::BigDecimal => Kind::Decimal,
+        TypeRepresentation::Date => Kind::Date,
+        TypeRepresentation::Timestamp | TypeRepresentation::TimestampTZ => Kind::Timestamp,
+        TypeRepresentation::Bytes => Kind::Bytes,
+        TypeRepresentation::JSON => Kind::Json,
+        TypeRepresentation::Geography
+        | TypeRepresentation::Geometry
+        | TypeRepresentation::Vector { .. } => Kind::Opaque,
+    }
+}
+
+fn literal_type(literal: &RelationalLiteral) -> Typed {
+    let cast_type = match literal {
+        RelationalLiteral::Null => {
+            return Typed {
+                ty: RelationalType::Null,
+                nullable: true,
+            }
+        }
+        RelationalLiteral::Boolean { .. } => CastType::Boolean,
+        RelationalLiteral::String { .. } => CastType::Utf8,
+        RelationalLiteral::Int8 { .. } => CastType::Int8,
+        RelationalLiteral::Int16 { .. } => CastType::Int16,
+        RelationalLiteral::Int32 { .. } => CastType::Int32,
+        RelationalLiteral::Int64 { .. } => CastType::Int64,
+        RelationalLiteral::UInt8 { .. } => CastType::UInt8,
+        RelationalLiteral::UInt16 { .. } => CastType::UInt16,
+        RelationalLiteral::UInt32 { .. } => CastType::UInt32,
+        RelationalLiteral::UInt64 { .. } => CastType::UInt64,
+        RelationalLiteral::Float32 { .. } => CastType::Float32,
+        RelationalLiteral::Float64 { .. } => CastType::Float64,
+        RelationalLiteral::Decimal128 { scale, prec, .. } => CastType::Decimal128 {
+            scale: *scale,
+            prec: *prec,
+        },
+        RelationalLiteral::Decimal256 { scale, prec, .. } => CastType::Decimal256 {
+            scale: *scale,
+            prec: *prec,
+        },
+        RelationalLiteral::Date32 { .. } | RelationalLiteral::Date64 { .. } => CastType::Date,
+        RelationalLiteral::Time32Second { .. }
+        | RelationalLiteral::Time32Millisecond { .. }
+        | RelationalLiteral::Time64Microsecond { .. }
+        | RelationalLiteral::Time64Nanosecond { .. } => CastType::Time,
+        RelationalLiteral::TimestampSecond { .. }
+        | RelationalLiteral::TimestampMillisecond { .. }
+        | RelationalLiteral::TimestampMicrosecond { .. }
+        | RelationalLiteral::TimestampNanosecond { .. } => CastType::Timestamp,
+        RelationalLiteral::DurationSecond { .. }
+        | RelationalLiteral::DurationMillisecond { .. }
+        | RelationalLiteral::DurationMicrosecond { .. }
+        | RelationalLiteral::DurationNanosecond { .. } => CastType::Duration,
+        RelationalLiteral::Interval { .. } => CastType::Interval,
+    };
+    Typed::builtin(cast_type, false)
+}
+
+fn nullable(columns: Vec<RelationalColumn>) -> impl Iterator<Item = RelationalColumn> {
+    columns.into_iter().map(|column| RelationalColumn {
+        nullable: true,
+        ..column
+    })
+}
+
+struct Checker<'a> {
+    schema: &'a SchemaResponse,
+    errors: Vec<TypeError>,
+}
+
+impl<'a> Checker<'a> {
+    fn error(&mut self, path: &[String], kind: impl Into<TypeErrorKind>) {
+        self.errors.push(TypeError {
+            path: path.to_vec(),
+            kind: kind.into(),
+        });
+    }
+
+    fn kind(&self, ty: &RelationalType) -> Kind {
+        match ty {
+            RelationalType::Scalar { name } => self
+                .schema
+                .scalar_types
+                .get(name)
+                .map_or(Kind::Opaque, |scalar_type| {
+                    representation_kind(&scalar_type.representation)
+                }),
+            RelationalType::Object { .. } => Kind::Object,
+            RelationalType::Array { .. } => Kind::Array,
+            RelationalType::Builtin { cast_type } => cast_type_kind(*cast_type),
+            RelationalType::Json => Kind::Json,
+            RelationalType::Null => Kind::Null,
+        }
+    }
+
+    /// The relational type of a column or field declared in the schema,
+    /// along with its nullability
+    fn schema_type(&mut self, ty: &Type, path: &[String]) -> Option<(RelationalType, bool)> {
+        match ty {
+            Type::Named { name } => {
+                if self.schema.scalar_types.contains_key(name.as_str()) {
+                    Some((
+                        RelationalType::Scalar {
+                            name: ScalarTypeName::new(name.clone()),
+                        },
+                        false,
+                    ))
+                } else if self.schema.object_types.contains_key(name.as_str()) {
+                    Some((
+                        RelationalType::Object {
+                            name: name.clone().into(),
+                        },
+                        false,
+                    ))
+                } else {
+                    self.error(
+                        path,
+                        ValidationErrorKind::NamedTypeIsNotDefined(name.clone()),
+                    );
+                    None
+                }
+            }
+            Type::Nullable { underlying_type } => {
+                let (ty, _) = self.schema_type(underlying_type, path)?;
+                Some((ty, true))
+            }
+            Type::Array { element_type } => {
+                let (element_type, _) = self.schema_type(element_type, path)?;
+                Some((
+                    RelationalType::Array {
+                        element_type: Box::new(element_type),
+                    },
+                    false,
+                ))
+            }
+            Type::Predicate { .. } => {
+                self.error(path, TypeErrorKind::PredicateColumn);
+                None
+            }
+        }
+    }
+
+    fn collection_type(
+        &mut self,
+        collection_name: &CollectionName,
+        path: &[String],
+    ) -> Option<Cow<'a, ObjectType>> {
+        let schema = self.schema;
+        if let Some(collection) = schema
+            .collections
+            .iter()
+            .find(|c| &c.name == collection_name)
+        {
+            if let Some(object_type) = schema.object_types.get(&collection.collection_type) {
+                Some(Cow::Borrowed(object_type))
+            } else {
+                self.error(
+                    path,
+                    ValidationErrorKind::ObjectTypeIsNotDefined(collection.collection_type.clone()),
+                );
+                None
+            }
+        } else if let Some(function) = schema
+            .functions
+            .iter()
+            .find(|f| f.name.inner() == collection_name)
+        {
+            Some(Cow::Owned(value_object_type(function.result_type.clone())))
+        } else {
+            self.error(
+                path,
+                ValidationErrorKind::CollectionIsNotDefined(collection_name.clone()),
+            );
+            None
+        }
+    }
+
+    fn relation(&mut self, relation: &Relation, path: &[String]) -> Option<Vec<RelationalColumn>> {
+        match relation {
+            Relation::From {
+                collection,
+                columns,
+                ..
+            } => {
+                let object_type = self.collection_type(collection, &at(path, "collection"))?;
+                let mut output = vec![];
+                for (index, column) in columns.iter().enumerate() {
+                    let path = at(&at(path, "columns"), &index);
+                    let Some(field) = object_type.fields.get(column) else {
+                        self.error(
+                            &path,
+                            ValidationErrorKind::FieldIsNotDefined(column.clone()),
+                        );
+                        continue;
+                    };
+                    if let Some((ty, nullable)) = self.schema_type(&field.r#type, &path) {
+                        output.push(RelationalColumn {
+                            name: column.to_string(),
+                            r#type: ty,
+                            nullable,
+                        });
+                    }
+                }
+                (output.len() == columns.len()).then_some(output)
+            }
+            Relation::Paginate { input, .. } => self.relation(input, &at(path, "input")),
+            Relation::Filter { input, predicate } => {
+                let columns = self.relation(input, &at(path, "input"))?;
+                self.operand(
+                    &columns,
+                    predicate,
+                    SCALAR,
+                    &at(path, "predicate"),
+                    ("boolean", is_boolean),
+                )?;
+                Some(columns)
+            }
+            Relation::Sort { input, exprs } => {
+                let columns = self.relation(input, &at(path, "input"))?;
+                self.sorts(&columns, exprs, &at(path, "exprs"))
+                    .then_some(columns)
+            }
+            Relation::Project { input, exprs } => {
+                let columns = self.relation(input, &at(path, "input"))?;
+                self.columns(&columns, exprs, SCALAR, &at(path, "exprs"))
+            }
+            Relation::Join {
+                left,
+                right,
+                on,
+                join_type,
+            } => {
+                let left = self.relation(left, &at(path, "left"));
+                let right = self.relation(right, &at(path, "right"));
+                let (left, right) = (left?, right?);
+
+                let mut valid = true;
+                for (index, join_on) in on.iter().enumerate() {
+                    let path = at(&at(path, "on"), &index);
+                    let left_type =
+                        self.expression(&left, &join_on.left, SCALAR, &at(&path, "left"));
+                    let right_type =
+                        self.expression(&right, &join_on.right, SCALAR, &at(&path, "right"));
+                    valid &= match (left_type, right_type) {
+                        (Some(left_type), Some(right_type)) => {
+                            self.unify(&left_type.ty, &right_type.ty, &path).is_some()
+                        }
+                        _ => false,
+                    };
+                }
+                if !valid {
+                    return None;
+                }
+
+                Some(match join_type {
+                    JoinType::LeftSemi | JoinType::LeftAnti => left,
+                    JoinType::RightSemi | JoinType::RightAnti => right,
+                    JoinType::Inner => left.into_iter().chain(right).collect(),
+                    JoinType::Left => left.into_iter().chain(nullable(right)).collect(),
+                    JoinType::Right => nullable(left).chain(right).collect(),
+                    JoinType::Full => nullable(left).chain(nullable(right)).collect(),
+                })
+            }
+            Relation::Aggregate {
+                input,
+                group_by,
+                aggregates,
+            } => {
+                let columns = self.relation(input, &at(path, "input"))?;
+                let group_by = self.columns(&columns, group_by, SCALAR, &at(path, "group_by"));
+                let context = Context {
+                    aggregates: true,
+                    windows: false,
+                };
+                let aggregates =
+                    self.columns(&columns, aggregates, context, &at(path, "aggregates"));
+                Some(group_by?.into_iter().chain(aggregates?).collect())
+            }
+            Relation::Window { input, exprs } => {
+                let columns = self.relation(input, &at(path, "input"))?;
+                let context = Context {
+                    aggregates: true,
+                    windows: true,
+                };
+                let window = self.columns(&columns, exprs, context, &at(path, "exprs"))?;
+                Some(columns.into_iter().chain(window).collect())
+            }
+            Relation::Union { relations } => {
+                let path = at(path, "relations");
+                let inputs: Vec<_> = relations
+                    .iter()
+                    .enumerate()
+                    .map(|(index, relation)| self.relation(relation, &at(&path, &index)))
+                    .collect();
+                let mut inputs = inputs.into_iter().collect::<Option<Vec<_>>>()?.into_iter();
+                let mut output = inputs.next().unwrap_or_default();
+                for (index, columns) in inputs.enumerate() {
+                    let path = at(&path, &(index + 1));
+                    if columns.len() != output.len() {
+                        self.error(
+                            &path,
+                            TypeErrorKind::ColumnCountMismatch {
+                                expected: output.len(),
+                                found: columns.len(),
+                            },
+                        );
+                        return None;
+                    }
+                    for (column, other) in output.iter_mut().zip(columns) {
+                        column.r#type = self.unify(&column.r#type, &other.r#type, &path)?;
+                        column.nullable |= other.nullable;
+                    }
+                }
+                Some(output)
+            }
+        }
+    }
+
+    fn columns(
+        &mut self,
+        input: &[RelationalColumn],
+        exprs: &[RelationalExpression],
+        context: Context,
+        path: &[String],
+    ) -> Option<Vec<RelationalColumn>> {
+        let columns: Vec<_> = exprs
+            .iter()
+            .enumerate()
+            .map(|(index, expr)| {
+                let typed = self.expression(input, expr, context, &at(path, &index))?;
+                Some(column(expr, input, typed))
+            })
+            .collect();
+        columns.into_iter().collect()
+    }
+
+    fn sorts(&mut self, input: &[RelationalColumn], sorts: &[Sort], path: &[String]) -> bool {
+        let mut valid = true;
+        for (index, sort) in sorts.iter().enumerate() {
+            let path = at(&at(path, &index), "expr");
+            valid &= self.expression(input, &sort.expr, SCALAR, &path).is_some();
+        }
+        valid
+    }
+
+    /// The common type of two types, such as the operands of a comparison or
+    /// the branches of a `case` expression. Numeric types are widened.
+    fn unify(
+        &mut self,
+        left: &RelationalType,
+        right: &RelationalType,
+        path: &[String],
+    ) -> Option<RelationalType> {
+        if left == right {
+            return Some(left.clone());
+        }
+        let (left_kind, right_kind) = (self.kind(left), self.kind(right));
+        match (left_kind, right_kind) {
+            (Kind::Null, _) => return Some(right.clone()),
+            (_, Kind::Null) => return Some(left.clone()),
+            (Kind::Array, Kind::Array) => {
+                if let (
+                    RelationalType::Array {
+                        element_type: left_element,
+                    },
+                    RelationalType::Array {
+                        element_type: right_element,
+                    },
+                ) = (left, right)
+                {
+                    let element_type = self.unify(left_element, right_element, path)?;
+                    return Some(RelationalType::Array {
+                        element_type: Box::new(element_type),
+                    });
+                }
+            }
+            _ => {}
+        }
+        if let (Some(left_rank), Some(right_rank)) =
+            (left_kind.numeric_rank(), right_kind.numeric_rank())
+        {
+            return Some(if right_rank > left_rank {
+                right.clone()
+            } else {
+                left.clone()
+            });
+        }
+        if left_kind == right_kind && !matches!(left_kind, Kind::Object | Kind::Opaque) {
+            return Some(left.clone());
+        }
+        self.error(
+            path,
+            TypeErrorKind::IncompatibleTypes(left.clone(), right.clone()),
+        );
+        None
+    }
+
+    /// Check that an operand has one of the expected kinds. Null literals are
+    /// accepted anywhere.
+    fn operand(
+        &mut self,
+        input: &[RelationalColumn],
+        expr: &RelationalExpression,
+        context: Context,
+        path: &[String],
+        (expected, accept): (&'static str, fn(Kind) -> bool),
+    ) -> Option<Typed> {
+        let typed = self.expression(input, expr, context, path)?;
+        let kind = self.kind(&typed.ty);
+        if kind == Kind::Null || accept(kind) {
+            Some(typed)
+        } else {
+            self.error(
+                path,
+                TypeErrorKind::UnexpectedType {
+                    expected,
+                    found: typed.ty,
+                },
+            );
+            None
+        }
+    }
+
+    /// Check a list of operands with the same expected kinds, returning their
+    /// types if they are all valid
+    fn operands(
+        &mut self,
+        input: &[RelationalColumn],
+        exprs: &[RelationalExpression],
+        context: Context,
+        path: &[String],
+        expected: (&'static str, fn(Kind) -> bool),
+    ) -> Option<Vec<Typed>> {
+        let typed: Vec<_> = exprs
+            .iter()
+            .enumerate()
+            .map(|(index, expr)| self.operand(input, expr, context, &at(path, &index), expected))
+            .collect();
+        typed.into_iter().collect()
+    }
+
+    /// Unify the types of a list of operands, which must be non-empty
+    fn unify_all(&mut self, typed: &[Typed], path: &[String]) -> Option<RelationalType> {
+        let (first, rest) = typed.split_first()?;
+        rest.iter().try_fold(first.ty.clone(), |ty, other| {
+            self.unify(&ty, &other.ty, path)
+        })
+    }
+
+    fn expression(
+        &mut self,
+        input: &[RelationalColumn],
+        expr: &RelationalExpression,
+        context: Context,
+        path: &[String],
+    ) -> Option<Typed> {
+        use RelationalExpression as E;
+
+        const ANY: (&str, fn(Kind) -> bool) = ("any type", is_any);
+        const BOOLEAN: (&str, fn(Kind) -> bool) = ("boolean", is_boolean);
+        const STRING: (&str, fn(Kind) -> bool) = ("string", is_string);
+        const INTEGER: (&str, fn(Kind) -> bool) = ("integer", is_integer);
+        const NUMERIC: (&str, fn(Kind) -> bool) = ("numeric", is_numeric);
+        const TEMPORAL: (&str, fn(Kind) -> bool) = ("date, time or timestamp", is_temporal);
+        const JSON: (&str, fn(Kind) -> bool) = ("json or string", is_json);
+        const JSON_KEY: (&str, fn(Kind) -> bool) = ("string or integer", is_json_key);
+
+        // Check a required operand
+        macro_rules! operand {
+            ($expr: expr, $segment: expr, $expected: expr) => {
+                self.operand(input, $expr, context, &at(path, $segment), $expected)
+            };
+        }
+        // Check an optional operand, which is considered valid if absent
+        macro_rules! optional {
+            ($expr: expr, $segment: expr, $expected: expr) => {
+                match $expr {
+                    Some(expr) => operand!(expr, $segment, $expected).map(Some),
+                    None => Some(None),
+                }
+            };
+        }
+        // Check the operand of an aggregate function
+        macro_rules! aggregate {
+            ($expr: expr, $expected: expr) => {{
+                if !context.aggregates {
+                    self.error(path, TypeErrorKind::UnexpectedAggregate);
+                }
+                let typed = self.operand(input, $expr, SCALAR, &at(path, "expr"), $expected);
+                if !context.aggregates {
+                    return None;
+                }
+                typed?
+            }};
+        }
+        // Check the partitioning and ordering of a window function
+        macro_rules! window {
+            ($order_by: expr, $partition_by: expr) => {{
+                if !context.windows {
+                    self.error(path, TypeErrorKind::UnexpectedWindowFunction);
+                }
+                let order_by = self.sorts(input, $order_by, &at(path, "order_by"));
+                let partition_by =
+                    self.operands(input, $partition_by, SCALAR, &at(path, "partition_by"), ANY);
+                if !context.windows || !order_by || partition_by.is_none() {
+                    return None;
+                }
+            }};
+        }
+
+        let boolean = |nullable| Typed::builtin(CastType::Boolean, nullable);
+        let try_cast = matches!(expr, E::TryCast { .. });
+
+        match expr {
+            E::Literal { literal } => Some(literal_type(literal)),
+            E::Column { index } => {
+                let column = usize::try_from(*index)
+                    .ok()
+                    .and_then(|index| input.get(index));
+                if let Some(column) = column {
+                    Some(Typed {
+                        ty: column.r#type.clone(),
+                        nullable: column.nullable,
+                    })
+                } else {
+                    self.error(
+                        path,
+                        TypeErrorKind::ColumnIndexOutOfRange {
+                            index: *index,
+                            width: input.len(),
+                        },
+                    );
+                    None
+                }
+            }
+            E::Case {
+                scrutinee,
+                when,
+                default,
+            } => {
+                let scrutinee = optional!(scrutinee, "scrutinee", ANY);
+                let mut valid = scrutinee.is_some();
+                let mut results = vec![];
+                for (index, case) in when.iter().enumerate() {
+                    let path = at(&at(path, "when"), &index);
+                    let condition = match &scrutinee {
+                        Some(Some(scrutinee)) => self
+                            .expression(input, &case.when, context, &at(&path, "when"))
+                            .and_then(|condition| {
+                                self.unify(&scrutinee.ty, &condition.ty, &at(&path, "when"))
+                            })
+                            .map(|_| ()),
+                        _ => self
+                            .operand(input, &case.when, context, &at(&path, "when"), BOOLEAN)
+                            .map(|_| ()),
+                    };
+                    let then = self.expression(input, &case.then, context, &at(&path, "then"));
+                    valid &= condition.is_some();
+                    results.extend(then);
+                }
+                let default = optional!(default, "default", ANY);
+                if !valid || results.len() != when.len() {
+                    return None;
+                }
+                let default = default?;
+                let nullable = results.iter().any(|typed| typed.nullable)
+                    || default.as_ref().is_none_or(|typed| typed.nullable);
+                results.extend(default);
+                Some(Typed {
+                    ty: self
+                        .unify_all(&results, path)
+                        .unwrap_or(RelationalType::Null),
+                    nullable,
+                })
+            }
+            E::And { left, right } | E::Or { left, right } => {
+                let left = operand!(left, "left", BOOLEAN);
+                let right = operand!(right, "right", BOOLEAN);
+                Some(boolean(left?.nullable || right?.nullable))
+            }
+            E::Not { expr } => Some(boolean(operand!(expr, "expr", BOOLEAN)?.nullable)),
+            E::Eq { left, right }
+            | E::NotEq { left, right }
+            | E::Lt { left, right }
+            | E::LtEq { left, right }
+            | E::Gt { left, right }
+            | E::GtEq { left, right } => {
+                let left = operand!(left, "left", ANY);
+                let right = operand!(right, "right", ANY);
+                let (left, right) = (left?, right?);
+                self.unify(&left.ty, &right.ty, path)?;
+                Some(boolean(left.nullable || right.nullable))
+            }
+            E::IsDistinctFrom { left, right } | E::IsNotDistinctFrom { left, right } => {
+                let left = operand!(left, "left", ANY);
+                let right = operand!(right, "right", ANY);
+                self.unify(&left?.ty, &right?.ty, path)?;
+                Some(boolean(false))
+            }
+            E::IsNull { expr } | E::IsNotNull { expr } => {
+                operand!(expr, "expr", ANY)?;
+                Some(boolean(false))
+            }
+            E::IsTrue { expr }
+            | E::IsFalse { expr }
+            | E::IsNotTrue { expr }
+            | E::IsNotFalse { expr } => {
+                operand!(expr, "expr", BOOLEAN)?;
+                Some(boolean(false))
+            }
+            E::In { expr, list } | E::NotIn { expr, list } => {
+                let expr = operand!(expr, "expr", ANY);
+                let list = self.operands(input, list, context, &at(path, "list"), ANY);
+                let mut operands = vec![expr?];
+                operands.extend(list?);
+                self.unify_all(&operands, path)?;
+                Some(boolean(operands.iter().any(|typed| typed.nullable)))
+            }
+            E::Like { expr, pattern }
+            | E::NotLike { expr, pattern }
+            | E::ILike { expr, pattern }
+            | E::NotILike { expr, pattern } => {
+                let expr = operand!(expr, "expr", STRING);
+                let pattern = operand!(pattern, "pattern", STRING);
+                Some(boolean(expr?.nullable || pattern?.nullable))
+            }
+            E::Between { low, expr, high } | E::NotBetween { low, expr, high } => {
+                let low = operand!(low, "low", ANY);
+                let expr = operand!(expr, "expr", ANY);
+                let high = operand!(high, "high", ANY);
+                let operands = [low?, expr?, high?];
+                self.unify_all(&operands, path)?;
+                Some(boolean(operands.iter().any(|typed| typed.nullable)))
+            }
+            E::Contains { str, search_str } => {
+                let str = operand!(str, "str", STRING);
+                let search_str = operand!(search_str, "search_str", STRING);
+                Some(boolean(str?.nullable || search_str?.nullable))
+            }
+            E::IsNaN { expr } | E::IsZero { expr } => {
+                Some(boolean(operand!(expr, "expr", NUMERIC)?.nullable))
+            }
+            E::Plus { left, right }
+            | E::Minus { left, right }
+            | E::Multiply { left, right }
+            | E::Divide { left, right }
+            | E::Modulo { left, right } => {
+                let left = operand!(left, "left", ANY);
+                let right = operand!(right, "right", ANY);
+                self.arithmetic(expr, &left?, &right?, path)
+            }
+            E::Negate { expr } | E::Abs { expr } => {
+                let typed = operand!(expr, "expr", ANY)?;
+                let kind = self.kind(&typed.ty);
+                if kind.is_numeric() || matches!(kind, Kind::Interval | Kind::Null) {
+                    Some(typed)
+                } else {
+                    self.error(
+                        &at(path, "expr"),
+                        TypeErrorKind::UnexpectedType {
+                            expected: "numeric or interval",
+                            found: typed.ty,
+                        },
+                    );
+                    None
+                }
+            }
+            E::Cast { expr, as_type, .. } | E::TryCast { expr, as_type, .. } => {
+                let typed = operand!(expr, "expr", ANY)?;
+                if matches!(self.kind(&typed.ty), Kind::Object | Kind::Array) {
+                    self.error(&at(path, "expr"), TypeErrorKind::InvalidCast(typed.ty));
+                    return None;
+                }
+                let nullable = typed.nullable || try_cast;
+                Some(Typed::builtin(*as_type, nullable))
+            }
+            E::ArrayElement { column, .. } => {
+                let typed = operand!(column, "column", ("array", |kind| kind == Kind::Array))?;
+                let ty = match typed.ty {
+                    RelationalType::Array { element_type } => *element_type,
+                    ty => ty,
+                };
+                Some(Typed { ty, nullable: true })
+            }
+            E::BTrim { str, trim_str }
+            | E::LTrim { str, trim_str }
+            | E::RTrim { str, trim_str } => {
+                let str = operand!(str, "str", STRING);
+                let trim_str = optional!(trim_str, "trim_str", STRING);
+                Some(same_as(str?, trim_str?))
+            }
+            E::Reverse { str } => operand!(str, "str", STRING),
+            E::ToLower { expr } | E::ToUpper { expr } => operand!(expr, "expr", STRING),
+            E::Replace {
+                str,
+                substr,
+                replacement,
+            } => {
+                let str = operand!(str, "str", STRING);
+                let substr = operand!(substr, "substr", STRING);
+                let replacement = operand!(replacement, "replacement", STRING);
+                Some(same_as(str?, [substr?, replacement?]))
+            }
+            E::LPad {
+                str,
+                n,
+                padding_str,
+            }
+            | E::RPad {
+                str,
+                n,
+                padding_str,
+            } => {
+                let str = operand!(str, "str", STRING);
+                let n = operand!(n, "n", INTEGER);
+                let padding_str = optional!(padding_str, "padding_str", STRING);
+                Some(same_as(str?, padding_str?.into_iter().chain([n?])))
+            }
+            E::Left { str, n } | E::Right { str, n } => {
+                let str = operand!(str, "str", STRING);
+                let n = operand!(n, "n", INTEGER);
+                Some(same_as(str?, [n?]))
+            }
+            E::Substr {
+                str,
+                start_pos,
+                len,
+            } => {
+                let str = operand!(str, "str", STRING);
+                let start_pos = operand!(start_pos, "start_pos", INTEGER);
+                let len = optional!(len, "len", INTEGER);
+                Some(same_as(str?, len?.into_iter().chain([start_pos?])))
+            }
+            E::SubstrIndex { str, delim, count } => {
+                let str = operand!(str, "str", STRING);
+                let delim = operand!(delim, "delim", STRING);
+                let count = operand!(count, "count", INTEGER);
+                Some(same_as(str?, [delim?, count?]))
+            }
+            E::CharacterLength { str } => {
+                let str = operand!(str, "str", STRING)?;
+                Some(Typed::builtin(CastType::Int32, str.nullable))
+            }
+            E::StrPos { str, substr } => {
+                let str = operand!(str, "str", STRING);
+                let substr = operand!(substr, "substr", STRING);
+                Some(Typed::builtin(
+                    CastType::Int32,
+                    str?.nullable || substr?.nullable,
+                ))
+            }
+            E::Ceil { expr } | E::Floor { expr } => operand!(expr, "expr", NUMERIC),
+            E::Round { expr, prec } | E::Trunc { expr, prec } => {
+                let expr = operand!(expr, "expr", NUMERIC);
+                let prec = optional!(prec, "prec", INTEGER);
+                Some(same_as(expr?, prec?))
+            }
+            E::Cos { expr }
+            | E::Exp { expr }
+            | E::Ln { expr }
+            | E::Log10 { expr }
+            | E::Log2 { expr }
+            | E::Sqrt { expr }
+            | E::Tan { expr } => {
+                let expr = operand!(expr, "expr", NUMERIC)?;
+                Some(Typed::builtin(CastType::Float64, expr.nullable))
+            }
+            E::Log { expr, base } => {
+                let expr = operand!(expr, "expr", NUMERIC);
+                let base = optional!(base, "base", NUMERIC);
+                let nullable = expr?.nullable || base?.is_some_and(|base| base.nullable);
+                Some(Typed::builtin(CastType::Float64, nullable))
+            }
+            E::Power { base, exp } => {
+                let base = operand!(base, "base", NUMERIC);
+                let exp = operand!(exp, "exp", NUMERIC);
+                Some(Typed::builtin(
+                    CastType::Float64,
+                    base?.nullable || exp?.nullable,
+                ))
+            }
+            E::Coalesce { exprs } | E::Greatest { exprs } | E::Least { exprs } => {
+                let operands = self.operands(input, exprs, context, &at(path, "exprs"), ANY)?;
+                Some(Typed {
+                    ty: self
+                        .unify_all(&operands, path)
+                        .unwrap_or(RelationalType::Null),
+                    nullable: operands.iter().all(|typed| typed.nullable),
+                })
+            }
+            E::Nvl { expr1, expr2 } => {
+                let expr1 = operand!(expr1, "expr1", ANY);
+                let expr2 = operand!(expr2, "expr2", ANY);
+                let (expr1, expr2) = (expr1?, expr2?);
+                Some(Typed {
+                    ty: self.unify(&expr1.ty, &expr2.ty, path)?,
+                    nullable: expr1.nullable && expr2.nullable,
+                })
+            }
+            E::NullIf { expr1, expr2 } => {
+                let expr1 = operand!(expr1, "expr1", ANY);
+                let expr2 = operand!(expr2, "expr2", ANY);
+                let (expr1, expr2) = (expr1?, expr2?);
+                self.unify(&expr1.ty, &expr2.ty, path)?;
+                Some(Typed {
+                    ty: expr1.ty,
+                    nullable: true,
+                })
+            }
+            E::Concat { exprs } => {
+                self.operands(input, exprs, context, &at(path, "exprs"), ANY)?;
+                Some(Typed::builtin(CastType::Utf8, false))
+            }
+            E::BinaryConcat { left, right } => {
+                let left = operand!(left, "left", STRING);
+                let right = operand!(right, "right", STRING);
+                Some(Typed::builtin(
+                    CastType::Utf8,
+                    left?.nullable || right?.nullable,
+                ))
+            }
+            E::CurrentDate => Some(Typed::builtin(CastType::Date, false)),
+            E::CurrentTime => Some(Typed::builtin(CastType::Time, false)),
+            E::CurrentTimestamp => Some(Typed::builtin(CastType::Timestamp, false)),
+            E::DatePart { expr, part } => {
+                let expr = operand!(
+                    expr,
+                    "expr",
+                    ("date, time, timestamp or interval", |kind| {
+                        kind.is_temporal() || kind == Kind::Interval
+                    })
+                )?;
+                let cast_type = match part {
+                    DatePartUnit::Epoch => CastType::Float64,
+                    _ => CastType::Int32,
+                };
+                Some(Typed::builtin(cast_type, expr.nullable))
+            }
+            E::DateTrunc { expr, part } => {
+                let expr = operand!(expr, "expr", TEMPORAL);
+                let part = operand!(part, "part", STRING);
+                Some(same_as(expr?, [part?]))
+            }
+            E::GetField { column, field } => {
+                let typed = operand!(
+                    column,
+                    "column",
+                    ("object or json", |kind| matches!(
+                        kind,
+                        Kind::Object | Kind::Json
+                    ))
+                )?;
+                match &typed.ty {
+                    RelationalType::Object { name } => {
+                        let schema = self.schema;
+                        let Some(object_type) = schema.object_types.get(name) else {
+                            self.error(
+                                path,
+                                ValidationErrorKind::ObjectTypeIsNotDefined(name.clone()),
+                            );
+                            return None;
+                        };
+                        let Some(object_field) = object_type.fields.get(field.as_str()) else {
+                            self.error(
+                                &at(path, "field"),
+                                ValidationErrorKind::FieldIsNotDefined(FieldName::from(
+                                    field.as_str(),
+                                )),
+                            );
+                            return None;
+                        };
+                        let (ty, nullable) =
+                            self.schema_type(&object_field.r#type, &at(path, "field"))?;
+                        Some(Typed {
+                            ty,
+                            nullable: nullable || typed.nullable,
+                        })
+                    }
+                    RelationalType::Null => Some(typed),
+                    _ => Some(Typed {
+                        ty: RelationalType::Json,
+                        nullable: true,
+                    }),
+                }
+            }
+            E::Random => Some(Typed::builtin(CastType::Float64, false)),
+            E::ToDate { expr } => {
+                let expr = operand!(
+                    expr,
+                    "expr",
+                    ("string, integer or temporal", |kind| {
+                        matches!(kind, Kind::String | Kind::Integer(_)) || kind.is_temporal()
+                    })
+                )?;
+                Some(Typed::builtin(CastType::Date, expr.nullable))
+            }
+            E::ToTimestamp { expr } => {
+                let expr = operand!(
+                    expr,
+                    "expr",
+                    ("string, numeric or temporal", |kind| {
+                        kind == Kind::String || kind.is_numeric() || kind.is_temporal()
+                    })
+                )?;
+                Some(Typed::builtin(CastType::Timestamp, expr.nullable))
+            }
+            E::JsonContains { json, keys }
+            | E::JsonGet { json, keys }
+            | E::JsonGetStr { json, keys }
+            | E::JsonGetInt { json, keys }
+            | E::JsonGetFloat { json, keys }
+            | E::JsonGetBool { json, keys }
+            | E::JsonGetJson { json, keys }
+            | E::JsonAsText { json, keys }
+            | E::JsonLength { json, keys } => {
+                let json = operand!(json, "json", JSON);
+                let keys = self.operands(input, keys, context, &at(path, "keys"), JSON_KEY);
+                json?;
+                keys?;
+                Some(match expr {
+                    E::JsonGet { .. } => Typed {
+                        ty: RelationalType::Json,
+                        nullable: true,
+                    },
+                    E::JsonContains { .. } | E::JsonGetBool { .. } => {
+                        Typed::builtin(CastType::Boolean, true)
+                    }
+                    E::JsonGetInt { .. } | E::JsonLength { .. } => {
+                        Typed::builtin(CastType::Int64, true)
+                    }
+                    E::JsonGetFloat { .. } => Typed::builtin(CastType::Float64, true),
+                    _ => Typed::builtin(CastType::Utf8, true),
+                })
+            }
+
+            // Aggregate functions
+            E::Count { expr, .. } => {
+                aggregate!(expr, ANY);
+                Some(Typed::builtin(CastType::Int64, false))
+            }
+            E::ApproxDistinct { expr } => {
+                aggregate!(expr, ANY);
+                Some(Typed::builtin(CastType::UInt64, false))
+            }
+            E::Average { expr } => {
+                let typed = aggregate!(
+                    expr,
+                    ("numeric or interval", |kind| kind.is_numeric()
+                        || kind == Kind::Interval)
+                );
+                let ty = match self.kind(&typed.ty) {
+                    Kind::Integer(_) | Kind::Float(_) => RelationalType::Builtin {
+                        cast_type: CastType::Float64,
+                    },
+                    _ => typed.ty,
+                };
+                Some(Typed { ty, nullable: true })
+            }
+            E::Sum { expr } => {
+                let typed = aggregate!(
+                    expr,
+                    ("numeric or interval", |kind| kind.is_numeric()
+                        || kind == Kind::Interval)
+                );
+                let ty = match self.kind(&typed.ty) {
+                    Kind::Integer(bits) if bits < 64 => RelationalType::Builtin {
+                        cast_type: CastType::Int64,
+                    },
+                    _ => typed.ty,
+                };
+                Some(Typed { ty, nullable: true })
+            }
+            E::BoolAnd { expr } | E::BoolOr { expr } => {
+                aggregate!(expr, BOOLEAN);
+                Some(boolean(true))
+            }
+            E::Max { expr } | E::Min { expr } => {
+                let typed = aggregate!(expr, ANY);
+                Some(Typed {
+                    ty: typed.ty,
+                    nullable: true,
+                })
+            }
+            E::Median { expr } => {
+                let typed = aggregate!(expr, NUMERIC);
+                Some(Typed {
+                    ty: typed.ty,
+                    nullable: true,
+                })
+            }
+            E::Var { expr }
+            | E::Stddev { expr }
+            | E::StddevPop { expr }
+            | E::ApproxPercentileCont { expr, .. } => {
+                aggregate!(expr, NUMERIC);
+                Some(Typed::builtin(CastType::Float64, true))
+            }
+            E::FirstValue { expr, order_by } | E::LastValue { expr, order_by } => {
+                let typed = aggregate!(expr, ANY);
+                if !self.sorts(
+                    input,
+                    order_by.as_deref().unwrap_or_default(),
+                    &at(path, "order_by"),
+                ) {
+                    return None;
+                }
+                Some(Typed {
+                    ty: typed.ty,
+                    nullable: true,
+                })
+            }
+            E::StringAgg { expr, order_by, .. } => {
+                aggregate!(expr, STRING);
+                if !self.sorts(
+                    input,
+                    order_by.as_deref().unwrap_or_default(),
+                    &at(path, "order_by"),
+                ) {
+                    return None;
+                }
+                Some(Typed::builtin(CastType::Utf8, true))
+            }
+            E::ArrayAgg { expr, order_by, .. } => {
+                let typed = aggregate!(expr, ANY);
+                if !self.sorts(
+                    input,
+                    order_by.as_deref().unwrap_or_default(),
+                    &at(path, "order_by"),
+                ) {
+                    return None;
+                }
+                Some(Typed {
+                    ty: RelationalType::Array {
+                        element_type: Box::new(typed.ty),
+                    },
+                    nullable: true,
+                })
+            }
+
+            // Window functions
+            E::RowNumber {
+                order_by,
+                partition_by,
+            }
+            | E::DenseRank {
+                order_by,
+                partition_by,
+            }
+            | E::NTile {
+                order_by,
+                partition_by,
+                ..
+            }
+            | E::Rank {
+                order_by,
+                partition_by,
+            } => {
+                window!(order_by, partition_by);
+                Some(Typed::builtin(CastType::UInt64, false))
+            }
+            E::CumeDist {
+                order_by,
+                partition_by,
+            }
+            | E::PercentRank {
+                order_by,
+                partition_by,
+            } => {
+                window!(order_by, partition_by);
+                Some(Typed::builtin(CastType::Float64, false))
+            }
+        }
+    }
+
+    /// The result type of an arithmetic expression
+    fn arithmetic(
+        &mut self,
+        expr: &RelationalExpression,
+        left: &Typed,
+        right: &Typed,
+        path: &[String],
+    ) -> Option<Typed> {
+        use RelationalExpression as E;
+
+        let nullable = left.nullable || right.nullable;
+        let (left_kind, right_kind) = (self.kind(&left.ty), self.kind(&right.ty));
+        let interval = || RelationalType::Builtin {
+            cast_type: CastType::Interval,
+        };
+        let ty = match (expr, left_kind, right_kind) {
+            (_, Kind::Null, Kind::Null) => Some(RelationalType::Null),
+            (_, l, r)
+                if (l.is_numeric() || l == Kind::Null) && (r.is_numeric() || r == Kind::Null) =>
+            {
+                self.unify(&left.ty, &right.ty, path)
+            }
+            (E::Plus { .. } | E::Minus { .. }, l, Kind::Interval | Kind::Null)
+                if l.is_temporal() || l == Kind::Interval =>
+            {
+                Some(left.ty.clone())
+            }
+            (E::Plus { .. }, Kind::Interval | Kind::Null, r) if r.is_temporal() => {
+                Some(right.ty.clone())
+            }
+            (E::Minus { .. }, l, r) if l.is_temporal() && l == r => Some(interval()),
+            (E::Multiply { .. } | E::Divide { .. }, Kind::Interval, r) if r.is_numeric() => {
+                Some(left.ty.clone())
+            }
+            (E::Multiply { .. }, l, Kind::Interval) if l.is_numeric() => Some(right.ty.clone()),
+            _ => {
+                let (found, segment) = if left_kind.is_numeric()
+                    || left_kind.is_temporal()
+                    || matches!(left_kind, Kind::Interval | Kind::Null)
+                {
+                    (right.ty.clone(), "right")
+                } else {
+                    (left.ty.clone(), "left")
+                };
+                self.error(
+                    &at(path, segment),
+                    TypeErrorKind::UnexpectedType {
+                        expected: "numeric, temporal or interval",
+                        found,
+                    },
+                );
+                None
+            }
+        }?;
+        Some(Typed { ty, nullable })
+    }
+}
+
+/// The result of a function which returns a value of the same type as its
+/// first operand, which is nullable if any operand is
+fn same_as(first: Typed, rest: impl IntoIterator<Item = Typed>) -> Typed {
+    let nullable = rest
+        .into_iter()
+        .fold(first.nullable, |nullable, typed| nullable || typed.nullable);
+    Typed {
+        ty: first.ty,
+        nullable,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> SchemaResponse {
+        serde_json::from_value(serde_json::json!({
+            "scalar_types": {
+                "Int": {
+                    "representation": {"type": "int32"},
+                    "aggregate_functions": {},
+                    "comparison_operators": {}
+                },
+                "String": {
+                    "representation": {"type": "string"},
+                    "aggregate_functions": {},
+                    "comparison_operators": {}
+                },
+                "Decimal": {
+                    "representation": {"type": "bigdecimal"},
+                    "aggregate_functions": {},
+                    "comparison_operators": {}
+                }
+            },
+            "object_types": {
+                "author": {
+                    "fields": {
+                        "id": {"type": {"type": "named", "name": "Int"}},
+                        "name": {"type": {"type": "named", "name": "String"}},
+                        "address": {"type": {"type": "nullable", "underlying_type": {"type": "named", "name": "address"}}}
+                    },
+                    "foreign_keys": {}
+                },
+                "address": {
+                    "fields": {
+                        "city": {"type": {"type": "named", "name": "String"}}
+                    },
+                    "foreign_keys": {}
+                },
+                "article": {
+                    "fields": {
+                        "id": {"type": {"type": "named", "name": "Int"}},
+                        "author_id": {"type": {"type": "named", "name": "Int"}},
+                        "price": {"type": {"type": "nullable", "underlying_type": {"type": "named", "name": "Decimal"}}}
+                    },
+                    "foreign_keys": {}
+                }
+            },
+            "collections": [
+                {"name": "authors", "arguments": {}, "type": "author", "uniqueness_constraints": {}},
+                {"name": "articles", "arguments": {}, "type": "article", "uniqueness_constraints": {}}
+            ],
+            "functions": [],
+            "procedures": []
+        }))
+        .unwrap()
+    }
+
+    fn relation(value: serde_json::Value) -> Relation {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn scalar(name: &str, nullable: bool) -> (RelationalType, bool) {
+        (RelationalType::Scalar { name: name.into() }, nullable)
+    }
+
+    fn types(columns: &[RelationalColumn]) -> Vec<(RelationalType, bool)> {
+        columns
+            .iter()
+            .map(|column| (column.r#type.clone(), column.nullable))
+            .collect()
+    }
+
+    #[test]
+    fn test_join_and_project() {
+        let relation = relation(serde_json::json!({
+            "type": "project",
+            "input": {
+                "type": "join",
+                "left": {"type": "from", "collection": "authors", "columns": ["id", "name", "address"]},
+                "right": {"type": "from", "collection": "articles", "columns": ["author_id", "price"]},
+                "on": [{"left": {"type": "column", "index": 0}, "right": {"type": "column", "index": 0}}],
+                "join_type": "left"
+            },
+            "exprs": [
+                {"type": "column", "index": 1},
+                {"type": "get_field", "column": {"type": "column", "index": 2}, "field": "city"},
+                {"type": "plus", "left": {"type": "column", "index": 4}, "right": {"type": "literal", "literal": {"type": "Int32", "value": 1}}},
+                {"type": "character_length", "str": {"type": "column", "index": 1}},
+                {"type": "try_cast", "expr": {"type": "column", "index": 0}, "as_type": {"type": "Utf8"}}
+            ]
+        }));
+        let columns = relation_columns(&schema(), &relation).unwrap();
+        assert_eq!(
+            columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(),
+            vec!["name", "get_field", "plus", "character_length", "try_cast"]
+        );
+        assert_eq!(
+            types(&columns),
+            vec![
+                scalar("String", false),
+                scalar("String", true),
+                scalar("Decimal", true),
+                (
+                    RelationalType::Builtin {
+                        cast_type: CastType::Int32
+                    },
+                    false
+                ),
+                (
+                    RelationalType::Builtin {
+                        cast_type: CastType::Utf8
+                    },
+                    true
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let relation = relation(serde_json::json!({
+            "type": "aggregate",
+            "input": {"type": "from", "collection": "articles", "columns": ["author_id", "price"]},
+            "group_by": [{"type": "column", "index": 0}],
+            "aggregates": [
+                {"type": "count", "expr": {"type": "column", "index": 1}, "distinct": false},
+                {"type": "sum", "expr": {"type": "column", "index": 0}},
+                {"type": "max", "expr": {"type": "column", "index": 1}}
+            ]
+        }));
+        let columns = relation_columns(&schema(), &relation).unwrap();
+        assert_eq!(
+            types(&columns),
+            vec![
+                scalar("Int", false),
+                (
+                    RelationalType::Builtin {
+                        cast_type: CastType::Int64
+                    },
+                    false
+                ),
+                (
+                    RelationalType::Builtin {
+                        cast_type: CastType::Int64
+                    },
+                    true
+                ),
+                scalar("Decimal", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_join_types() {
+        let join = |join_type: &str| {
+            relation(serde_json::json!({
+                "type": "join",
+                "left": {"type": "from", "collection": "authors", "columns": ["id", "name"]},
+                "right": {"type": "from", "collection": "articles", "columns": ["author_id"]},
+                "on": [{"left": {"type": "column", "index": 0}, "right": {"type": "column", "index": 0}}],
+                "join_type": join_type
+            }))
+        };
+        let join_types =
+            |join_type: &str| types(&relation_columns(&schema(), &join(join_type)).unwrap());
+
+        assert_eq!(
+            join_types("inner"),
+            vec![
+                scalar("Int", false),
+                scalar("String", false),
+                scalar("Int", false)
+            ]
+        );
+        assert_eq!(
+            join_types("right"),
+            vec![
+                scalar("Int", true),
+                scalar("String", true),
+                scalar("Int", false)
+            ]
+        );
+        assert_eq!(
+            join_types("full"),
+            vec![
+                scalar("Int", true),
+                scalar("String", true),
+                scalar("Int", true)
+            ]
+        );
+        assert_eq!(
+            join_types("left_anti"),
+            vec![scalar("Int", false), scalar("String", false)]
+        );
+        assert_eq!(join_types("right_semi"), vec![scalar("Int", false)]);
+    }
+
+    #[test]
+    fn test_window() {
+        let relation = relation(serde_json::json!({
+            "type": "window",
+            "input": {"type": "from", "collection": "articles", "columns": ["id", "price"]},
+            "exprs": [
+                {
+                    "type": "row_number",
+                    "order_by": [{"expr": {"type": "column", "index": 1}, "direction": "desc", "nulls_sort": "nulls_last"}],
+                    "partition_by": [{"type": "column", "index": 0}]
+                },
+                {"type": "percent_rank", "order_by": [], "partition_by": []},
+                {"type": "first_value", "expr": {"type": "column", "index": 0}},
+                {"type": "sum", "expr": {"type": "column", "index": 1}}
+            ]
+        }));
+        let columns = relation_columns(&schema(), &relation).unwrap();
+        assert_eq!(
+            columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(),
+            vec![
+                "id",
+                "price",
+                "row_number",
+                "percent_rank",
+                "first_value",
+                "sum"
+            ]
+        );
+        assert_eq!(
+            types(&columns),
+            vec![
+                scalar("Int", false),
+                scalar("Decimal", true),
+                (
+                    RelationalType::Builtin {
+                        cast_type: CastType::UInt64
+                    },
+                    false
+                ),
+                (
+                    RelationalType::Builtin {
+                        cast_type: CastType::Float64
+                    },
+                    false
+                ),
+                scalar("Int", true),
+                scalar("Decimal", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_errors() {
+        let relation = relation(serde_json::json!({
+            "type": "project",
+            "input": {"type": "from", "collection": "authors", "columns": ["id", "name"]},
+            "exprs": [
+                {"type": "plus", "left": {"type": "column", "index": 1}, "right": {"type": "column", "index": 1}},
+                {"type": "column", "index": 2},
+                {"type": "count", "expr": {"type": "column", "index": 0}, "distinct": false}
+            ]
+        }));
+        let errors = relation_columns(&schema(), &relation).unwrap_err();
+        assert_eq!(
+            errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "expected numeric, temporal or interval, found String at path $.exprs.0.left",
+                "column index 2 is out of range for a relation with 2 columns at path $.exprs.1",
+                "aggregate functions can only be used in aggregate and window relations at path $.exprs.2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relation_errors() {
+        let errors = |value: serde_json::Value| {
+            relation_columns(&schema(), &relation(value))
+                .unwrap_err()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            errors(serde_json::json!({
+                "type": "union",
+                "relations": [
+                    {"type": "from", "collection": "books", "columns": ["id"]},
+                    {"type": "from", "collection": "authors", "columns": ["id", "title"]}
+                ]
+            })),
+            vec![
+                "collection books is not defined at path $.relations.0.collection",
+                "field title is not defined on object type at path $.relations.1.columns.1",
+            ]
+        );
+        assert_eq!(
+            errors(serde_json::json!({
+                "type": "union",
+                "relations": [
+                    {"type": "from", "collection": "authors", "columns": ["id", "name"]},
+                    {"type": "from", "collection": "authors", "columns": ["id"]}
+                ]
+            })),
+            vec!["expected 2 columns, found 1 at path $.relations.1"]
+        );
+        assert_eq!(
+            errors(serde_json::json!({
+                "type": "join",
+                "left": {"type": "from", "collection": "authors", "columns": ["name"]},
+                "right": {"type": "from", "collection": "articles", "columns": ["author_id"]},
+                "on": [{"left": {"type": "column", "index": 0}, "right": {"type": "column", "index": 0}}],
+                "join_type": "inner"
+            })),
+            vec!["types String and Int are not compatible at path $.on.0"]
+        );
+        assert_eq!(
+            errors(serde_json::json!({
+                "type": "filter",
+                "input": {"type": "from", "collection": "authors", "columns": ["id"]},
+                "predicate": {"type": "column", "index": 0}
+            })),
+            vec!["expected boolean, found Int at path $.predicate"]
+        );
+        assert_eq!(
+            errors(serde_json::json!({
+                "type": "aggregate",
+                "input": {"type": "from", "collection": "articles", "columns": ["id"]},
+                "group_by": [],
+                "aggregates": [{"type": "rank", "order_by": [], "partition_by": []}]
+            })),
+            vec!["window functions can only be used in window relations at path $.aggregates.0"]
+        );
+    }
+}
@@ -0,0 +1,1395 @@
+//! Validation of requests against the schema advertised by a connector.
+//!
+//! The checks in this module resolve every name used in a request (collections,
+//! fields, relationships, operators and functions) and type-check any literal
+//! comparison values, so that connectors and clients can agree on exactly which
+//! requests are well-formed.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use crate::path::at;
+use crate::value::ScalarValue;
+use crate::{
+    Aggregate, AggregateFunctionDefinition, AggregateFunctionName, ArrayComparison, CollectionName,
+    ComparisonOperatorDefinition, ComparisonOperatorName, ComparisonTarget, ComparisonValue,
+    Dimension, Distinct, ExistsInCollection, Expression, ExtractionFunctionName, Field, FieldName,
+    GroupComparisonTarget, GroupComparisonValue, GroupExpression, GroupOrderByTarget, Grouping,
+    NestedField, ObjectField, ObjectType, ObjectTypeName, OrderByTarget, PathElement, Query,
+    QueryRequest, Relationship, RelationshipArgument, RelationshipName, ScalarType, ScalarTypeName,
+    SchemaResponse, Type, TypeName, TypeRepresentation,
+};
+
+/// An error found while validating a request, along with the JSON path
+/// of the offending part of the request.
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+#[error("{kind} at path {}", .path.join("."))]
+pub struct ValidationError {
+    /// The path to the invalid value, starting with `$` for the request itself
+    pub path: Vec<String>,
+    pub kind: ValidationErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum ValidationErrorKind {
+    #[error("collection {0} is not defined")]
+    CollectionIsNotDefined(CollectionName),
+    #[error("object type {0} is not defined")]
+    ObjectTypeIsNotDefined(ObjectTypeName),
+    #[error("named type {0} is not a defined object or scalar type")]
+    NamedTypeIsNotDefined(TypeName),
+    #[error("field {0} is not defined on object type")]
+    FieldIsNotDefined(FieldName),
+    #[error("relationship {0} is not defined in request")]
+    RelationshipIsNotDefined(RelationshipName),
+    #[error("comparison operator {operator} is not defined on scalar type {scalar_type}")]
+    ComparisonOperatorIsNotDefined {
+        scalar_type: ScalarTypeName,
+        operator: ComparisonOperatorName,
+    },
+    #[error("aggregate function {function} is not defined on scalar type {scalar_type}")]
+    AggregateFunctionIsNotDefined {
+        scalar_type: ScalarTypeName,
+        function: AggregateFunctionName,
+    },
+    #[error("extraction function {function} is not defined on scalar type {scalar_type}")]
+    ExtractionFunctionIsNotDefined {
+        scalar_type: ScalarTypeName,
+        function: ExtractionFunctionName,
+    },
+    #[error("comparison operator {0} is not a full-text search operator")]
+    ExpectedFullTextSearchOperator(ComparisonOperatorName),
+    #[error("expected a scalar type")]
+    ExpectedScalarType,
+    #[error("expected a scalar type with the type representation geography or geometry")]
+    ExpectedGeospatialType,
+    #[error("expected a scalar type with the type representation vector")]
+    ExpectedVectorType,
+    #[error("expected an object type")]
+    ExpectedObjectType,
+    #[error("expected an array type")]
+    ExpectedArrayType,
+    #[error("scope {0} does not refer to an enclosing collection")]
+    ScopeIsNotDefined(usize),
+    #[error("dimension index {0} is out of range")]
+    DimensionIsNotDefined(usize),
+    #[error("required field {0} is missing")]
+    MissingField(FieldName),
+    #[error("invalid literal value, expected {0}")]
+    InvalidLiteral(String),
+}
+
+/// Validate a query request against a schema, returning every error found.
+///
+/// An empty result means that the request only refers to names defined in the
+/// schema (or in the request's `collection_relationships`), and that every
+/// scalar literal matches the argument type of its comparison operator.
+pub fn validate_query_request(
+    schema: &SchemaResponse,
+    request: &QueryRequest,
+) -> Vec<ValidationError> {
+    let mut validator = Validator {
+        schema,
+        collection_relationships: &request.collection_relationships,
+        errors: vec![],
+    };
+
+    let path = vec!["$".to_string()];
+
+    if let Some(collection_type) =
+        validator.collection_type(&request.collection, &at(&path, "collection"))
+    {
+        validator.query(&collection_type, &request.query, &at(&path, "query"));
+    }
+
+    validator.errors
+}
+
+pub(crate) fn unwrap_nullable(ty: &Type) -> &Type {
+    match ty {
+        Type::Nullable { underlying_type } => unwrap_nullable(underlying_type),
+        Type::Named { .. } | Type::Array { .. } | Type::Predicate { .. } => ty,
+    }
+}
+
+/// The object type of a function used as a collection, or of a nested
+/// array of scalars used in an `EXISTS` predicate: a single `__value` column.
+pub(crate) fn value_object_type(value_type: Type) -> ObjectType {
+    ObjectType {
+        description: None,
+        fields: BTreeMap::from_iter([(
+            "__value".into(),
+            ObjectField {
+                description: None,
+                r#type: value_type,
+                arguments: BTreeMap::new(),
+            },
+        )]),
+        foreign_keys: BTreeMap::new(),
+    }
+}
+
+pub(crate) fn describe_type(ty: &Type) -> String {
+    match ty {
+        Type::Named { name } => name.to_string(),
+        Type::Nullable { underlying_type } => {
+            format!("nullable {}", describe_type(underlying_type))
+        }
+        Type::Array { element_type } => format!("array of {}", describe_type(element_type)),
+        Type::Predicate { object_type_name } => format!("predicate on {object_type_name}"),
+    }
+}
+
+struct Validator<'a> {
+    schema: &'a SchemaResponse,
+    collection_relationships: &'a BTreeMap<RelationshipName, Relationship>,
+    errors: Vec<ValidationError>,
+}
+
+impl<'a> Validator<'a> {
+    fn error(&mut self, path: &[String], kind: ValidationErrorKind) {
+        self.errors.push(ValidationError {
+            path: path.to_vec(),
+            kind,
+        });
+    }
+
+    fn collection_type(
+        &mut self,
+        collection_name: &CollectionName,
+        path: &[String],
+    ) -> Option<Cow<'a, ObjectType>> {
+        let schema = self.schema;
+
+        if let Some(collection) = schema
+            .collections
+            .iter()
+            .find(|c| &c.name == collection_name)
+        {
+            return self.named_object_type(&collection.collection_type, path);
+        }
+
+        if let Some(function) = schema
+            .functions
+            .iter()
+            .find(|f| f.name.inner() == collection_name)
+        {
+            return Some(Cow::Owned(value_object_type(function.result_type.clone())));
+        }
+
+        self.error(
+            path,
+            ValidationErrorKind::CollectionIsNotDefined(collection_name.clone()),
+        );
+        None
+    }
+
+    fn named_object_type(
+        &mut self,
+        name: &ObjectTypeName,
+        path: &[String],
+    ) -> Option<Cow<'a, ObjectType>> {
+        let schema = self.schema;
+        if let Some(object_type) = schema.object_types.get(name) {
+            Some(Cow::Borrowed(object_type))
+        } else {
+            self.error(
+                path,
+                ValidationErrorKind::ObjectTypeIsNotDefined(name.clone()),
+            );
+            None
+        }
+    }
+
+    fn object_type_of(&mut self, ty: &Type, path: &[String]) -> Option<Cow<'a, ObjectType>> {
+        let schema = self.schema;
+        match unwrap_nullable(ty) {
+            Type::Named { name } => {
+                if let Some(object_type) = schema.object_types.get(name.as_str()) {
+                    Some(Cow::Borrowed(object_type))
+                } else {
+                    self.error(path, ValidationErrorKind::ExpectedObjectType);
+                    None
+                }
+            }
+            Type::Array { .. } | Type::Nullable { .. } | Type::Predicate { .. } => {
+                self.error(path, ValidationErrorKind::ExpectedObjectType);
+                None
+            }
+        }
+    }
+
+    fn element_type_of<'t>(&mut self, ty: &'t Type, path: &[String]) -> Option<&'t Type> {
+        if let Type::Array { element_type } = unwrap_nullable(ty) {
+            Some(element_type)
+        } else {
+            self.error(path, ValidationErrorKind::ExpectedArrayType);
+            None
+        }
+    }
+
+    fn scalar_type_of(
+        &mut self,
+        ty: &Type,
+        path: &[String],
+    ) -> Option<(&'a ScalarTypeName, &'a ScalarType)> {
+        let schema = self.schema;
+        if let Type::Named { name } = unwrap_nullable(ty) {
+            if let Some(scalar_type) = schema.scalar_types.get_key_value(name.as_str()) {
+                return Some(scalar_type);
+            }
+            if !schema.object_types.contains_key(name.as_str()) {
+                self.error(
+                    path,
+                    ValidationErrorKind::NamedTypeIsNotDefined(name.clone()),
+                );
+                return None;
+            }
+        }
+        self.error(path, ValidationErrorKind::ExpectedScalarType);
+        None
+    }
+
+    fn field_type(
+        &mut self,
+        object_type: &ObjectType,
+        field_name: &FieldName,
+        path: &[String],
+    ) -> Option<Type> {
+        if let Some(field) = object_type.fields.get(field_name) {
+            Some(field.r#type.clone())
+        } else {
+            self.error(
+                path,
+                ValidationErrorKind::FieldIsNotDefined(field_name.clone()),
+            );
+            None
+        }
+    }
+
+    /// Resolve the type of a column, descending through nested object fields
+    fn column_type(
+        &mut self,
+        object_type: &ObjectType,
+        column: &FieldName,
+        field_path: &[FieldName],
+        path: &[String],
+    ) -> Option<Type> {
+        let mut ty = self.field_type(object_type, column, path)?;
+        for field_name in field_path {
+            let object_type = self.object_type_of(&ty, path)?;
+            ty = self.field_type(&object_type, field_name, path)?;
+        }
+        Some(ty)
+    }
+
+    /// Resolve the object type reached by navigating a field path from an object
+    fn nested_object_type(
+        &mut self,
+        object_type: Cow<'a, ObjectType>,
+        field_path: &[FieldName],
+        path: &[String],
+    ) -> Option<Cow<'a, ObjectType>> {
+        match field_path.split_first() {
+            None => Some(object_type),
+            Some((column, rest)) => {
+                let ty = self.column_type(&object_type, column, rest, path)?;
+                self.object_type_of(&ty, path)
+            }
+        }
+    }
+
+    fn relationship(
+        &mut self,
+        source_type: &ObjectType,
+        relationship_name: &RelationshipName,
+        arguments: &BTreeMap<crate::ArgumentName, RelationshipArgument>,
+        path: &[String],
+    ) -> Option<Cow<'a, ObjectType>> {
+        let Some(relationship) = self.collection_relationships.get(relationship_name) else {
+            self.error(
+                path,
+                ValidationErrorKind::RelationshipIsNotDefined(relationship_name.clone()),
+            );
+            return None;
+        };
+
+        for argument in arguments.values() {
+            if let RelationshipArgument::Column { name } = argument {
+                self.field_type(source_type, name, &at(path, "arguments"));
+            }
+        }
+        for argument in relationship.arguments.values() {
+            if let RelationshipArgument::Column { name } = argument {
+                self.field_type(source_type, name, path);
+            }
+        }
+
+        let target_type = self.collection_type(&relationship.target_collection, path)?;
+
+        for (source_column, target_path) in &relationship.column_mapping {
+            self.field_type(source_type, source_column, path);
+            if let Some((target_column, rest)) = target_path.split_first() {
+                self.column_type(&target_type, target_column, rest, path);
+            }
+        }
+
+        Some(target_type)
+    }
+
+    /// Navigate a sequence of relationships, validating any predicates along the way
+    fn path_target(
+        &mut self,
+        object_type: Cow<'a, ObjectType>,
+        path_elements: &[PathElement],
+        path: &[String],
+    ) -> Option<Cow<'a, ObjectType>> {
+        let mut current = object_type;
+        for (index, path_element) in path_elements.iter().enumerate() {
+            let path = at(path, &index);
+            let source_type = self.nested_object_type(
+                current,
+                path_element.field_path.as_deref().unwrap_or_default(),
+                &at(&path, "field_path"),
+            )?;
+            let target_type = self.relationship(
+                &source_type,
+                &path_element.relationship,
+                &path_element.arguments,
+                &at(&path, "relationship"),
+            )?;
+            if let Some(predicate) = &path_element.predicate {
+                self.expression(&[&*target_type], predicate, &at(&path, "predicate"));
+            }
+            current = target_type;
+        }
+        Some(current)
+    }
+
+    fn query(&mut self, object_type: &ObjectType, query: &Query, path: &[String]) {
+        if let Some(aggregates) = &query.aggregates {
+            let path = at(path, "aggregates");
+            for (name, aggregate) in aggregates {
+                self.aggregate(object_type, aggregate, &at(&path, name));
+            }
+        }
+
+        if let Some(fields) = &query.fields {
+            let path = at(path, "fields");
+            for (name, field) in fields {
+                self.field(object_type, field, &at(&path, name));
+            }
+        }
+
+        if let Some(order_by) = &query.order_by {
+            let path = at(path, "order_by");
+            for (index, element) in order_by.elements.iter().enumerate() {
+                self.order_by_target(
+                    object_type,
+                    &element.target,
+                    &at(&at(&path, &index), "target"),
+                );
+            }
+        }
+
+        if let Some(predicate) = &query.predicate {
+            self.expression(&[object_type], predicate, &at(path, "predicate"));
+        }
+
+        if let Some(Distinct::On { columns }) = &query.distinct {
+            let path = at(&at(path, "distinct"), "columns");
+            for (index, column) in columns.iter().enumerate() {
+                self.field_type(object_type, column, &at(&path, &index));
+            }
+        }
+
+        if let Some(grouping) = &query.groups {
+            self.grouping(object_type, grouping, &at(path, "groups"));
+        }
+    }
+
+    fn field(&mut self, object_type: &ObjectType, field: &Field, path: &[String]) {
+        match field {
+            Field::Column {
+                column,
+                fields,
+                arguments: _,
+            } => {
+                let Some(column_type) = self.field_type(object_type, column, &at(path, "column"))
+                else {
+                    return;
+                };
+                if let Some(fields) = fields {
+                    self.nested_field(&column_type, fields, &at(path, "fields"));
+                }
+            }
+            Field::Relationship {
+                query,
+                relationship,
+                arguments,
+            } => {
+                if let Some(target_type) = self.relationship(
+                    object_type,
+                    relationship,
+                    arguments,
+                    &at(path, "relationship"),
+                ) {
+                    self.query(&target_type, query, &at(path, "query"));
+                }
+            }
+        }
+    }
+
+    fn nested_field(&mut self, ty: &Type, nested_field: &NestedField, path: &[String]) {
+        match nested_field {
+            NestedField::Object(nested_object) => {
+                let Some(object_type) = self.object_type_of(ty, path) else {
+                    return;
+                };
+                let path = at(path, "fields");
+                for (name, field) in &nested_object.fields {
+                    self.field(&object_type, field, &at(&path, name));
+                }
+            }
+            NestedField::Array(nested_array) => {
+                if let Some(element_type) = self.element_type_of(ty, path) {
+                    self.nested_field(element_type, &nested_array.fields, &at(path, "fields"));
+                }
+            }
+            NestedField::Collection(nested_collection) => {
+                let Some(element_type) = self.element_type_of(ty, path) else {
+                    return;
+                };
+                if let Some(object_type) = self.object_type_of(element_type, path) {
+                    self.query(&object_type, &nested_collection.query, &at(path, "query"));
+                }
+            }
+        }
+    }
+
+    /// Validate an aggregate, returning its result type if it can be determined
+    fn aggregate(
+        &mut self,
+        object_type: &ObjectType,
+        aggregate: &Aggregate,
+        path: &[String],
+    ) -> Option<Type> {
+        let (Aggregate::ColumnCount { filter, .. }
+        | Aggregate::SingleColumn { filter, .. }
+        | Aggregate::StarCount { filter }) = aggregate;
+        if let Some(filter) = filter {
+            self.expression(&[object_type], filter, &at(path, "filter"));
+        }
+
+        match aggregate {
+            Aggregate::ColumnCount {
+                column, field_path, ..
+            } => {
+                self.column_type(
+                    object_type,
+                    column,
+                    field_path.as_deref().unwrap_or_default(),
+                    &at(path, "column"),
+                )?;
+                self.count_type()
+            }
+            Aggregate::SingleColumn {
+                column,
+                field_path,
+                function,
+                ..
+            } => {
+                let column_type = self.column_type(
+                    object_type,
+                    column,
+                    field_path.as_deref().unwrap_or_default(),
+                    &at(path, "column"),
+                )?;
+                let (scalar_type_name, scalar_type) =
+                    self.scalar_type_of(&column_type, &at(path, "column"))?;
+                let Some(definition) = scalar_type.aggregate_functions.get(function) else {
+                    self.error(
+                        &at(path, "function"),
+                        ValidationErrorKind::AggregateFunctionIsNotDefined {
+                            scalar_type: scalar_type_name.clone(),
+                            function: function.clone(),
+                        },
+                    );
+                    return None;
+                };
+                Some(aggregate_function_result_type(scalar_type_name, definition))
+            }
+            Aggregate::StarCount { .. } => self.count_type(),
+        }
+    }
+
+    fn count_type(&self) -> Option<Type> {
+        let count_scalar_type = &self
+            .schema
+            .capabilities
+            .as_ref()?
+            .query
+            .as_ref()?
+            .aggregates
+            .as_ref()?
+            .count_scalar_type;
+        Some(Type::Named {
+            name: count_scalar_type.inner().clone(),
+        })
+    }
+
+    fn order_by_target(
+        &mut self,
+        object_type: &ObjectType,
+        target: &OrderByTarget,
+        path: &[String],
+    ) {
+        match target {
+            OrderByTarget::Column {
+                path: path_elements,
+                name,
+                field_path,
+                ..
+            } => {
+                if let Some(target_type) = self.path_target(
+                    Cow::Owned(object_type.clone()),
+                    path_elements,
+                    &at(path, "path"),
+                ) {
+                    self.column_type(
+                        &target_type,
+                        name,
+                        field_path.as_deref().unwrap_or_default(),
+                        &at(path, "name"),
+                    );
+                }
+            }
+            OrderByTarget::Aggregate {
+                path: path_elements,
+                aggregate,
+            } => {
+                if let Some(target_type) = self.path_target(
+                    Cow::Owned(object_type.clone()),
+                    path_elements,
+                    &at(path, "path"),
+                ) {
+                    self.aggregate(&target_type, aggregate, &at(path, "aggregate"));
+                }
+            }
+            OrderByTarget::Relevance {
+                name,
+                field_path,
+                operator,
+                value,
+            } => {
+                let Some(column_type) = self.column_type(
+                    object_type,
+                    name,
+                    field_path.as_deref().unwrap_or_default(),
+                    &at(path, "name"),
+                ) else {
+                    return;
+                };
+                let operator_path = at(path, "operator");
+                let Some(argument_type) =
+                    self.operator_argument_type(&column_type, operator, &operator_path)
+                else {
+                    return;
+                };
+                if let Some((_, scalar_type)) = self.scalar_type_of(&column_type, &operator_path) {
+                    if !matches!(
+                        scalar_type.comparison_operators.get(operator),
+                        Some(ComparisonOperatorDefinition::FullTextSearch { .. })
+                    ) {
+                        self.error(
+                            &operator_path,
+                            ValidationErrorKind::ExpectedFullTextSearchOperator(operator.clone()),
+                        );
+                    }
+                }
+                self.comparison_value(&[object_type], value, &argument_type, &at(path, "value"));
+            }
+            OrderByTarget::Distance {
+                name,
+                field_path,
+                value,
+            } => self.ordering_value(
+                object_type,
+                name,
+                field_path.as_deref().unwrap_or_default(),
+                value,
+                |representation| {
+                    matches!(
+                        representation,
+                        TypeRepresentation::Geography | TypeRepresentation::Geometry
+                    )
+                    .then_some(())
+                    .ok_or(ValidationErrorKind::ExpectedGeospatialType)
+                },
+                path,
+            ),
+            OrderByTarget::Similarity {
+                name,
+                field_path,
+                value,
+                ..
+            } => self.ordering_value(
+                object_type,
+                name,
+                field_path.as_deref().unwrap_or_default(),
+                value,
+                |representation| {
+                    matches!(representation, TypeRepresentation::Vector { .. })
+                        .then_some(())
+                        .ok_or(ValidationErrorKind::ExpectedVectorType)
+                },
+                path,
+            ),
+        }
+    }
+
+    /// Validate an ordering which compares a column with a value of the same
+    /// scalar type, whose representation must pass `check_representation`
+    fn ordering_value(
+        &mut self,
+        object_type: &ObjectType,
+        name: &FieldName,
+        field_path: &[FieldName],
+        value: &ComparisonValue,
+        check_representation: impl Fn(&TypeRepresentation) -> Result<(), ValidationErrorKind>,
+        path: &[String],
+    ) {
+        let name_path = at(path, "name");
+        let Some(column_type) = self.column_type(object_type, name, field_path, &name_path) else {
+            return;
+        };
+        let Some((scalar_type_name, scalar_type)) = self.scalar_type_of(&column_type, &name_path)
+        else {
+            return;
+        };
+        if let Err(kind) = check_representation(&scalar_type.representation) {
+            self.error(&name_path, kind);
+            return;
+        }
+        let argument_type = Type::Named {
+            name: scalar_type_name.inner().clone(),
+        };
+        self.comparison_value(&[object_type], value, &argument_type, &at(path, "value"));
+    }
+
+    /// Validate an expression. The last element of `scopes` is the current collection,
+    /// and earlier elements are the collections outside each enclosing `EXISTS`.
+    fn expression(&mut self, scopes: &[&ObjectType], expression: &Expression, path: &[String]) {
+        let Some(&object_type) = scopes.last() else {
+            return;
+        };
+
+        match expression {
+            Expression::And { expressions } | Expression::Or { expressions } => {
+                let path = at(path, "expressions");
+                for (index, expression) in expressions.iter().enumerate() {
+                    self.expression(scopes, expression, &at(&path, &index));
+                }
+            }
+            Expression::Not { expression } => {
+                self.expression(scopes, expression, &at(path, "expression"));
+            }
+            Expression::UnaryComparisonOperator { column, .. } => {
+                self.comparison_target(object_type, column, &at(path, "column"));
+            }
+            Expression::BinaryComparisonOperator {
+                column,
+                operator,
+                value,
+            } => {
+                let Some(column_type) =
+                    self.comparison_target(object_type, column, &at(path, "column"))
+                else {
+                    return;
+                };
+                if let Some(argument_type) =
+                    self.operator_argument_type(&column_type, operator, &at(path, "operator"))
+                {
+                    self.comparison_value(scopes, value, &argument_type, &at(path, "value"));
+                }
+            }
+            Expression::ArrayComparison { column, comparison } => {
+                let Some(column_type) =
+                    self.comparison_target(object_type, column, &at(path, "column"))
+                else {
+                    return;
+                };
+                let Some(element_type) = self.element_type_of(&column_type, &at(path, "column"))
+                else {
+                    return;
+                };
+                match comparison {
+                    ArrayComparison::Contains { value } => {
+                        self.comparison_value(
+                            scopes,
+                            value,
+                            element_type,
+                            &at(&at(path, "comparison"), "value"),
+                        );
+                    }
+                    ArrayComparison::IsEmpty => {}
+                }
+            }
+            Expression::Exists {
+                in_collection,
+                predicate,
+            } => {
+                let Some(collection_type) =
+                    self.in_collection(object_type, in_collection, &at(path, "in_collection"))
+                else {
+                    return;
+                };
+                if let Some(predicate) = predicate {
+                    let mut scopes = scopes.to_vec();
+                    scopes.push(&collection_type);
+                    self.expression(&scopes, predicate, &at(path, "predicate"));
+                }
+            }
+        }
+    }
+
+    fn in_collection(
+        &mut self,
+        object_type: &ObjectType,
+        in_collection: &ExistsInCollection,
+        path: &[String],
+    ) -> Option<Cow<'a, ObjectType>> {
+        match in_collection {
+            ExistsInCollection::Related {
+                field_path,
+                relationship,
+                arguments,
+            } => {
+                let source_type = self.nested_object_type(
+                    Cow::Owned(object_type.clone()),
+                    field_path.as_deref().unwrap_or_default(),
+                    &at(path, "field_path"),
+                )?;
+                self.relationship(
+                    &source_type,
+                    relationship,
+                    arguments,
+                    &at(path, "relationship"),
+                )
+            }
+            ExistsInCollection::Unrelated { collection, .. } => {
+                self.collection_type(collection, &at(path, "collection"))
+            }
+            ExistsInCollection::NestedCollection {
+                column_name,
+                field_path,
+                ..
+            } => {
+                let path = at(path, "column_name");
+                let column_type = self.column_type(object_type, column_name, field_path, &path)?;
+                let element_type = self.element_type_of(&column_type, &path)?;
+                self.object_type_of(element_type, &path)
+            }
+            ExistsInCollection::NestedScalarCollection {
+                column_name,
+                field_path,
+                ..
+            } => {
+                let path = at(path, "column_name");
+                let column_type = self.column_type(object_type, column_name, field_path, &path)?;
+                let element_type = self.element_type_of(&column_type, &path)?;
+                Some(Cow::Owned(value_object_type(element_type.clone())))
+            }
+        }
+    }
+
+    /// Validate a comparison target, returning the type of the compared value
+    fn comparison_target(
+        &mut self,
+        object_type: &ObjectType,
+        target: &ComparisonTarget,
+        path: &[String],
+    ) -> Option<Type> {
+        match target {
+            ComparisonTarget::Column {
+                name, field_path, ..
+            } => self.column_type(
+                object_type,
+                name,
+                field_path.as_deref().unwrap_or_default(),
+                &at(path, "name"),
+            ),
+            ComparisonTarget::Aggregate {
+                path: path_elements,
+                aggregate,
+            } => {
+                let target_type = self.path_target(
+                    Cow::Owned(object_type.clone()),
+                    path_elements,
+                    &at(path, "path"),
+                )?;
+                self.aggregate(&target_type, aggregate, &at(path, "aggregate"))
+            }
+        }
+    }
+
+    /// Look up a comparison operator on the scalar type of the compared value,
+    /// and return the type of its argument
+    fn operator_argument_type(
+        &mut self,
+        column_type: &Type,
+        operator: &ComparisonOperatorName,
+        path: &[String],
+    ) -> Option<Type> {
+        let (scalar_type_name, scalar_type) = self.scalar_type_of(column_type, path)?;
+        let Some(definition) = scalar_type.comparison_operators.get(operator) else {
+            self.error(
+                path,
+                ValidationErrorKind::ComparisonOperatorIsNotDefined {
+                    scalar_type: scalar_type_name.clone(),
+                    operator: operator.clone(),
+                },
+            );
+            return None;
+        };
+        Some(operator_argument_type(scalar_type_name, definition))
+    }
+
+    fn comparison_value(
+        &mut self,
+        scopes: &[&ObjectType],
+        value: &ComparisonValue,
+        argument_type: &Type,
+        path: &[String],
+    ) {
+        match value {
+            ComparisonValue::Column {
+                path: path_elements,
+                name,
+                field_path,
+                scope,
+                ..
+            } => {
+                let scope = scope.unwrap_or(0);
+                let Some(&object_type) = scopes
+                    .len()
+                    .checked_sub(scope + 1)
+                    .and_then(|index| scopes.get(index))
+                else {
+                    self.error(
+                        &at(path, "scope"),
+                        ValidationErrorKind::ScopeIsNotDefined(scope),
+                    );
+                    return;
+                };
+                if let Some(target_type) = self.path_target(
+                    Cow::Owned(object_type.clone()),
+                    path_elements,
+                    &at(path, "path"),
+                ) {
+                    self.column_type(
+                        &target_type,
+                        name,
+                        field_path.as_deref().unwrap_or_default(),
+                        &at(path, "name"),
+                    );
+                }
+            }
+            ComparisonValue::Scalar { value } => {
+                self.literal(value, argument_type, &at(path, "value"));
+            }
+            ComparisonValue::Variable { .. } => {}
+        }
+    }
+
+    /// Check that a literal JSON value inhabits a type
+    fn literal(&mut self, value: &serde_json::Value, ty: &Type, path: &[String]) {
+        let schema = self.schema;
+        match ty {
+            Type::Nullable { underlying_type } => {
+                if !value.is_null() {
+                    self.literal(value, underlying_type, path);
+                }
+            }
+            Type::Named { name } => {
+                if let Some(scalar_type) = schema.scalar_types.get(name.as_str()) {
+                    if ScalarValue::decode(&scalar_type.representation, value).is_err() {
+                        self.error(path, ValidationErrorKind::InvalidLiteral(name.to_string()));
+                    }
+                } else if let Some(object_type) = schema.object_types.get(name.as_str()) {
+                    let Some(object) = value.as_object() else {
+                        self.error(path, ValidationErrorKind::InvalidLiteral(name.to_string()));
+                        return;
+                    };
+                    for (field_name, field) in &object_type.fields {
+                        match object.get(field_name.as_str()) {
+                            Some(field_value) => {
+                                self.literal(field_value, &field.r#type, &at(path, field_name));
+                            }
+                            None => {
+                                if !matches!(field.r#type, Type::Nullable { .. }) {
+                                    self.error(
+                                        path,
+                                        ValidationErrorKind::MissingField(field_name.clone()),
+                                    );
+                                }
+                            }
+                        }
+                    }
+                } else {
+                    self.error(
+                        path,
+                        ValidationErrorKind::NamedTypeIsNotDefined(name.clone()),
+                    );
+                }
+            }
+            Type::Array { element_type } => {
+                let Some(elements) = value.as_array() else {
+                    self.error(path, ValidationErrorKind::InvalidLiteral(describe_type(ty)));
+                    return;
+                };
+                for (index, element) in elements.iter().enumerate() {
+                    self.literal(element, element_type, &at(path, &index));
+                }
+            }
+            Type::Predicate { object_type_name } => {
+                let Ok(predicate) = serde_json::from_value::<Expression>(value.clone()) else {
+                    self.error(path, ValidationErrorKind::InvalidLiteral(describe_type(ty)));
+                    return;
+                };
+                if let Some(object_type) = self.named_object_type(object_type_name, path) {
+                    self.expression(&[&*object_type], &predicate, path);
+                }
+            }
+        }
+    }
+
+    fn grouping(&mut self, object_type: &ObjectType, grouping: &Grouping, path: &[String]) {
+        let dimensions_path = at(path, "dimensions");
+        for (index, dimension) in grouping.dimensions.iter().enumerate() {
+            self.dimension(object_type, dimension, &at(&dimensions_path, &index));
+        }
+
+        let aggregates_path = at(path, "aggregates");
+        for (name, aggregate) in &grouping.aggregates {
+            self.aggregate(object_type, aggregate, &at(&aggregates_path, name));
+        }
+
+        if let Some(predicate) = &grouping.predicate {
+            self.group_expression(object_type, predicate, &at(path, "predicate"));
+        }
+
+        if let Some(order_by) = &grouping.order_by {
+            let path = at(path, "order_by");
+            for (index, element) in order_by.elements.iter().enumerate() {
+                let path = at(&at(&path, &index), "target");
+                match &element.target {
+                    GroupOrderByTarget::Dimension { index } => {
+                        if *index >= grouping.dimensions.len() {
+                            self.error(
+                                &at(&path, "index"),
+                                ValidationErrorKind::DimensionIsNotDefined(*index),
+                            );
+                        }
+                    }
+                    GroupOrderByTarget::Aggregate { aggregate } => {
+                        self.aggregate(object_type, aggregate, &at(&path, "aggregate"));
+                    }
+                }
+            }
+        }
+    }
+
+    fn dimension(&mut self, object_type: &ObjectType, dimension: &Dimension, path: &[String]) {
+        match dimension {
+            Dimension::Column {
+                path: path_elements,
+                column_name,
+                field_path,
+                extraction,
+                ..
+            } => {
+                let Some(target_type) = self.path_target(
+                    Cow::Owned(object_type.clone()),
+                    path_elements,
+                    &at(path, "path"),
+                ) else {
+                    return;
+                };
+                let Some(column_type) = self.column_type(
+                    &target_type,
+                    column_name,
+                    field_path.as_deref().unwrap_or_default(),
+                    &at(path, "column_name"),
+                ) else {
+                    return;
+                };
+                if let Some(extraction) = extraction {
+                    let path = at(path, "extraction");
+                    let Some((scalar_type_name, scalar_type)) =
+                        self.scalar_type_of(&column_type, &path)
+                    else {
+                        return;
+                    };
+                    if !scalar_type.extraction_functions.contains_key(extraction) {
+                        self.error(
+                            &path,
+                            ValidationErrorKind::ExtractionFunctionIsNotDefined {
+                                scalar_type: scalar_type_name.clone(),
+                                function: extraction.clone(),
+                            },
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn group_expression(
+        &mut self,
+        object_type: &ObjectType,
+        expression: &GroupExpression,
+        path: &[String],
+    ) {
+        match expression {
+            GroupExpression::And { expressions } | GroupExpression::Or { expressions } => {
+                let path = at(path, "expressions");
+                for (index, expression) in expressions.iter().enumerate() {
+                    self.group_expression(object_type, expression, &at(&path, &index));
+                }
+            }
+            GroupExpression::Not { expression } => {
+                self.group_expression(object_type, expression, &at(path, "expression"));
+            }
+            GroupExpression::UnaryComparisonOperator { target, .. } => {
+                self.group_comparison_target(object_type, target, &at(path, "target"));
+            }
+            GroupExpression::BinaryComparisonOperator {
+                target,
+                operator,
+                value,
+            } => {
+                let Some(target_type) =
+                    self.group_comparison_target(object_type, target, &at(path, "target"))
+                else {
+                    return;
+                };
+                let Some(argument_type) =
+                    self.operator_argument_type(&target_type, operator, &at(path, "operator"))
+                else {
+                    return;
+                };
+                match value {
+                    GroupComparisonValue::Scalar { value } => {
+                        self.literal(value, &argument_type, &at(&at(path, "value"), "value"));
+                    }
+                    GroupComparisonValue::Variable { .. } => {}
+                }
+            }
+        }
+    }
+
+    fn group_comparison_target(
+        &mut self,
+        object_type: &ObjectType,
+        target: &GroupComparisonTarget,
+        path: &[String],
+    ) -> Option<Type> {
+        match target {
+            GroupComparisonTarget::Aggregate { aggregate } => {
+                self.aggregate(object_type, aggregate, &at(path, "aggregate"))
+            }
+        }
+    }
+}
+
+/// The type of the argument to a comparison operator defined on a scalar type
+pub(crate) fn operator_argument_type(
+    scalar_type_name: &ScalarTypeName,
+    definition: &ComparisonOperatorDefinition,
+) -> Type {
+    let scalar_type = Type::Named {
+        name: scalar_type_name.inner().clone(),
+    };
+    match definition {
+        ComparisonOperatorDefinition::Equal
+        | ComparisonOperatorDefinition::LessThan
+        | ComparisonOperatorDefinition::LessThanOrEqual
+        | ComparisonOperatorDefinition::GreaterThan
+        | ComparisonOperatorDefinition::GreaterThanOrEqual
+        | ComparisonOperatorDefinition::Contains
+        | ComparisonOperatorDefinition::ContainsInsensitive
+        | ComparisonOperatorDefinition::StartsWith
+        | ComparisonOperatorDefinition::StartsWithInsensitive
+        | ComparisonOperatorDefinition::EndsWith
+        | ComparisonOperatorDefinition::EndsWithInsensitive
+        | ComparisonOperatorDefinition::FullTextSearch { .. }
+        | ComparisonOperatorDefinition::SpatialIntersects
+        | ComparisonOperatorDefinition::SpatialWithin
+        | ComparisonOperatorDefinition::SpatialContains => scalar_type,
+        ComparisonOperatorDefinition::In => Type::Array {
+            element_type: Box::new(scalar_type),
+        },
+        ComparisonOperatorDefinition::SpatialWithinDistance { argument_type } => Type::Named {
+            name: argument_type.inner().clone(),
+        },
+        ComparisonOperatorDefinition::Custom { argument_type } => argument_type.clone(),
+    }
+}
+
+/// The result type of an aggregate function defined on a scalar type
+pub(crate) fn aggregate_function_result_type(
+    scalar_type_name: &ScalarTypeName,
+    definition: &AggregateFunctionDefinition,
+) -> Type {
+    match definition {
+        AggregateFunctionDefinition::Min
+        | AggregateFunctionDefinition::Max
+        | AggregateFunctionDefinition::Median
+        | AggregateFunctionDefinition::BoolAnd
+        | AggregateFunctionDefinition::BoolOr
+        | AggregateFunctionDefinition::StringAgg { .. } => Type::Nullable {
+            underlying_type: Box::new(Type::Named {
+                name: scalar_type_name.inner().clone(),
+            }),
+        },
+        AggregateFunctionDefinition::Sum { result_type }
+        | AggregateFunctionDefinition::ApproxDistinct { result_type } => Type::Named {
+            name: result_type.inner().clone(),
+        },
+        AggregateFunctionDefinition::Average { result_type }
+        | AggregateFunctionDefinition::Stddev { result_type }
+        | AggregateFunctionDefinition::StddevPop { result_type }
+        | AggregateFunctionDefinition::Var { result_type } => Type::Nullable {
+            underlying_type: Box::new(Type::Named {
+                name: result_type.inner().clone(),
+            }),
+        },
+        AggregateFunctionDefinition::ArrayAgg => Type::Nullable {
+            underlying_type: Box::new(Type::Array {
+                element_type: Box::new(Type::Nullable {
+                    underlying_type: Box::new(Type::Named {
+                        name: scalar_type_name.inner().clone(),
+                    }),
+                }),
+            }),
+        },
+        AggregateFunctionDefinition::Custom { result_type } => result_type.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::{CollectionInfo, ComparisonOperatorDefinition, ObjectField, ScalarType};
+
+    fn schema() -> SchemaResponse {
+        SchemaResponse {
+            scalar_types: BTreeMap::from_iter([(
+                "Int".into(),
+                ScalarType {
+                    representation: TypeRepresentation::Int32,
+                    aggregate_functions: BTreeMap::new(),
+                    comparison_operators: BTreeMap::from_iter([
+                        ("eq".into(), ComparisonOperatorDefinition::Equal),
+                        ("in".into(), ComparisonOperatorDefinition::In),
+                    ]),
+                    extraction_functions: BTreeMap::new(),
+                },
+            )]),
+            object_types: BTreeMap::from_iter([(
+                "article".into(),
+                ObjectType {
+                    description: None,
+                    fields: BTreeMap::from_iter([(
+                        "id".into(),
+                        ObjectField {
+                            description: None,
+                            r#type: Type::Named { name: "Int".into() },
+                            arguments: BTreeMap::new(),
+                        },
+                    )]),
+                    foreign_keys: BTreeMap::new(),
+                },
+            )]),
+            collections: vec![CollectionInfo {
+                name: "articles".into(),
+                description: None,
+                arguments: BTreeMap::new(),
+                collection_type: "article".into(),
+                uniqueness_constraints: BTreeMap::new(),
+                relational_mutations: None,
+            }],
+            ..SchemaResponse::default()
+        }
+    }
+
+    fn request(predicate: &serde_json::Value) -> QueryRequest {
+        serde_json::from_value(serde_json::json!({
+            "collection": "articles",
+            "arguments": {},
+            "collection_relationships": {},
+            "query": {
+                "fields": { "id": { "type": "column", "column": "id" } },
+                "predicate": predicate,
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_valid_request() {
+        let request = request(&serde_json::json!({
+            "type": "binary_comparison_operator",
+            "column": { "type": "column", "name": "id" },
+            "operator": "in",
+            "value": { "type": "scalar", "value": [1, 2, 3] },
+        }));
+        assert_eq!(validate_query_request(&schema(), &request), vec![]);
+    }
+
+    #[test]
+    fn test_invalid_names_and_literals() {
+        let request = request(&serde_json::json!({
+            "type": "and",
+            "expressions": [
+                {
+                    "type": "binary_comparison_operator",
+                    "column": { "type": "column", "name": "title" },
+                    "operator": "eq",
+                    "value": { "type": "scalar", "value": "x" },
+                },
+                {
+                    "type": "binary_comparison_operator",
+                    "column": { "type": "column", "name": "id" },
+                    "operator": "like",
+                    "value": { "type": "scalar", "value": "x" },
+                },
+                {
+                    "type": "binary_comparison_operator",
+                    "column": { "type": "column", "name": "id" },
+                    "operator": "eq",
+                    "value": { "type": "scalar", "value": "x" },
+                },
+            ],
+        }));
+        let errors = validate_query_request(&schema(), &request)
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec![
+                "field title is not defined on object type at path $.query.predicate.expressions.0.column.name",
+                "comparison operator like is not defined on scalar type Int at path $.query.predicate.expressions.1.operator",
+                "invalid literal value, expected Int at path $.query.predicate.expressions.2.value.value",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_valid_distinct() {
+        let mut request = request(&serde_json::json!({ "type": "and", "expressions": [] }));
+        request.query = request.query.distinct();
+        assert_eq!(validate_query_request(&schema(), &request), vec![]);
+        request.query = request.query.distinct_on(["id"]);
+        assert_eq!(validate_query_request(&schema(), &request), vec![]);
+    }
+
+    #[test]
+    fn test_invalid_distinct_columns() {
+        let mut request = request(&serde_json::json!({ "type": "and", "expressions": [] }));
+        request.query = request.query.distinct_on(["id", "title"]);
+        let errors = validate_query_request(&schema(), &request)
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec!["field title is not defined on object type at path $.query.distinct.columns.1"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_relevance_operator() {
+        let mut request = request(&serde_json::json!({ "type": "and", "expressions": [] }));
+        request.query.order_by = Some(crate::OrderBy {
+            elements: vec![crate::OrderByElement {
+                order_direction: crate::OrderDirection::Desc,
+                target: OrderByTarget::Relevance {
+                    name: "id".into(),
+                    field_path: None,
+                    operator: "eq".into(),
+                    value: ComparisonValue::Scalar {
+                        value: serde_json::json!(1),
+                    },
+                },
+            }],
+        });
+        let errors = validate_query_request(&schema(), &request)
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec!["comparison operator eq is not a full-text search operator at path $.query.order_by.0.target.operator"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_aggregate_filter() {
+        let mut request = request(&serde_json::json!({ "type": "and", "expressions": [] }));
+        request.query.aggregates = Some(indexmap::IndexMap::from_iter([(
+            "count".into(),
+            crate::Aggregate::StarCount {
+                filter: Some(Box::new(Expression::BinaryComparisonOperator {
+                    column: crate::ComparisonTarget::Column {
+                        name: "title".into(),
+                        arguments: BTreeMap::new(),
+                        field_path: None,
+                    },
+                    operator: "eq".into(),
+                    value: ComparisonValue::Scalar {
+                        value: serde_json::json!("x"),
+                    },
+                })),
+            },
+        )]));
+        let errors = validate_query_request(&schema(), &request)
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec!["field title is not defined on object type at path $.query.aggregates.count.filter.column.name"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_similarity_column() {
+        let mut request = request(&serde_json::json!({ "type": "and", "expressions": [] }));
+        request.query.order_by = Some(crate::OrderBy {
+            elements: vec![crate::OrderByElement {
+                order_direction: crate::OrderDirection::Desc,
+                target: OrderByTarget::Similarity {
+                    name: "id".into(),
+                    field_path: None,
+                    metric: crate::SimilarityMetric::Cosine,
+                    value: ComparisonValue::Scalar {
+                        value: serde_json::json!([0.5, 0.5]),
+                    },
+                },
+            }],
+        });
+        let errors = validate_query_request(&schema(), &request)
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec!["expected a scalar type with the type representation vector at path $.query.order_by.0.target.name"]
+        );
+    }
+}
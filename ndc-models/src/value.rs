@@ -0,0 +1,732 @@
+//! Typed scalar values, and their JSON encodings.
+//!
+//! A [`TypeRepresentation`] determines how values of a scalar type appear in
+//! JSON. [`ScalarValue::decode`] checks a JSON value against a representation
+//! and converts it into a typed value, and [`ScalarValue::encode`] converts it
+//! back into the wire format.
+//!
+//! The wire formats are as follows:
+//!
+//! - `Int8`, `Int16` and `Int32` are JSON integers within range,
+//! - `Float32` and `Float64` are JSON numbers,
+//! - `Int64`, `BigInteger` and `BigDecimal` are strings of decimal digits, so
+//!   that their precision is not lost by JSON parsers which use doubles,
+//! - `UUID` is a hexadecimal string in the 8-4-4-4-12 format,
+//! - `Date` is an ISO 8601 `YYYY-MM-DD` string,
+//! - `Timestamp` is an ISO 8601 date and time without a UTC offset, and
+//!   `TimestampTZ` is an ISO 8601 date and time with a UTC offset (or `Z`),
+//! - `Bytes` is a string in standard, padded base64,
+//! - `Geography` is any GeoJSON object (a geometry, `Feature` or
+//!   `FeatureCollection`) per RFC 7946, with longitude and latitude
+//!   coordinates in WGS 84,
+//! - `Geometry` is a GeoJSON geometry object, whose coordinates may use any
+//!   coordinate system,
+//! - `JSON` is an arbitrary JSON value,
+//! - `Vector` is an array of JSON numbers, whose length is the number of
+//!   dimensions.
+
+use crate::{calendar, TypeRepresentation};
+
+/// A JSON value which has been decoded using a [`TypeRepresentation`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScalarValue {
+    Boolean(bool),
+    String(String),
+    Int8(i8),
+    Int16(i16),
+    Int32(i32),
+    Int64(i64),
+    Float32(f32),
+    Float64(f64),
+    /// An arbitrary-precision integer, as a string of decimal digits
+    BigInteger(String),
+    /// An arbitrary-precision decimal, as a string of decimal digits
+    BigDecimal(String),
+    UUID(String),
+    Date(String),
+    Timestamp(String),
+    TimestampTZ(String),
+    Geography(serde_json::Value),
+    Geometry(serde_json::Value),
+    Bytes(Vec<u8>),
+    JSON(serde_json::Value),
+    Enum(String),
+    Vector(Vec<f64>),
+}
+
+/// The reason why a value could not be decoded or encoded
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum ValueError {
+    #[error("expected {expected}, found {found}")]
+    UnexpectedJson {
+        expected: &'static str,
+        found: &'static str,
+    },
+    #[error("{value} is out of range for {representation}")]
+    OutOfRange {
+        representation: &'static str,
+        value: String,
+    },
+    #[error("{value:?} is not a valid {representation} string")]
+    InvalidString {
+        representation: &'static str,
+        value: String,
+    },
+    #[error("{0:?} is not one of the enum values")]
+    UnknownEnumValue(String),
+    #[error("invalid GeoJSON: {0}")]
+    InvalidGeoJSON(String),
+    #[error("expected a vector with {expected} dimensions, found {found}")]
+    WrongDimensions { expected: u32, found: usize },
+    #[error("{0} cannot be encoded as a JSON number")]
+    NonFiniteFloat(f64),
+}
+
+impl ScalarValue {
+    /// Decode a JSON value using the representation of its scalar type
+    pub fn decode(
+        representation: &TypeRepresentation,
+        value: &serde_json::Value,
+    ) -> Result<Self, ValueError> {
+        let name = representation_name(representation);
+        match representation {
+            TypeRepresentation::Boolean => value
+                .as_bool()
+                .map(ScalarValue::Boolean)
+                .ok_or_else(|| unexpected("boolean", value)),
+            TypeRepresentation::String => string(value).map(ScalarValue::String),
+            TypeRepresentation::Int8 => integer(name, value).map(ScalarValue::Int8),
+            TypeRepresentation::Int16 => integer(name, value).map(ScalarValue::Int16),
+            TypeRepresentation::Int32 => integer(name, value).map(ScalarValue::Int32),
+            TypeRepresentation::Int64 => {
+                let s = string(value)?;
+                if !is_integer(&s) {
+                    return Err(invalid(name, s));
+                }
+                s.parse()
+                    .map(ScalarValue::Int64)
+                    .map_err(|_| ValueError::OutOfRange {
+                        representation: name,
+                        value: s,
+                    })
+            }
+            TypeRepresentation::Float32 => {
+                let f = number(value)?;
+                #[allow(clippy::cast_possible_truncation)]
+                let f32 = f as f32;
+                if f32.is_finite() {
+                    Ok(ScalarValue::Float32(f32))
+                } else {
+                    Err(ValueError::OutOfRange {
+                        representation: name,
+                        value: value.to_string(),
+                    })
+                }
+            }
+            TypeRepresentation::Float64 => number(value).map(ScalarValue::Float64),
+            TypeRepresentation::BigInteger => {
+                checked_string(name, value, is_integer).map(ScalarValue::BigInteger)
+            }
+            TypeRepresentation::BigDecimal => {
+                checked_string(name, value, is_decimal).map(ScalarValue::BigDecimal)
+            }
+            TypeRepresentation::UUID => checked_string(name, value, is_uuid).map(ScalarValue::UUID),
+            TypeRepresentation::Date => checked_string(name, value, is_date).map(ScalarValue::Date),
+            TypeRepresentation::Timestamp => {
+                checked_string(name, value, |s| is_timestamp(s, false)).map(ScalarValue::Timestamp)
+            }
+            TypeRepresentation::TimestampTZ => {
+                checked_string(name, value, |s| is_timestamp(s, true)).map(ScalarValue::TimestampTZ)
+            }
+            TypeRepresentation::Geography => {
+                geojson(value).map(|()| ScalarValue::Geography(value.clone()))
+            }
+            TypeRepresentation::Geometry => {
+                geometry(value, false).map(|()| ScalarValue::Geometry(value.clone()))
+            }
+            TypeRepresentation::Bytes => {
+                let s = string(value)?;
+                base64_decode(&s)
+                    .map(ScalarValue::Bytes)
+                    .ok_or_else(|| invalid(name, s))
+            }
+            TypeRepresentation::JSON => Ok(ScalarValue::JSON(value.clone())),
+            TypeRepresentation::Enum { one_of } => {
+                let s = string(value)?;
+                if one_of.contains(&s) {
+                    Ok(ScalarValue::Enum(s))
+                } else {
+                    Err(ValueError::UnknownEnumValue(s))
+                }
+            }
+            TypeRepresentation::Vector { dimensions } => {
+                let elements = value.as_array().ok_or_else(|| unexpected("array", value))?;
+                if usize::try_from(*dimensions).ok() != Some(elements.len()) {
+                    return Err(ValueError::WrongDimensions {
+                        expected: *dimensions,
+                        found: elements.len(),
+                    });
+                }
+                elements
+                    .iter()
+                    .map(number)
+                    .collect::<Result<_, _>>()
+                    .map(ScalarValue::Vector)
+            }
+        }
+    }
+
+    /// Encode this value in the wire format of its representation
+    pub fn encode(&self) -> Result<serde_json::Value, ValueError> {
+        Ok(match self {
+            ScalarValue::Boolean(b) => (*b).into(),
+            ScalarValue::Int8(i) => (*i).into(),
+            ScalarValue::Int16(i) => (*i).into(),
+            ScalarValue::Int32(i) => (*i).into(),
+            ScalarValue::Int64(i) => i.to_string().into(),
+            ScalarValue::Float32(f) => float(f64::from(*f))?,
+            ScalarValue::Float64(f) => float(*f)?,
+            ScalarValue::String(s)
+            | ScalarValue::BigInteger(s)
+            | ScalarValue::BigDecimal(s)
+            | ScalarValue::UUID(s)
+            | ScalarValue::Date(s)
+            | ScalarValue::Timestamp(s)
+            | ScalarValue::TimestampTZ(s)
+            | ScalarValue::Enum(s) => s.clone().into(),
+            ScalarValue::Bytes(bytes) => base64_encode(bytes).into(),
+            ScalarValue::Geography(value)
+            | ScalarValue::Geometry(value)
+            | ScalarValue::JSON(value) => value.clone(),
+            ScalarValue::Vector(elements) => elements
+                .iter()
+                .map(|f| float(*f))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+fn representation_name(representation: &TypeRepresentation) -> &'static str {
+    match representation {
+        TypeRepresentation::Boolean => "boolean",
+        TypeRepresentation::String => "string",
+        TypeRepresentation::Int8 => "int8",
+        TypeRepresentation::Int16 => "int16",
+        TypeRepresentation::Int32 => "int32",
+        TypeRepresentation::Int64 => "int64",
+        TypeRepresentation::Float32 => "float32",
+        TypeRepresentation::Float64 => "float64",
+        TypeRepresentation::BigInteger => "biginteger",
+        TypeRepresentation::BigDecimal => "bigdecimal",
+        TypeRepresentation::UUID => "uuid",
+        TypeRepresentation::Date => "date",
+        TypeRepresentation::Timestamp => "timestamp",
+        TypeRepresentation::TimestampTZ => "timestamptz",
+        TypeRepresentation::Geography => "geography",
+        TypeRepresentation::Geometry => "geometry",
+        TypeRepresentation::Bytes => "bytes",
+        TypeRepresentation::JSON => "json",
+        TypeRepresentation::Enum { .. } => "enum",
+        TypeRepresentation::Vector { .. } => "vector",
+    }
+}
+
+fn json_kind(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::Number(n) if n.is_f64() => "number",
+        serde_json::Value::Number(_) => "integer",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
+
+fn unexpected(expected: &'static str, value: &serde_json::Value) -> ValueError {
+    ValueError::UnexpectedJson {
+        expected,
+        found: json_kind(value),
+    }
+}
+
+fn invalid(representation: &'static str, value: String) -> ValueError {
+    ValueError::InvalidString {
+        representation,
+        value,
+    }
+}
+
+fn string(value: &serde_json::Value) -> Result<String, ValueError> {
+    value
+        .as_str()
+        .map(str::to_owned)
+        .ok_or_else(|| unexpected("string", value))
+}
+
+fn checked_string(
+    representation: &'static str,
+    value: &serde_json::Value,
+    check: impl Fn(&str) -> bool,
+) -> Result<String, ValueError> {
+    let s = string(value)?;
+    if check(&s) {
+        Ok(s)
+    } else {
+        Err(invalid(representation, s))
+    }
+}
+
+fn integer<T: TryFrom<i64>>(
+    representation: &'static str,
+    value: &serde_json::Value,
+) -> Result<T, ValueError> {
+    let out_of_range = || ValueError::OutOfRange {
+        representation,
+        value: value.to_string(),
+    };
+    match value {
+        serde_json::Value::Number(n) if n.is_u64() || n.is_i64() => n
+            .as_i64()
+            .and_then(|i| T::try_from(i).ok())
+            .ok_or_else(out_of_range),
+        _ => Err(unexpected("integer", value)),
+    }
+}
+
+fn number(value: &serde_json::Value) -> Result<f64, ValueError> {
+    value.as_f64().ok_or_else(|| unexpected("number", value))
+}
+
+fn float(f: f64) -> Result<serde_json::Value, ValueError> {
+    serde_json::Number::from_f64(f)
+        .map(serde_json::Value::Number)
+        .ok_or(ValueError::NonFiniteFloat(f))
+}
+
+fn digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// An optional minus sign, followed by decimal digits
+fn is_integer(s: &str) -> bool {
+    digits(s.strip_prefix('-').unwrap_or(s))
+}
+
+/// An integer, followed by an optional fractional part and an optional
+/// exponent, as in JSON numbers
+fn is_decimal(s: &str) -> bool {
+    let (mantissa, exponent) = match s.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (s, None),
+    };
+    let (integral, fractional) = match mantissa.split_once('.') {
+        Some((integral, fractional)) => (integral, Some(fractional)),
+        None => (mantissa, None),
+    };
+    is_integer(integral)
+        && fractional.is_none_or(digits)
+        && exponent.is_none_or(|e| is_integer(e.strip_prefix('+').unwrap_or(e)))
+}
+
+fn is_uuid(s: &str) -> bool {
+    let groups: Vec<&str> = s.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// A `YYYY-MM-DD` calendar date
+fn is_date(s: &str) -> bool {
+    calendar::parse_date(s).is_some()
+}
+
+/// A date and time, which is followed by a UTC offset if and only if
+/// `with_offset` is set
+fn is_timestamp(s: &str, with_offset: bool) -> bool {
+    calendar::parse_timestamp(s)
+        .is_some_and(|timestamp| timestamp.offset_minutes.is_some() == with_offset)
+}
+
+fn invalid_geojson(message: impl Into<String>) -> ValueError {
+    ValueError::InvalidGeoJSON(message.into())
+}
+
+fn object(
+    value: &serde_json::Value,
+) -> Result<&serde_json::Map<String, serde_json::Value>, ValueError> {
+    value.as_object().ok_or_else(|| unexpected("object", value))
+}
+
+fn member<'a>(
+    object: &'a serde_json::Map<String, serde_json::Value>,
+    name: &str,
+) -> Result<&'a serde_json::Value, ValueError> {
+    object
+        .get(name)
+        .ok_or_else(|| invalid_geojson(format!("missing {name} member")))
+}
+
+fn array_member<'a>(
+    object: &'a serde_json::Map<String, serde_json::Value>,
+    name: &str,
+) -> Result<&'a Vec<serde_json::Value>, ValueError> {
+    member(object, name)?
+        .as_array()
+        .ok_or_else(|| invalid_geojson(format!("{name} member is not an array")))
+}
+
+/// Any GeoJSON object with geographic coordinates: a geometry, a `Feature`
+/// or a `FeatureCollection`
+fn geojson(value: &serde_json::Value) -> Result<(), ValueError> {
+    let object = object(value)?;
+    match member(object, "type")?.as_str() {
+        Some("Feature") => feature(object),
+        Some("FeatureCollection") => {
+            for feature in array_member(object, "features")? {
+                let object = self::object(feature)?;
+                if member(object, "type")?.as_str() != Some("Feature") {
+                    return Err(invalid_geojson("features must be of type Feature"));
+                }
+                self::feature(object)?;
+            }
+            Ok(())
+        }
+        _ => geometry(value, true),
+    }
+}
+
+fn feature(object: &serde_json::Map<String, serde_json::Value>) -> Result<(), ValueError> {
+    match member(object, "geometry")? {
+        serde_json::Value::Null => {}
+        geometry => self::geometry(geometry, true)?,
+    }
+    match member(object, "properties")? {
+        serde_json::Value::Null | serde_json::Value::Object(_) => Ok(()),
+        _ => Err(invalid_geojson("properties member is not an object")),
+    }
+}
+
+/// A GeoJSON geometry object. If `geographic` is set, positions must be
+/// longitude and latitude coordinates within range.
+fn geometry(value: &serde_json::Value, geographic: bool) -> Result<(), ValueError> {
+    let object = object(value)?;
+    let Some(geometry_type) = member(object, "type")?.as_str() else {
+        return Err(invalid_geojson("type member is not a string"));
+    };
+    if geometry_type == "GeometryCollection" {
+        return array_member(object, "geometries")?
+            .iter()
+            .try_for_each(|geometry| self::geometry(geometry, geographic));
+    }
+    let coordinates = || member(object, "coordinates");
+    let position = |value: &serde_json::Value| position(value, geographic);
+    let line_string = |value: &serde_json::Value| {
+        value
+            .as_array()
+            .is_some_and(|positions| positions.len() != 1 && positions.iter().all(position))
+    };
+    let polygon = |value: &serde_json::Value| array_of(value, |ring| linear_ring(ring, geographic));
+    let valid = match geometry_type {
+        "Point" => position(coordinates()?),
+        "MultiPoint" => array_of(coordinates()?, position),
+        "LineString" => line_string(coordinates()?),
+        "MultiLineString" => array_of(coordinates()?, line_string),
+        "Polygon" => polygon(coordinates()?),
+        "MultiPolygon" => array_of(coordinates()?, polygon),
+        _ => {
+            return Err(invalid_geojson(format!(
+                "{geometry_type} is not a geometry type"
+            )))
+        }
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(invalid_geojson(format!(
+            "invalid coordinates for {geometry_type}"
+        )))
+    }
+}
+
+fn array_of(value: &serde_json::Value, element: impl Fn(&serde_json::Value) -> bool) -> bool {
+    value
+        .as_array()
+        .is_some_and(|elements| elements.iter().all(element))
+}
+
+/// At least two coordinates, which are longitude and latitude (in that
+/// order) if `geographic` is set
+fn position(value: &serde_json::Value, geographic: bool) -> bool {
+    let Some(coordinates) = value.as_array().and_then(|array| {
+        array
+            .iter()
+            .map(serde_json::Value::as_f64)
+            .collect::<Option<Vec<_>>>()
+    }) else {
+        return false;
+    };
+    match coordinates[..] {
+        [longitude, latitude, ..] => {
+            !geographic
+                || ((-180.0..=180.0).contains(&longitude) && (-90.0..=90.0).contains(&latitude))
+        }
+        _ => false,
+    }
+}
+
+/// A closed line string with at least four positions
+fn linear_ring(value: &serde_json::Value, geographic: bool) -> bool {
+    value.as_array().is_some_and(|positions| {
+        positions.len() >= 4
+            && positions.first() == positions.last()
+            && positions.iter().all(|p| position(p, geographic))
+    })
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | u32::from(*b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(char::from(
+                    BASE64_ALPHABET[(n >> (18 - 6 * i) & 63) as usize],
+                ));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(4) {
+        return None;
+    }
+    let mut out = Vec::with_capacity(s.len() / 4 * 3);
+    let chunks: Vec<&[u8]> = s.as_bytes().chunks(4).collect();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let padding = chunk.iter().rev().take_while(|b| **b == b'=').count();
+        if padding > 2 || (padding > 0 && index + 1 != chunks.len()) {
+            return None;
+        }
+        let mut n = 0u32;
+        for (i, b) in chunk[..4 - padding].iter().enumerate() {
+            let sextet = BASE64_ALPHABET.iter().position(|a| a == b)?;
+            n |= u32::try_from(sextet).ok()? << (18 - 6 * i);
+        }
+        out.extend(n.to_be_bytes()[1..4 - padding].iter());
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decode_numbers() {
+        assert_eq!(
+            ScalarValue::decode(&TypeRepresentation::Int8, &json!(-128)),
+            Ok(ScalarValue::Int8(-128))
+        );
+        assert_eq!(
+            ScalarValue::decode(&TypeRepresentation::Int8, &json!(128)),
+            Err(ValueError::OutOfRange {
+                representation: "int8",
+                value: "128".into()
+            })
+        );
+        assert_eq!(
+            ScalarValue::decode(&TypeRepresentation::Int32, &json!(1.5)),
+            Err(ValueError::UnexpectedJson {
+                expected: "integer",
+                found: "number"
+            })
+        );
+        assert_eq!(
+            ScalarValue::decode(&TypeRepresentation::Int64, &json!("-9223372036854775808")),
+            Ok(ScalarValue::Int64(i64::MIN))
+        );
+        assert_eq!(
+            ScalarValue::decode(&TypeRepresentation::Int64, &json!(1)),
+            Err(ValueError::UnexpectedJson {
+                expected: "string",
+                found: "integer"
+            })
+        );
+        assert!(
+            ScalarValue::decode(&TypeRepresentation::Int64, &json!("9223372036854775808")).is_err()
+        );
+        assert!(ScalarValue::decode(&TypeRepresentation::Float32, &json!(1e300)).is_err());
+        assert_eq!(
+            ScalarValue::decode(&TypeRepresentation::BigDecimal, &json!("-12.50e+3")),
+            Ok(ScalarValue::BigDecimal("-12.50e+3".into()))
+        );
+        for invalid in ["", "1.", ".5", "+1", "1e", "NaN"] {
+            assert!(
+                ScalarValue::decode(&TypeRepresentation::BigDecimal, &json!(invalid)).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_strings() {
+        let valid = [
+            (
+                TypeRepresentation::UUID,
+                "123e4567-E89B-12d3-a456-426614174000",
+            ),
+            (TypeRepresentation::Date, "2024-02-29"),
+            (TypeRepresentation::Timestamp, "2024-02-29T23:59:60.123"),
+            (TypeRepresentation::TimestampTZ, "2024-02-29 12:00:00Z"),
+            (TypeRepresentation::TimestampTZ, "2024-02-29T12:00:00-05:30"),
+            (TypeRepresentation::TimestampTZ, "2024-02-29T12:00:00+0100"),
+        ];
+        for (representation, value) in valid {
+            assert!(
+                ScalarValue::decode(&representation, &json!(value)).is_ok(),
+                "{value}"
+            );
+        }
+        let invalid = [
+            (TypeRepresentation::UUID, "123e4567e89b12d3a456426614174000"),
+            (TypeRepresentation::Date, "2023-02-29"),
+            (TypeRepresentation::Date, "2024-2-1"),
+            (TypeRepresentation::Timestamp, "2024-02-29T12:00:00Z"),
+            (TypeRepresentation::TimestampTZ, "2024-02-29T12:00:00"),
+            (TypeRepresentation::TimestampTZ, "2024-02-29T24:00:00Z"),
+            (TypeRepresentation::TimestampTZ, "2024-02-29T12:00:00+1é2"),
+            (TypeRepresentation::TimestampTZ, "2024-02-29T12:00:00+é12"),
+        ];
+        for (representation, value) in invalid {
+            assert!(
+                ScalarValue::decode(&representation, &json!(value)).is_err(),
+                "{value}"
+            );
+        }
+        assert_eq!(
+            ScalarValue::decode(
+                &TypeRepresentation::Enum {
+                    one_of: vec!["a".into()]
+                },
+                &json!("b")
+            ),
+            Err(ValueError::UnknownEnumValue("b".into()))
+        );
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        for (bytes, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (&[0xff, 0xfe, 0x00][..], "//4A"),
+        ] {
+            let value = ScalarValue::Bytes(bytes.to_vec());
+            assert_eq!(value.encode(), Ok(json!(encoded)));
+            assert_eq!(
+                ScalarValue::decode(&TypeRepresentation::Bytes, &json!(encoded)),
+                Ok(value)
+            );
+        }
+        for invalid in ["Zg", "Zg=a", "Z===", "Zg==Zg==", "Zm9v!A=="] {
+            assert!(
+                ScalarValue::decode(&TypeRepresentation::Bytes, &json!(invalid)).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(ScalarValue::Int64(42).encode(), Ok(json!("42")));
+        assert_eq!(ScalarValue::Float32(1.5).encode(), Ok(json!(1.5)));
+        assert_eq!(
+            ScalarValue::Float64(f64::NAN)
+                .encode()
+                .map_err(|e| e.to_string()),
+            Err("NaN cannot be encoded as a JSON number".into())
+        );
+    }
+
+    #[test]
+    fn test_decode_geojson() {
+        let point = json!({ "type": "Point", "coordinates": [-122.4, 37.8] });
+        let polygon = json!({
+            "type": "Polygon",
+            "coordinates": [[[0, 0], [1, 0], [1, 1], [0, 0]]],
+        });
+        let feature = json!({ "type": "Feature", "geometry": point, "properties": null });
+        for value in [&point, &polygon] {
+            assert!(ScalarValue::decode(&TypeRepresentation::Geometry, value).is_ok());
+        }
+        for value in [
+            &point,
+            &feature,
+            &json!({ "type": "FeatureCollection", "features": [feature] }),
+            &json!({ "type": "GeometryCollection", "geometries": [point, polygon] }),
+        ] {
+            assert!(ScalarValue::decode(&TypeRepresentation::Geography, value).is_ok());
+        }
+        assert_eq!(
+            ScalarValue::decode(&TypeRepresentation::Geometry, &feature),
+            Err(ValueError::InvalidGeoJSON(
+                "Feature is not a geometry type".into()
+            ))
+        );
+        let out_of_range = json!({ "type": "Point", "coordinates": [200, 0] });
+        assert!(ScalarValue::decode(&TypeRepresentation::Geometry, &out_of_range).is_ok());
+        assert!(ScalarValue::decode(&TypeRepresentation::Geography, &out_of_range).is_err());
+        let open_ring = json!({
+            "type": "Polygon",
+            "coordinates": [[[0, 0], [1, 0], [1, 1], [0, 1]]],
+        });
+        assert_eq!(
+            ScalarValue::decode(&TypeRepresentation::Geometry, &open_ring),
+            Err(ValueError::InvalidGeoJSON(
+                "invalid coordinates for Polygon".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_vector_round_trip() {
+        let representation = TypeRepresentation::Vector { dimensions: 3 };
+        let value = ScalarValue::Vector(vec![0.5, -1.0, 2.0]);
+        assert_eq!(
+            ScalarValue::decode(&representation, &json!([0.5, -1, 2])),
+            Ok(value.clone())
+        );
+        assert_eq!(value.encode(), Ok(json!([0.5, -1.0, 2.0])));
+        assert_eq!(
+            ScalarValue::decode(&representation, &json!([0.5, 1])),
+            Err(ValueError::WrongDimensions {
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            ScalarValue::decode(&representation, &json!([0.5, "1", 2])),
+            Err(ValueError::UnexpectedJson {
+                expected: "number",
+                found: "string"
+            })
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Runtime checks of specification version compatibility.
+//!
+//! A client may send the version of the specification it intends to use in the
+//! [`VERSION_HEADER_NAME`](crate::VERSION_HEADER_NAME) header, and a connector declares the version it
+//! implements in [`CapabilitiesResponse::version`]. In both cases, the versions
+//! are compatible if the implemented version satisfies the semver range
+//! `^{requested}`.
+//!
+//! Pre-release versions follow the usual semver rules: a pre-release
+//! implementation only satisfies a request for a pre-release of the same
+//! `major.minor.patch` version, and only if it is not older than the request.
+//! For example, `0.3.0-rc.2` satisfies `^0.3.0-rc.1`, but not `^0.3.0` or
+//! `^0.2.0`.
+
+use crate::{CapabilitiesResponse, ErrorResponse, VERSION};
+
+/// The reason why a requested version cannot be served
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum VersionError {
+    #[error("invalid version {0:?}, expected a semver version string")]
+    InvalidVersion(String),
+    #[error("NDC version range ^{requested} does not match implemented version {implemented}")]
+    Incompatible {
+        requested: semver::Version,
+        implemented: semver::Version,
+    },
+}
+
+impl From<&VersionError> for ErrorResponse {
+    fn from(error: &VersionError) -> Self {
+        let details = match error {
+            VersionError::InvalidVersion(version) => serde_json::json!({
+                "version": version,
+            }),
+            VersionError::Incompatible {
+                requested,
+                implemented,
+            } => serde_json::json!({
+                "requested_version": requested.to_string(),
+                "implemented_version": implemented.to_string(),
+            }),
+        };
+        ErrorResponse {
+            message: error.to_string(),
+            details,
+        }
+    }
+}
+
+/// Check that the `implemented` version of the specification is compatible
+/// with the `requested` version
+pub fn check_version(requested: &str, implemented: &str) -> Result<(), VersionError> {
+    let parse = |version: &str| {
+        semver::Version::parse(version.trim())
+            .map_err(|_| VersionError::InvalidVersion(version.into()))
+    };
+    let requested = parse(requested)?;
+    let implemented = parse(implemented)?;
+
+    let comparator = semver::Comparator {
+        op: semver::Op::Caret,
+        major: requested.major,
+        minor: Some(requested.minor),
+        patch: Some(requested.patch),
+        pre: requested.pre.clone(),
+    };
+    if comparator.matches(&implemented) {
+        Ok(())
+    } else {
+        Err(VersionError::Incompatible {
+            requested,
+            implemented,
+        })
+    }
+}
+
+/// Check the value of a [`VERSION_HEADER_NAME`](crate::VERSION_HEADER_NAME) header sent by a client,
+/// against the version of the specification implemented by this crate.
+///
+/// Connectors should respond with `400 Bad Request` and the corresponding
+/// [`ErrorResponse`] if the check fails. No check is needed when the header
+/// is absent.
+pub fn check_version_header(value: &[u8]) -> Result<(), VersionError> {
+    let value = std::str::from_utf8(value)
+        .map_err(|_| VersionError::InvalidVersion(String::from_utf8_lossy(value).into()))?;
+    check_version(value, VERSION)
+}
+
+/// Check that the version of the specification claimed by a connector is
+/// compatible with the version implemented by this crate. Clients should
+/// refuse to use connectors for which the check fails.
+pub fn check_capabilities_version(capabilities: &CapabilitiesResponse) -> Result<(), VersionError> {
+    check_version(VERSION, &capabilities.version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_version() {
+        assert_eq!(check_version("0.2.0", "0.2.13"), Ok(()));
+        assert_eq!(check_version("0.2.13", "0.2.13"), Ok(()));
+        assert!(matches!(
+            check_version("0.2.14", "0.2.13"),
+            Err(VersionError::Incompatible { .. })
+        ));
+        assert!(matches!(
+            check_version("0.1.6", "0.2.13"),
+            Err(VersionError::Incompatible { .. })
+        ));
+        assert_eq!(
+            check_version("latest", "0.2.13"),
+            Err(VersionError::InvalidVersion("latest".into()))
+        );
+    }
+
+    #[test]
+    fn test_check_pre_release_version() {
+        assert_eq!(check_version("0.3.0-rc.1", "0.3.0-rc.2"), Ok(()));
+        assert_eq!(check_version("0.3.0-rc.1", "0.3.0"), Ok(()));
+        assert_eq!(check_version("0.3.0-rc.1", "0.3.1"), Ok(()));
+        assert!(check_version("0.3.0-rc.2", "0.3.0-rc.1").is_err());
+        assert!(check_version("0.3.0", "0.3.0-rc.2").is_err());
+        assert!(check_version("0.2.0", "0.3.0-rc.2").is_err());
+    }
+
+    #[test]
+    fn test_error_response() {
+        let error = check_version("0.1.0", "0.2.13").unwrap_err();
+        assert_eq!(
+            ErrorResponse::from(&error),
+            ErrorResponse {
+                message: "NDC version range ^0.1.0 does not match implemented version 0.2.13"
+                    .into(),
+                details: serde_json::json!({
+                    "requested_version": "0.1.0",
+                    "implemented_version": "0.2.13",
+                }),
+            }
+        );
+    }
+}
@@ -0,0 +1,1037 @@
+//! Traversal of query and relation trees by shared reference.
+//!
+//! Implement [`Visit`] and override the methods for the nodes of interest.
+//! The default implementation of each method delegates to the free function
+//! of the same name, which visits each child node in turn, so an overriding
+//! method should call that function itself if it wants traversal to continue
+//! into its children.
+//!
+//! For example, to collect the names of all variables used in a query:
+//!
+//! ```
+//! use ndc_models::visit::{self, Visit};
+//! use ndc_models::{Argument, ComparisonValue, VariableName};
+//!
+//! #[derive(Default)]
+//! struct Variables(Vec<VariableName>);
+//!
+//! impl Visit for Variables {
+//!     fn visit_argument(&mut self, argument: &Argument) {
+//!         if let Argument::Variable { name } = argument {
+//!             self.0.push(name.clone());
+//!         }
+//!     }
+//!
+//!     fn visit_comparison_value(&mut self, value: &ComparisonValue) {
+//!         if let ComparisonValue::Variable { name } = value {
+//!             self.0.push(name.clone());
+//!         }
+//!         visit::visit_comparison_value(self, value);
+//!     }
+//! }
+//! ```
+//!
+//! See [`crate::visit_mut`] for the corresponding mutable traversal.
+
+use crate::{
+    Aggregate, Argument, ArrayComparison, CaseWhen, ComparisonTarget, ComparisonValue, Dimension,
+    ExistsInCollection, Expression, Field, GroupComparisonTarget, GroupComparisonValue,
+    GroupExpression, GroupOrderByTarget, Grouping, JoinOn, MutationOperation, MutationRequest,
+    NestedField, OrderBy, OrderByTarget, PathElement, Query, QueryRequest, Relation,
+    RelationalExpression, RelationalLiteral, RelationalQuery, Relationship, RelationshipArgument,
+    Sort,
+};
+
+/// A visitor over query and relation trees, by shared reference
+pub trait Visit {
+    fn visit_query_request(&mut self, request: &QueryRequest) {
+        visit_query_request(self, request);
+    }
+
+    fn visit_mutation_request(&mut self, request: &MutationRequest) {
+        visit_mutation_request(self, request);
+    }
+
+    fn visit_mutation_operation(&mut self, operation: &MutationOperation) {
+        visit_mutation_operation(self, operation);
+    }
+
+    fn visit_relationship(&mut self, relationship: &Relationship) {
+        visit_relationship(self, relationship);
+    }
+
+    fn visit_query(&mut self, query: &Query) {
+        visit_query(self, query);
+    }
+
+    fn visit_field(&mut self, field: &Field) {
+        visit_field(self, field);
+    }
+
+    fn visit_nested_field(&mut self, nested_field: &NestedField) {
+        visit_nested_field(self, nested_field);
+    }
+
+    fn visit_argument(&mut self, _argument: &Argument) {}
+
+    fn visit_relationship_argument(&mut self, _argument: &RelationshipArgument) {}
+
+    fn visit_path_element(&mut self, path_element: &PathElement) {
+        visit_path_element(self, path_element);
+    }
+
+    fn visit_aggregate(&mut self, aggregate: &Aggregate) {
+        visit_aggregate(self, aggregate);
+    }
+
+    fn visit_order_by(&mut self, order_by: &OrderBy) {
+        visit_order_by(self, order_by);
+    }
+
+    fn visit_order_by_target(&mut self, target: &OrderByTarget) {
+        visit_order_by_target(self, target);
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        visit_expression(self, expression);
+    }
+
+    fn visit_exists_in_collection(&mut self, in_collection: &ExistsInCollection) {
+        visit_exists_in_collection(self, in_collection);
+    }
+
+    fn visit_comparison_target(&mut self, target: &ComparisonTarget) {
+        visit_comparison_target(self, target);
+    }
+
+    fn visit_comparison_value(&mut self, value: &ComparisonValue) {
+        visit_comparison_value(self, value);
+    }
+
+    fn visit_array_comparison(&mut self, comparison: &ArrayComparison) {
+        visit_array_comparison(self, comparison);
+    }
+
+    fn visit_grouping(&mut self, grouping: &Grouping) {
+        visit_grouping(self, grouping);
+    }
+
+    fn visit_dimension(&mut self, dimension: &Dimension) {
+        visit_dimension(self, dimension);
+    }
+
+    fn visit_group_expression(&mut self, expression: &GroupExpression) {
+        visit_group_expression(self, expression);
+    }
+
+    fn visit_group_comparison_target(&mut self, target: &GroupComparisonTarget) {
+        visit_group_comparison_target(self, target);
+    }
+
+    fn visit_group_comparison_value(&mut self, _value: &GroupComparisonValue) {}
+
+    fn visit_group_order_by_target(&mut self, target: &GroupOrderByTarget) {
+        visit_group_order_by_target(self, target);
+    }
+
+    fn visit_relational_query(&mut self, query: &RelationalQuery) {
+        visit_relational_query(self, query);
+    }
+
+    fn visit_relation(&mut self, relation: &Relation) {
+        visit_relation(self, relation);
+    }
+
+    fn visit_relational_expression(&mut self, expr: &RelationalExpression) {
+        visit_relational_expression(self, expr);
+    }
+
+    fn visit_relational_literal(&mut self, _literal: &RelationalLiteral) {}
+
+    fn visit_sort(&mut self, sort: &Sort) {
+        visit_sort(self, sort);
+    }
+
+    fn visit_join_on(&mut self, join_on: &JoinOn) {
+        visit_join_on(self, join_on);
+    }
+
+    fn visit_case_when(&mut self, case_when: &CaseWhen) {
+        visit_case_when(self, case_when);
+    }
+}
+
+pub fn visit_query_request<V: Visit + ?Sized>(v: &mut V, request: &QueryRequest) {
+    for argument in request.arguments.values() {
+        v.visit_argument(argument);
+    }
+    for relationship in request.collection_relationships.values() {
+        v.visit_relationship(relationship);
+    }
+    v.visit_query(&request.query);
+}
+
+pub fn visit_mutation_request<V: Visit + ?Sized>(v: &mut V, request: &MutationRequest) {
+    for relationship in request.collection_relationships.values() {
+        v.visit_relationship(relationship);
+    }
+    for operation in &request.operations {
+        v.visit_mutation_operation(operation);
+    }
+}
+
+pub fn visit_mutation_operation<V: Visit + ?Sized>(v: &mut V, operation: &MutationOperation) {
+    match operation {
+        MutationOperation::Procedure { fields, .. } => {
+            if let Some(fields) = fields {
+                v.visit_nested_field(fields);
+            }
+        }
+        MutationOperation::RelationalInsert(request) => {
+            for argument in request.arguments.values() {
+                v.visit_argument(argument);
+            }
+        }
+        MutationOperation::RelationalUpsert(request) => {
+            for argument in request.arguments.values() {
+                v.visit_argument(argument);
+            }
+        }
+        MutationOperation::RelationalUpdate(request) => {
+            for argument in request.arguments.values() {
+                v.visit_argument(argument);
+            }
+            v.visit_relation(&request.relation);
+        }
+        MutationOperation::RelationalDelete(request) => {
+            for argument in request.arguments.values() {
+                v.visit_argument(argument);
+            }
+            v.visit_relation(&request.relation);
+        }
+    }
+}
+
+pub fn visit_relationship<V: Visit + ?Sized>(v: &mut V, relationship: &Relationship) {
+    for argument in relationship.arguments.values() {
+        v.visit_relationship_argument(argument);
+    }
+}
+
+pub fn visit_query<V: Visit + ?Sized>(v: &mut V, query: &Query) {
+    if let Some(aggregates) = &query.aggregates {
+        for aggregate in aggregates.values() {
+            v.visit_aggregate(aggregate);
+        }
+    }
+    if let Some(fields) = &query.fields {
+        for field in fields.values() {
+            v.visit_field(field);
+        }
+    }
+    if let Some(order_by) = &query.order_by {
+        v.visit_order_by(order_by);
+    }
+    if let Some(predicate) = &query.predicate {
+        v.visit_expression(predicate);
+    }
+    if let Some(groups) = &query.groups {
+        v.visit_grouping(groups);
+    }
+}
+
+pub fn visit_field<V: Visit + ?Sized>(v: &mut V, field: &Field) {
+    match field {
+        Field::Column {
+            fields, arguments, ..
+        } => {
+            for argument in arguments.values() {
+                v.visit_argument(argument);
+            }
+            if let Some(fields) = fields {
+                v.visit_nested_field(fields);
+            }
+        }
+        Field::Relationship {
+            query, arguments, ..
+        } => {
+            for argument in arguments.values() {
+                v.visit_relationship_argument(argument);
+            }
+            v.visit_query(query);
+        }
+    }
+}
+
+pub fn visit_nested_field<V: Visit + ?Sized>(v: &mut V, nested_field: &NestedField) {
+    match nested_field {
+        NestedField::Object(nested_object) => {
+            for field in nested_object.fields.values() {
+                v.visit_field(field);
+            }
+        }
+        NestedField::Array(nested_array) => v.visit_nested_field(&nested_array.fields),
+        NestedField::Collection(nested_collection) => v.visit_query(&nested_collection.query),
+    }
+}
+
+pub fn visit_path_element<V: Visit + ?Sized>(v: &mut V, path_element: &PathElement) {
+    for argument in path_element.arguments.values() {
+        v.visit_relationship_argument(argument);
+    }
+    if let Some(predicate) = &path_element.predicate {
+        v.visit_expression(predicate);
+    }
+}
+
+pub fn visit_aggregate<V: Visit + ?Sized>(v: &mut V, aggregate: &Aggregate) {
+    match aggregate {
+        Aggregate::ColumnCount {
+            arguments, filter, ..
+        }
+        | Aggregate::SingleColumn {
+            arguments, filter, ..
+        } => {
+            for argument in arguments.values() {
+                v.visit_argument(argument);
+            }
+            if let Some(filter) = filter {
+                v.visit_expression(filter);
+            }
+        }
+        Aggregate::StarCount { filter } => {
+            if let Some(filter) = filter {
+                v.visit_expression(filter);
+            }
+        }
+    }
+}
+
+pub fn visit_order_by<V: Visit + ?Sized>(v: &mut V, order_by: &OrderBy) {
+    for element in &order_by.elements {
+        v.visit_order_by_target(&element.target);
+    }
+}
+
+pub fn visit_order_by_target<V: Visit + ?Sized>(v: &mut V, target: &OrderByTarget) {
+    match target {
+        OrderByTarget::Column {
+            path, arguments, ..
+        } => {
+            for path_element in path {
+                v.visit_path_element(path_element);
+            }
+            for argument in arguments.values() {
+                v.visit_argument(argument);
+            }
+        }
+        OrderByTarget::Aggregate { path, aggregate } => {
+            for path_element in path {
+                v.visit_path_element(path_element);
+            }
+            v.visit_aggregate(aggregate);
+        }
+        OrderByTarget::Relevance { value, .. }
+        | OrderByTarget::Distance { value, .. }
+        | OrderByTarget::Similarity { value, .. } => {
+            v.visit_comparison_value(value);
+        }
+    }
+}
+
+pub fn visit_expression<V: Visit + ?Sized>(v: &mut V, expression: &Expression) {
+    match expression {
+        Expression::And { expressions } | Expression::Or { expressions } => {
+            for expression in expressions {
+                v.visit_expression(expression);
+            }
+        }
+        Expression::Not { expression } => v.visit_expression(expression),
+        Expression::UnaryComparisonOperator { column, .. } => v.visit_comparison_target(column),
+        Expression::BinaryComparisonOperator { column, value, .. } => {
+            v.visit_comparison_target(column);
+            v.visit_comparison_value(value);
+        }
+        Expression::ArrayComparison { column, comparison } => {
+            v.visit_comparison_target(column);
+            v.visit_array_comparison(comparison);
+        }
+        Expression::Exists {
+            in_collection,
+            predicate,
+        } => {
+            v.visit_exists_in_collection(in_collection);
+            if let Some(predicate) = predicate {
+                v.visit_expression(predicate);
+            }
+        }
+    }
+}
+
+pub fn visit_exists_in_collection<V: Visit + ?Sized>(
+    v: &mut V,
+    in_collection: &ExistsInCollection,
+) {
+    match in_collection {
+        ExistsInCollection::Related { arguments, .. }
+        | ExistsInCollection::Unrelated { arguments, .. } => {
+            for argument in arguments.values() {
+                v.visit_relationship_argument(argument);
+            }
+        }
+        ExistsInCollection::NestedCollection { arguments, .. }
+        | ExistsInCollection::NestedScalarCollection { arguments, .. } => {
+            for argument in arguments.values() {
+                v.visit_argument(argument);
+            }
+        }
+    }
+}
+
+pub fn visit_comparison_target<V: Visit + ?Sized>(v: &mut V, target: &ComparisonTarget) {
+    match target {
+        ComparisonTarget::Column { arguments, .. } => {
+            for argument in arguments.values() {
+                v.visit_argument(argument);
+            }
+        }
+        ComparisonTarget::Aggregate { path, aggregate } => {
+            for path_element in path {
+                v.visit_path_element(path_element);
+            }
+            v.visit_aggregate(aggregate);
+        }
+    }
+}
+
+pub fn visit_comparison_value<V: Visit + ?Sized>(v: &mut V, value: &ComparisonValue) {
+    match value {
+        ComparisonValue::Column {
+            path, arguments, ..
+        } => {
+            for path_element in path {
+                v.visit_path_element(path_element);
+            }
+            for argument in arguments.values() {
+                v.visit_argument(argument);
+            }
+        }
+        ComparisonValue::Scalar { .. } | ComparisonValue::Variable { .. } => {}
+    }
+}
+
+pub fn visit_array_comparison<V: Visit + ?Sized>(v: &mut V, comparison: &ArrayComparison) {
+    match comparison {
+        ArrayComparison::Contains { value } => v.visit_comparison_value(value),
+        ArrayComparison::IsEmpty => {}
+    }
+}
+
+pub fn visit_grouping<V: Visit + ?Sized>(v: &mut V, grouping: &Grouping) {
+    for dimension in &grouping.dimensions {
+        v.visit_dimension(dimension);
+    }
+    for aggregate in grouping.aggregates.values() {
+        v.visit_aggregate(aggregate);
+    }
+    if let Some(predicate) = &grouping.predicate {
+        v.visit_group_expression(predicate);
+    }
+    if let Some(order_by) = &grouping.order_by {
+        for element in &order_by.elements {
+            v.visit_group_order_by_target(&element.target);
+        }
+    }
+}
+
+pub fn visit_dimension<V: Visit + ?Sized>(v: &mut V, dimension: &Dimension) {
+    match dimension {
+        Dimension::Column {
+            path, arguments, ..
+        } => {
+            for path_element in path {
+                v.visit_path_element(path_element);
+            }
+            for argument in arguments.values() {
+                v.visit_argument(argument);
+            }
+        }
+    }
+}
+
+pub fn visit_group_expression<V: Visit + ?Sized>(v: &mut V, expression: &GroupExpression) {
+    match expression {
+        GroupExpression::And { expressions } | GroupExpression::Or { expressions } => {
+            for expression in expressions {
+                v.visit_group_expression(expression);
+            }
+        }
+        GroupExpression::Not { expression } => v.visit_group_expression(expression),
+        GroupExpression::UnaryComparisonOperator { target, .. } => {
+            v.visit_group_comparison_target(target);
+        }
+        GroupExpression::BinaryComparisonOperator { target, value, .. } => {
+            v.visit_group_comparison_target(target);
+            v.visit_group_comparison_value(value);
+        }
+    }
+}
+
+pub fn visit_group_comparison_target<V: Visit + ?Sized>(v: &mut V, target: &GroupComparisonTarget) {
+    match target {
+        GroupComparisonTarget::Aggregate { aggregate } => v.visit_aggregate(aggregate),
+    }
+}
+
+pub fn visit_group_order_by_target<V: Visit + ?Sized>(v: &mut V, target: &GroupOrderByTarget) {
+    match target {
+        GroupOrderByTarget::Dimension { .. } => {}
+        GroupOrderByTarget::Aggregate { aggregate } => v.visit_aggregate(aggregate),
+    }
+}
+
+pub fn visit_relational_query<V: Visit + ?Sized>(v: &mut V, query: &RelationalQuery) {
+    v.visit_relation(&query.root_relation);
+}
+
+pub fn visit_relation<V: Visit + ?Sized>(v: &mut V, relation: &Relation) {
+    match relation {
+        Relation::From { arguments, .. } => {
+            for argument in arguments.values() {
+                v.visit_relational_literal(argument);
+            }
+        }
+        Relation::Paginate { input, .. } => v.visit_relation(input),
+        Relation::Project { input, exprs } | Relation::Window { input, exprs } => {
+            v.visit_relation(input);
+            for expr in exprs {
+                v.visit_relational_expression(expr);
+            }
+        }
+        Relation::Filter { input, predicate } => {
+            v.visit_relation(input);
+            v.visit_relational_expression(predicate);
+        }
+        Relation::Sort { input, exprs } => {
+            v.visit_relation(input);
+            for sort in exprs {
+                v.visit_sort(sort);
+            }
+        }
+        Relation::Join {
+            left, right, on, ..
+        } => {
+            v.visit_relation(left);
+            v.visit_relation(right);
+            for join_on in on {
+                v.visit_join_on(join_on);
+            }
+        }
+        Relation::Aggregate {
+            input,
+            group_by,
+            aggregates,
+        } => {
+            v.visit_relation(input);
+            for expr in group_by {
+                v.visit_relational_expression(expr);
+            }
+            for expr in aggregates {
+                v.visit_relational_expression(expr);
+            }
+        }
+        Relation::Union { relations } => {
+            for relation in relations {
+                v.visit_relation(relation);
+            }
+        }
+    }
+}
+
+pub fn visit_sort<V: Visit + ?Sized>(v: &mut V, sort: &Sort) {
+    v.visit_relational_expression(&sort.expr);
+}
+
+pub fn visit_join_on<V: Visit + ?Sized>(v: &mut V, join_on: &JoinOn) {
+    v.visit_relational_expression(&join_on.left);
+    v.visit_relational_expression(&join_on.right);
+}
+
+pub fn visit_case_when<V: Visit + ?Sized>(v: &mut V, case_when: &CaseWhen) {
+    v.visit_relational_expression(&case_when.when);
+    v.visit_relational_expression(&case_when.then);
+}
+
+#[allow(clippy::too_many_lines)]
+pub fn visit_relational_expression<V: Visit + ?Sized>(v: &mut V, expr: &RelationalExpression) {
+    match expr {
+        RelationalExpression::Literal { literal } => v.visit_relational_literal(literal),
+        RelationalExpression::Column { .. }
+        | RelationalExpression::CurrentDate
+        | RelationalExpression::CurrentTime
+        | RelationalExpression::CurrentTimestamp
+        | RelationalExpression::Random => {}
+        RelationalExpression::Case {
+            scrutinee,
+            when,
+            default,
+        } => {
+            if let Some(scrutinee) = scrutinee {
+                v.visit_relational_expression(scrutinee);
+            }
+            for case_when in when {
+                v.visit_case_when(case_when);
+            }
+            if let Some(default) = default {
+                v.visit_relational_expression(default);
+            }
+        }
+        RelationalExpression::And { left, right }
+        | RelationalExpression::Or { left, right }
+        | RelationalExpression::Eq { left, right }
+        | RelationalExpression::NotEq { left, right }
+        | RelationalExpression::IsDistinctFrom { left, right }
+        | RelationalExpression::IsNotDistinctFrom { left, right }
+        | RelationalExpression::Lt { left, right }
+        | RelationalExpression::LtEq { left, right }
+        | RelationalExpression::Gt { left, right }
+        | RelationalExpression::GtEq { left, right }
+        | RelationalExpression::Plus { left, right }
+        | RelationalExpression::Minus { left, right }
+        | RelationalExpression::Multiply { left, right }
+        | RelationalExpression::Divide { left, right }
+        | RelationalExpression::Modulo { left, right }
+        | RelationalExpression::BinaryConcat { left, right } => {
+            v.visit_relational_expression(left);
+            v.visit_relational_expression(right);
+        }
+        RelationalExpression::Not { expr }
+        | RelationalExpression::IsNotNull { expr }
+        | RelationalExpression::IsNull { expr }
+        | RelationalExpression::IsTrue { expr }
+        | RelationalExpression::IsFalse { expr }
+        | RelationalExpression::IsNotTrue { expr }
+        | RelationalExpression::IsNotFalse { expr }
+        | RelationalExpression::IsNaN { expr }
+        | RelationalExpression::IsZero { expr }
+        | RelationalExpression::Negate { expr }
+        | RelationalExpression::Cast { expr, .. }
+        | RelationalExpression::TryCast { expr, .. }
+        | RelationalExpression::Abs { expr }
+        | RelationalExpression::Ceil { expr }
+        | RelationalExpression::Cos { expr }
+        | RelationalExpression::DatePart { expr, .. }
+        | RelationalExpression::Exp { expr }
+        | RelationalExpression::Floor { expr }
+        | RelationalExpression::Ln { expr }
+        | RelationalExpression::Log10 { expr }
+        | RelationalExpression::Log2 { expr }
+        | RelationalExpression::Sqrt { expr }
+        | RelationalExpression::Tan { expr }
+        | RelationalExpression::ToDate { expr }
+        | RelationalExpression::ToTimestamp { expr }
+        | RelationalExpression::ToLower { expr }
+        | RelationalExpression::ToUpper { expr }
+        | RelationalExpression::Average { expr }
+        | RelationalExpression::BoolAnd { expr }
+        | RelationalExpression::BoolOr { expr }
+        | RelationalExpression::Count { expr, .. }
+        | RelationalExpression::Max { expr }
+        | RelationalExpression::Median { expr }
+        | RelationalExpression::Min { expr }
+        | RelationalExpression::Sum { expr }
+        | RelationalExpression::Var { expr }
+        | RelationalExpression::Stddev { expr }
+        | RelationalExpression::StddevPop { expr }
+        | RelationalExpression::ApproxPercentileCont { expr, .. }
+        | RelationalExpression::ApproxDistinct { expr } => v.visit_relational_expression(expr),
+        RelationalExpression::FirstValue { expr, order_by }
+        | RelationalExpression::LastValue { expr, order_by }
+        | RelationalExpression::StringAgg { expr, order_by, .. }
+        | RelationalExpression::ArrayAgg { expr, order_by, .. } => {
+            v.visit_relational_expression(expr);
+            if let Some(order_by) = order_by {
+                for sort in order_by {
+                    v.visit_sort(sort);
+                }
+            }
+        }
+        RelationalExpression::In { expr, list } | RelationalExpression::NotIn { expr, list } => {
+            v.visit_relational_expression(expr);
+            for expr in list {
+                v.visit_relational_expression(expr);
+            }
+        }
+        RelationalExpression::Like { expr, pattern }
+        | RelationalExpression::NotLike { expr, pattern }
+        | RelationalExpression::ILike { expr, pattern }
+        | RelationalExpression::NotILike { expr, pattern } => {
+            v.visit_relational_expression(expr);
+            v.visit_relational_expression(pattern);
+        }
+        RelationalExpression::Between { low, expr, high }
+        | RelationalExpression::NotBetween { low, expr, high } => {
+            v.visit_relational_expression(low);
+            v.visit_relational_expression(expr);
+            v.visit_relational_expression(high);
+        }
+        RelationalExpression::Contains { str, search_str } => {
+            v.visit_relational_expression(str);
+            v.visit_relational_expression(search_str);
+        }
+        RelationalExpression::ArrayElement { column, .. }
+        | RelationalExpression::GetField { column, .. } => v.visit_relational_expression(column),
+        RelationalExpression::BTrim { str, trim_str }
+        | RelationalExpression::LTrim { str, trim_str }
+        | RelationalExpression::RTrim { str, trim_str } => {
+            v.visit_relational_expression(str);
+            if let Some(trim_str) = trim_str {
+                v.visit_relational_expression(trim_str);
+            }
+        }
+        RelationalExpression::CharacterLength { str } | RelationalExpression::Reverse { str } => {
+            v.visit_relational_expression(str);
+        }
+        RelationalExpression::Coalesce { exprs }
+        | RelationalExpression::Concat { exprs }
+        | RelationalExpression::Greatest { exprs }
+        | RelationalExpression::Least { exprs } => {
+            for expr in exprs {
+                v.visit_relational_expression(expr);
+            }
+        }
+        RelationalExpression::DateTrunc { expr, part } => {
+            v.visit_relational_expression(expr);
+            v.visit_relational_expression(part);
+        }
+        RelationalExpression::Left { str, n } | RelationalExpression::Right { str, n } => {
+            v.visit_relational_expression(str);
+            v.visit_relational_expression(n);
+        }
+        RelationalExpression::Log { expr, base } => {
+            v.visit_relational_expression(expr);
+            if let Some(base) = base {
+                v.visit_relational_expression(base);
+            }
+        }
+        RelationalExpression::LPad {
+            str,
+            n,
+            padding_str,
+        }
+        | RelationalExpression::RPad {
+            str,
+            n,
+            padding_str,
+        } => {
+            v.visit_relational_expression(str);
+            v.visit_relational_expression(n);
+            if let Some(padding_str) = padding_str {
+                v.visit_relational_expression(padding_str);
+            }
+        }
+        RelationalExpression::NullIf { expr1, expr2 }
+        | RelationalExpression::Nvl { expr1, expr2 } => {
+            v.visit_relational_expression(expr1);
+            v.visit_relational_expression(expr2);
+        }
+        RelationalExpression::Power { base, exp } => {
+            v.visit_relational_expression(base);
+            v.visit_relational_expression(exp);
+        }
+        RelationalExpression::Replace {
+            str,
+            substr,
+            replacement,
+        } => {
+            v.visit_relational_expression(str);
+            v.visit_relational_expression(substr);
+            v.visit_relational_expression(replacement);
+        }
+        RelationalExpression::Round { expr, prec } | RelationalExpression::Trunc { expr, prec } => {
+            v.visit_relational_expression(expr);
+            if let Some(prec) = prec {
+                v.visit_relational_expression(prec);
+            }
+        }
+        RelationalExpression::StrPos { str, substr } => {
+            v.visit_relational_expression(str);
+            v.visit_relational_expression(substr);
+        }
+        RelationalExpression::Substr {
+            str,
+            start_pos,
+            len,
+        } => {
+            v.visit_relational_expression(str);
+            v.visit_relational_expression(start_pos);
+            if let Some(len) = len {
+                v.visit_relational_expression(len);
+            }
+        }
+        RelationalExpression::SubstrIndex { str, delim, count } => {
+            v.visit_relational_expression(str);
+            v.visit_relational_expression(delim);
+            v.visit_relational_expression(count);
+        }
+        RelationalExpression::JsonContains { json, keys }
+        | RelationalExpression::JsonGet { json, keys }
+        | RelationalExpression::JsonGetStr { json, keys }
+        | RelationalExpression::JsonGetInt { json, keys }
+        | RelationalExpression::JsonGetFloat { json, keys }
+        | RelationalExpression::JsonGetBool { json, keys }
+        | RelationalExpression::JsonGetJson { json, keys }
+        | RelationalExpression::JsonAsText { json, keys }
+        | RelationalExpression::JsonLength { json, keys } => {
+            v.visit_relational_expression(json);
+            for key in keys {
+                v.visit_relational_expression(key);
+            }
+        }
+        RelationalExpression::RowNumber {
+            order_by,
+            partition_by,
+        }
+        | RelationalExpression::DenseRank {
+            order_by,
+            partition_by,
+        }
+        | RelationalExpression::NTile {
+            order_by,
+            partition_by,
+            ..
+        }
+        | RelationalExpression::Rank {
+            order_by,
+            partition_by,
+        }
+        | RelationalExpression::CumeDist {
+            order_by,
+            partition_by,
+        }
+        | RelationalExpression::PercentRank {
+            order_by,
+            partition_by,
+        } => {
+            for sort in order_by {
+                v.visit_sort(sort);
+            }
+            for expr in partition_by {
+                v.visit_relational_expression(expr);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Counter {
+        columns: usize,
+        relations: usize,
+    }
+
+    impl Visit for Counter {
+        fn visit_field(&mut self, field: &Field) {
+            if let Field::Column { .. } = field {
+                self.columns += 1;
+            }
+            visit_field(self, field);
+        }
+
+        fn visit_comparison_target(&mut self, target: &ComparisonTarget) {
+            if let ComparisonTarget::Column { .. } = target {
+                self.columns += 1;
+            }
+            visit_comparison_target(self, target);
+        }
+
+        fn visit_relation(&mut self, relation: &Relation) {
+            self.relations += 1;
+            visit_relation(self, relation);
+        }
+
+        fn visit_relational_expression(&mut self, expr: &RelationalExpression) {
+            if let RelationalExpression::Column { .. } = expr {
+                self.columns += 1;
+            }
+            visit_relational_expression(self, expr);
+        }
+    }
+
+    #[test]
+    fn test_count_query_request() {
+        let request: QueryRequest = serde_json::from_value(serde_json::json!({
+            "collection": "authors",
+            "arguments": {},
+            "collection_relationships": {},
+            "query": {
+                "fields": {
+                    "name": { "type": "column", "column": "name" },
+                    "articles": {
+                        "type": "relationship",
+                        "relationship": "author_articles",
+                        "arguments": {},
+                        "query": {
+                            "fields": {
+                                "id": { "type": "column", "column": "id" },
+                                "title": { "type": "column", "column": "title" },
+                            },
+                            "predicate": {
+                                "type": "unary_comparison_operator",
+                                "column": { "type": "column", "name": "title" },
+                                "operator": "is_null",
+                            },
+                        },
+                    },
+                },
+                "predicate": {
+                    "type": "and",
+                    "expressions": [
+                        {
+                            "type": "binary_comparison_operator",
+                            "column": { "type": "column", "name": "id" },
+                            "operator": "eq",
+                            "value": { "type": "scalar", "value": 1 },
+                        },
+                        {
+                            "type": "not",
+                            "expression": {
+                                "type": "unary_comparison_operator",
+                                "column": { "type": "column", "name": "name" },
+                                "operator": "is_null",
+                            },
+                        },
+                    ],
+                },
+            },
+        }))
+        .unwrap();
+
+        let mut counter = Counter::default();
+        counter.visit_query_request(&request);
+
+        assert_eq!(counter.columns, 6);
+        assert_eq!(counter.relations, 0);
+    }
+
+    #[test]
+    fn test_count_relational_query() {
+        let query: RelationalQuery = serde_json::from_value(serde_json::json!({
+            "root_relation": {
+                "type": "project",
+                "input": {
+                    "type": "sort",
+                    "input": {
+                        "type": "join",
+                        "left": {
+                            "type": "filter",
+                            "input": {
+                                "type": "from",
+                                "collection": "articles",
+                                "columns": ["author_id", "title"],
+                            },
+                            "predicate": {
+                                "type": "is_not_null",
+                                "expr": { "type": "column", "index": 1 },
+                            },
+                        },
+                        "right": {
+                            "type": "from",
+                            "collection": "authors",
+                            "columns": ["id", "name"],
+                        },
+                        "on": [{
+                            "left": { "type": "column", "index": 0 },
+                            "right": { "type": "column", "index": 0 },
+                        }],
+                        "join_type": "inner",
+                    },
+                    "exprs": [{
+                        "expr": { "type": "column", "index": 3 },
+                        "direction": "asc",
+                        "nulls_sort": "nulls_first",
+                    }],
+                },
+                "exprs": [
+                    { "type": "column", "index": 1 },
+                    { "type": "column", "index": 3 },
+                ],
+            },
+        }))
+        .unwrap();
+
+        let mut counter = Counter::default();
+        counter.visit_relational_query(&query);
+
+        assert_eq!(counter.columns, 6);
+        assert_eq!(counter.relations, 6);
+    }
+
+    #[test]
+    fn test_count_mutation_request() {
+        let request: MutationRequest = serde_json::from_value(serde_json::json!({
+            "operations": [
+                {
+                    "type": "procedure",
+                    "name": "upsert_article",
+                    "arguments": {},
+                    "fields": {
+                        "type": "object",
+                        "fields": {
+                            "id": { "type": "column", "column": "id" },
+                        },
+                    },
+                },
+                {
+                    "type": "relational_insert",
+                    "collection": "articles",
+                    "arguments": {},
+                    "columns": ["id"],
+                    "rows": [[1]],
+                },
+                {
+                    "type": "relational_update",
+                    "collection": "articles",
+                    "arguments": {},
+                    "relation": {
+                        "type": "filter",
+                        "input": {
+                            "type": "from",
+                            "collection": "articles",
+                            "columns": ["id"],
+                        },
+                        "predicate": {
+                            "type": "eq",
+                            "left": { "type": "column", "index": 0 },
+                            "right": {
+                                "type": "literal",
+                                "literal": { "type": "Int32", "value": 1 },
+                            },
+                        },
+                    },
+                },
+                {
+                    "type": "relational_delete",
+                    "collection": "articles",
+                    "arguments": {},
+                    "relation": {
+                        "type": "from",
+                        "collection": "articles",
+                        "columns": ["id"],
+                    },
+                },
+            ],
+            "collection_relationships": {},
+        }))
+        .unwrap();
+
+        let mut counter = Counter::default();
+        counter.visit_mutation_request(&request);
+
+        assert_eq!(counter.columns, 2);
+        assert_eq!(counter.relations, 3);
+    }
+}
@@ -0,0 +1,1141 @@
+//! Traversal of query and relation trees by mutable reference.
+//!
+//! Implement [`VisitMut`] and override the methods for the nodes of interest.
+//! As in [`crate::visit`], the default implementation of each method delegates
+//! to the free function of the same name, which visits each child node in turn.
+//!
+//! For example, to substitute the values of variables into a query:
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use ndc_models::visit_mut::{self, VisitMut};
+//! use ndc_models::{Argument, ComparisonValue, VariableName};
+//!
+//! struct SubstituteVariables(BTreeMap<VariableName, serde_json::Value>);
+//!
+//! impl VisitMut for SubstituteVariables {
+//!     fn visit_argument_mut(&mut self, argument: &mut Argument) {
+//!         if let Argument::Variable { name } = argument {
+//!             if let Some(value) = self.0.get(name) {
+//!                 *argument = Argument::Literal {
+//!                     value: value.clone(),
+//!                 };
+//!             }
+//!         }
+//!     }
+//!
+//!     fn visit_comparison_value_mut(&mut self, value: &mut ComparisonValue) {
+//!         if let ComparisonValue::Variable { name } = value {
+//!             if let Some(scalar) = self.0.get(name) {
+//!                 *value = ComparisonValue::Scalar {
+//!                     value: scalar.clone(),
+//!                 };
+//!             }
+//!         }
+//!         visit_mut::visit_comparison_value_mut(self, value);
+//!     }
+//! }
+//! ```
+
+use crate::{
+    Aggregate, Argument, ArrayComparison, CaseWhen, ComparisonTarget, ComparisonValue, Dimension,
+    ExistsInCollection, Expression, Field, GroupComparisonTarget, GroupComparisonValue,
+    GroupExpression, GroupOrderByTarget, Grouping, JoinOn, MutationOperation, MutationRequest,
+    NestedField, OrderBy, OrderByTarget, PathElement, Query, QueryRequest, Relation,
+    RelationalExpression, RelationalLiteral, RelationalQuery, Relationship, RelationshipArgument,
+    Sort,
+};
+
+/// A visitor over query and relation trees, by mutable reference
+pub trait VisitMut {
+    fn visit_query_request_mut(&mut self, request: &mut QueryRequest) {
+        visit_query_request_mut(self, request);
+    }
+
+    fn visit_mutation_request_mut(&mut self, request: &mut MutationRequest) {
+        visit_mutation_request_mut(self, request);
+    }
+
+    fn visit_mutation_operation_mut(&mut self, operation: &mut MutationOperation) {
+        visit_mutation_operation_mut(self, operation);
+    }
+
+    fn visit_relationship_mut(&mut self, relationship: &mut Relationship) {
+        visit_relationship_mut(self, relationship);
+    }
+
+    fn visit_query_mut(&mut self, query: &mut Query) {
+        visit_query_mut(self, query);
+    }
+
+    fn visit_field_mut(&mut self, field: &mut Field) {
+        visit_field_mut(self, field);
+    }
+
+    fn visit_nested_field_mut(&mut self, nested_field: &mut NestedField) {
+        visit_nested_field_mut(self, nested_field);
+    }
+
+    fn visit_argument_mut(&mut self, _argument: &mut Argument) {}
+
+    fn visit_relationship_argument_mut(&mut self, _argument: &mut RelationshipArgument) {}
+
+    fn visit_path_element_mut(&mut self, path_element: &mut PathElement) {
+        visit_path_element_mut(self, path_element);
+    }
+
+    fn visit_aggregate_mut(&mut self, aggregate: &mut Aggregate) {
+        visit_aggregate_mut(self, aggregate);
+    }
+
+    fn visit_order_by_mut(&mut self, order_by: &mut OrderBy) {
+        visit_order_by_mut(self, order_by);
+    }
+
+    fn visit_order_by_target_mut(&mut self, target: &mut OrderByTarget) {
+        visit_order_by_target_mut(self, target);
+    }
+
+    fn visit_expression_mut(&mut self, expression: &mut Expression) {
+        visit_expression_mut(self, expression);
+    }
+
+    fn visit_exists_in_collection_mut(&mut self, in_collection: &mut ExistsInCollection) {
+        visit_exists_in_collection_mut(self, in_collection);
+    }
+
+    fn visit_comparison_target_mut(&mut self, target: &mut ComparisonTarget) {
+        visit_comparison_target_mut(self, target);
+    }
+
+    fn visit_comparison_value_mut(&mut self, value: &mut ComparisonValue) {
+        visit_comparison_value_mut(self, value);
+    }
+
+    fn visit_array_comparison_mut(&mut self, comparison: &mut ArrayComparison) {
+        visit_array_comparison_mut(self, comparison);
+    }
+
+    fn visit_grouping_mut(&mut self, grouping: &mut Grouping) {
+        visit_grouping_mut(self, grouping);
+    }
+
+    fn visit_dimension_mut(&mut self, dimension: &mut Dimension) {
+        visit_dimension_mut(self, dimension);
+    }
+
+    fn visit_group_expression_mut(&mut self, expression: &mut GroupExpression) {
+        visit_group_expression_mut(self, expression);
+    }
+
+    fn visit_group_comparison_target_mut(&mut self, target: &mut GroupComparisonTarget) {
+        visit_group_comparison_target_mut(self, target);
+    }
+
+    fn visit_group_comparison_value_mut(&mut self, _value: &mut GroupComparisonValue) {}
+
+    fn visit_group_order_by_target_mut(&mut self, target: &mut GroupOrderByTarget) {
+        visit_group_order_by_target_mut(self, target);
+    }
+
+    fn visit_relational_query_mut(&mut self, query: &mut RelationalQuery) {
+        visit_relational_query_mut(self, query);
+    }
+
+    fn visit_relation_mut(&mut self, relation: &mut Relation) {
+        visit_relation_mut(self, relation);
+    }
+
+    fn visit_relational_expression_mut(&mut self, expr: &mut RelationalExpression) {
+        visit_relational_expression_mut(self, expr);
+    }
+
+    fn visit_relational_literal_mut(&mut self, _literal: &mut RelationalLiteral) {}
+
+    fn visit_sort_mut(&mut self, sort: &mut Sort) {
+        visit_sort_mut(self, sort);
+    }
+
+    fn visit_join_on_mut(&mut self, join_on: &mut JoinOn) {
+        visit_join_on_mut(self, join_on);
+    }
+
+    fn visit_case_when_mut(&mut self, case_when: &mut CaseWhen) {
+        visit_case_when_mut(self, case_when);
+    }
+}
+
+pub fn visit_query_request_mut<V: VisitMut + ?Sized>(v: &mut V, request: &mut QueryRequest) {
+    for argument in request.arguments.values_mut() {
+        v.visit_argument_mut(argument);
+    }
+    for relationship in request.collection_relationships.values_mut() {
+        v.visit_relationship_mut(relationship);
+    }
+    v.visit_query_mut(&mut request.query);
+}
+
+pub fn visit_mutation_request_mut<V: VisitMut + ?Sized>(v: &mut V, request: &mut MutationRequest) {
+    for relationship in request.collection_relationships.values_mut() {
+        v.visit_relationship_mut(relationship);
+    }
+    for operation in &mut request.operations {
+        v.visit_mutation_operation_mut(operation);
+    }
+}
+
+pub fn visit_mutation_operation_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    operation: &mut MutationOperation,
+) {
+    match operation {
+        MutationOperation::Procedure { fields, .. } => {
+            if let Some(fields) = fields {
+                v.visit_nested_field_mut(fields);
+            }
+        }
+        MutationOperation::RelationalInsert(request) => {
+            for argument in request.arguments.values_mut() {
+                v.visit_argument_mut(argument);
+            }
+        }
+        MutationOperation::RelationalUpsert(request) => {
+            for argument in request.arguments.values_mut() {
+                v.visit_argument_mut(argument);
+            }
+        }
+        MutationOperation::RelationalUpdate(request) => {
+            for argument in request.arguments.values_mut() {
+                v.visit_argument_mut(argument);
+            }
+            v.visit_relation_mut(&mut request.relation);
+        }
+        MutationOperation::RelationalDelete(request) => {
+            for argument in request.arguments.values_mut() {
+                v.visit_argument_mut(argument);
+            }
+            v.visit_relation_mut(&mut request.relation);
+        }
+    }
+}
+
+pub fn visit_relationship_mut<V: VisitMut + ?Sized>(v: &mut V, relationship: &mut Relationship) {
+    for argument in relationship.arguments.values_mut() {
+        v.visit_relationship_argument_mut(argument);
+    }
+}
+
+pub fn visit_query_mut<V: VisitMut + ?Sized>(v: &mut V, query: &mut Query) {
+    if let Some(aggregates) = &mut query.aggregates {
+        for aggregate in aggregates.values_mut() {
+            v.visit_aggregate_mut(aggregate);
+        }
+    }
+    if let Some(fields) = &mut query.fields {
+        for field in fields.values_mut() {
+            v.visit_field_mut(field);
+        }
+    }
+    if let Some(order_by) = &mut query.order_by {
+        v.visit_order_by_mut(order_by);
+    }
+    if let Some(predicate) = &mut query.predicate {
+        v.visit_expression_mut(predicate);
+    }
+    if let Some(groups) = &mut query.groups {
+        v.visit_grouping_mut(groups);
+    }
+}
+
+pub fn visit_field_mut<V: VisitMut + ?Sized>(v: &mut V, field: &mut Field) {
+    match field {
+        Field::Column {
+            fields, arguments, ..
+        } => {
+            for argument in arguments.values_mut() {
+                v.visit_argument_mut(argument);
+            }
+            if let Some(fields) = fields {
+                v.visit_nested_field_mut(fields);
+            }
+        }
+        Field::Relationship {
+            query, arguments, ..
+        } => {
+            for argument in arguments.values_mut() {
+                v.visit_relationship_argument_mut(argument);
+            }
+            v.visit_query_mut(query);
+        }
+    }
+}
+
+pub fn visit_nested_field_mut<V: VisitMut + ?Sized>(v: &mut V, nested_field: &mut NestedField) {
+    match nested_field {
+        NestedField::Object(nested_object) => {
+            for field in nested_object.fields.values_mut() {
+                v.visit_field_mut(field);
+            }
+        }
+        NestedField::Array(nested_array) => v.visit_nested_field_mut(&mut nested_array.fields),
+        NestedField::Collection(nested_collection) => {
+            v.visit_query_mut(&mut nested_collection.query);
+        }
+    }
+}
+
+pub fn visit_path_element_mut<V: VisitMut + ?Sized>(v: &mut V, path_element: &mut PathElement) {
+    for argument in path_element.arguments.values_mut() {
+        v.visit_relationship_argument_mut(argument);
+    }
+    if let Some(predicate) = &mut path_element.predicate {
+        v.visit_expression_mut(predicate);
+    }
+}
+
+pub fn visit_aggregate_mut<V: VisitMut + ?Sized>(v: &mut V, aggregate: &mut Aggregate) {
+    match aggregate {
+        Aggregate::ColumnCount {
+            arguments, filter, ..
+        }
+        | Aggregate::SingleColumn {
+            arguments, filter, ..
+        } => {
+            for argument in arguments.values_mut() {
+                v.visit_argument_mut(argument);
+            }
+            if let Some(filter) = filter {
+                v.visit_expression_mut(filter);
+            }
+        }
+        Aggregate::StarCount { filter } => {
+            if let Some(filter) = filter {
+                v.visit_expression_mut(filter);
+            }
+        }
+    }
+}
+
+pub fn visit_order_by_mut<V: VisitMut + ?Sized>(v: &mut V, order_by: &mut OrderBy) {
+    for element in &mut order_by.elements {
+        v.visit_order_by_target_mut(&mut element.target);
+    }
+}
+
+pub fn visit_order_by_target_mut<V: VisitMut + ?Sized>(v: &mut V, target: &mut OrderByTarget) {
+    match target {
+        OrderByTarget::Column {
+            path, arguments, ..
+        } => {
+            for path_element in path {
+                v.visit_path_element_mut(path_element);
+            }
+            for argument in arguments.values_mut() {
+                v.visit_argument_mut(argument);
+            }
+        }
+        OrderByTarget::Aggregate { path, aggregate } => {
+            for path_element in path {
+                v.visit_path_element_mut(path_element);
+            }
+            v.visit_aggregate_mut(aggregate);
+        }
+        OrderByTarget::Relevance { value, .. }
+        | OrderByTarget::Distance { value, .. }
+        | OrderByTarget::Similarity { value, .. } => {
+            v.visit_comparison_value_mut(value);
+        }
+    }
+}
+
+pub fn visit_expression_mut<V: VisitMut + ?Sized>(v: &mut V, expression: &mut Expression) {
+    match expression {
+        Expression::And { expressions } | Expression::Or { expressions } => {
+            for expression in expressions {
+                v.visit_expression_mut(expression);
+            }
+        }
+        Expression::Not { expression } => v.visit_expression_mut(expression),
+        Expression::UnaryComparisonOperator { column, .. } => v.visit_comparison_target_mut(column),
+        Expression::BinaryComparisonOperator { column, value, .. } => {
+            v.visit_comparison_target_mut(column);
+            v.visit_comparison_value_mut(value);
+        }
+        Expression::ArrayComparison { column, comparison } => {
+            v.visit_comparison_target_mut(column);
+            v.visit_array_comparison_mut(comparison);
+        }
+        Expression::Exists {
+            in_collection,
+            predicate,
+        } => {
+            v.visit_exists_in_collection_mut(in_collection);
+            if let Some(predicate) = predicate {
+                v.visit_expression_mut(predicate);
+            }
+        }
+    }
+}
+
+pub fn visit_exists_in_collection_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    in_collection: &mut ExistsInCollection,
+) {
+    match in_collection {
+        ExistsInCollection::Related { arguments, .. }
+        | ExistsInCollection::Unrelated { arguments, .. } => {
+            for argument in arguments.values_mut() {
+                v.visit_relationship_argument_mut(argument);
+            }
+        }
+        ExistsInCollection::NestedCollection { arguments, .. }
+        | ExistsInCollection::NestedScalarCollection { arguments, .. } => {
+            for argument in arguments.values_mut() {
+                v.visit_argument_mut(argument);
+            }
+        }
+    }
+}
+
+pub fn visit_comparison_target_mut<V: VisitMut + ?Sized>(v: &mut V, target: &mut ComparisonTarget) {
+    match target {
+        ComparisonTarget::Column { arguments, .. } => {
+            for argument in arguments.values_mut() {
+                v.visit_argument_mut(argument);
+            }
+        }
+        ComparisonTarget::Aggregate { path, aggregate } => {
+            for path_element in path {
+                v.visit_path_element_mut(path_element);
+            }
+            v.visit_aggregate_mut(aggregate);
+        }
+    }
+}
+
+pub fn visit_comparison_value_mut<V: VisitMut + ?Sized>(v: &mut V, value: &mut ComparisonValue) {
+    match value {
+        ComparisonValue::Column {
+            path, arguments, ..
+        } => {
+            for path_element in path {
+                v.visit_path_element_mut(path_element);
+            }
+            for argument in arguments.values_mut() {
+                v.visit_argument_mut(argument);
+            }
+        }
+        ComparisonValue::Scalar { .. } | ComparisonValue::Variable { .. } => {}
+    }
+}
+
+pub fn visit_array_comparison_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    comparison: &mut ArrayComparison,
+) {
+    match comparison {
+        ArrayComparison::Contains { value } => v.visit_comparison_value_mut(value),
+        ArrayComparison::IsEmpty => {}
+    }
+}
+
+pub fn visit_grouping_mut<V: VisitMut + ?Sized>(v: &mut V, grouping: &mut Grouping) {
+    for dimension in &mut grouping.dimensions {
+        v.visit_dimension_mut(dimension);
+    }
+    for aggregate in grouping.aggregates.values_mut() {
+        v.visit_aggregate_mut(aggregate);
+    }
+    if let Some(predicate) = &mut grouping.predicate {
+        v.visit_group_expression_mut(predicate);
+    }
+    if let Some(order_by) = &mut grouping.order_by {
+        for element in &mut order_by.elements {
+            v.visit_group_order_by_target_mut(&mut element.target);
+        }
+    }
+}
+
+pub fn visit_dimension_mut<V: VisitMut + ?Sized>(v: &mut V, dimension: &mut Dimension) {
+    match dimension {
+        Dimension::Column {
+            path, arguments, ..
+        } => {
+            for path_element in path {
+                v.visit_path_element_mut(path_element);
+            }
+            for argument in arguments.values_mut() {
+                v.visit_argument_mut(argument);
+            }
+        }
+    }
+}
+
+pub fn visit_group_expression_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    expression: &mut GroupExpression,
+) {
+    match expression {
+        GroupExpression::And { expressions } | GroupExpression::Or { expressions } => {
+            for expression in expressions {
+                v.visit_group_expression_mut(expression);
+            }
+        }
+        GroupExpression::Not { expression } => v.visit_group_expression_mut(expression),
+        GroupExpression::UnaryComparisonOperator { target, .. } => {
+            v.visit_group_comparison_target_mut(target);
+        }
+        GroupExpression::BinaryComparisonOperator { target, value, .. } => {
+            v.visit_group_comparison_target_mut(target);
+            v.visit_group_comparison_value_mut(value);
+        }
+    }
+}
+
+pub fn visit_group_comparison_target_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    target: &mut GroupComparisonTarget,
+) {
+    match target {
+        GroupComparisonTarget::Aggregate { aggregate } => v.visit_aggregate_mut(aggregate),
+    }
+}
+
+pub fn visit_group_order_by_target_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    target: &mut GroupOrderByTarget,
+) {
+    match target {
+        GroupOrderByTarget::Dimension { .. } => {}
+        GroupOrderByTarget::Aggregate { aggregate } => v.visit_aggregate_mut(aggregate),
+    }
+}
+
+pub fn visit_relational_query_mut<V: VisitMut + ?Sized>(v: &mut V, query: &mut RelationalQuery) {
+    v.visit_relation_mut(&mut query.root_relation);
+}
+
+pub fn visit_relation_mut<V: VisitMut + ?Sized>(v: &mut V, relation: &mut Relation) {
+    match relation {
+        Relation::From { arguments, .. } => {
+            for argument in arguments.values_mut() {
+                v.visit_relational_literal_mut(argument);
+            }
+        }
+        Relation::Paginate { input, .. } => v.visit_relation_mut(relation_mut(input)),
+        Relation::Project { input, exprs } | Relation::Window { input, exprs } => {
+            v.visit_relation_mut(relation_mut(input));
+            for expr in exprs {
+                v.visit_relational_expression_mut(expr);
+            }
+        }
+        Relation::Filter { input, predicate } => {
+            v.visit_relation_mut(relation_mut(input));
+            v.visit_relational_expression_mut(predicate);
+        }
+        Relation::Sort { input, exprs } => {
+            v.visit_relation_mut(relation_mut(input));
+            for sort in exprs {
+                v.visit_sort_mut(sort);
+            }
+        }
+        Relation::Join {
+            left, right, on, ..
+        } => {
+            v.visit_relation_mut(relation_mut(left));
+            v.visit_relation_mut(relation_mut(right));
+            for join_on in on {
+                v.visit_join_on_mut(join_on);
+            }
+        }
+        Relation::Aggregate {
+            input,
+            group_by,
+            aggregates,
+        } => {
+            v.visit_relation_mut(relation_mut(input));
+            for expr in group_by {
+                v.visit_relational_expression_mut(expr);
+            }
+            for expr in aggregates {
+                v.visit_relational_expression_mut(expr);
+            }
+        }
+        Relation::Union { relations } => {
+            for relation in relations {
+                v.visit_relation_mut(relation);
+            }
+        }
+    }
+}
+
+pub fn visit_sort_mut<V: VisitMut + ?Sized>(v: &mut V, sort: &mut Sort) {
+    v.visit_relational_expression_mut(&mut sort.expr);
+}
+
+pub fn visit_join_on_mut<V: VisitMut + ?Sized>(v: &mut V, join_on: &mut JoinOn) {
+    v.visit_relational_expression_mut(&mut join_on.left);
+    v.visit_relational_expression_mut(&mut join_on.right);
+}
+
+pub fn visit_case_when_mut<V: VisitMut + ?Sized>(v: &mut V, case_when: &mut CaseWhen) {
+    v.visit_relational_expression_mut(&mut case_when.when);
+    v.visit_relational_expression_mut(&mut case_when.then);
+}
+
+#[allow(clippy::too_many_lines)]
+pub fn visit_relational_expression_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    expr: &mut RelationalExpression,
+) {
+    match expr {
+        RelationalExpression::Literal { literal } => v.visit_relational_literal_mut(literal),
+        RelationalExpression::Column { .. }
+        | RelationalExpression::CurrentDate
+        | RelationalExpression::CurrentTime
+        | RelationalExpression::CurrentTimestamp
+        | RelationalExpression::Random => {}
+        RelationalExpression::Case {
+            scrutinee,
+            when,
+            default,
+        } => {
+            if let Some(scrutinee) = scrutinee {
+                v.visit_relational_expression_mut(scrutinee);
+            }
+            for case_when in when {
+                v.visit_case_when_mut(case_when);
+            }
+            if let Some(default) = default {
+                v.visit_relational_expression_mut(default);
+            }
+        }
+        RelationalExpression::And { left, right }
+        | RelationalExpression::Or { left, right }
+        | RelationalExpression::Eq { left, right }
+        | RelationalExpression::NotEq { left, right }
+        | RelationalExpression::IsDistinctFrom { left, right }
+        | RelationalExpression::IsNotDistinctFrom { left, right }
+        | RelationalExpression::Lt { left, right }
+        | RelationalExpression::LtEq { left, right }
+        | RelationalExpression::Gt { left, right }
+        | RelationalExpression::GtEq { left, right }
+        | RelationalExpression::Plus { left, right }
+        | RelationalExpression::Minus { left, right }
+        | RelationalExpression::Multiply { left, right }
+        | RelationalExpression::Divide { left, right }
+        | RelationalExpression::Modulo { left, right }
+        | RelationalExpression::BinaryConcat { left, right } => {
+            v.visit_relational_expression_mut(left);
+            v.visit_relational_expression_mut(right);
+        }
+        RelationalExpression::Not { expr }
+        | RelationalExpression::IsNotNull { expr }
+        | RelationalExpression::IsNull { expr }
+        | RelationalExpression::IsTrue { expr }
+        | RelationalExpression::IsFalse { expr }
+        | RelationalExpression::IsNotTrue { expr }
+        | RelationalExpression::IsNotFalse { expr }
+        | RelationalExpression::IsNaN { expr }
+        | RelationalExpression::IsZero { expr }
+        | RelationalExpression::Negate { expr }
+        | RelationalExpression::Cast { expr, .. }
+        | RelationalExpression::TryCast { expr, .. }
+        | RelationalExpression::Abs { expr }
+        | RelationalExpression::Ceil { expr }
+        | RelationalExpression::Cos { expr }
+        | RelationalExpression::DatePart { expr, .. }
+        | RelationalExpression::Exp { expr }
+        | RelationalExpression::Floor { expr }
+        | RelationalExpression::Ln { expr }
+        | RelationalExpression::Log10 { expr }
+        | RelationalExpression::Log2 { expr }
+        | RelationalExpression::Sqrt { expr }
+        | RelationalExpression::Tan { expr }
+        | RelationalExpression::ToDate { expr }
+        | RelationalExpression::ToTimestamp { expr }
+        | RelationalExpression::ToLower { expr }
+        | RelationalExpression::ToUpper { expr }
+        | RelationalExpression::Average { expr }
+        | RelationalExpression::BoolAnd { expr }
+        | RelationalExpression::BoolOr { expr }
+        | RelationalExpression::Count { expr, .. }
+        | RelationalExpression::Max { expr }
+        | RelationalExpression::Median { expr }
+        | RelationalExpression::Min { expr }
+        | RelationalExpression::Sum { expr }
+        | RelationalExpression::Var { expr }
+        | RelationalExpression::Stddev { expr }
+        | RelationalExpression::StddevPop { expr }
+        | RelationalExpression::ApproxPercentileCont { expr, .. }
+        | RelationalExpression::ApproxDistinct { expr } => v.visit_relational_expression_mut(expr),
+        RelationalExpression::FirstValue { expr, order_by }
+        | RelationalExpression::LastValue { expr, order_by }
+        | RelationalExpression::StringAgg { expr, order_by, .. }
+        | RelationalExpression::ArrayAgg { expr, order_by, .. } => {
+            v.visit_relational_expression_mut(expr);
+            if let Some(order_by) = order_by {
+                for sort in order_by {
+                    v.visit_sort_mut(sort);
+                }
+            }
+        }
+        RelationalExpression::In { expr, list } | RelationalExpression::NotIn { expr, list } => {
+            v.visit_relational_expression_mut(expr);
+            for expr in list {
+                v.visit_relational_expression_mut(expr);
+            }
+        }
+        RelationalExpression::Like { expr, pattern }
+        | RelationalExpression::NotLike { expr, pattern }
+        | RelationalExpression::ILike { expr, pattern }
+        | RelationalExpression::NotILike { expr, pattern } => {
+            v.visit_relational_expression_mut(expr);
+            v.visit_relational_expression_mut(pattern);
+        }
+        RelationalExpression::Between { low, expr, high }
+        | RelationalExpression::NotBetween { low, expr, high } => {
+            v.visit_relational_expression_mut(low);
+            v.visit_relational_expression_mut(expr);
+            v.visit_relational_expression_mut(high);
+        }
+        RelationalExpression::Contains { str, search_str } => {
+            v.visit_relational_expression_mut(str);
+            v.visit_relational_expression_mut(search_str);
+        }
+        RelationalExpression::ArrayElement { column, .. }
+        | RelationalExpression::GetField { column, .. } => {
+            v.visit_relational_expression_mut(column);
+        }
+        RelationalExpression::BTrim { str, trim_str }
+        | RelationalExpression::LTrim { str, trim_str }
+        | RelationalExpression::RTrim { str, trim_str } => {
+            v.visit_relational_expression_mut(str);
+            if let Some(trim_str) = trim_str {
+                v.visit_relational_expression_mut(trim_str);
+            }
+        }
+        RelationalExpression::CharacterLength { str } | RelationalExpression::Reverse { str } => {
+            v.visit_relational_expression_mut(str);
+        }
+        RelationalExpression::Coalesce { exprs }
+        | RelationalExpression::Concat { exprs }
+        | RelationalExpression::Greatest { exprs }
+        | RelationalExpression::Least { exprs } => {
+            for expr in exprs {
+                v.visit_relational_expression_mut(expr);
+            }
+        }
+        RelationalExpression::DateTrunc { expr, part } => {
+            v.visit_relational_expression_mut(expr);
+            v.visit_relational_expression_mut(part);
+        }
+        RelationalExpression::Left { str, n } | RelationalExpression::Right { str, n } => {
+            v.visit_relational_expression_mut(str);
+            v.visit_relational_expression_mut(n);
+        }
+        RelationalExpression::Log { expr, base } => {
+            v.visit_relational_expression_mut(expr);
+            if let Some(base) = base {
+                v.visit_relational_expression_mut(base);
+            }
+        }
+        RelationalExpression::LPad {
+            str,
+            n,
+            padding_str,
+        }
+        | RelationalExpression::RPad {
+            str,
+            n,
+            padding_str,
+        } => {
+            v.visit_relational_expression_mut(str);
+            v.visit_relational_expression_mut(n);
+            if let Some(padding_str) = padding_str {
+                v.visit_relational_expression_mut(padding_str);
+            }
+        }
+        RelationalExpression::NullIf { expr1, expr2 }
+        | RelationalExpression::Nvl { expr1, expr2 } => {
+            v.visit_relational_expression_mut(expr1);
+            v.visit_relational_expression_mut(expr2);
+        }
+        RelationalExpression::Power { base, exp } => {
+            v.visit_relational_expression_mut(base);
+            v.visit_relational_expression_mut(exp);
+        }
+        RelationalExpression::Replace {
+            str,
+            substr,
+            replacement,
+        } => {
+            v.visit_relational_expression_mut(str);
+            v.visit_relational_expression_mut(substr);
+            v.visit_relational_expression_mut(replacement);
+        }
+        RelationalExpression::Round { expr, prec } | RelationalExpression::Trunc { expr, prec } => {
+            v.visit_relational_expression_mut(expr);
+            if let Some(prec) = prec {
+                v.visit_relational_expression_mut(prec);
+            }
+        }
+        RelationalExpression::StrPos { str, substr } => {
+            v.visit_relational_expression_mut(str);
+            v.visit_relational_expression_mut(substr);
+        }
+        RelationalExpression::Substr {
+            str,
+            start_pos,
+            len,
+        } => {
+            v.visit_relational_expression_mut(str);
+            v.visit_relational_expression_mut(start_pos);
+            if let Some(len) = len {
+                v.visit_relational_expression_mut(len);
+            }
+        }
+        RelationalExpression::SubstrIndex { str, delim, count } => {
+            v.visit_relational_expression_mut(str);
+            v.visit_relational_expression_mut(delim);
+            v.visit_relational_expression_mut(count);
+        }
+        RelationalExpression::JsonContains { json, keys }
+        | RelationalExpression::JsonGet { json, keys }
+        | RelationalExpression::JsonGetStr { json, keys }
+        | RelationalExpression::JsonGetInt { json, keys }
+        | RelationalExpression::JsonGetFloat { json, keys }
+        | RelationalExpression::JsonGetBool { json, keys }
+        | RelationalExpression::JsonGetJson { json, keys }
+        | RelationalExpression::JsonAsText { json, keys }
+        | RelationalExpression::JsonLength { json, keys } => {
+            v.visit_relational_expression_mut(json);
+            for key in keys {
+                v.visit_relational_expression_mut(key);
+            }
+        }
+        RelationalExpression::RowNumber {
+            order_by,
+            partition_by,
+        }
+        | RelationalExpression::DenseRank {
+            order_by,
+            partition_by,
+        }
+        | RelationalExpression::NTile {
+            order_by,
+            partition_by,
+            ..
+        }
+        | RelationalExpression::Rank {
+            order_by,
+            partition_by,
+        }
+        | RelationalExpression::CumeDist {
+            order_by,
+            partition_by,
+        }
+        | RelationalExpression::PercentRank {
+            order_by,
+            partition_by,
+        } => {
+            for sort in order_by {
+                v.visit_sort_mut(sort);
+            }
+            for expr in partition_by {
+                v.visit_relational_expression_mut(expr);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "arc-relation"))]
+fn relation_mut(input: &mut Box<Relation>) -> &mut Relation {
+    input
+}
+
+#[cfg(feature = "arc-relation")]
+fn relation_mut(input: &mut std::sync::Arc<Relation>) -> &mut Relation {
+    std::sync::Arc::make_mut(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FieldName;
+
+    struct RenameColumns;
+
+    impl VisitMut for RenameColumns {
+        fn visit_comparison_target_mut(&mut self, target: &mut ComparisonTarget) {
+            if let ComparisonTarget::Column { name, .. } = target {
+                *name = FieldName::from(format!("renamed_{name}"));
+            }
+            visit_comparison_target_mut(self, target);
+        }
+
+        fn visit_field_mut(&mut self, field: &mut Field) {
+            if let Field::Column { column, .. } = field {
+                *column = FieldName::from(format!("renamed_{column}"));
+            }
+            visit_field_mut(self, field);
+        }
+    }
+
+    #[test]
+    fn test_rename_columns() {
+        let mut request: QueryRequest = serde_json::from_value(serde_json::json!({
+            "collection": "authors",
+            "arguments": {},
+            "collection_relationships": {},
+            "query": {
+                "fields": {
+                    "name": { "type": "column", "column": "name" },
+                    "articles": {
+                        "type": "relationship",
+                        "relationship": "author_articles",
+                        "arguments": {},
+                        "query": {
+                            "fields": { "title": { "type": "column", "column": "title" } },
+                        },
+                    },
+                },
+                "predicate": {
+                    "type": "not",
+                    "expression": {
+                        "type": "unary_comparison_operator",
+                        "column": { "type": "column", "name": "name" },
+                        "operator": "is_null",
+                    },
+                },
+            },
+        }))
+        .unwrap();
+
+        RenameColumns.visit_query_request_mut(&mut request);
+
+        let expected: Query = serde_json::from_value(serde_json::json!({
+                "fields": {
+                    "name": { "type": "column", "column": "renamed_name" },
+                    "articles": {
+                        "type": "relationship",
+                        "relationship": "author_articles",
+                        "arguments": {},
+                        "query": {
+                            "fields": { "title": { "type": "column", "column": "renamed_title" } },
+                        },
+                    },
+                },
+                "predicate": {
+                    "type": "not",
+                    "expression": {
+                        "type": "unary_comparison_operator",
+                        "column": { "type": "column", "name": "renamed_name" },
+                        "operator": "is_null",
+                    },
+                },
+        }))
+        .unwrap();
+
+        assert_eq!(request.query, expected);
+    }
+
+    #[derive(Default)]
+    struct ShiftColumns {
+        relations: usize,
+    }
+
+    impl VisitMut for ShiftColumns {
+        fn visit_relation_mut(&mut self, relation: &mut Relation) {
+            self.relations += 1;
+            visit_relation_mut(self, relation);
+        }
+
+        fn visit_relational_expression_mut(&mut self, expr: &mut RelationalExpression) {
+            if let RelationalExpression::Column { index } = expr {
+                *index += 1;
+            }
+            visit_relational_expression_mut(self, expr);
+        }
+    }
+
+    #[test]
+    fn test_shift_columns_in_relational_mutations() {
+        let relation = |index: u64| {
+            serde_json::json!({
+                "type": "filter",
+                "input": {
+                    "type": "from",
+                    "collection": "articles",
+                    "columns": ["id", "title"],
+                },
+                "predicate": {
+                    "type": "is_null",
+                    "expr": { "type": "column", "index": index },
+                },
+            })
+        };
+        let request = |index: u64| -> MutationRequest {
+            serde_json::from_value(serde_json::json!({
+                "operations": [
+                    {
+                        "type": "relational_update",
+                        "collection": "articles",
+                        "arguments": {},
+                        "relation": relation(index),
+                    },
+                    {
+                        "type": "relational_delete",
+                        "collection": "articles",
+                        "arguments": {},
+                        "relation": relation(index),
+                    },
+                ],
+                "collection_relationships": {},
+            }))
+            .unwrap()
+        };
+
+        let mut actual = request(0);
+        let mut visitor = ShiftColumns::default();
+        visitor.visit_mutation_request_mut(&mut actual);
+
+        assert_eq!(actual, request(1));
+        assert_eq!(visitor.relations, 4);
+    }
+
+    #[derive(Default)]
+    struct RenameRelationships {
+        relationships: usize,
+        variables: usize,
+    }
+
+    impl VisitMut for RenameRelationships {
+        fn visit_field_mut(&mut self, field: &mut Field) {
+            if let Field::Relationship { relationship, .. } = field {
+                *relationship = format!("v2_{relationship}").into();
+                self.relationships += 1;
+            }
+            visit_field_mut(self, field);
+        }
+
+        fn visit_exists_in_collection_mut(&mut self, in_collection: &mut ExistsInCollection) {
+            if let ExistsInCollection::Related { relationship, .. } = in_collection {
+                *relationship = format!("v2_{relationship}").into();
+                self.relationships += 1;
+            }
+            visit_exists_in_collection_mut(self, in_collection);
+        }
+
+        fn visit_path_element_mut(&mut self, path_element: &mut PathElement) {
+            path_element.relationship = format!("v2_{}", path_element.relationship).into();
+            self.relationships += 1;
+            visit_path_element_mut(self, path_element);
+        }
+
+        fn visit_comparison_value_mut(&mut self, value: &mut ComparisonValue) {
+            if let ComparisonValue::Variable { .. } = value {
+                *value = ComparisonValue::Scalar {
+                    value: serde_json::json!(1),
+                };
+                self.variables += 1;
+            }
+            visit_comparison_value_mut(self, value);
+        }
+    }
+
+    #[test]
+    fn test_rename_nested_relationships() {
+        let request = |prefix: &str, value: &serde_json::Value| -> QueryRequest {
+            let relationship = |name: &str| format!("{prefix}{name}");
+            let compare = |column: &str| {
+                serde_json::json!({
+                    "type": "binary_comparison_operator",
+                    "column": { "type": "column", "name": column },
+                    "operator": "eq",
+                    "value": value,
+                })
+            };
+            serde_json::from_value(serde_json::json!({
+                "collection": "authors",
+                "arguments": {},
+                "collection_relationships": {},
+                "query": {
+                    "fields": {
+                        "articles": {
+                            "type": "relationship",
+                            "relationship": relationship("author_articles"),
+                            "arguments": {},
+                            "query": {
+                                "fields": {
+                                    "author": {
+                                        "type": "relationship",
+                                        "relationship": relationship("article_author"),
+                                        "arguments": {},
+                                        "query": {
+                                            "predicate": compare("id"),
+                                        },
+                                    },
+                                },
+                                "predicate": {
+                                    "type": "not",
+                                    "expression": compare("title"),
+                                },
+                            },
+                        },
+                    },
+                    "predicate": {
+                        "type": "exists",
+                        "in_collection": {
+                            "type": "related",
+                            "relationship": relationship("author_articles"),
+                            "arguments": {},
+                        },
+                        "predicate": {
+                            "type": "or",
+                            "expressions": [
+                                compare("title"),
+                                {
+                                    "type": "exists",
+                                    "in_collection": {
+                                        "type": "related",
+                                        "relationship": relationship("article_author"),
+                                        "arguments": {},
+                                    },
+                                    "predicate": compare("id"),
+                                },
+                            ],
+                        },
+                    },
+                    "order_by": {
+                        "elements": [{
+                            "order_direction": "asc",
+                            "target": {
+                                "type": "column",
+                                "name": "title",
+                                "path": [{
+                                    "relationship": relationship("author_articles"),
+                                    "arguments": {},
+                                    "predicate": compare("author_id"),
+                                }],
+                            },
+                        }],
+                    },
+                },
+            }))
+            .unwrap()
+        };
+
+        let mut actual = request("", &serde_json::json!({ "type": "variable", "name": "x" }));
+        let mut visitor = RenameRelationships::default();
+        visitor.visit_query_request_mut(&mut actual);
+
+        assert_eq!(
+            actual,
+            request("v2_", &serde_json::json!({ "type": "scalar", "value": 1 }))
+        );
+        assert_eq!(visitor.relationships, 5);
+        assert_eq!(visitor.variables, 5);
+    }
+}
@@ -67,6 +67,11 @@ fn test_json_schemas() {
         schema_for!(RelationalQueryResponse),
         "relational_query_response.jsonschema",
     );
+    test_json_schema(
+        &mut mint,
+        schema_for!(RelationalQueryStreamChunk),
+        "relational_query_stream_chunk.jsonschema",
+    );
     test_json_schema(
         &mut mint,
         schema_for!(RelationalInsertRequest),
@@ -77,6 +82,16 @@ fn test_json_schemas() {
         schema_for!(RelationalInsertResponse),
         "relational_insert_response.jsonschema",
     );
+    test_json_schema(
+        &mut mint,
+        schema_for!(RelationalUpsertRequest),
+        "relational_upsert_request.jsonschema",
+    );
+    test_json_schema(
+        &mut mint,
+        schema_for!(RelationalUpsertResponse),
+        "relational_upsert_response.jsonschema",
+    );
     test_json_schema(
         &mut mint,
         schema_for!(RelationalUpdateRequest),
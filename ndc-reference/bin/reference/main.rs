@@ -1,11 +1,11 @@
 use std::{
     borrow::Cow,
     cmp::Ordering,
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashSet},
     env,
     error::Error,
     net,
-    sync::Arc,
+    sync::{Arc, LazyLock},
 };
 
 use axum::{
@@ -206,44 +206,10 @@ async fn check_version_header(
     next: axum::middleware::Next,
 ) -> axum::response::Response {
     if let Some(version) = request.headers().get(ndc_models::VERSION_HEADER_NAME) {
-        let Ok(version) = version.to_str() else {
+        if let Err(err) = ndc_models::version::check_version_header(version.as_bytes()) {
             return (
                 StatusCode::BAD_REQUEST,
-                format!(
-                    "Invalid {} header, expected a semver version string",
-                    ndc_models::VERSION_HEADER_NAME
-                ),
-            )
-                .into_response();
-        };
-
-        let Ok(wanted_version) = semver::Version::parse(version) else {
-            return (
-                StatusCode::BAD_REQUEST,
-                format!(
-                    "Invalid {} header, expected a semver version string",
-                    ndc_models::VERSION_HEADER_NAME
-                ),
-            )
-                .into_response();
-        };
-
-        let comparator = semver::Comparator {
-            op: semver::Op::Caret,
-            major: wanted_version.major,
-            minor: Some(wanted_version.minor),
-            patch: Some(wanted_version.patch),
-            pre: wanted_version.pre,
-        };
-
-        if !comparator.matches(&semver::Version::parse(ndc_models::VERSION).unwrap()) {
-            return (
-                StatusCode::BAD_REQUEST,
-                format!(
-                    "NDC version range ^{} does not match implemented version {}",
-                    version,
-                    ndc_models::VERSION
-                ),
+                Json(models::ErrorResponse::from(&err)),
             )
                 .into_response();
         }
@@ -282,6 +248,8 @@ async fn get_capabilities() -> Json<models::CapabilitiesResponse> {
                         order: Some(models::LeafCapability {}),
                         paginate: Some(models::LeafCapability {}),
                     }),
+                    filter: Some(models::LeafCapability {}),
+                    distinct: Some(models::LeafCapability {}),
                 }),
                 variables: Some(models::LeafCapability {}),
                 exists: models::ExistsCapabilities {
@@ -302,6 +270,23 @@ async fn get_capabilities() -> Json<models::CapabilitiesResponse> {
                     aggregates: Some(models::LeafCapability {}),
                     nested_collections: Some(models::LeafCapability {}),
                 },
+                distinct: Some(models::DistinctCapabilities {
+                    on: Some(models::LeafCapability {}),
+                }),
+                pagination: models::PaginationCapabilities {
+                    cursor: Some(models::LeafCapability {}),
+                },
+                full_text_search: Some(models::FullTextSearchCapabilities {
+                    order_by_relevance: Some(models::LeafCapability {}),
+                }),
+                geospatial: Some(models::GeospatialCapabilities {
+                    order_by_distance: Some(models::LeafCapability {}),
+                }),
+                vector_search: Some(models::VectorSearchCapabilities {
+                    cosine: Some(models::LeafCapability {}),
+                    l2: Some(models::LeafCapability {}),
+                    inner_product: Some(models::LeafCapability {}),
+                }),
             },
             mutation: models::MutationCapabilities {
                 transactional: None,
@@ -322,8 +307,13 @@ async fn get_capabilities() -> Json<models::CapabilitiesResponse> {
     })
 }
 // ANCHOR_END: capabilities
-// ANCHOR: schema1
+// ANCHOR: get_schema
 async fn get_schema() -> Json<models::SchemaResponse> {
+    Json(schema())
+}
+// ANCHOR_END: get_schema
+// ANCHOR: schema1
+fn schema() -> models::SchemaResponse {
     // ANCHOR_END: schema1
     let array_arguments: BTreeMap<models::ArgumentName, models::ArgumentInfo> = vec![(
         models::ArgumentName::from("limit"),
@@ -386,6 +376,13 @@ async fn get_schema() -> Json<models::SchemaResponse> {
                         models::ComparisonOperatorDefinition::EndsWithInsensitive,
                     ),
                     ("in".into(), models::ComparisonOperatorDefinition::In),
+                    (
+                        "search".into(),
+                        models::ComparisonOperatorDefinition::FullTextSearch {
+                            language: None,
+                            syntax: models::FullTextSearchSyntax::Plain,
+                        },
+                    ),
                     (
                         "like".into(),
                         models::ComparisonOperatorDefinition::Custom {
@@ -549,6 +546,29 @@ async fn get_schema() -> Json<models::SchemaResponse> {
                 ]),
             },
         ),
+        (
+            "Embedding".into(),
+            models::ScalarType {
+                representation: models::TypeRepresentation::Vector { dimensions: 3 },
+                aggregate_functions: BTreeMap::new(),
+                comparison_operators: BTreeMap::new(),
+                extraction_functions: BTreeMap::new(),
+            },
+        ),
+        (
+            "Geography".into(),
+            models::ScalarType {
+                representation: models::TypeRepresentation::Geography,
+                aggregate_functions: BTreeMap::new(),
+                comparison_operators: BTreeMap::from_iter([(
+                    "within_distance".into(),
+                    models::ComparisonOperatorDefinition::SpatialWithinDistance {
+                        argument_type: "point_within_distance".into(),
+                    },
+                )]),
+                extraction_functions: BTreeMap::new(),
+            },
+        ),
     ]);
     // ANCHOR_END: schema_scalar_types
     // ANCHOR: schema_object_type_article
@@ -633,6 +653,18 @@ async fn get_schema() -> Json<models::SchemaResponse> {
                     arguments: BTreeMap::new(),
                 },
             ),
+            (
+                "embedding".into(),
+                models::ObjectField {
+                    description: Some("An embedding of the author's research interests".into()),
+                    r#type: models::Type::Nullable {
+                        underlying_type: Box::new(models::Type::Named {
+                            name: "Embedding".into(),
+                        }),
+                    },
+                    arguments: BTreeMap::new(),
+                },
+            ),
         ]),
         foreign_keys: BTreeMap::new(),
     };
@@ -669,6 +701,18 @@ async fn get_schema() -> Json<models::SchemaResponse> {
                     arguments: BTreeMap::new(),
                 },
             ),
+            (
+                "geolocation".into(),
+                models::ObjectField {
+                    description: Some("The position of the institution's main campus".into()),
+                    r#type: models::Type::Nullable {
+                        underlying_type: Box::new(models::Type::Named {
+                            name: "Geography".into(),
+                        }),
+                    },
+                    arguments: BTreeMap::new(),
+                },
+            ),
             (
                 "staff".into(),
                 models::ObjectField {
@@ -870,6 +914,34 @@ async fn get_schema() -> Json<models::SchemaResponse> {
         foreign_keys: BTreeMap::new(),
     };
     // ANCHOR_END: schema_object_type_city
+    // ANCHOR: schema_object_type_point_within_distance
+    let point_within_distance_type = models::ObjectType {
+        description: Some("The argument to the within_distance operator".into()),
+        fields: BTreeMap::from_iter([
+            (
+                "geometry".into(),
+                models::ObjectField {
+                    description: Some("The geometry to measure the distance to".into()),
+                    r#type: models::Type::Named {
+                        name: "Geography".into(),
+                    },
+                    arguments: BTreeMap::new(),
+                },
+            ),
+            (
+                "distance".into(),
+                models::ObjectField {
+                    description: Some("The maximum distance, in meters".into()),
+                    r#type: models::Type::Named {
+                        name: "Float".into(),
+                    },
+                    arguments: BTreeMap::new(),
+                },
+            ),
+        ]),
+        foreign_keys: BTreeMap::new(),
+    };
+    // ANCHOR_END: schema_object_type_point_within_distance
     // ANCHOR: schema_object_types
     let object_types = BTreeMap::from_iter([
         ("article".into(), article_type),
@@ -879,6 +951,7 @@ async fn get_schema() -> Json<models::SchemaResponse> {
         ("staff_member".into(), staff_member_type),
         ("country".into(), country_type),
         ("city".into(), city_type),
+        ("point_within_distance".into(), point_within_distance_type),
     ]);
     // ANCHOR_END: schema_object_types
     // ANCHOR: schema_collection_article
@@ -1055,7 +1128,7 @@ async fn get_schema() -> Json<models::SchemaResponse> {
     // ANCHOR_END: request_arguments
 
     // ANCHOR: schema2
-    Json(models::SchemaResponse {
+    models::SchemaResponse {
         scalar_types,
         object_types,
         collections,
@@ -1063,7 +1136,7 @@ async fn get_schema() -> Json<models::SchemaResponse> {
         procedures,
         capabilities,
         request_arguments,
-    })
+    }
 }
 // ANCHOR_END: schema2
 // ANCHOR: post_query
@@ -1314,14 +1387,58 @@ fn execute_query(
         }
     })?;
     // ANCHOR_END: execute_query_filter
+    // ANCHOR: execute_query_distinct
+    let filtered: Vec<Row> = match &query.distinct {
+        None => filtered,
+        Some(distinct) => distinct_rows(
+            collection_relationships,
+            variables,
+            state,
+            query.fields.as_ref(),
+            distinct,
+            filtered,
+        )?,
+    };
+    // ANCHOR_END: execute_query_distinct
     // ANCHOR: execute_query_paginate
+    let filtered: Vec<Row> = match &query.after {
+        None => filtered,
+        Some(cursor) => {
+            let after = parse_cursor(cursor, query.order_by.as_ref())?;
+            let mut remaining = vec![];
+            for item in filtered {
+                let key = eval_sort_key(
+                    collection_relationships,
+                    variables,
+                    state,
+                    query.order_by.as_ref(),
+                    &item,
+                )?;
+                if compare_sort_keys(query.order_by.as_ref(), key, after.clone())?
+                    == Ordering::Greater
+                {
+                    remaining.push(item);
+                }
+            }
+            remaining
+        }
+    };
+    let remaining_count = filtered.len();
     let paginated: Vec<Row> = paginate(filtered.into_iter(), query.limit, query.offset);
     // ANCHOR_END: execute_query_paginate
     // ANCHOR: execute_query_aggregates
     let aggregates = query
         .aggregates
         .as_ref()
-        .map(|aggregates| eval_aggregates(variables, aggregates, &paginated))
+        .map(|aggregates| {
+            eval_aggregates(
+                collection_relationships,
+                variables,
+                state,
+                aggregates,
+                &paginated,
+            )
+        })
         .transpose()?;
     // ANCHOR_END: execute_query_aggregates
     // ANCHOR: execute_query_groups
@@ -1353,11 +1470,28 @@ fn execute_query(
         })
         .transpose()?;
     // ANCHOR_END: execute_query_fields
+    // ANCHOR: execute_query_next_cursor
+    let skipped_count = query.offset.unwrap_or(0) as usize;
+    let next_cursor = match paginated.last() {
+        Some(last) if rows.is_some() && skipped_count + paginated.len() < remaining_count => {
+            let key = eval_sort_key(
+                collection_relationships,
+                variables,
+                state,
+                query.order_by.as_ref(),
+                last,
+            )?;
+            Some(serde_json::Value::Array(key).to_string())
+        }
+        _ => None,
+    };
+    // ANCHOR_END: execute_query_next_cursor
     // ANCHOR: execute_query_rowset
     Ok(models::RowSet {
         aggregates,
         rows,
         groups,
+        next_cursor,
     })
     // ANCHOR_END: execute_query_rowset
 }
@@ -1391,7 +1525,13 @@ fn eval_groups(
         .collect();
     // ANCHOR_END: eval_groups_partition
     // ANCHOR: eval_groups_sort
-    let sorted = group_sort(variables, chunks, grouping.order_by.as_ref())?;
+    let sorted = group_sort(
+        collection_relationships,
+        variables,
+        state,
+        chunks,
+        grouping.order_by.as_ref(),
+    )?;
     // ANCHOR_END: eval_groups_sort
     // ANCHOR: eval_groups_filter
     let mut groups: Vec<models::Group> = vec![];
@@ -1403,11 +1543,23 @@ fn eval_groups(
         for (aggregate_name, aggregate) in &grouping.aggregates {
             aggregates.insert(
                 aggregate_name.clone(),
-                eval_aggregate(variables, aggregate, &chunk.rows)?,
+                eval_aggregate(
+                    collection_relationships,
+                    variables,
+                    state,
+                    aggregate,
+                    &chunk.rows,
+                )?,
             );
         }
         if let Some(predicate) = &grouping.predicate {
-            if eval_group_expression(variables, predicate, &chunk.rows)? {
+            if eval_group_expression(
+                collection_relationships,
+                variables,
+                state,
+                predicate,
+                &chunk.rows,
+            )? {
                 groups.push(models::Group {
                     dimensions: dimensions.clone(),
                     aggregates,
@@ -1431,14 +1583,16 @@ fn eval_groups(
 // ANCHOR_END: eval_groups
 // ANCHOR: eval_group_expression
 fn eval_group_expression(
+    collection_relationships: &BTreeMap<models::RelationshipName, models::Relationship>,
     variables: &BTreeMap<models::VariableName, serde_json::Value>,
+    state: &AppState,
     expr: &models::GroupExpression,
     rows: &[Row],
 ) -> Result<bool> {
     match expr {
         models::GroupExpression::And { expressions } => {
             for expr in expressions {
-                if !eval_group_expression(variables, expr, rows)? {
+                if !eval_group_expression(collection_relationships, variables, state, expr, rows)? {
                     return Ok(false);
                 }
             }
@@ -1446,14 +1600,20 @@ fn eval_group_expression(
         }
         models::GroupExpression::Or { expressions } => {
             for expr in expressions {
-                if eval_group_expression(variables, expr, rows)? {
+                if eval_group_expression(collection_relationships, variables, state, expr, rows)? {
                     return Ok(true);
                 }
             }
             Ok(false)
         }
         models::GroupExpression::Not { expression } => {
-            let b = eval_group_expression(variables, expression, rows)?;
+            let b = eval_group_expression(
+                collection_relationships,
+                variables,
+                state,
+                expression,
+                rows,
+            )?;
             Ok(!b)
         }
         models::GroupExpression::BinaryComparisonOperator {
@@ -1461,14 +1621,26 @@ fn eval_group_expression(
             operator,
             value,
         } => {
-            let left_val = eval_group_comparison_target(variables, target, rows)?;
+            let left_val = eval_group_comparison_target(
+                collection_relationships,
+                variables,
+                state,
+                target,
+                rows,
+            )?;
             let right_vals = eval_aggregate_comparison_value(variables, value)?;
             eval_comparison_operator(operator, &left_val, &right_vals)
         }
         ndc_models::GroupExpression::UnaryComparisonOperator { target, operator } => match operator
         {
             models::UnaryComparisonOperator::IsNull => {
-                let val = eval_group_comparison_target(variables, target, rows)?;
+                let val = eval_group_comparison_target(
+                    collection_relationships,
+                    variables,
+                    state,
+                    target,
+                    rows,
+                )?;
                 Ok(val.is_null())
             }
         },
@@ -1506,7 +1678,9 @@ struct Chunk {
 // ANCHOR_END: Chunk
 // ANCHOR: group_sort
 fn group_sort(
+    collection_relationships: &BTreeMap<models::RelationshipName, models::Relationship>,
     variables: &BTreeMap<models::VariableName, serde_json::Value>,
+    state: &AppState,
     groups: Vec<Chunk>,
     order_by: Option<&models::GroupOrderBy>,
 ) -> Result<Vec<Chunk>> {
@@ -1517,9 +1691,14 @@ fn group_sort(
             for item_to_insert in groups {
                 let mut index = 0;
                 for other in &copy {
-                    if let Ordering::Greater =
-                        eval_group_order_by(variables, order_by, other, &item_to_insert)?
-                    {
+                    if let Ordering::Greater = eval_group_order_by(
+                        collection_relationships,
+                        variables,
+                        state,
+                        order_by,
+                        other,
+                        &item_to_insert,
+                    )? {
                         break;
                     }
                     index += 1;
@@ -1534,7 +1713,9 @@ fn group_sort(
 
 // ANCHOR: eval_group_order_by
 fn eval_group_order_by(
+    collection_relationships: &BTreeMap<models::RelationshipName, models::Relationship>,
     variables: &BTreeMap<models::VariableName, serde_json::Value>,
+    state: &AppState,
     order_by: &models::GroupOrderBy,
     t1: &Chunk,
     t2: &Chunk,
@@ -1542,8 +1723,10 @@ fn eval_group_order_by(
     let mut result = Ordering::Equal;
 
     for element in &order_by.elements {
-        let v1 = eval_group_order_by_element(variables, element, t1)?;
-        let v2 = eval_group_order_by_element(variables, element, t2)?;
+        let v1 =
+            eval_group_order_by_element(collection_relationships, variables, state, element, t1)?;
+        let v2 =
+            eval_group_order_by_element(collection_relationships, variables, state, element, t2)?;
         let x = match element.order_direction {
             models::OrderDirection::Asc => compare(v1, v2)?,
             models::OrderDirection::Desc => compare(v2, v1)?,
@@ -1556,7 +1739,9 @@ fn eval_group_order_by(
 // ANCHOR_END: eval_group_order_by
 // ANCHOR: eval_group_order_by_element
 fn eval_group_order_by_element(
+    collection_relationships: &BTreeMap<models::RelationshipName, models::Relationship>,
     variables: &BTreeMap<models::VariableName, serde_json::Value>,
+    state: &AppState,
     element: &models::GroupOrderByElement,
     group: &Chunk,
 ) -> Result<serde_json::Value> {
@@ -1570,9 +1755,13 @@ fn eval_group_order_by_element(
                 }),
             ))
         }
-        models::GroupOrderByTarget::Aggregate { aggregate } => {
-            eval_aggregate(variables, &aggregate, &group.rows)
-        }
+        models::GroupOrderByTarget::Aggregate { aggregate } => eval_aggregate(
+            collection_relationships,
+            variables,
+            state,
+            &aggregate,
+            &group.rows,
+        ),
     }
 }
 // ANCHOR_END: eval_group_order_by_element
@@ -1721,20 +1910,24 @@ fn eval_row(
 // ANCHOR_END: eval_row
 // ANCHOR: eval_group_comparison_target
 fn eval_group_comparison_target(
+    collection_relationships: &BTreeMap<models::RelationshipName, models::Relationship>,
     variables: &BTreeMap<models::VariableName, serde_json::Value>,
+    state: &AppState,
     target: &models::GroupComparisonTarget,
     rows: &[Row],
 ) -> Result<serde_json::Value> {
     match target {
         models::GroupComparisonTarget::Aggregate { aggregate } => {
-            eval_aggregate(variables, aggregate, rows)
+            eval_aggregate(collection_relationships, variables, state, aggregate, rows)
         }
     }
 }
 // ANCHOR_END: eval_group_comparison_target
 // ANCHOR: eval_aggregates
 fn eval_aggregates(
+    collection_relationships: &BTreeMap<models::RelationshipName, models::Relationship>,
     variables: &BTreeMap<models::VariableName, serde_json::Value>,
+    state: &AppState,
     aggregates: &IndexMap<ndc_models::FieldName, ndc_models::Aggregate>,
     rows: &[Row],
 ) -> std::result::Result<
@@ -1745,7 +1938,7 @@ fn eval_aggregates(
     for (aggregate_name, aggregate) in aggregates {
         row.insert(
             aggregate_name.clone(),
-            eval_aggregate(variables, aggregate, rows)?,
+            eval_aggregate(collection_relationships, variables, state, aggregate, rows)?,
         );
     }
     Ok(row)
@@ -1753,17 +1946,42 @@ fn eval_aggregates(
 // ANCHOR_END: eval_aggregates
 // ANCHOR: eval_aggregate
 fn eval_aggregate(
+    collection_relationships: &BTreeMap<models::RelationshipName, models::Relationship>,
     variables: &BTreeMap<models::VariableName, serde_json::Value>,
+    state: &AppState,
     aggregate: &models::Aggregate,
     rows: &[Row],
 ) -> Result<serde_json::Value> {
+    let (models::Aggregate::StarCount { filter }
+    | models::Aggregate::ColumnCount { filter, .. }
+    | models::Aggregate::SingleColumn { filter, .. }) = aggregate;
+    let rows = match filter {
+        None => rows.iter().collect::<Vec<_>>(),
+        Some(filter) => {
+            let mut filtered = vec![];
+            for row in rows {
+                if eval_expression(
+                    collection_relationships,
+                    variables,
+                    state,
+                    filter,
+                    &[row],
+                    row,
+                )? {
+                    filtered.push(row);
+                }
+            }
+            filtered
+        }
+    };
     match aggregate {
-        models::Aggregate::StarCount {} => Ok(serde_json::Value::from(rows.len())),
+        models::Aggregate::StarCount { .. } => Ok(serde_json::Value::from(rows.len())),
         models::Aggregate::ColumnCount {
             column,
             arguments,
             field_path,
             distinct,
+            ..
         } => {
             let values = rows
                 .iter()
@@ -1807,13 +2025,19 @@ fn eval_aggregate(
             arguments,
             field_path,
             function,
+            distinct,
+            ..
         } => {
-            let values = rows
+            let mut values = rows
                 .iter()
                 .map(|row| {
                     eval_column_field_path(variables, row, column, field_path.as_deref(), arguments)
                 })
                 .collect::<Result<Vec<_>>>()?;
+            if distinct.unwrap_or_default() {
+                let mut seen = HashSet::new();
+                values.retain(|value| seen.insert(value.to_string()));
+            }
             eval_aggregate_function(function, &values)
         }
     }
@@ -1975,32 +2199,81 @@ fn sort(
     collection: Vec<Row>,
     order_by: Option<&models::OrderBy>,
 ) -> Result<Vec<Row>> {
-    match order_by {
-        None => Ok(collection),
-        Some(order_by) => {
-            let mut copy = vec![];
-            for item_to_insert in collection {
-                let mut index = 0;
-                for other in &copy {
-                    if let Ordering::Greater = eval_order_by(
-                        collection_relationships,
-                        variables,
-                        state,
-                        order_by,
-                        other,
-                        &item_to_insert,
-                    )? {
-                        break;
-                    }
-                    index += 1;
-                }
-                copy.insert(index, item_to_insert);
+    let mut copy = vec![];
+    for item_to_insert in collection {
+        let mut index = 0;
+        for other in &copy {
+            if let Ordering::Greater = eval_order_by(
+                collection_relationships,
+                variables,
+                state,
+                order_by,
+                other,
+                &item_to_insert,
+            )? {
+                break;
             }
-            Ok(copy)
+            index += 1;
         }
+        copy.insert(index, item_to_insert);
     }
+    Ok(copy)
 }
 // ANCHOR_END: sort
+// ANCHOR: distinct_rows
+fn distinct_rows(
+    collection_relationships: &BTreeMap<models::RelationshipName, models::Relationship>,
+    variables: &BTreeMap<models::VariableName, serde_json::Value>,
+    state: &AppState,
+    fields: Option<&IndexMap<models::FieldName, models::Field>>,
+    distinct: &models::Distinct,
+    rows: Vec<Row>,
+) -> Result<Vec<Row>> {
+    let mut keys: Vec<serde_json::Value> = vec![];
+    let mut kept: Vec<Row> = vec![];
+
+    for row in rows {
+        let key = match distinct {
+            models::Distinct::Fields => {
+                let selected = match fields {
+                    None => IndexMap::new(),
+                    Some(fields) => {
+                        eval_row(fields, collection_relationships, variables, state, &row)?
+                    }
+                };
+                serde_json::to_value(selected).map_err(|_| {
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(models::ErrorResponse {
+                            message: "unable to encode selected fields".into(),
+                            details: serde_json::Value::Null,
+                        }),
+                    )
+                })?
+            }
+            models::Distinct::On { columns } => columns
+                .iter()
+                .map(|column| {
+                    row.get(column).cloned().ok_or((
+                        StatusCode::BAD_REQUEST,
+                        Json(models::ErrorResponse {
+                            message: "invalid column name".into(),
+                            details: serde_json::Value::Null,
+                        }),
+                    ))
+                })
+                .collect::<Result<_>>()?,
+        };
+        // Rows are already sorted, so the first row with each key is kept
+        if !keys.contains(&key) {
+            keys.push(key);
+            kept.push(row);
+        }
+    }
+
+    Ok(kept)
+}
+// ANCHOR_END: distinct_rows
 // ANCHOR: paginate
 fn paginate<I: Iterator>(collection: I, limit: Option<u32>, offset: Option<u32>) -> Vec<I::Item> {
     let start = offset.unwrap_or(0).try_into().unwrap();
@@ -2010,21 +2283,91 @@ fn paginate<I: Iterator>(collection: I, limit: Option<u32>, offset: Option<u32>)
     }
 }
 // ANCHOR_END: paginate
+// ANCHOR: parse_cursor
+fn parse_cursor(
+    cursor: &str,
+    order_by: Option<&models::OrderBy>,
+) -> Result<Vec<serde_json::Value>> {
+    let element_count = order_by.map_or(0, |order_by| order_by.elements.len());
+    serde_json::from_str::<Vec<serde_json::Value>>(cursor)
+        .ok()
+        .filter(|key| key.len() == element_count + 1)
+        .ok_or((
+            StatusCode::BAD_REQUEST,
+            Json(models::ErrorResponse {
+                message: "invalid cursor".into(),
+                details: serde_json::Value::Null,
+            }),
+        ))
+}
+// ANCHOR_END: parse_cursor
 // ANCHOR: eval_order_by
 fn eval_order_by(
     collection_relationships: &BTreeMap<models::RelationshipName, models::Relationship>,
     variables: &BTreeMap<models::VariableName, serde_json::Value>,
     state: &AppState,
-    order_by: &models::OrderBy,
+    order_by: Option<&models::OrderBy>,
     t1: &Row,
     t2: &Row,
 ) -> Result<Ordering> {
+    let k1 = eval_sort_key(collection_relationships, variables, state, order_by, t1)?;
+    let k2 = eval_sort_key(collection_relationships, variables, state, order_by, t2)?;
+    compare_sort_keys(order_by, k1, k2)
+}
+// ANCHOR_END: eval_order_by
+// ANCHOR: eval_sort_key
+fn eval_sort_key(
+    collection_relationships: &BTreeMap<models::RelationshipName, models::Relationship>,
+    variables: &BTreeMap<models::VariableName, serde_json::Value>,
+    state: &AppState,
+    order_by: Option<&models::OrderBy>,
+    item: &Row,
+) -> Result<Vec<serde_json::Value>> {
+    let mut key = vec![];
+    for element in order_by.map_or(&[][..], |order_by| &order_by.elements) {
+        key.push(eval_order_by_element(
+            collection_relationships,
+            variables,
+            state,
+            element,
+            item,
+        )?);
+    }
+    // Break ties using the primary key, or the entire row if there is none,
+    // so that rows are totally ordered, and a cursor identifies a position
+    let tiebreaker = match item.get("id") {
+        Some(id) => id.clone(),
+        None => serde_json::to_string(item)
+            .map(serde_json::Value::String)
+            .map_err(|_| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(models::ErrorResponse {
+                        message: "unable to encode row".into(),
+                        details: serde_json::Value::Null,
+                    }),
+                )
+            })?,
+    };
+    key.push(tiebreaker);
+    Ok(key)
+}
+// ANCHOR_END: eval_sort_key
+// ANCHOR: compare_sort_keys
+fn compare_sort_keys(
+    order_by: Option<&models::OrderBy>,
+    k1: Vec<serde_json::Value>,
+    k2: Vec<serde_json::Value>,
+) -> Result<Ordering> {
+    let directions = order_by
+        .map_or(&[][..], |order_by| &order_by.elements)
+        .iter()
+        .map(|element| element.order_direction)
+        .chain(std::iter::once(models::OrderDirection::Asc));
     let mut result = Ordering::Equal;
 
-    for element in &order_by.elements {
-        let v1 = eval_order_by_element(collection_relationships, variables, state, element, t1)?;
-        let v2 = eval_order_by_element(collection_relationships, variables, state, element, t2)?;
-        let x = match element.order_direction {
+    for ((v1, v2), direction) in k1.into_iter().zip(k2).zip(directions) {
+        let x = match direction {
             models::OrderDirection::Asc => compare(v1, v2)?,
             models::OrderDirection::Desc => compare(v2, v1)?,
         };
@@ -2033,7 +2376,7 @@ fn eval_order_by(
 
     Ok(result)
 }
-// ANCHOR_END: eval_order_by
+// ANCHOR_END: compare_sort_keys
 // ANCHOR: compare
 fn compare(v1: serde_json::Value, v2: serde_json::Value) -> Result<Ordering> {
     match (v1, v2) {
@@ -2043,11 +2386,7 @@ fn compare(v1: serde_json::Value, v2: serde_json::Value) -> Result<Ordering> {
 
         (serde_json::Value::Bool(b1), serde_json::Value::Bool(b2)) => Ok(b1.cmp(&b2)),
         (serde_json::Value::Number(n1), serde_json::Value::Number(n2)) => {
-            if n1.as_f64().unwrap() < n2.as_f64().unwrap() {
-                Ok(Ordering::Less)
-            } else {
-                Ok(Ordering::Greater)
-            }
+            Ok(n1.as_f64().unwrap().total_cmp(&n2.as_f64().unwrap()))
         }
         (serde_json::Value::String(s1), serde_json::Value::String(s2)) => Ok(s1.cmp(&s2)),
         _ => Err((
@@ -2092,7 +2431,107 @@ fn eval_order_by_element(
                 &path,
                 std::slice::from_ref(item),
             )?;
-            eval_aggregate(variables, &aggregate, &rows)
+            eval_aggregate(
+                collection_relationships,
+                variables,
+                state,
+                &aggregate,
+                &rows,
+            )
+        }
+        models::OrderByTarget::Relevance {
+            name,
+            field_path,
+            operator,
+            value,
+        } => {
+            if operator.as_str() != "search" {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(models::ErrorResponse {
+                        message: format!("{operator} is not a full-text search operator"),
+                        details: serde_json::Value::Null,
+                    }),
+                ));
+            }
+            let column_value = eval_column_field_path(
+                variables,
+                item,
+                &name,
+                field_path.as_deref(),
+                &BTreeMap::new(),
+            )?;
+            let queries = eval_comparison_value(
+                collection_relationships,
+                variables,
+                &value,
+                state,
+                &[],
+                item,
+            )?;
+            let mut score: f64 = 0.0;
+            for query in &queries {
+                score = score.max(eval_search_score(&column_value, query)?);
+            }
+            Ok(serde_json::Value::from(score))
+        }
+        models::OrderByTarget::Distance {
+            name,
+            field_path,
+            value,
+        } => {
+            let column_value = eval_column_field_path(
+                variables,
+                item,
+                &name,
+                field_path.as_deref(),
+                &BTreeMap::new(),
+            )?;
+            let geometries = eval_comparison_value(
+                collection_relationships,
+                variables,
+                &value,
+                state,
+                &[],
+                item,
+            )?;
+            let mut nearest: Option<f64> = None;
+            for geometry in &geometries {
+                if let Some(distance) = eval_distance(&column_value, geometry)? {
+                    nearest = Some(nearest.map_or(distance, |nearest| nearest.min(distance)));
+                }
+            }
+            // Rows without a distance are ordered as if they were furthest away
+            Ok(serde_json::Value::from(nearest.unwrap_or(f64::MAX)))
+        }
+        models::OrderByTarget::Similarity {
+            name,
+            field_path,
+            metric,
+            value,
+        } => {
+            let column_value = eval_column_field_path(
+                variables,
+                item,
+                &name,
+                field_path.as_deref(),
+                &BTreeMap::new(),
+            )?;
+            let vectors = eval_comparison_value(
+                collection_relationships,
+                variables,
+                &value,
+                state,
+                &[],
+                item,
+            )?;
+            let mut score: Option<f64> = None;
+            for vector in &vectors {
+                if let Some(similarity) = eval_similarity(metric, &column_value, vector)? {
+                    score = Some(score.map_or(similarity, |score| score.max(similarity)));
+                }
+            }
+            Ok(score.map_or(serde_json::Value::Null, serde_json::Value::from))
         }
     }
 }
@@ -2494,8 +2933,10 @@ fn eval_expression(
                 fields: Some(IndexMap::new()),
                 limit: None,
                 offset: None,
+                after: None,
                 order_by: None,
                 predicate: predicate.clone().map(|e| *e),
+                distinct: None,
                 groups: None,
             };
             let collection = eval_in_collection(
@@ -2671,6 +3112,47 @@ fn eval_comparison_operator(
             Ok(false)
         }
         // ANCHOR_END: eval_expression_custom_binary_operators
+        // ANCHOR: eval_expression_full_text_search_operators
+        "search" => {
+            for query in right_vals {
+                if eval_search_score(left_val, query)? > 0.0 {
+                    return Ok(true);
+                }
+            }
+
+            Ok(false)
+        }
+        // ANCHOR_END: eval_expression_full_text_search_operators
+        // ANCHOR: eval_expression_geospatial_operators
+        "within_distance" => {
+            for argument in right_vals {
+                let geometry = argument.get("geometry").ok_or((
+                    StatusCode::BAD_REQUEST,
+                    Json(models::ErrorResponse {
+                        message: "expected argument field 'geometry'".into(),
+                        details: serde_json::Value::Null,
+                    }),
+                ))?;
+                let max_distance = argument
+                    .get("distance")
+                    .and_then(serde_json::Value::as_f64)
+                    .ok_or((
+                        StatusCode::BAD_REQUEST,
+                        Json(models::ErrorResponse {
+                            message: "expected a number in argument field 'distance'".into(),
+                            details: serde_json::Value::Null,
+                        }),
+                    ))?;
+                if eval_distance(left_val, geometry)?
+                    .is_some_and(|distance| distance <= max_distance)
+                {
+                    return Ok(true);
+                }
+            }
+
+            Ok(false)
+        }
+        // ANCHOR_END: eval_expression_geospatial_operators
         // ANCHOR: eval_expression_binary_array_operators
         "in" => {
             for comparison_value in right_vals {
@@ -2701,6 +3183,155 @@ fn eval_comparison_operator(
     }
 }
 // ANCHOR_END: eval_comparison_operator
+// ANCHOR: eval_search_score
+/// Score a string against a plain full-text search query. A string matches if
+/// it contains every word of the query, ignoring case, and its score is the
+/// fraction of its words which appear in the query. A string which does not
+/// match, or a null value, has a score of zero.
+#[allow(clippy::cast_precision_loss)]
+fn eval_search_score(value: &serde_json::Value, query: &serde_json::Value) -> Result<f64> {
+    fn words(text: &str) -> Vec<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    let query = query.as_str().ok_or((
+        StatusCode::BAD_REQUEST,
+        Json(models::ErrorResponse {
+            message: "search query is not a string".into(),
+            details: serde_json::Value::Null,
+        }),
+    ))?;
+    let text = match value {
+        serde_json::Value::Null => return Ok(0.0),
+        serde_json::Value::String(text) => text,
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(models::ErrorResponse {
+                    message: "full-text search is only supported on strings".into(),
+                    details: serde_json::Value::Null,
+                }),
+            ))
+        }
+    };
+
+    let query_words = words(query);
+    let text_words = words(text);
+    if query_words.is_empty() || !query_words.iter().all(|word| text_words.contains(word)) {
+        return Ok(0.0);
+    }
+    let matching = text_words
+        .iter()
+        .filter(|word| query_words.contains(word))
+        .count();
+    Ok(matching as f64 / text_words.len() as f64)
+}
+// ANCHOR_END: eval_search_score
+// ANCHOR: eval_similarity
+/// Compute the similarity score of a vector column and a vector value, which
+/// is greater for more similar vectors. The score is null if the column is
+/// null, or if the cosine similarity of a zero vector is requested.
+fn eval_similarity(
+    metric: models::SimilarityMetric,
+    column_value: &serde_json::Value,
+    value: &serde_json::Value,
+) -> Result<Option<f64>> {
+    fn vector(value: &serde_json::Value) -> Result<Vec<f64>> {
+        value
+            .as_array()
+            .and_then(|elements| elements.iter().map(serde_json::Value::as_f64).collect())
+            .ok_or((
+                StatusCode::BAD_REQUEST,
+                Json(models::ErrorResponse {
+                    message: "expected a vector of numbers".into(),
+                    details: serde_json::Value::Null,
+                }),
+            ))
+    }
+
+    if column_value.is_null() {
+        return Ok(None);
+    }
+    let (left, right) = (vector(column_value)?, vector(value)?);
+    if left.len() != right.len() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(models::ErrorResponse {
+                message: "vectors have different dimensions".into(),
+                details: serde_json::Value::Null,
+            }),
+        ));
+    }
+
+    let dot = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(x, y)| x * y).sum::<f64>();
+    let score = match metric {
+        models::SimilarityMetric::Cosine => {
+            let norms = dot(&left, &left).sqrt() * dot(&right, &right).sqrt();
+            if norms == 0.0 {
+                return Ok(None);
+            }
+            dot(&left, &right) / norms
+        }
+        models::SimilarityMetric::L2 => -left
+            .iter()
+            .zip(&right)
+            .map(|(x, y)| (x - y) * (x - y))
+            .sum::<f64>()
+            .sqrt(),
+        models::SimilarityMetric::InnerProduct => dot(&left, &right),
+    };
+    Ok(Some(score))
+}
+// ANCHOR_END: eval_similarity
+// ANCHOR: eval_distance
+/// Compute the distance in meters between two GeoJSON points, along the
+/// surface of the earth. The distance is null if the column is null. Only
+/// points are supported, so any other geometry is rejected.
+fn eval_distance(
+    column_value: &serde_json::Value,
+    value: &serde_json::Value,
+) -> Result<Option<f64>> {
+    const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+    fn point(value: &serde_json::Value) -> Result<(f64, f64)> {
+        let coordinates = (value.get("type") == Some(&serde_json::json!("Point")))
+            .then(|| value.get("coordinates")?.as_array())
+            .flatten()
+            .filter(|coordinates| coordinates.len() >= 2)
+            .ok_or((
+                StatusCode::BAD_REQUEST,
+                Json(models::ErrorResponse {
+                    message: "expected a GeoJSON point".into(),
+                    details: serde_json::Value::Null,
+                }),
+            ))?;
+        match (coordinates[0].as_f64(), coordinates[1].as_f64()) {
+            (Some(longitude), Some(latitude)) => {
+                Ok((longitude.to_radians(), latitude.to_radians()))
+            }
+            _ => Err((
+                StatusCode::BAD_REQUEST,
+                Json(models::ErrorResponse {
+                    message: "expected numeric coordinates".into(),
+                    details: serde_json::Value::Null,
+                }),
+            )),
+        }
+    }
+
+    if column_value.is_null() {
+        return Ok(None);
+    }
+    let ((lon1, lat1), (lon2, lat2)) = (point(column_value)?, point(value)?);
+    // The haversine formula
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    Ok(Some(2.0 * EARTH_RADIUS_METERS * a.sqrt().min(1.0).asin()))
+}
+// ANCHOR_END: eval_distance
 // ANCHOR: eval_partial_ord_comparison
 fn eval_partial_ord_comparison<'a, T, FConvert>(
     operator: &ndc_models::ComparisonOperatorName,
@@ -2890,7 +3521,7 @@ fn eval_comparison_target(
                 path,
                 std::slice::from_ref(item),
             )?;
-            eval_aggregate(variables, aggregate, &rows)
+            eval_aggregate(collection_relationships, variables, state, aggregate, &rows)
         }
     }
 }
@@ -2910,6 +3541,12 @@ fn eval_column(
         }),
     ))?;
 
+    // Vectors are stored as arrays of numbers, but are scalar values, and take
+    // no arguments
+    if VECTOR_COLUMNS.contains(column_name) {
+        return Ok(column);
+    }
+
     if let Some(array) = column.as_array() {
         let limit_argument = arguments.get("limit").ok_or((
             StatusCode::BAD_REQUEST,
@@ -2946,6 +3583,40 @@ fn eval_column(
     }
 }
 // ANCHOR_END: eval_column
+// ANCHOR: vector_columns
+/// The names of the columns whose types are scalar types with a vector
+/// representation. Rows do not record their object type, so a column is
+/// identified by its name alone.
+static VECTOR_COLUMNS: LazyLock<BTreeSet<models::FieldName>> = LazyLock::new(|| {
+    let schema = schema();
+    let is_vector = |r#type: &models::Type| {
+        let name = match r#type {
+            models::Type::Named { name } => name,
+            models::Type::Nullable { underlying_type } => match underlying_type.as_ref() {
+                models::Type::Named { name } => name,
+                _ => return false,
+            },
+            _ => return false,
+        };
+        schema
+            .scalar_types
+            .get(name.as_str())
+            .is_some_and(|scalar_type| {
+                matches!(
+                    scalar_type.representation,
+                    models::TypeRepresentation::Vector { .. }
+                )
+            })
+    };
+    schema
+        .object_types
+        .values()
+        .flat_map(|object_type| &object_type.fields)
+        .filter(|(_, field)| is_vector(&field.r#type))
+        .map(|(field_name, _)| field_name.clone())
+        .collect()
+});
+// ANCHOR_END: vector_columns
 // ANCHOR: eval_comparison_value
 fn eval_comparison_value(
     collection_relationships: &BTreeMap<models::RelationshipName, models::Relationship>,
@@ -3260,6 +3931,16 @@ fn execute_mutation_operation(
             fields.as_ref(),
             collection_relationships,
         ),
+        models::MutationOperation::RelationalInsert(_)
+        | models::MutationOperation::RelationalUpsert(_)
+        | models::MutationOperation::RelationalUpdate(_)
+        | models::MutationOperation::RelationalDelete(_) => Err((
+            StatusCode::NOT_IMPLEMENTED,
+            Json(models::ErrorResponse {
+                message: "relational mutations are not supported".into(),
+                details: serde_json::Value::Null,
+            }),
+        )),
     }
 }
 // ANCHOR_END: execute_mutation_operation
@@ -3494,7 +4175,7 @@ mod tests {
         connector::Connector,
         error::{Error, Result},
         reporter::TestResults,
-        test_cases::query::validate::validate_response,
+        test_cases::query::validate::{validate_request, validate_response},
         test_connector,
     };
     use std::{fs::File, path::PathBuf, sync::Arc};
@@ -3558,6 +4239,7 @@ mod tests {
     fn test_query() {
         let test_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests");
         let schema = tokio_test::block_on(crate::get_schema());
+        let capabilities = tokio_test::block_on(crate::get_capabilities());
 
         insta::glob!(test_dir, "query/**/request.json", |req_path| {
             let path = req_path.parent().unwrap();
@@ -3568,6 +4250,20 @@ mod tests {
                     panic!("unable to deserialize request in test {test_name}: {err}")
                 });
 
+            validate_request(&schema, &request).unwrap_or_else(|err| {
+                panic!("unable to validate request in test {test_name}: {err}")
+            });
+
+            let missing = models::required_capabilities::missing_capabilities(
+                &models::required_capabilities::for_query_request(&request),
+                &capabilities,
+            );
+            assert!(
+                missing.is_empty(),
+                "request in test {test_name} requires unsupported capabilities: {}",
+                missing.join(", ")
+            );
+
             let response = tokio_test::block_on(async {
                 let state = Arc::new(Mutex::new(crate::init_app_state()));
                 crate::post_query(State(state), Json(request.clone()))
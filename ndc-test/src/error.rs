@@ -8,12 +8,10 @@ pub enum Error {
     CommunicationError(#[from] Box<super::client::Error>),
     #[error("error generating test data: {0}")]
     StrategyError(#[from] rand::Error),
-    #[error("error parsing semver range: {0}")]
-    SemverError(#[from] semver::Error),
     #[error(
-        "capabilities.version ({0}) is not compatible with the current version of the specification ({1})"
+        "capabilities.version is not compatible with the current version of the specification: {0}"
     )]
-    IncompatibleSpecification(semver::Version, semver::VersionReq),
+    IncompatibleSpecification(ndc_models::version::VersionError),
     #[error("collection {0} is not a defined collection")]
     CollectionIsNotDefined(ndc_models::CollectionName),
     #[error("collection type {0} is not a defined object type")]
@@ -56,6 +54,8 @@ pub enum Error {
     MultipleEqualityOperators(ndc_models::ScalarTypeName),
     #[error("scalar type {0} has an invalid type representation")]
     InvalidTypeRepresentation(ndc_models::ScalarTypeName),
+    #[error("field {1} of within-distance argument type {0} is invalid: expected {2}")]
+    InvalidWithinDistanceArgumentType(ndc_models::ObjectTypeName, ndc_models::FieldName, String),
     #[error("error response from connector: {0:?}")]
     ConnectorError(ndc_models::ErrorResponse),
     #[error("cannot open snapshot file: {0:?}")]
@@ -74,6 +74,8 @@ pub enum Error {
     ResponseDoesNotSatisfy(String),
     #[error("invalid response at path {}: expected {1}", .0.join("."))]
     InvalidValueInResponse(Vec<String>, String),
+    #[error("invalid scalar value in response at path {}: {1}", .0.join("."))]
+    InvalidScalarValueInResponse(Vec<String>, ndc_models::value::ValueError),
     #[error("expected matching responses, but got:\n{0:#?}\n{1:#?}")]
     ExpectedMatchingResponses(ndc_models::QueryResponse, ndc_models::QueryResponse),
     #[error("invalid request: {0}")]
@@ -15,15 +15,28 @@ use std::collections::BTreeMap;
 use super::common;
 use super::validate::expect_single_rowset;
 
+/// A collection whose aggregates should be tested, and the aggregate
+/// capabilities of the connector
+pub struct AggregateTestCase<'a> {
+    pub schema: &'a models::SchemaResponse,
+    pub capabilities: &'a models::AggregateCapabilities,
+    pub collection_info: &'a models::CollectionInfo,
+    pub request_arguments: Option<BTreeMap<models::ArgumentName, serde_json::Value>>,
+}
+
 pub async fn test_aggregate_queries<C: Connector, R: Reporter>(
     gen_config: &TestGenerationConfiguration,
     connector: &C,
     reporter: &mut R,
-    schema: &models::SchemaResponse,
-    collection_info: &models::CollectionInfo,
-    request_arguments: Option<BTreeMap<models::ArgumentName, serde_json::Value>>,
+    test_case: AggregateTestCase<'_>,
     rng: &mut SmallRng,
 ) -> Option<()> {
+    let AggregateTestCase {
+        schema,
+        capabilities,
+        collection_info,
+        request_arguments,
+    } = test_case;
     let collection_type = schema.object_types.get(&collection_info.collection_type)?;
 
     let total_count = test!(
@@ -50,6 +63,35 @@ pub async fn test_aggregate_queries<C: Connector, R: Reporter>(
         )
     );
 
+    if capabilities.filter.is_some() {
+        let _ = test!(
+            "filtered_star_count",
+            reporter,
+            test_filtered_star_count_aggregate(
+                gen_config,
+                connector,
+                collection_info,
+                total_count,
+                request_arguments.clone()
+            )
+        );
+    }
+
+    if capabilities.distinct.is_some() {
+        let _ = test!(
+            "distinct_single_column",
+            reporter,
+            test_distinct_single_column_aggregates(
+                gen_config,
+                connector,
+                schema,
+                collection_info,
+                collection_type,
+                request_arguments.clone()
+            )
+        );
+    }
+
     let _ = test!(
         "single_column",
         reporter,
@@ -73,7 +115,10 @@ pub async fn test_star_count_aggregate<C: Connector>(
     collection_info: &models::CollectionInfo,
     request_arguments: Option<BTreeMap<models::ArgumentName, serde_json::Value>>,
 ) -> Result<u64> {
-    let aggregates = IndexMap::from([("count".into(), models::Aggregate::StarCount {})]);
+    let aggregates = IndexMap::from([(
+        "count".into(),
+        models::Aggregate::StarCount { filter: None },
+    )]);
     let query_request = models::QueryRequest {
         collection: collection_info.name.clone(),
         query: models::Query {
@@ -81,8 +126,10 @@ pub async fn test_star_count_aggregate<C: Connector>(
             fields: None,
             limit: Some(gen_config.max_limit),
             offset: None,
+            after: None,
             order_by: None,
             predicate: None,
+            distinct: None,
             groups: None,
         },
         arguments: BTreeMap::new(),
@@ -104,6 +151,164 @@ pub async fn test_star_count_aggregate<C: Connector>(
     }
 }
 
+/// Count rows using filters which are always true and always false, and check
+/// the counts against the total count
+pub async fn test_filtered_star_count_aggregate<C: Connector>(
+    gen_config: &TestGenerationConfiguration,
+    connector: &C,
+    collection_info: &models::CollectionInfo,
+    total_count: u64,
+    request_arguments: Option<BTreeMap<models::ArgumentName, serde_json::Value>>,
+) -> Result<()> {
+    let filtered_count = |filter: models::Expression| models::Aggregate::StarCount {
+        filter: Some(Box::new(filter)),
+    };
+    let aggregates = IndexMap::from([
+        (
+            "all".into(),
+            filtered_count(models::Expression::And {
+                expressions: vec![],
+            }),
+        ),
+        (
+            "none".into(),
+            filtered_count(models::Expression::Or {
+                expressions: vec![],
+            }),
+        ),
+    ]);
+    let query_request = models::QueryRequest {
+        collection: collection_info.name.clone(),
+        query: models::Query {
+            aggregates: Some(aggregates),
+            fields: None,
+            limit: Some(gen_config.max_limit),
+            offset: None,
+            after: None,
+            order_by: None,
+            predicate: None,
+            distinct: None,
+            groups: None,
+        },
+        arguments: BTreeMap::new(),
+        collection_relationships: BTreeMap::new(),
+        variables: None,
+        request_arguments,
+    };
+    let response = connector.query(query_request).await?;
+
+    let row_set = expect_single_rowset(&response)?;
+    let Some(aggregates) = &row_set.aggregates else {
+        return Err(Error::AggregatesShouldBeNonNullInRowSet);
+    };
+
+    for (name, expected) in [("all", total_count), ("none", 0)] {
+        let Some(count) = aggregates.get(name).and_then(serde_json::Value::as_u64) else {
+            return Err(Error::MissingField(name.into()));
+        };
+        if count != expected {
+            return Err(Error::ResponseDoesNotSatisfy(format!(
+                "filtered count {name} should be {expected}, but was {count}"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Apply each `min` and `max` aggregate function with and without `distinct`,
+/// and check that the results are the same, since duplicate values cannot
+/// change the minimum or maximum
+pub async fn test_distinct_single_column_aggregates<C: Connector>(
+    gen_config: &TestGenerationConfiguration,
+    connector: &C,
+    schema: &models::SchemaResponse,
+    collection_info: &models::CollectionInfo,
+    collection_type: &models::ObjectType,
+    request_arguments: Option<BTreeMap<models::ArgumentName, serde_json::Value>>,
+) -> Result<()> {
+    let mut aggregates = IndexMap::new();
+    let mut pairs = vec![];
+
+    for (field_name, field) in common::select_all_columns_without_arguments(collection_type) {
+        let Some(scalar_type) =
+            common::as_named_type(&field.r#type).and_then(|name| schema.scalar_types.get(name))
+        else {
+            continue;
+        };
+        for (function_name, definition) in &scalar_type.aggregate_functions {
+            if !matches!(
+                definition,
+                models::AggregateFunctionDefinition::Min | models::AggregateFunctionDefinition::Max
+            ) {
+                continue;
+            }
+            let name = models::FieldName::from(format!("{field_name}_{function_name}"));
+            let distinct_name =
+                models::FieldName::from(format!("{field_name}_{function_name}_distinct"));
+            for (name, distinct) in [(&name, None), (&distinct_name, Some(true))] {
+                aggregates.insert(
+                    name.clone(),
+                    models::Aggregate::SingleColumn {
+                        column: field_name.clone(),
+                        arguments: BTreeMap::new(),
+                        field_path: None,
+                        function: function_name.clone(),
+                        distinct,
+                        filter: None,
+                    },
+                );
+            }
+            pairs.push((name, distinct_name));
+        }
+    }
+
+    if pairs.is_empty() {
+        return Ok(());
+    }
+
+    let query_request = models::QueryRequest {
+        collection: collection_info.name.clone(),
+        query: models::Query {
+            aggregates: Some(aggregates),
+            fields: None,
+            limit: Some(gen_config.max_limit),
+            offset: None,
+            after: None,
+            order_by: None,
+            predicate: None,
+            distinct: None,
+            groups: None,
+        },
+        arguments: BTreeMap::new(),
+        collection_relationships: BTreeMap::new(),
+        variables: None,
+        request_arguments,
+    };
+    let response = connector.query(query_request).await?;
+
+    let row_set = expect_single_rowset(&response)?;
+    let Some(aggregates) = &row_set.aggregates else {
+        return Err(Error::AggregatesShouldBeNonNullInRowSet);
+    };
+
+    for (name, distinct_name) in pairs {
+        let value = aggregates
+            .get(&name)
+            .ok_or_else(|| Error::MissingField(name.clone()))?;
+        let distinct_value = aggregates
+            .get(&distinct_name)
+            .ok_or_else(|| Error::MissingField(distinct_name.clone()))?;
+        if value != distinct_value {
+            return Err(Error::ResponseDoesNotSatisfy(format!(
+                "{distinct_name} should equal {name}, but was {distinct_value}"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 pub async fn test_column_count_aggregate<C: Connector>(
     gen_config: &TestGenerationConfiguration,
     connector: &C,
@@ -125,6 +330,7 @@ pub async fn test_column_count_aggregate<C: Connector>(
             arguments: BTreeMap::new(),
             field_path: None,
             distinct: false,
+            filter: None,
         };
         aggregates.insert(
             models::FieldName::from(format!("{field_name}_count")),
@@ -136,6 +342,7 @@ pub async fn test_column_count_aggregate<C: Connector>(
             arguments: BTreeMap::new(),
             field_path: None,
             distinct: true,
+            filter: None,
         };
         aggregates.insert(
             models::FieldName::from(format!("{field_name}_distinct_count")),
@@ -150,8 +357,10 @@ pub async fn test_column_count_aggregate<C: Connector>(
             fields: None,
             limit: Some(gen_config.max_limit),
             offset: None,
+            after: None,
             order_by: None,
             predicate: None,
+            distinct: None,
             groups: None,
         },
         arguments: BTreeMap::new(),
@@ -220,6 +429,8 @@ pub async fn test_single_column_aggregates<C: Connector>(
                         arguments: BTreeMap::new(),
                         field_path: None,
                         function: function_name.clone(),
+                        distinct: None,
+                        filter: None,
                     };
                     available_aggregates.insert(
                         models::FieldName::from(format!(
@@ -249,8 +460,10 @@ pub async fn test_single_column_aggregates<C: Connector>(
             fields: None,
             limit: Some(gen_config.max_limit),
             offset: None,
+            after: None,
             order_by: None,
             predicate: None,
+            distinct: None,
             groups: None,
         },
         arguments: BTreeMap::new(),
@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+
+use crate::configuration::TestGenerationConfiguration;
+use crate::connector::Connector;
+use crate::error::{Error, Result};
+use crate::reporter::Reporter;
+use crate::test;
+
+use ndc_models as models;
+
+use indexmap::IndexMap;
+use rand::rngs::SmallRng;
+use rand::seq::IteratorRandom;
+
+use super::validate::expect_single_rows;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn test_distinct<C: Connector, R: Reporter>(
+    gen_config: &TestGenerationConfiguration,
+    connector: &C,
+    reporter: &mut R,
+    capabilities: &models::DistinctCapabilities,
+    schema: &models::SchemaResponse,
+    request_arguments: Option<BTreeMap<models::ArgumentName, serde_json::Value>>,
+    collection_info: &models::CollectionInfo,
+    rng: &mut SmallRng,
+) -> Option<()> {
+    let collection_type = schema.object_types.get(&collection_info.collection_type)?;
+
+    test!("Distinct fields", reporter, async {
+        let fields = super::common::select_columns(collection_type, rng);
+
+        let response = connector
+            .query(make_request(
+                gen_config,
+                collection_info,
+                fields,
+                models::Distinct::Fields,
+                request_arguments.clone(),
+            ))
+            .await?;
+
+        expect_distinct(&expect_single_rows(&response)?)
+    });
+
+    if capabilities.on.is_some() {
+        test!("Distinct on a column", reporter, async {
+            let Some((column_name, field)) = super::common::select_all_columns(collection_type)
+                .into_iter()
+                .filter(|(_, field)| {
+                    matches!(field, models::Field::Column { arguments, .. } if arguments.is_empty())
+                })
+                .choose(rng)
+            else {
+                eprintln!("Skipping collection without columns {}", collection_info.name);
+                return Ok(());
+            };
+
+            let response = connector
+                .query(make_request(
+                    gen_config,
+                    collection_info,
+                    IndexMap::from([(column_name.clone(), field)]),
+                    models::Distinct::On {
+                        columns: vec![column_name],
+                    },
+                    request_arguments.clone(),
+                ))
+                .await?;
+
+            expect_distinct(&expect_single_rows(&response)?)
+        });
+    }
+
+    Some(())
+}
+
+fn make_request(
+    gen_config: &TestGenerationConfiguration,
+    collection_info: &models::CollectionInfo,
+    fields: IndexMap<models::FieldName, models::Field>,
+    distinct: models::Distinct,
+    request_arguments: Option<BTreeMap<models::ArgumentName, serde_json::Value>>,
+) -> models::QueryRequest {
+    models::QueryRequest {
+        collection: collection_info.name.clone(),
+        query: models::Query {
+            aggregates: None,
+            fields: Some(fields),
+            limit: Some(gen_config.max_limit),
+            offset: None,
+            after: None,
+            order_by: None,
+            predicate: None,
+            distinct: Some(distinct),
+            groups: None,
+        },
+        arguments: BTreeMap::new(),
+        collection_relationships: BTreeMap::new(),
+        variables: None,
+        request_arguments,
+    }
+}
+
+fn expect_distinct(rows: &[IndexMap<models::FieldName, models::RowFieldValue>]) -> Result<()> {
+    for (index, row) in rows.iter().enumerate() {
+        if rows[..index].contains(row) {
+            return Err(Error::ResponseDoesNotSatisfy(format!(
+                "expected distinct rows, but row {index} duplicates an earlier row"
+            )));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+
+use crate::configuration::TestGenerationConfiguration;
+use crate::connector::Connector;
+use crate::error::{Error, Result};
+
+use ndc_models as models;
+use ndc_models::builders::{col, lit};
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+
+use super::validate::expect_single_rows;
+
+pub async fn test_order_by_relevance<C: Connector>(
+    gen_config: &TestGenerationConfiguration,
+    connector: &C,
+    context: Option<&super::context::Context<'_>>,
+    schema: &models::SchemaResponse,
+    request_arguments: Option<BTreeMap<models::ArgumentName, serde_json::Value>>,
+    rng: &mut SmallRng,
+    collection_info: &models::CollectionInfo,
+) -> Result<()> {
+    let collection_type = schema
+        .object_types
+        .get(&collection_info.collection_type)
+        .ok_or_else(|| {
+            Error::CollectionTypeIsNotDefined(collection_info.collection_type.clone())
+        })?;
+    let Some(context) = context else {
+        eprintln!("Skipping empty collection {}", collection_info.name);
+        return Ok(());
+    };
+
+    // Search for a value taken from a column which supports full-text search
+    let candidates = collection_type
+        .fields
+        .iter()
+        .filter(|(_, field)| field.arguments.is_empty())
+        .filter_map(|(field_name, field)| {
+            let type_name = super::common::as_named_type(&field.r#type)?;
+            let scalar_type = schema
+                .scalar_types
+                .get(&models::ScalarTypeName::new(type_name.clone()))?;
+            let operator_name = scalar_type.comparison_operators.iter().find_map(
+                |(operator_name, definition)| {
+                    matches!(
+                        definition,
+                        models::ComparisonOperatorDefinition::FullTextSearch { .. }
+                    )
+                    .then_some(operator_name)
+                },
+            )?;
+            let values = context
+                .values
+                .get(field_name)?
+                .iter()
+                .filter(|value| value.is_string())
+                .collect::<Vec<_>>();
+            (!values.is_empty()).then_some((field_name, operator_name, values))
+        })
+        .collect::<Vec<_>>();
+
+    let Some((field_name, operator_name, values)) = candidates.choose(rng) else {
+        eprintln!(
+            "Skipping collection without full-text search columns {}",
+            collection_info.name
+        );
+        return Ok(());
+    };
+    let (field_name, operator_name) = ((*field_name).clone(), (*operator_name).clone());
+    let query = values
+        .choose(rng)
+        .map_or(serde_json::Value::Null, |&value| value.clone());
+
+    let fields = super::common::select_all_columns(collection_type);
+
+    let matching_query = models::Query::new()
+        .filter(col(field_name.clone()).op(operator_name.clone(), lit(query.clone())))
+        .limit(gen_config.max_limit);
+    let matching_rows = expect_single_rows(
+        &connector
+            .query(make_request(
+                collection_info,
+                models::Query {
+                    fields: Some(fields.clone()),
+                    ..matching_query
+                },
+                request_arguments.clone(),
+            ))
+            .await?,
+    )?;
+
+    // If the matching rows were truncated, then the most relevant rows cannot
+    // be compared with them
+    let Ok(limit) = u32::try_from(matching_rows.len()) else {
+        return Ok(());
+    };
+    if matching_rows.is_empty() || limit >= gen_config.max_limit {
+        return Ok(());
+    }
+
+    let ordered_query = models::Query::new()
+        .order_by(models::OrderByElement {
+            order_direction: models::OrderDirection::Desc,
+            target: models::OrderByTarget::Relevance {
+                name: field_name,
+                field_path: None,
+                operator: operator_name,
+                value: lit(query),
+            },
+        })
+        .limit(limit);
+    let ordered_rows = expect_single_rows(
+        &connector
+            .query(make_request(
+                collection_info,
+                models::Query {
+                    fields: Some(fields),
+                    ..ordered_query
+                },
+                request_arguments,
+            ))
+            .await?,
+    )?;
+
+    // Rows which do not match have the lowest score, so the most relevant
+    // rows are exactly the matching rows
+    if ordered_rows.len() != matching_rows.len()
+        || !ordered_rows.iter().all(|row| matching_rows.contains(row))
+    {
+        return Err(Error::ResponseDoesNotSatisfy(
+            "expected the most relevant rows to be the rows which match the search query".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn make_request(
+    collection_info: &models::CollectionInfo,
+    query: models::Query,
+    request_arguments: Option<BTreeMap<models::ArgumentName, serde_json::Value>>,
+) -> models::QueryRequest {
+    models::QueryRequest {
+        collection: collection_info.name.clone(),
+        query,
+        arguments: BTreeMap::new(),
+        collection_relationships: BTreeMap::new(),
+        variables: None,
+        request_arguments,
+    }
+}
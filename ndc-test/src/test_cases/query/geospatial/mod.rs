@@ -0,0 +1,288 @@
+use std::collections::BTreeMap;
+
+use crate::configuration::TestGenerationConfiguration;
+use crate::connector::Connector;
+use crate::error::{Error, Result};
+
+use indexmap::IndexMap;
+use ndc_models as models;
+use ndc_models::builders::{col, lit};
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+
+use super::validate::expect_single_rows;
+
+/// A column whose scalar type has a geospatial representation, along with the
+/// name of its `spatial_within_distance` operator, if any, and its values
+struct GeospatialColumn<'a> {
+    name: &'a models::FieldName,
+    representation: &'a models::TypeRepresentation,
+    within_distance: Option<&'a models::ComparisonOperatorName>,
+    values: &'a [serde_json::Value],
+}
+
+pub async fn test_within_distance<C: Connector>(
+    gen_config: &TestGenerationConfiguration,
+    connector: &C,
+    context: Option<&super::context::Context<'_>>,
+    schema: &models::SchemaResponse,
+    request_arguments: Option<BTreeMap<models::ArgumentName, serde_json::Value>>,
+    rng: &mut SmallRng,
+    collection_info: &models::CollectionInfo,
+) -> Result<()> {
+    let Some(context) = context else {
+        eprintln!("Skipping empty collection {}", collection_info.name);
+        return Ok(());
+    };
+
+    let candidates = geospatial_columns(schema, collection_info, context)?
+        .into_iter()
+        .filter(|column| column.within_distance.is_some())
+        .collect::<Vec<_>>();
+    let Some(column) = candidates.choose(rng) else {
+        eprintln!(
+            "Skipping collection without within-distance operators {}",
+            collection_info.name
+        );
+        return Ok(());
+    };
+    let Some((operator_name, geometry)) = column.within_distance.zip(column.values.choose(rng))
+    else {
+        return Ok(());
+    };
+
+    // The row which the geometry was taken from is at distance zero
+    let query = models::Query::new()
+        .filter(col(column.name.clone()).op(
+            operator_name.clone(),
+            lit(serde_json::json!({ "geometry": geometry, "distance": 0.0 })),
+        ))
+        .limit(gen_config.max_limit);
+    let rows = expect_single_rows(
+        &connector
+            .query(make_request(
+                collection_info,
+                column.name,
+                query,
+                request_arguments,
+            ))
+            .await?,
+    )?;
+
+    if rows.is_empty() {
+        return Err(Error::ResponseDoesNotSatisfy(
+            "expected the row containing the geometry to be within distance zero of it".into(),
+        ));
+    }
+    for row in &rows {
+        let value = &row
+            .get(column.name)
+            .ok_or_else(|| Error::MissingField(column.name.clone()))?
+            .0;
+        if point_distance(column.representation, value, geometry).is_some_and(|d| d > 1e-6) {
+            return Err(Error::ResponseDoesNotSatisfy(
+                "expected only points at distance zero to be within distance zero".into(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn test_order_by_distance<C: Connector>(
+    gen_config: &TestGenerationConfiguration,
+    connector: &C,
+    context: Option<&super::context::Context<'_>>,
+    schema: &models::SchemaResponse,
+    request_arguments: Option<BTreeMap<models::ArgumentName, serde_json::Value>>,
+    rng: &mut SmallRng,
+    collection_info: &models::CollectionInfo,
+) -> Result<()> {
+    let Some(context) = context else {
+        eprintln!("Skipping empty collection {}", collection_info.name);
+        return Ok(());
+    };
+
+    let candidates = geospatial_columns(schema, collection_info, context)?;
+    let Some(column) = candidates.choose(rng) else {
+        eprintln!(
+            "Skipping collection without geospatial columns {}",
+            collection_info.name
+        );
+        return Ok(());
+    };
+    let Some(geometry) = column.values.choose(rng) else {
+        return Ok(());
+    };
+
+    let query = models::Query::new()
+        .order_by(models::OrderByElement {
+            order_direction: models::OrderDirection::Asc,
+            target: models::OrderByTarget::Distance {
+                name: column.name.clone(),
+                field_path: None,
+                value: lit(geometry.clone()),
+            },
+        })
+        .limit(gen_config.max_limit);
+    let rows = expect_single_rows(
+        &connector
+            .query(make_request(
+                collection_info,
+                column.name,
+                query,
+                request_arguments,
+            ))
+            .await?,
+    )?;
+
+    // The distances of points must not decrease, and null values are furthest
+    // away. Distances to other geometries are not checked.
+    let mut previous: Option<f64> = None;
+    let mut seen_null = false;
+    for (index, row) in rows.iter().enumerate() {
+        let value = &row
+            .get(column.name)
+            .ok_or_else(|| Error::MissingField(column.name.clone()))?
+            .0;
+        if value.is_null() {
+            seen_null = true;
+            continue;
+        }
+        if seen_null {
+            return Err(Error::ResponseDoesNotSatisfy(
+                "expected rows with null values to be ordered last by distance".into(),
+            ));
+        }
+        let Some(distance) = point_distance(column.representation, value, geometry) else {
+            continue;
+        };
+        // The row which the geometry was taken from is the nearest
+        if index == 0 && distance > 1e-6 {
+            return Err(Error::ResponseDoesNotSatisfy(
+                "expected the nearest row to be at distance zero".into(),
+            ));
+        }
+        if previous.is_some_and(|previous| distance + 1e-6 * distance.max(1.0) < previous) {
+            return Err(Error::ResponseDoesNotSatisfy(
+                "expected rows to be ordered by distance".into(),
+            ));
+        }
+        previous = Some(distance);
+    }
+
+    Ok(())
+}
+
+fn geospatial_columns<'a>(
+    schema: &'a models::SchemaResponse,
+    collection_info: &models::CollectionInfo,
+    context: &'a super::context::Context<'_>,
+) -> Result<Vec<GeospatialColumn<'a>>> {
+    let collection_type = schema
+        .object_types
+        .get(&collection_info.collection_type)
+        .ok_or_else(|| {
+            Error::CollectionTypeIsNotDefined(collection_info.collection_type.clone())
+        })?;
+
+    Ok(collection_type
+        .fields
+        .iter()
+        .filter(|(_, field)| field.arguments.is_empty())
+        .filter_map(|(field_name, field)| {
+            let type_name = super::common::as_named_type(&field.r#type)?;
+            let scalar_type = schema
+                .scalar_types
+                .get(&models::ScalarTypeName::new(type_name.clone()))?;
+            if !matches!(
+                scalar_type.representation,
+                models::TypeRepresentation::Geography | models::TypeRepresentation::Geometry
+            ) {
+                return None;
+            }
+            let within_distance =
+                scalar_type
+                    .comparison_operators
+                    .iter()
+                    .find_map(|(operator_name, definition)| {
+                        matches!(
+                            definition,
+                            models::ComparisonOperatorDefinition::SpatialWithinDistance { .. }
+                        )
+                        .then_some(operator_name)
+                    });
+            let values = context.values.get(field_name)?;
+            Some(GeospatialColumn {
+                name: field_name,
+                representation: &scalar_type.representation,
+                within_distance,
+                values,
+            })
+        })
+        .collect())
+}
+
+/// The distance between two GeoJSON points, which is `None` if either is not
+/// a point. Geography distances are measured in meters along the surface of
+/// the earth, and geometry distances in the units of the coordinate system.
+fn point_distance(
+    representation: &models::TypeRepresentation,
+    left: &serde_json::Value,
+    right: &serde_json::Value,
+) -> Option<f64> {
+    const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+    let point = |value: &serde_json::Value| -> Option<(f64, f64)> {
+        if value.get("type")?.as_str()? != "Point" {
+            return None;
+        }
+        let coordinates = value.get("coordinates")?.as_array()?;
+        Some((
+            coordinates.first()?.as_f64()?,
+            coordinates.get(1)?.as_f64()?,
+        ))
+    };
+    let ((x1, y1), (x2, y2)) = (point(left)?, point(right)?);
+
+    match representation {
+        models::TypeRepresentation::Geography => {
+            let (lon1, lat1, lon2, lat2) = (
+                x1.to_radians(),
+                y1.to_radians(),
+                x2.to_radians(),
+                y2.to_radians(),
+            );
+            let a = ((lat2 - lat1) / 2.0).sin().powi(2)
+                + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+            Some(2.0 * EARTH_RADIUS_METERS * a.sqrt().min(1.0).asin())
+        }
+        _ => Some((x1 - x2).hypot(y1 - y2)),
+    }
+}
+
+fn make_request(
+    collection_info: &models::CollectionInfo,
+    field_name: &models::FieldName,
+    query: models::Query,
+    request_arguments: Option<BTreeMap<models::ArgumentName, serde_json::Value>>,
+) -> models::QueryRequest {
+    models::QueryRequest {
+        collection: collection_info.name.clone(),
+        query: models::Query {
+            fields: Some(IndexMap::from([(
+                field_name.clone(),
+                models::Field::Column {
+                    column: field_name.clone(),
+                    fields: None,
+                    arguments: BTreeMap::new(),
+                },
+            )])),
+            ..query
+        },
+        arguments: BTreeMap::new(),
+        collection_relationships: BTreeMap::new(),
+        variables: None,
+        request_arguments,
+    }
+}
@@ -51,12 +51,14 @@ pub async fn test_grouping<C: Connector, R: Reporter>(
                             fields: None,
                             limit: None,
                             offset: None,
+                            after: None,
                             order_by: None,
                             predicate: None,
+                            distinct: None,
                             groups: Some(models::Grouping {
                                 aggregates: IndexMap::from_iter([(
                                     "count".into(),
-                                    models::Aggregate::StarCount {},
+                                    models::Aggregate::StarCount { filter: None },
                                 )]),
                                 dimensions: vec![models::Dimension::Column {
                                     column_name: dimension_column_name.clone(),
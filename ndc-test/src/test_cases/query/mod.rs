@@ -1,7 +1,12 @@
 mod aggregates;
+mod distinct;
+mod full_text_search;
+mod geospatial;
 mod grouping;
+mod pagination;
 mod relationships;
 mod simple_queries;
+mod vector_search;
 
 mod common;
 mod context;
@@ -13,6 +18,7 @@ use crate::configuration::TestGenerationConfiguration;
 use crate::connector::Connector;
 use crate::nest;
 use crate::reporter::Reporter;
+use crate::test;
 
 use ndc_models as models;
 use rand::rngs::SmallRng;
@@ -57,15 +63,106 @@ pub async fn test_query<C: Connector, R: Reporter>(
                         });
                     }
 
-                    if let Some(aggregates) = &capabilities.capabilities.query.aggregates {
-                        nest!("Aggregate queries", reporter, {
-                            aggregates::test_aggregate_queries(
+                    if let Some(models::FullTextSearchCapabilities {
+                        order_by_relevance: Some(_),
+                    }) = &capabilities.capabilities.query.full_text_search
+                    {
+                        test!("Order by relevance", reporter, {
+                            full_text_search::test_order_by_relevance(
+                                gen_config,
+                                connector,
+                                context.as_ref(),
+                                schema,
+                                request_arguments.clone(),
+                                rng,
+                                collection_info,
+                            )
+                        });
+                    }
+
+                    if let Some(geospatial) = &capabilities.capabilities.query.geospatial {
+                        test!("Within distance", reporter, {
+                            geospatial::test_within_distance(
+                                gen_config,
+                                connector,
+                                context.as_ref(),
+                                schema,
+                                request_arguments.clone(),
+                                rng,
+                                collection_info,
+                            )
+                        });
+
+                        if geospatial.order_by_distance.is_some() {
+                            test!("Order by distance", reporter, {
+                                geospatial::test_order_by_distance(
+                                    gen_config,
+                                    connector,
+                                    context.as_ref(),
+                                    schema,
+                                    request_arguments.clone(),
+                                    rng,
+                                    collection_info,
+                                )
+                            });
+                        }
+                    }
+
+                    if let Some(vector_search) = &capabilities.capabilities.query.vector_search {
+                        test!("Order by similarity", reporter, {
+                            vector_search::test_order_by_similarity(
+                                gen_config,
+                                connector,
+                                vector_search,
+                                context.as_ref(),
+                                schema,
+                                request_arguments.clone(),
+                                rng,
+                                collection_info,
+                            )
+                        });
+                    }
+
+                    if let Some(distinct) = &capabilities.capabilities.query.distinct {
+                        nest!("Distinct queries", reporter, {
+                            distinct::test_distinct(
                                 gen_config,
                                 connector,
                                 reporter,
+                                distinct,
                                 schema,
+                                request_arguments.clone(),
                                 collection_info,
+                                rng,
+                            )
+                        });
+                    }
+
+                    if capabilities.capabilities.query.pagination.cursor.is_some() {
+                        nest!("Cursor pagination", reporter, {
+                            pagination::test_cursor_pagination(
+                                gen_config,
+                                connector,
+                                reporter,
+                                schema,
                                 request_arguments.clone(),
+                                collection_info,
+                            )
+                        });
+                    }
+
+                    if let Some(aggregates) = &capabilities.capabilities.query.aggregates {
+                        nest!("Aggregate queries", reporter, {
+                            aggregates::test_aggregate_queries(
+                                gen_config,
+                                connector,
+                                reporter,
+                                aggregates::AggregateTestCase {
+                                    schema,
+                                    capabilities: aggregates,
+                                    collection_info,
+                                    request_arguments: request_arguments.clone(),
+                                },
                                 rng,
                             )
                         });
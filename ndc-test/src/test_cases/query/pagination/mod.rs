@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+
+use crate::configuration::TestGenerationConfiguration;
+use crate::connector::Connector;
+use crate::error::Error;
+use crate::reporter::Reporter;
+use crate::test;
+
+use ndc_models as models;
+
+use indexmap::IndexMap;
+
+use super::validate::{expect_single_rows, expect_single_rowset};
+
+pub async fn test_cursor_pagination<C: Connector, R: Reporter>(
+    gen_config: &TestGenerationConfiguration,
+    connector: &C,
+    reporter: &mut R,
+    schema: &models::SchemaResponse,
+    request_arguments: Option<BTreeMap<models::ArgumentName, serde_json::Value>>,
+    collection_info: &models::CollectionInfo,
+) -> Option<()> {
+    let collection_type = schema.object_types.get(&collection_info.collection_type)?;
+
+    test!("Pages of one row", reporter, async {
+        let fields = super::common::select_all_columns(collection_type);
+
+        let response = connector
+            .query(make_request(
+                collection_info,
+                fields.clone(),
+                gen_config.sample_size,
+                None,
+                request_arguments.clone(),
+            ))
+            .await?;
+        let expected_rows = expect_single_rows(&response)?;
+
+        let mut rows = vec![];
+        let mut after = None;
+
+        while rows.len() < expected_rows.len() {
+            let response = connector
+                .query(make_request(
+                    collection_info,
+                    fields.clone(),
+                    1,
+                    after.take(),
+                    request_arguments.clone(),
+                ))
+                .await?;
+            let row_set = expect_single_rowset(&response)?;
+            let page = row_set.rows.ok_or(Error::RowsShouldBeNonNullInRowSet)?;
+
+            if page.len() != 1 {
+                return Err(Error::ResponseDoesNotSatisfy(format!(
+                    "expected one row after cursor, got {}",
+                    page.len()
+                )));
+            }
+
+            rows.extend(page);
+
+            if rows.len() < expected_rows.len() {
+                after = Some(row_set.next_cursor.ok_or_else(|| {
+                    Error::ResponseDoesNotSatisfy(
+                        "expected next_cursor when more rows follow the page".into(),
+                    )
+                })?);
+            }
+        }
+
+        if rows != expected_rows {
+            return Err(Error::ResponseDoesNotSatisfy(
+                "rows returned page by page do not match the rows returned in one page".into(),
+            ));
+        }
+
+        Ok(())
+    })
+}
+
+fn make_request(
+    collection_info: &models::CollectionInfo,
+    fields: IndexMap<models::FieldName, models::Field>,
+    limit: u32,
+    after: Option<String>,
+    request_arguments: Option<BTreeMap<models::ArgumentName, serde_json::Value>>,
+) -> models::QueryRequest {
+    models::QueryRequest {
+        collection: collection_info.name.clone(),
+        query: models::Query {
+            aggregates: None,
+            fields: Some(fields),
+            limit: Some(limit),
+            offset: None,
+            after,
+            order_by: None,
+            predicate: None,
+            distinct: None,
+            groups: None,
+        },
+        arguments: BTreeMap::new(),
+        collection_relationships: BTreeMap::new(),
+        variables: None,
+        request_arguments,
+    }
+}
@@ -130,8 +130,10 @@ async fn select_top_n_using_foreign_key<C: Connector>(
                     fields: Some(other_fields.clone()),
                     limit: Some(gen_config.max_limit),
                     offset: None,
+                    after: None,
                     order_by: None,
                     predicate: None,
+                    distinct: None,
                     groups: None,
                 }),
                 relationship: "__relationship".into(),
@@ -146,8 +148,10 @@ async fn select_top_n_using_foreign_key<C: Connector>(
                 fields: Some(fields.clone()),
                 limit: Some(gen_config.max_limit),
                 offset: None,
+                after: None,
                 order_by: None,
                 predicate: None,
+                distinct: None,
                 groups: None,
             },
             arguments: BTreeMap::new(),
@@ -234,6 +238,7 @@ async fn select_top_n_using_foreign_key_exists<C: Connector>(
                 fields: Some(other_fields.clone()),
                 limit: Some(gen_config.max_limit),
                 offset: None,
+                after: None,
                 order_by: None,
                 predicate: Some(models::Expression::Exists {
                     in_collection: models::ExistsInCollection::Related {
@@ -243,6 +248,7 @@ async fn select_top_n_using_foreign_key_exists<C: Connector>(
                     },
                     predicate: predicate.map(Box::new),
                 }),
+                distinct: None,
                 groups: None,
             },
             arguments: BTreeMap::new(),
@@ -318,8 +324,10 @@ async fn select_top_n_using_foreign_key_as_array_relationship<C: Connector>(
                 fields: Some(fields.clone()),
                 limit: Some(gen_config.max_limit),
                 offset: None,
+                after: None,
                 order_by: None,
                 predicate: None,
+                distinct: None,
                 groups: None,
             }),
             relationship: "__array_relationship".into(),
@@ -341,8 +349,10 @@ async fn select_top_n_using_foreign_key_as_array_relationship<C: Connector>(
             fields: Some(other_fields.clone()),
             limit: Some(gen_config.max_limit),
             offset: None,
+            after: None,
             order_by: None,
             predicate: None,
+            distinct: None,
             groups: None,
         },
         arguments: BTreeMap::new(),
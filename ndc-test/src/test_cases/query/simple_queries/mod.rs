@@ -87,8 +87,10 @@ async fn test_select_top_n_rows<C: Connector>(
             fields: Some(fields.clone()),
             limit: Some(limit),
             offset: None,
+            after: None,
             order_by: None,
             predicate: None,
+            distinct: None,
             groups: None,
         },
         arguments: BTreeMap::new(),
@@ -5,6 +5,7 @@ use crate::connector::Connector;
 use crate::error::{Error, Result};
 
 use ndc_models as models;
+use ndc_models::builders::{and, col, lit, or};
 use rand::rngs::SmallRng;
 use rand::seq::SliceRandom;
 use rand::Rng;
@@ -89,9 +90,7 @@ pub fn make_predicate(
             [] => {}
             [expression] => expressions.push(expression.clone()),
             _ => expressions.push(GeneratedExpression {
-                expr: models::Expression::Or {
-                    expressions: chosen.iter().map(|e| e.expr.clone()).collect::<Vec<_>>(),
-                },
+                expr: or(chosen.iter().map(|e| e.expr.clone())),
                 expect_nonempty: chosen.iter().any(|e| e.expect_nonempty),
             }),
         }
@@ -101,12 +100,7 @@ pub fn make_predicate(
         [] => None,
         [expression] => Some(expression.clone()),
         _ => Some(GeneratedExpression {
-            expr: models::Expression::And {
-                expressions: expressions
-                    .iter()
-                    .map(|e| e.expr.clone())
-                    .collect::<Vec<_>>(),
-            },
+            expr: and(expressions.iter().map(|e| e.expr.clone())),
             expect_nonempty: false,
         }),
     })
@@ -135,14 +129,7 @@ fn make_single_expressions(
 
     if super::super::common::is_nullable_type(field_type) {
         expressions.push(GeneratedExpression {
-            expr: models::Expression::UnaryComparisonOperator {
-                column: models::ComparisonTarget::Column {
-                    name: field_name.clone(),
-                    arguments: BTreeMap::new(),
-                    field_path: None,
-                },
-                operator: models::UnaryComparisonOperator::IsNull,
-            },
+            expr: col(field_name.clone()).is_null(),
             expect_nonempty: false,
         });
     }
@@ -155,17 +142,8 @@ fn make_single_expressions(
                         let value = values.choose(rng).ok_or(Error::ExpectedNonEmptyRows)?;
 
                         expressions.push(GeneratedExpression {
-                            expr: models::Expression::BinaryComparisonOperator {
-                                column: models::ComparisonTarget::Column {
-                                    name: field_name.clone(),
-                                    arguments: BTreeMap::new(),
-                                    field_path: None,
-                                },
-                                operator: operator_name.clone(),
-                                value: models::ComparisonValue::Scalar {
-                                    value: value.clone(),
-                                },
-                            },
+                            expr: col(field_name.clone())
+                                .op(operator_name.clone(), lit(value.clone())),
                             expect_nonempty: true,
                         });
                     }
@@ -178,17 +156,10 @@ fn make_single_expressions(
                         > = values.choose_multiple(rng, value_count);
 
                         expressions.push(GeneratedExpression {
-                            expr: models::Expression::BinaryComparisonOperator {
-                                column: models::ComparisonTarget::Column {
-                                    name: field_name.clone(),
-                                    arguments: BTreeMap::new(),
-                                    field_path: None,
-                                },
-                                operator: operator_name.clone(),
-                                value: models::ComparisonValue::Scalar {
-                                    value: serde_json::Value::Array(values.cloned().collect()),
-                                },
-                            },
+                            expr: col(field_name.clone()).op(
+                                operator_name.clone(),
+                                lit(serde_json::Value::Array(values.cloned().collect())),
+                            ),
                             expect_nonempty: value_count > 0,
                         });
                     }
@@ -212,20 +183,14 @@ async fn test_select_top_n_rows_with_predicate<C: Connector>(
     let fields = super::super::common::select_all_columns(collection_type);
 
     let query_request = models::QueryRequest {
-        collection: collection_info.name.clone(),
-        query: models::Query {
-            aggregates: None,
-            fields: Some(fields),
-            limit: Some(gen_config.max_limit),
-            offset: None,
-            order_by: None,
-            predicate: Some(predicate.expr.clone()),
-            groups: None,
-        },
-        arguments: BTreeMap::new(),
-        collection_relationships: BTreeMap::new(),
-        variables: None,
         request_arguments,
+        ..models::QueryRequest::collection(collection_info.name.clone())
+            .query(models::Query {
+                fields: Some(fields),
+                ..models::Query::new()
+            })
+            .limit(gen_config.max_limit)
+            .filter(predicate.expr.clone())
     };
 
     let response = connector.query(query_request.clone()).await?;
@@ -247,33 +212,18 @@ async fn test_empty_and_predicate_is_no_op<C: Connector>(
     let fields = super::super::common::select_all_columns(collection_type);
 
     let query_request_no_predicate = models::QueryRequest {
-        collection: collection_info.name.clone(),
-        query: models::Query {
-            aggregates: None,
-            fields: Some(fields),
-            limit: Some(gen_config.max_limit),
-            offset: None,
-            order_by: None,
-            predicate: None,
-            groups: None,
-        },
-        arguments: BTreeMap::new(),
-        collection_relationships: BTreeMap::new(),
-        variables: None,
         request_arguments,
+        ..models::QueryRequest::collection(collection_info.name.clone())
+            .query(models::Query {
+                fields: Some(fields),
+                ..models::Query::new()
+            })
+            .limit(gen_config.max_limit)
     };
 
     let response_no_predicate = connector.query(query_request_no_predicate.clone()).await?;
 
-    let query_request_with_empty_and_predicate = models::QueryRequest {
-        query: models::Query {
-            predicate: Some(models::Expression::And {
-                expressions: vec![],
-            }),
-            ..query_request_no_predicate.query
-        },
-        ..query_request_no_predicate
-    };
+    let query_request_with_empty_and_predicate = query_request_no_predicate.filter(and([]));
 
     let response_empty_and_predicate = connector
         .query(query_request_with_empty_and_predicate.clone())
@@ -295,22 +245,14 @@ async fn test_empty_or_predicate_returns_no_rows<C: Connector>(
     let fields = super::super::common::select_all_columns(collection_type);
 
     let query_request = models::QueryRequest {
-        collection: collection_info.name.clone(),
-        query: models::Query {
-            aggregates: None,
-            fields: Some(fields),
-            limit: Some(gen_config.max_limit),
-            offset: None,
-            order_by: None,
-            predicate: Some(models::Expression::Or {
-                expressions: vec![],
-            }),
-            groups: None,
-        },
-        arguments: BTreeMap::new(),
-        collection_relationships: BTreeMap::new(),
-        variables: None,
         request_arguments,
+        ..models::QueryRequest::collection(collection_info.name.clone())
+            .query(models::Query {
+                fields: Some(fields),
+                ..models::Query::new()
+            })
+            .limit(gen_config.max_limit)
+            .filter(or([]))
     };
 
     let response = connector.query(query_request.clone()).await?;
@@ -49,7 +49,17 @@ fn make_order_by_elements(
         if let Some(name) = super::super::common::as_named_type(&field.r#type) {
             if schema
                 .scalar_types
-                .contains_key(&ndc_models::ScalarTypeName::new(name.clone()))
+                .get(&ndc_models::ScalarTypeName::new(name.clone()))
+                // Vectors are compared by similarity, and geospatial values by
+                // distance, rather than ordered by value
+                .is_some_and(|scalar_type| {
+                    !matches!(
+                        scalar_type.representation,
+                        models::TypeRepresentation::Vector { .. }
+                            | models::TypeRepresentation::Geography
+                            | models::TypeRepresentation::Geometry
+                    )
+                })
                 // The tests don't support fields with arguments at this time
                 && field.arguments.is_empty()
             {
@@ -105,8 +115,10 @@ async fn test_select_top_n_rows_with_sort<C: Connector>(
             fields: Some(fields),
             limit: Some(gen_config.max_limit),
             offset: None,
+            after: None,
             order_by: Some(models::OrderBy { elements }),
             predicate: None,
+            distinct: None,
             groups: None,
         },
         arguments: BTreeMap::new(),
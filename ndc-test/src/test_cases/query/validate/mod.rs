@@ -59,6 +59,25 @@ pub fn expect_single_rowset(response: &models::QueryResponse) -> Result<models::
     }
 }
 
+pub fn validate_request(
+    schema: &models::SchemaResponse,
+    request: &models::QueryRequest,
+) -> Result<()> {
+    let errors = models::validate::validate_query_request(schema, request);
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::InvalidRequest(
+            errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+        ))
+    }
+}
+
 pub fn validate_response(
     schema: &models::SchemaResponse,
     request: &models::QueryRequest,
@@ -547,11 +566,9 @@ pub fn check_value_has_type(
                     ))
                 }
             } else if let Some(scalar_type) = schema.scalar_types.get(name) {
-                representations::check_value_has_representation(
-                    &scalar_type.representation,
-                    &value,
-                    json_path,
-                )
+                models::value::ScalarValue::decode(&scalar_type.representation, &value)
+                    .map(|_| ())
+                    .map_err(|err| Error::InvalidScalarValueInResponse(json_path.to_vec(), err))
             } else {
                 Err(Error::NamedTypeIsNotDefined(name.clone()))
             }
@@ -590,63 +607,6 @@ pub fn check_value_has_type(
     }
 }
 
-mod representations {
-    #![allow(deprecated)]
-
-    use super::{Error, Result};
-    use ndc_models as models;
-
-    pub fn check_value_has_representation(
-        representation: &models::TypeRepresentation,
-        value: &serde_json::Value,
-        json_path: &[String],
-    ) -> Result<()> {
-        macro_rules! check {
-            ($test: expr, $expected: expr) => {{
-                if !$test {
-                    return Err(Error::InvalidValueInResponse(
-                        json_path.to_vec(),
-                        $expected.into(),
-                    ));
-                }
-            }};
-        }
-
-        match representation {
-            models::TypeRepresentation::Boolean => check!(value.is_boolean(), "boolean"),
-            models::TypeRepresentation::Float32 | models::TypeRepresentation::Float64 => {
-                check!(value.is_number(), "number");
-            }
-            models::TypeRepresentation::Int8
-            | models::TypeRepresentation::Int16
-            | models::TypeRepresentation::Int32 => check!(value.is_i64(), "integer"),
-            models::TypeRepresentation::String
-            | models::TypeRepresentation::Int64
-            | models::TypeRepresentation::BigInteger
-            | models::TypeRepresentation::BigDecimal
-            | models::TypeRepresentation::UUID
-            | models::TypeRepresentation::Date
-            | models::TypeRepresentation::Timestamp
-            | models::TypeRepresentation::TimestampTZ
-            | models::TypeRepresentation::Bytes => check!(value.is_string(), "string"),
-            models::TypeRepresentation::Enum { one_of } => {
-                check!(
-                    {
-                        let s = value.as_str();
-                        s.is_some_and(|x| one_of.contains(&x.to_string()))
-                    },
-                    "string"
-                );
-            }
-            models::TypeRepresentation::Geography
-            | models::TypeRepresentation::Geometry
-            | models::TypeRepresentation::JSON => {}
-        }
-
-        Ok(())
-    }
-}
-
 pub(crate) fn check_value_has_object_type(
     schema: &models::SchemaResponse,
     collection_relationships: &BTreeMap<models::RelationshipName, models::Relationship>,
@@ -691,6 +651,7 @@ impl<C: Connector> Connector for ValidatingConnector<'_, C> {
     }
 
     async fn query(&self, request: models::QueryRequest) -> Result<models::QueryResponse> {
+        validate_request(self.schema, &request)?;
         let response = self.connector.query(request.clone()).await?;
         validate_response(self.schema, &request, &response)?;
         Ok(response)
@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+
+use crate::configuration::TestGenerationConfiguration;
+use crate::connector::Connector;
+use crate::error::{Error, Result};
+
+use indexmap::IndexMap;
+use ndc_models as models;
+use ndc_models::builders::lit;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+
+use super::validate::expect_single_rows;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn test_order_by_similarity<C: Connector>(
+    gen_config: &TestGenerationConfiguration,
+    connector: &C,
+    capabilities: &models::VectorSearchCapabilities,
+    context: Option<&super::context::Context<'_>>,
+    schema: &models::SchemaResponse,
+    request_arguments: Option<BTreeMap<models::ArgumentName, serde_json::Value>>,
+    rng: &mut SmallRng,
+    collection_info: &models::CollectionInfo,
+) -> Result<()> {
+    let collection_type = schema
+        .object_types
+        .get(&collection_info.collection_type)
+        .ok_or_else(|| {
+            Error::CollectionTypeIsNotDefined(collection_info.collection_type.clone())
+        })?;
+    let Some(context) = context else {
+        eprintln!("Skipping empty collection {}", collection_info.name);
+        return Ok(());
+    };
+
+    // Compare with a vector taken from a column with a vector type
+    let candidates = collection_type
+        .fields
+        .iter()
+        .filter(|(_, field)| field.arguments.is_empty())
+        .filter_map(|(field_name, field)| {
+            let type_name = super::common::as_named_type(&field.r#type)?;
+            let scalar_type = schema
+                .scalar_types
+                .get(&models::ScalarTypeName::new(type_name.clone()))?;
+            if !matches!(
+                scalar_type.representation,
+                models::TypeRepresentation::Vector { .. }
+            ) {
+                return None;
+            }
+            let values = context.values.get(field_name)?;
+            Some((field_name, values))
+        })
+        .collect::<Vec<_>>();
+
+    let Some(&(field_name, values)) = candidates.choose(rng) else {
+        eprintln!(
+            "Skipping collection without vector columns {}",
+            collection_info.name
+        );
+        return Ok(());
+    };
+    let Some(vector) = values.choose(rng) else {
+        return Ok(());
+    };
+
+    for (metric, capability) in [
+        (models::SimilarityMetric::Cosine, &capabilities.cosine),
+        (models::SimilarityMetric::L2, &capabilities.l2),
+        (
+            models::SimilarityMetric::InnerProduct,
+            &capabilities.inner_product,
+        ),
+    ] {
+        if capability.is_none() {
+            continue;
+        }
+
+        let query = models::Query::new()
+            .order_by(models::OrderByElement {
+                order_direction: models::OrderDirection::Desc,
+                target: models::OrderByTarget::Similarity {
+                    name: field_name.clone(),
+                    field_path: None,
+                    metric,
+                    value: lit(vector.clone()),
+                },
+            })
+            .limit(gen_config.max_limit);
+        let rows = expect_single_rows(
+            &connector
+                .query(models::QueryRequest {
+                    collection: collection_info.name.clone(),
+                    query: models::Query {
+                        fields: Some(IndexMap::from([(
+                            field_name.clone(),
+                            models::Field::Column {
+                                column: field_name.clone(),
+                                fields: None,
+                                arguments: BTreeMap::new(),
+                            },
+                        )])),
+                        ..query
+                    },
+                    arguments: BTreeMap::new(),
+                    collection_relationships: BTreeMap::new(),
+                    variables: None,
+                    request_arguments: request_arguments.clone(),
+                })
+                .await?,
+        )?;
+
+        let mut previous: Option<Option<f64>> = None;
+        for row in &rows {
+            let value = &row
+                .get(field_name)
+                .ok_or_else(|| Error::MissingField(field_name.clone()))?
+                .0;
+            let score = similarity(metric, value, vector);
+            // Null scores are the lowest, and scores must not increase
+            let ordered = match (previous, score) {
+                (None, _) | (Some(_), None) => true,
+                (Some(None), Some(_)) => false,
+                (Some(Some(previous)), Some(score)) => {
+                    score <= previous + 1e-9 * previous.abs().max(1.0)
+                }
+            };
+            if !ordered {
+                return Err(Error::ResponseDoesNotSatisfy(format!(
+                    "expected rows to be ordered by {metric:?} similarity"
+                )));
+            }
+            previous = Some(score);
+        }
+    }
+
+    Ok(())
+}
+
+/// The similarity score of two vectors, which is `None` if either is not a
+/// vector, or if the cosine similarity of a zero vector is requested
+fn similarity(
+    metric: models::SimilarityMetric,
+    left: &serde_json::Value,
+    right: &serde_json::Value,
+) -> Option<f64> {
+    let vector = |value: &serde_json::Value| -> Option<Vec<f64>> {
+        value
+            .as_array()?
+            .iter()
+            .map(serde_json::Value::as_f64)
+            .collect()
+    };
+    let (left, right) = (vector(left)?, vector(right)?);
+    let dot = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(x, y)| x * y).sum::<f64>();
+
+    match metric {
+        models::SimilarityMetric::Cosine => {
+            let norms = dot(&left, &left).sqrt() * dot(&right, &right).sqrt();
+            (norms != 0.0).then(|| dot(&left, &right) / norms)
+        }
+        models::SimilarityMetric::L2 => Some(
+            -left
+                .iter()
+                .zip(&right)
+                .map(|(x, y)| (x - y) * (x - y))
+                .sum::<f64>()
+                .sqrt(),
+        ),
+        models::SimilarityMetric::InnerProduct => Some(dot(&left, &right)),
+    }
+}
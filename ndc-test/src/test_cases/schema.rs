@@ -24,6 +24,11 @@ pub async fn validate_schema<R: Reporter>(
 ) -> Option<()> {
     let _ = test!("scalar_types", reporter, async {
         for (type_name, scalar_type) in &schema.scalar_types {
+            if let models::TypeRepresentation::Vector { dimensions: 0 } = scalar_type.representation
+            {
+                return Err(Error::InvalidTypeRepresentation(type_name.clone()));
+            }
+
             for aggregate_function in scalar_type.aggregate_functions.values() {
                 match aggregate_function {
                     models::AggregateFunctionDefinition::Sum { result_type }
@@ -38,10 +43,50 @@ pub async fn validate_schema<R: Reporter>(
                         };
                         Ok(())
                     }
+                    models::AggregateFunctionDefinition::Stddev { result_type }
+                    | models::AggregateFunctionDefinition::StddevPop { result_type }
+                    | models::AggregateFunctionDefinition::Var { result_type } => {
+                        if !is_numeric(&scalar_type.representation) {
+                            return Err(Error::InvalidTypeRepresentation(type_name.clone()));
+                        }
+                        validate_result_representation(schema, result_type, |representation| {
+                            matches!(representation, models::TypeRepresentation::Float64)
+                        })
+                    }
+                    models::AggregateFunctionDefinition::ApproxDistinct { result_type } => {
+                        validate_result_representation(schema, result_type, |representation| {
+                            matches!(
+                                representation,
+                                models::TypeRepresentation::Int32
+                                    | models::TypeRepresentation::Int64
+                            )
+                        })
+                    }
+                    models::AggregateFunctionDefinition::Median => {
+                        if !is_numeric(&scalar_type.representation) {
+                            return Err(Error::InvalidTypeRepresentation(type_name.clone()));
+                        }
+                        Ok(())
+                    }
+                    models::AggregateFunctionDefinition::BoolAnd
+                    | models::AggregateFunctionDefinition::BoolOr => {
+                        let models::TypeRepresentation::Boolean = scalar_type.representation else {
+                            return Err(Error::InvalidTypeRepresentation(type_name.clone()));
+                        };
+                        Ok(())
+                    }
+                    models::AggregateFunctionDefinition::StringAgg { .. } => {
+                        let models::TypeRepresentation::String = scalar_type.representation else {
+                            return Err(Error::InvalidTypeRepresentation(type_name.clone()));
+                        };
+                        Ok(())
+                    }
                     models::AggregateFunctionDefinition::Custom { result_type } => {
                         validate_type(schema, result_type)
                     }
-                    _ => Ok(()),
+                    models::AggregateFunctionDefinition::Min
+                    | models::AggregateFunctionDefinition::Max
+                    | models::AggregateFunctionDefinition::ArrayAgg => Ok(()),
                 }?;
             }
 
@@ -93,6 +138,26 @@ pub async fn validate_schema<R: Reporter>(
                 {
                     validate_type(schema, argument_type)?;
                 }
+
+                match comparison_operator {
+                    models::ComparisonOperatorDefinition::FullTextSearch { .. } => {
+                        let models::TypeRepresentation::String = scalar_type.representation else {
+                            return Err(Error::InvalidTypeRepresentation(type_name.clone()));
+                        };
+                    }
+                    models::ComparisonOperatorDefinition::SpatialIntersects
+                    | models::ComparisonOperatorDefinition::SpatialWithin
+                    | models::ComparisonOperatorDefinition::SpatialContains => {
+                        validate_geospatial_type(type_name, scalar_type)?;
+                    }
+                    models::ComparisonOperatorDefinition::SpatialWithinDistance {
+                        argument_type,
+                    } => {
+                        validate_geospatial_type(type_name, scalar_type)?;
+                        validate_within_distance_argument_type(schema, type_name, argument_type)?;
+                    }
+                    _ => {}
+                }
             }
         }
 
@@ -211,3 +276,85 @@ pub fn validate_type(schema: &models::SchemaResponse, r#type: &models::Type) ->
 
     Ok(())
 }
+
+fn validate_geospatial_type(
+    type_name: &models::ScalarTypeName,
+    scalar_type: &models::ScalarType,
+) -> Result<()> {
+    let (models::TypeRepresentation::Geography | models::TypeRepresentation::Geometry) =
+        scalar_type.representation
+    else {
+        return Err(Error::InvalidTypeRepresentation(type_name.clone()));
+    };
+    Ok(())
+}
+
+/// The argument to a within-distance operator must be an object with a
+/// `geometry` field of the operator's scalar type, and a `distance` field
+/// with a Float64 representation
+fn validate_within_distance_argument_type(
+    schema: &models::SchemaResponse,
+    type_name: &models::ScalarTypeName,
+    argument_type: &models::ObjectTypeName,
+) -> Result<()> {
+    let Some(object_type) = schema.object_types.get(argument_type) else {
+        return Err(Error::ObjectTypeIsNotDefined(argument_type.clone()));
+    };
+    let invalid = |field: &str, expected: String| {
+        Error::InvalidWithinDistanceArgumentType(argument_type.clone(), field.into(), expected)
+    };
+    let field_type_name = |field: &str| match object_type.fields.get(field) {
+        Some(models::ObjectField {
+            r#type: models::Type::Named { name },
+            ..
+        }) => Some(name),
+        _ => None,
+    };
+
+    if field_type_name("geometry").map(models::TypeName::as_str) != Some(type_name.as_str()) {
+        return Err(invalid("geometry", format!("scalar type {type_name}")));
+    }
+
+    let is_float64 = field_type_name("distance")
+        .and_then(|name| schema.scalar_types.get(name.as_str()))
+        .is_some_and(|scalar_type| {
+            scalar_type.representation == models::TypeRepresentation::Float64
+        });
+    if !is_float64 {
+        return Err(invalid(
+            "distance",
+            "a scalar type with a float64 representation".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn is_numeric(representation: &models::TypeRepresentation) -> bool {
+    matches!(
+        representation,
+        models::TypeRepresentation::Int8
+            | models::TypeRepresentation::Int16
+            | models::TypeRepresentation::Int32
+            | models::TypeRepresentation::Int64
+            | models::TypeRepresentation::Float32
+            | models::TypeRepresentation::Float64
+            | models::TypeRepresentation::BigInteger
+            | models::TypeRepresentation::BigDecimal
+    )
+}
+
+/// The result type of a standard aggregate function must be a defined scalar
+/// type with one of the expected representations
+fn validate_result_representation(
+    schema: &models::SchemaResponse,
+    result_type: &models::ScalarTypeName,
+    expected: impl Fn(&models::TypeRepresentation) -> bool,
+) -> Result<()> {
+    let Some(scalar_type) = schema.scalar_types.get(result_type) else {
+        return Err(Error::NamedTypeIsNotDefined(result_type.inner().clone()));
+    };
+    if !expected(&scalar_type.representation) {
+        return Err(Error::InvalidTypeRepresentation(result_type.clone()));
+    }
+    Ok(())
+}